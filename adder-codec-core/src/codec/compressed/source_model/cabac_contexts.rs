@@ -1,6 +1,6 @@
 use crate::codec::compressed::fenwick::context_switching::FenwickModel;
 use crate::codec::compressed::fenwick::Weights;
-use crate::{AbsoluteT, DeltaT, EventCoordless, Intensity, D, D_SHIFT};
+use crate::{d_shift, AbsoluteT, DeltaT, EventCoordless, Intensity, D};
 use arithmetic_coding_adder_dep::Encoder;
 use bitstream_io::{BigEndian, BitWrite, BitWriter};
 
@@ -70,11 +70,11 @@ impl Contexts {
     }
 
     fn event_to_intensity(&self, d: D, delta_t: DeltaT, dt_ref: DeltaT) -> f64 {
-        let intensity = match d as usize {
-            a if a >= D_SHIFT.len() => f64::from(0),
-            _ => match delta_t {
-                0 => D_SHIFT[d as usize] as Intensity, // treat it as dt = 1
-                _ => D_SHIFT[d as usize] as Intensity / f64::from(delta_t),
+        let intensity = match d_shift(d) {
+            None => f64::from(0),
+            Some(shift) => match delta_t {
+                0 => shift as Intensity, // treat it as dt = 1
+                _ => shift as Intensity / f64::from(delta_t),
             },
         };
         intensity * dt_ref as f64
@@ -78,6 +78,12 @@ impl EventAdu {
         }
     }
 
+    /// Compress the Adu's events to `stream`, then clear them.
+    ///
+    /// There is no per-block mode decision: every cube intra-codes the first event of each pixel,
+    /// then every cube inter-codes the remaining events, with cubes and pixels visited in
+    /// row-major order. The output is therefore fully determined by the events and
+    /// `c_thresh_max`, so identical input always produces identical bytes.
     pub fn compress(
         &mut self,
         stream: &mut BitWriter<Vec<u8>, BigEndian>,
@@ -444,4 +450,39 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn compress_is_deterministic() -> Result<(), Box<dyn std::error::Error>> {
+        let plane = PlaneSize::new(16, 30, 1)?;
+        let start_t = 0;
+        let dt_ref = 255;
+        let num_intervals = 10;
+
+        let encode = |c_thresh_max: u8| -> Result<Vec<u8>, CodecError> {
+            let mut adu = EventAdu::new(plane, start_t, dt_ref, num_intervals);
+            for y in 0..30 {
+                for x in 0..16 {
+                    for i in 0..3 {
+                        adu.ingest_event(Event {
+                            coord: Coord { x, y, c: None },
+                            t: 280 + 700 * i + u32::from(x + y),
+                            d: 7 + (x % 3) as u8,
+                        });
+                    }
+                }
+            }
+            let mut stream = BitWriter::endian(Vec::new(), BigEndian);
+            adu.compress(&mut stream, c_thresh_max)?;
+            Ok(stream.into_writer())
+        };
+
+        // Both lossless and lossy coding, which compares intensities as floats
+        for c_thresh_max in [0, 40] {
+            let first = encode(c_thresh_max)?;
+            assert!(!first.is_empty());
+            assert_eq!(first, encode(c_thresh_max)?);
+        }
+
+        Ok(())
+    }
 }
@@ -1,11 +1,12 @@
 use crate::codec::{CodecError, CodecMetadata, EncoderOptions, ReadCompression, WriteCompression};
 use bitstream_io::{BigEndian, BitRead, BitReader, BitWrite, BitWriter};
+use log::{debug, warn};
 use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 
 use crate::codec::compressed::source_model::event_structure::event_adu::EventAdu;
 use crate::codec::compressed::source_model::HandleEvent;
 use crate::codec::header::{Magic, MAGIC_COMPRESSED};
-use crate::{DeltaT, Event};
+use crate::{DeltaT, Event, PixelMultiMode};
 
 /// Write compressed ADΔER data to a stream.
 pub struct CompressedOutput<W: Write> {
@@ -143,6 +144,9 @@ impl<R: Read> CompressedInput<R> {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             adu: None,
             _phantom: std::marker::PhantomData,
@@ -189,9 +193,16 @@ impl<R: Read + Seek> ReadCompression<R> for CompressedInput<R> {
         if let Some(adu) = &mut self.adu {
             if adu.decoder_is_empty() {
                 let start = std::time::Instant::now();
-                // Read the size of the Adu in bytes
+                // Read the size of the Adu in bytes. Compressed streams have no end-of-stream
+                // marker, so running out of data here is the end of the stream.
                 let mut buffer = [0u8; 4];
-                reader.read_bytes(&mut buffer)?;
+                match reader.read_bytes(&mut buffer) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                        return Err(CodecError::Eof)
+                    }
+                    Err(e) => return Err(e.into()),
+                }
                 let num_bytes = u32::from_be_bytes(buffer);
 
                 // Read the compressed Adu from the stream
@@ -204,7 +215,7 @@ impl<R: Read + Seek> ReadCompression<R> for CompressedInput<R> {
                 adu.decompress(&mut adu_stream);
 
                 let duration = start.elapsed();
-                println!("Decompressed Adu in {:?} ns", duration.as_nanos());
+                debug!("Decompressed Adu in {:?} ns", duration.as_nanos());
             }
             // Then return the next event from the queue
             match adu.digest_event() {
@@ -227,7 +238,7 @@ impl<R: Read + Seek> ReadCompression<R> for CompressedInput<R> {
         pos: u64,
     ) -> Result<(), CodecError> {
         if pos.saturating_sub(self.meta.header_size as u64) % u64::from(self.meta.event_size) != 0 {
-            eprintln!("Attempted to seek to bad position in stream: {pos}");
+            warn!("Attempted to seek to bad position in stream: {pos}");
             return Err(CodecError::Seek);
         }
 
@@ -242,11 +253,10 @@ impl<R: Read + Seek> ReadCompression<R> for CompressedInput<R> {
 mod tests {
     use crate::codec::compressed::stream::CompressedInput;
     use crate::codec::{CodecError, ReadCompression};
-    use crate::PlaneSize;
+    use crate::{PixelMultiMode, PlaneSize};
     use bitstream_io::{BigEndian, BitReader};
     use std::cmp::min;
     use std::error::Error;
-    use std::io;
 
     /// Test the creation a CompressedOutput and writing a bunch of events to it but NOT getting
     /// to the time where we compress the Adu
@@ -278,6 +288,9 @@ mod tests {
                 event_size: 0,
                 source_camera: SourceCamera::FramedU8,
                 adu_interval: num_intervals as usize,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             Cursor::new(Vec::new()),
         );
@@ -339,6 +352,9 @@ mod tests {
                 event_size: 0,
                 source_camera: SourceCamera::FramedU8,
                 adu_interval: num_intervals as usize,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             Cursor::new(Vec::new()),
         );
@@ -434,6 +450,9 @@ mod tests {
                 event_size: 0,
                 source_camera: SourceCamera::FramedU8,
                 adu_interval: num_intervals as usize,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             Cursor::new(Vec::new()),
         );
@@ -476,7 +495,7 @@ mod tests {
                         output_px_events.push(event);
                     }
                 }
-                Err(CodecError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(CodecError::Eof) => break,
 
                 Err(e) => return Err(Box::new(e)),
             }
@@ -522,6 +541,9 @@ mod tests {
                 event_size: 0,
                 source_camera: SourceCamera::FramedU8,
                 adu_interval: num_intervals as usize,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             Cursor::new(Vec::new()),
         );
@@ -593,7 +615,7 @@ mod tests {
                         output_px_events.push(event);
                     }
                 }
-                Err(CodecError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(CodecError::Eof) => break,
 
                 Err(e) => return Err(Box::new(e)),
             }
@@ -637,6 +659,9 @@ mod tests {
                 event_size: 0,
                 source_camera: SourceCamera::FramedU8,
                 adu_interval: num_intervals as usize,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             Cursor::new(Vec::new()),
         );
@@ -715,7 +740,7 @@ mod tests {
                         output_px_events.push(event);
                     }
                 }
-                Err(CodecError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(CodecError::Eof) => break,
 
                 Err(e) => return Err(Box::new(e)),
             }
@@ -1,7 +1,14 @@
 use crate::codec::Magic;
-use crate::codec::{CodecError, CodecMetadata, EncoderType, ReadCompression, ReadCompressionEnum};
+use crate::codec::{
+    end_of_stream, supported_codec_versions, CodecError, CodecMetadata, EncoderType, FormatInfo,
+    ReadCompression, ReadCompressionEnum,
+};
 use crate::SourceType::*;
-use crate::{Event, PlaneSize, SourceCamera, SourceType};
+use crate::{
+    is_framed, BigT, Coord, DeltaT, Event, PixelMultiMode, PlaneSize, SourceCamera, SourceType,
+    TimeMode, D_EMPTY, D_MAX, D_NO_EVENT, D_ZERO_INTEGRATION,
+};
+use ndarray::{Array2, Array3};
 
 // #[cfg(feature = "compression")]
 // use crate::codec::compressed::adu::frame::Adu;
@@ -11,15 +18,90 @@ use crate::codec::compressed::stream::CompressedInput;
 use crate::codec::encoder::Encoder;
 use crate::codec::header::{
     EventStreamHeader, EventStreamHeaderExtensionV1, EventStreamHeaderExtensionV2,
-    EventStreamHeaderExtensionV3, MAGIC_COMPRESSED,
+    EventStreamHeaderExtensionV3, EventStreamHeaderExtensionV4, EventStreamHeaderExtensionV5,
+    EventStreamHeaderExtensionV6, EventStreamHeaderExtensionV7, MAGIC_COMPRESSED,
+    MAX_THUMBNAIL_BYTES,
 };
-use crate::codec::raw::stream::RawInput;
+use crate::codec::raw::stream::{RawInput, RawOutput};
+#[cfg(feature = "thumbnail")]
+use crate::codec::thumbnail::decode_thumbnail;
 use crate::codec::CodecError::Deserialize;
+use crate::codec::EncoderOptions;
 use crate::SourceType::U8;
 use bincode::config::{FixintEncoding, WithOtherEndian, WithOtherIntEncoding};
 use bincode::{DefaultOptions, Options};
 use bitstream_io::{BigEndian, BitRead, BitReader};
-use std::io::{Read, Seek, SeekFrom};
+use log::warn;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// The number of elements in each chunk of the datasets written by [`Decoder::write_hdf5`]
+#[cfg(feature = "hdf5")]
+const HDF5_CHUNK_LEN: usize = 1 << 16;
+
+/// A disagreement between a stream's header and one of its events, as found by
+/// [`Decoder::audit_header_consistency`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// The event's Δt is greater than the header's `delta_t_max`
+    DeltaTExceedsMax {
+        /// The index of the event in the stream
+        index: u64,
+        /// The offending event
+        event: Event,
+        /// The event's Δt since the pixel's previous event
+        delta_t: DeltaT,
+    },
+    /// The event's `d` is greater than [`D_MAX`], and is not one of the special `d` values
+    DExceedsMax {
+        /// The index of the event in the stream
+        index: u64,
+        /// The offending event
+        event: Event,
+    },
+    /// The event's coordinate lies outside the header's plane
+    CoordOutOfBounds {
+        /// The index of the event in the stream
+        index: u64,
+        /// The offending event
+        event: Event,
+    },
+}
+
+/// The result of [`Decoder::validate`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The number of events decoded. Scanning continues past a violation in a decodable event,
+    /// so this counts every event up to the end of the stream unless it was truncated.
+    pub events_scanned: u64,
+
+    /// The first violation in the stream, or `None` if the stream is valid
+    pub first_violation: Option<Violation>,
+}
+
+/// The location and nature of a problem found by [`Decoder::validate`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The byte offset of the start of the offending event. Compressed streams are decoded an
+    /// ADU at a time, so for them this only locates the ADU being read.
+    pub offset: u64,
+
+    /// The index of the offending event in the stream
+    pub index: u64,
+
+    /// What is wrong with the event
+    pub kind: ViolationKind,
+}
+
+/// What is wrong with the event reported in a [`Violation`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The event disagrees with the stream's header
+    Inconsistent(Inconsistency),
+
+    /// The stream ends partway through the event, without an end-of-stream marker
+    Truncated,
+}
 
 /// Struct for decoding [`Event`]s from a stream
 pub struct Decoder<R: Read + Seek> {
@@ -28,6 +110,13 @@ pub struct Decoder<R: Read + Seek> {
         WithOtherIntEncoding<DefaultOptions, FixintEncoding>,
         bincode::config::BigEndian,
     >,
+
+    /// JPEG thumbnail embedded in the header, if any
+    #[cfg_attr(not(feature = "thumbnail"), allow(dead_code))]
+    thumbnail: Vec<u8>,
+
+    /// Whether [`Decoder::next_event`] has read the end-of-stream marker
+    marker_read: bool,
     _phantom: std::marker::PhantomData<R>,
 }
 
@@ -47,6 +136,8 @@ impl<R: Read + Seek> Decoder<R> {
             bincode: DefaultOptions::new()
                 .with_fixint_encoding()
                 .with_big_endian(),
+            thumbnail: Vec::new(),
+            marker_read: false,
             _phantom: std::marker::PhantomData,
         };
         decoder.decode_header(reader)?;
@@ -66,6 +157,8 @@ impl<R: Read + Seek> Decoder<R> {
             bincode: DefaultOptions::new()
                 .with_fixint_encoding()
                 .with_big_endian(),
+            thumbnail: Vec::new(),
+            marker_read: false,
             _phantom: std::marker::PhantomData,
         };
         decoder.decode_header(reader)?;
@@ -78,12 +171,40 @@ impl<R: Read + Seek> Decoder<R> {
         self.input.meta()
     }
 
+    /// Returns a summary of the input stream format
+    pub fn format_info(&self) -> FormatInfo {
+        self.input.format_info()
+    }
+
+    /// Returns the codec version of the input stream
+    pub fn version(&self) -> u8 {
+        self.input.version()
+    }
+
     /// Returns a mutable reference to the metadata of the underlying compression scheme
     #[inline]
     pub fn meta_mut(&mut self) -> &mut CodecMetadata {
         self.input.meta_mut()
     }
 
+    /// Returns the thumbnail embedded in the stream header by
+    /// [`crate::codec::encoder::Encoder::set_thumbnail`], with shape (height, width, channels).
+    /// Color thumbnails are in BGR order. Returns `None` if the stream has no thumbnail, or it
+    /// can't be decoded.
+    #[cfg(feature = "thumbnail")]
+    pub fn thumbnail(&self) -> Option<Array3<u8>> {
+        if self.thumbnail.is_empty() {
+            return None;
+        }
+        match decode_thumbnail(&self.thumbnail) {
+            Ok(thumbnail) => Some(thumbnail),
+            Err(e) => {
+                warn!("Could not decode the stream thumbnail: {e}");
+                None
+            }
+        }
+    }
+
     /// Get the source data representation, based on the source camera
     #[allow(clippy::match_same_arms)]
     pub fn get_source_type(&self) -> SourceType {
@@ -119,6 +240,9 @@ impl<R: Read + Seek> Decoder<R> {
             if header.magic != self.input.magic() {
                 return Err(CodecError::WrongMagic);
             }
+            if !supported_codec_versions().contains(&header.version) {
+                return Err(CodecError::UnsupportedVersion(header.version));
+            }
             let meta = self.input.meta_mut();
             *meta = CodecMetadata {
                 codec_version: header.version,
@@ -131,6 +255,9 @@ impl<R: Read + Seek> Decoder<R> {
                 event_size: header.event_size,
                 source_camera: Default::default(), // Gets filled by decoding the V2 header extension
                 adu_interval: Default::default(), // Gets filled by decoding the V3 header extension
+                delta_t_encoding: Default::default(), // Gets filled by decoding the V4 header extension
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0, // Gets filled by decoding the V6 header extension
             };
 
             // Manual fix for malformed files with old software
@@ -202,6 +329,79 @@ impl<R: Read + Seek> Decoder<R> {
             return Ok(());
         }
 
+        extension_size = bincode::serialized_size(&EventStreamHeaderExtensionV4::default())?;
+        buffer = vec![0; extension_size as usize];
+        reader.read_bytes(&mut buffer)?;
+        let extension_v4 = match self
+            .bincode
+            .deserialize_from::<_, EventStreamHeaderExtensionV4>(&*buffer)
+        {
+            Ok(header) => header,
+            Err(_) => return Err(Deserialize),
+        };
+        self.input.meta_mut().delta_t_encoding = extension_v4.delta_t_encoding;
+        self.input.meta_mut().header_size += extension_size as usize;
+
+        if codec_version == 4 {
+            return Ok(());
+        }
+
+        extension_size = bincode::serialized_size(&EventStreamHeaderExtensionV5::default())?;
+        buffer = vec![0; extension_size as usize];
+        reader.read_bytes(&mut buffer)?;
+        let extension_v5 = match self
+            .bincode
+            .deserialize_from::<_, EventStreamHeaderExtensionV5>(&*buffer)
+        {
+            Ok(header) => header,
+            Err(_) => return Err(Deserialize),
+        };
+        self.input.meta_mut().pixel_multi_mode = extension_v5.pixel_multi_mode;
+        self.input.meta_mut().header_size += extension_size as usize;
+
+        if codec_version == 5 {
+            return Ok(());
+        }
+
+        extension_size = bincode::serialized_size(&EventStreamHeaderExtensionV6::default())?;
+        buffer = vec![0; extension_size as usize];
+        reader.read_bytes(&mut buffer)?;
+        let extension_v6 = match self
+            .bincode
+            .deserialize_from::<_, EventStreamHeaderExtensionV6>(&*buffer)
+        {
+            Ok(header) => header,
+            Err(_) => return Err(Deserialize),
+        };
+        self.input.meta_mut().t_fraction_bits = extension_v6.t_fraction_bits;
+        self.input.meta_mut().header_size += extension_size as usize;
+
+        if codec_version == 6 {
+            return Ok(());
+        }
+
+        extension_size = bincode::serialized_size(&EventStreamHeaderExtensionV7::default())?;
+        buffer = vec![0; extension_size as usize];
+        reader.read_bytes(&mut buffer)?;
+        let extension_v7 = match self
+            .bincode
+            .deserialize_from::<_, EventStreamHeaderExtensionV7>(&*buffer)
+        {
+            Ok(header) => header,
+            Err(_) => return Err(Deserialize),
+        };
+        let thumbnail_len = extension_v7.thumbnail_len as usize;
+        if thumbnail_len > MAX_THUMBNAIL_BYTES {
+            return Err(Deserialize);
+        }
+        self.thumbnail = vec![0; thumbnail_len];
+        reader.read_bytes(&mut self.thumbnail)?;
+        self.input.meta_mut().header_size += extension_size as usize + thumbnail_len;
+
+        if codec_version == 7 {
+            return Ok(());
+        }
+
         Err(CodecError::UnsupportedVersion(codec_version))
     }
 
@@ -214,6 +414,729 @@ impl<R: Read + Seek> Decoder<R> {
         self.input.digest_event(reader)
     }
 
+    /// Read and decode the next event, or `None` at the end of the stream. Reading on after the
+    /// end keeps returning `None`.
+    ///
+    /// # Errors
+    /// Returns [`CodecError::Truncated`] if the stream is cut off without an end-of-stream marker.
+    fn next_event(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Option<Event>, CodecError> {
+        let event = end_of_stream(self.input.digest_event(reader), self.marker_read)?;
+        self.marker_read = event.is_none();
+        Ok(event)
+    }
+
+    /// Returns an iterator which lazily reads and decodes the rest of the stream's events. It ends
+    /// at the end of the stream, or after yielding the first error, which is
+    /// [`CodecError::Truncated`] if the stream was cut off.
+    pub fn events<'a>(&'a mut self, reader: &'a mut BitReader<R, BigEndian>) -> EventIter<'a, R> {
+        EventIter {
+            decoder: self,
+            reader,
+            done: false,
+        }
+    }
+
+    /// Read and decode events into `buf`, until it's full or the stream ends. Returns the number
+    /// of events written to the front of `buf`, which is 0 once the stream is exhausted. Reusing
+    /// one buffer across calls avoids handling each event individually in tight loops.
+    ///
+    /// # Errors
+    /// Returns an error if an event cannot be decoded, or [`CodecError::Truncated`] if the stream
+    /// is cut off without an end-of-stream marker. Events already written to `buf` by this call
+    /// are then discarded.
+    pub fn digest_events_into(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        buf: &mut [Event],
+    ) -> Result<usize, CodecError> {
+        for (count, slot) in buf.iter_mut().enumerate() {
+            match self.next_event(reader)? {
+                Some(event) => *slot = event,
+                None => return Ok(count),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    /// Read and decode up to `max` events, appending them to `buf`. Returns the number of events
+    /// read, which is less than `max` only once the stream is exhausted.
+    ///
+    /// Raw streams with fixed-size timestamps read the whole batch at once. Only whole events
+    /// are consumed, so a truncated event at the end of the stream doesn't disturb the reader's
+    /// position.
+    ///
+    /// # Errors
+    /// Returns an error if an event cannot be decoded.
+    pub fn digest_events(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        max: usize,
+        buf: &mut Vec<Event>,
+    ) -> Result<usize, CodecError> {
+        self.input.digest_events(max, buf, reader)
+    }
+
+    /// Measure the temporal jitter of each pixel: the standard deviation of the intervals between
+    /// its events, over the rest of the stream.
+    ///
+    /// Reads every event from the current position to the end of the stream in a single pass,
+    /// accumulating the variance online. Empty events carry no integration, so they don't
+    /// count as intervals. Pixels with fewer than two intervals have a jitter of 0.
+    ///
+    /// Intervals are measured as in a [`TimeMode::DeltaT`] stream, whatever the stream's time
+    /// mode, so a framed pixel's interval starts at the reference interval boundary after its
+    /// previous event. Returns an array of shape (height, width, channels).
+    ///
+    /// # Errors
+    /// * If an event cannot be decoded
+    /// * [`CodecError::CoordOutOfBounds`] if an event's coordinate lies outside the plane
+    pub fn temporal_jitter(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Array3<f32>, CodecError> {
+        let meta = *self.meta();
+        let shape = (
+            meta.plane.h_usize(),
+            meta.plane.w_usize(),
+            meta.plane.c_usize(),
+        );
+
+        // Welford's algorithm: (count, mean, sum of squared differences from the mean)
+        let mut stats: Array3<(u64, f64, f64)> = Array3::from_elem(shape, (0, 0.0, 0.0));
+        let mut clock = PixelClock::new(&meta);
+
+        loop {
+            let event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            let start = clock.anchor(event.coord).unwrap_or_default();
+            let Some(t) = clock.advance(&event)? else {
+                continue;
+            };
+            if event.d == D_EMPTY {
+                continue;
+            }
+
+            let (count, mean, m2) = &mut stats[[
+                event.coord.y_usize(),
+                event.coord.x_usize(),
+                event.coord.c_usize(),
+            ]];
+            let interval = t.saturating_sub(start) as f64;
+            *count += 1;
+            let delta = interval - *mean;
+            *mean += delta / *count as f64;
+            *m2 += delta * (interval - *mean);
+        }
+
+        Ok(stats.mapv(|(count, _, m2)| {
+            if count < 2 {
+                0.0
+            } else {
+                (m2 / count as f64).sqrt() as f32
+            }
+        }))
+    }
+
+    /// Check that the events in the rest of the stream agree with the parameters in its header.
+    ///
+    /// Reports every event whose Δt exceeds the header's `delta_t_max`, whose `d` exceeds
+    /// [`D_MAX`], or whose coordinate lies outside the header's plane. An event may be reported
+    /// more than once if it has several problems. Decoding continues to the end of the stream
+    /// rather than stopping at the first inconsistency.
+    ///
+    /// # Errors
+    /// Returns an error if an event cannot be decoded.
+    pub fn audit_header_consistency(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Vec<Inconsistency>, CodecError> {
+        let meta = *self.meta();
+        let plane = meta.plane;
+        let mut last_t: Array3<DeltaT> =
+            Array3::zeros((plane.h_usize(), plane.w_usize(), plane.c_usize()));
+
+        let mut inconsistencies = Vec::new();
+        let mut index: u64 = 0;
+        loop {
+            let event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            audit_event(&meta, &mut last_t, index, event, &mut inconsistencies);
+            index += 1;
+        }
+
+        Ok(inconsistencies)
+    }
+
+    /// Scan the whole stream, from just after its header to its end, and report the first event
+    /// which is out of bounds for the header (as in [`Decoder::audit_header_consistency`]) or
+    /// which is cut off by the end of the stream. Leaves the reader at the end of the stream.
+    ///
+    /// # Errors
+    /// Returns an error if the reader isn't just after the header and can't be moved there (as
+    /// for a stream with varint-encoded timestamps), or an event can't be decoded for any reason
+    /// other than the stream ending.
+    pub fn validate(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<ValidationReport, CodecError> {
+        let meta = *self.meta();
+        let plane = meta.plane;
+        if self.get_input_stream_position(reader)? != meta.header_size as u64 {
+            self.set_input_stream_position(reader, meta.header_size as u64)?;
+        }
+        let mut last_t: Array3<DeltaT> =
+            Array3::zeros((plane.h_usize(), plane.w_usize(), plane.c_usize()));
+
+        let mut report = ValidationReport {
+            events_scanned: 0,
+            first_violation: None,
+        };
+        let mut inconsistencies = Vec::new();
+        loop {
+            let offset = self.get_input_stream_position(reader)?;
+            let index = report.events_scanned;
+            let event = match end_of_stream(self.digest_event(reader), false) {
+                Ok(Some(event)) => event,
+                Ok(None) => break,
+                Err(CodecError::Truncated) => {
+                    report.first_violation.get_or_insert(Violation {
+                        offset,
+                        index,
+                        kind: ViolationKind::Truncated,
+                    });
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            report.events_scanned += 1;
+
+            if report.first_violation.is_none() {
+                audit_event(&meta, &mut last_t, index, event, &mut inconsistencies);
+                if let Some(inconsistency) = inconsistencies.first() {
+                    report.first_violation = Some(Violation {
+                        offset,
+                        index,
+                        kind: ViolationKind::Inconsistent(*inconsistency),
+                    });
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Export the rest of the stream to an HDF5 file, for use with scientific tooling.
+    ///
+    /// The events are written to the `events` group as the separate, compressed datasets `x`,
+    /// `y`, `c`, `d`, and `t`, following the one-dataset-per-field layout common to event camera
+    /// recordings. Events without a channel are given `c = 0`. Timestamps are written as they are
+    /// stored, so they are Δt values if the stream's `time_mode` is [`TimeMode::DeltaT`]. The
+    /// header parameters are written as attributes of the `events` group.
+    ///
+    /// Rebase events aren't pixel events, so they're written to the separate datasets
+    /// `rebase_index` and `rebase_offset` instead: each rebase's offset applies to the events from
+    /// `rebase_index` onward.
+    ///
+    /// # Errors
+    /// Returns an error if an event cannot be decoded or the HDF5 file cannot be written.
+    #[cfg(feature = "hdf5")]
+    pub fn write_hdf5<P: AsRef<Path>>(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        path: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let meta = *self.meta();
+
+        let (mut x, mut y, mut c, mut d, mut t) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let (mut rebase_index, mut rebase_offset): (Vec<u64>, Vec<DeltaT>) =
+            (Vec::new(), Vec::new());
+        loop {
+            let event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e.into()),
+            };
+            if event.coord.is_reserved() {
+                if event.coord.is_rebase() {
+                    rebase_index.push(x.len() as u64);
+                    rebase_offset.push(event.t);
+                }
+                continue;
+            }
+            x.push(event.coord.x);
+            y.push(event.coord.y);
+            c.push(event.coord.c.unwrap_or(0));
+            d.push(event.d);
+            t.push(event.t);
+        }
+
+        let file = hdf5::File::create(path)?;
+        let group = file.create_group("events")?;
+        write_hdf5_dataset(&group, "x", &x)?;
+        write_hdf5_dataset(&group, "y", &y)?;
+        write_hdf5_dataset(&group, "c", &c)?;
+        write_hdf5_dataset(&group, "d", &d)?;
+        write_hdf5_dataset(&group, "t", &t)?;
+        write_hdf5_dataset(&group, "rebase_index", &rebase_index)?;
+        write_hdf5_dataset(&group, "rebase_offset", &rebase_offset)?;
+
+        let time_mode: u8 = match meta.time_mode {
+            TimeMode::DeltaT => 0,
+            TimeMode::AbsoluteT => 1,
+            TimeMode::Mixed => 2,
+        };
+        group
+            .new_attr::<u8>()
+            .create("codec_version")?
+            .write_scalar(&meta.codec_version)?;
+        group
+            .new_attr::<u8>()
+            .create("time_mode")?
+            .write_scalar(&time_mode)?;
+        group
+            .new_attr::<u16>()
+            .create("width")?
+            .write_scalar(&meta.plane.width)?;
+        group
+            .new_attr::<u16>()
+            .create("height")?
+            .write_scalar(&meta.plane.height)?;
+        group
+            .new_attr::<u8>()
+            .create("channels")?
+            .write_scalar(&meta.plane.channels)?;
+        group
+            .new_attr::<DeltaT>()
+            .create("tps")?
+            .write_scalar(&meta.tps)?;
+        group
+            .new_attr::<DeltaT>()
+            .create("ref_interval")?
+            .write_scalar(&meta.ref_interval)?;
+        group
+            .new_attr::<DeltaT>()
+            .create("delta_t_max")?
+            .write_scalar(&meta.delta_t_max)?;
+
+        Ok(())
+    }
+
+    /// Export up to `max_events` of the rest of the stream to an ASCII PLY point cloud, for
+    /// viewing the events in space-time with tools like MeshLab or CloudCompare.
+    ///
+    /// Each event is a vertex at its pixel's `x` and `y`, with `z` being its absolute timestamp
+    /// in units of the reference interval, so that one source frame spans one unit, like one
+    /// pixel does. Vertices are colored by `d`, from blue at 0 to red at the largest `d` among
+    /// the exported events. Events with a special `d` (e.g., [`D_EMPTY`]) are gray. Rebase events
+    /// aren't exported, but their offsets are included in the timestamps.
+    ///
+    /// Returns the number of vertices written.
+    ///
+    /// # Errors
+    /// * If an event cannot be decoded
+    /// * [`CodecError::CoordOutOfBounds`] if an event lies outside the plane
+    /// * If the PLY file cannot be written
+    pub fn write_ply<P: AsRef<Path>>(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        path: P,
+        max_events: usize,
+    ) -> Result<usize, CodecError> {
+        let meta = *self.meta();
+        let ref_interval = BigT::from(meta.ref_interval.max(1));
+
+        let mut clock = PixelClock::new(&meta);
+        let mut vertices: Vec<(Event, BigT)> = Vec::new();
+        while vertices.len() < max_events {
+            let event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            if let Some(t) = clock.advance(&event)? {
+                vertices.push((event, t));
+            }
+        }
+
+        let max_d = vertices
+            .iter()
+            .map(|(event, _)| event.d)
+            .filter(|&d| d <= D_MAX)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+        writeln!(writer, "ply")?;
+        writeln!(writer, "format ascii 1.0")?;
+        writeln!(writer, "comment ADΔER events: x, y, t / ref_interval")?;
+        writeln!(writer, "element vertex {}", vertices.len())?;
+        for property in ["float x", "float y", "float z"] {
+            writeln!(writer, "property {property}")?;
+        }
+        for property in ["uchar red", "uchar green", "uchar blue"] {
+            writeln!(writer, "property {property}")?;
+        }
+        writeln!(writer, "end_header")?;
+        for (event, absolute) in &vertices {
+            let (red, green, blue) = if event.d <= D_MAX {
+                let red = (u16::from(event.d) * 255 / u16::from(max_d)) as u8;
+                (red, 0, 255 - red)
+            } else {
+                (128, 128, 128)
+            };
+            writeln!(
+                writer,
+                "{} {} {} {red} {green} {blue}",
+                event.coord.x,
+                event.coord.y,
+                *absolute as f64 / ref_interval as f64
+            )?;
+        }
+        writer.flush()?;
+
+        Ok(vertices.len())
+    }
+
+    /// Re-encode the rest of the stream as a raw stream in the `target` time mode, converting
+    /// each event's timestamp between Δt and absolute time. The new stream's header is otherwise
+    /// the same as this one's.
+    ///
+    /// Timestamps are tracked per pixel. For framed sources, a pixel's time is rounded up to the
+    /// next multiple of `ref_interval` after each of its events, as in the framer. Rebase events
+    /// are copied to the new stream.
+    ///
+    /// Returns the writer, after the new stream is closed.
+    ///
+    /// # Errors
+    /// * If the target is [`TimeMode::Mixed`]
+    /// * If an event cannot be decoded or written
+    /// * [`CodecError::CoordOutOfBounds`] if an event lies outside the plane
+    pub fn convert_time_mode<W: Write + 'static>(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        target: TimeMode,
+        writer: W,
+    ) -> Result<Option<W>, CodecError> {
+        if target == TimeMode::Mixed {
+            return Err(CodecError::UnsupportedTimeMode(target));
+        }
+        let mut meta = *self.meta();
+        let mut clock = PixelClock::new(&meta);
+
+        // Time modes are only recorded in the header from version 2
+        meta.codec_version = meta.codec_version.max(2);
+        meta.time_mode = target;
+        let mut encoder = Encoder::new_raw(
+            RawOutput::new(meta, writer),
+            EncoderOptions::default(meta.plane),
+        );
+
+        loop {
+            let mut event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            let start = clock.anchor(event.coord).unwrap_or_default();
+            let Some(t) = clock.advance(&event)? else {
+                if event.coord.is_rebase() {
+                    encoder.rebase(event.t)?;
+                }
+                continue;
+            };
+
+            let t = match target {
+                TimeMode::AbsoluteT => t - clock.time_base(),
+                _ => t.saturating_sub(start),
+            };
+            event.t = DeltaT::try_from(t).unwrap_or(DeltaT::MAX);
+            encoder.ingest_event(event)?;
+        }
+
+        encoder.close_writer()
+    }
+
+    /// Seek to the first event in the stream whose absolute timestamp is at least `t`, so that
+    /// it's the next event decoded. Returns the byte position of that event. If no event
+    /// reaches `t`, the stream is left at its end, and the end position is returned.
+    ///
+    /// For [`TimeMode::DeltaT`] streams, each event's timestamp is only known relative to its
+    /// pixel's previous event, so the scan always starts from the first event and accumulates
+    /// each pixel's Δt, as in [`Decoder::count_events_in_range`]. Events of different pixels
+    /// aren't globally ordered in time, so this finds the first event, in stream order, to cross
+    /// `t`. Earlier events of other pixels may still lie after `t`. [`TimeMode::AbsoluteT`]
+    /// timestamps are compared directly. Either way, rebase offsets are included.
+    ///
+    /// # Errors
+    /// * If the stream doesn't support seeking (e.g., its timestamps are varint-encoded)
+    /// * If an event cannot be decoded
+    /// * [`CodecError::CoordOutOfBounds`] if an event lies outside the plane
+    pub fn seek_to_time(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        t: BigT,
+    ) -> Result<u64, CodecError> {
+        let meta = *self.meta();
+        self.set_input_stream_position(reader, meta.header_size as u64)?;
+
+        let mut clock = PixelClock::new(&meta);
+        loop {
+            let pos = self.get_input_stream_position(reader)?;
+            let event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => {
+                    self.set_input_stream_position(reader, pos)?;
+                    return Ok(pos);
+                }
+                Err(e) => return Err(e),
+            };
+            if matches!(clock.advance(&event)?, Some(event_t) if event_t >= t) {
+                self.set_input_stream_position(reader, pos)?;
+                return Ok(pos);
+            }
+        }
+    }
+
+    /// Count the events in the rest of the stream whose absolute timestamp lies in
+    /// `[t_start, t_end)`, e.g. for a timeline display.
+    ///
+    /// ADΔER streams carry no seek index, so this scans every remaining event. Absolute
+    /// timestamps are tracked per pixel as in [`Decoder::convert_time_mode`], and include any
+    /// offset from rebase events.
+    ///
+    /// # Errors
+    /// * If an event cannot be decoded
+    /// * [`CodecError::CoordOutOfBounds`] if an event lies outside the plane
+    pub fn count_events_in_range(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        t_start: BigT,
+        t_end: BigT,
+    ) -> Result<u64, CodecError> {
+        let meta = *self.meta();
+        let mut clock = PixelClock::new(&meta);
+        let mut count = 0;
+        loop {
+            let event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            if matches!(clock.advance(&event)?, Some(t) if (t_start..t_end).contains(&t)) {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Filter background activity out of the rest of the stream, writing the events which pass
+    /// to `out`. An event passes only if a different pixel within `spatial_radius` pixels of it
+    /// (in both x and y, on any channel) fired within `temporal_window` ticks of it. Isolated
+    /// events, including those from hot pixels firing on their own, are dropped.
+    ///
+    /// The latest absolute firing time of each pixel is kept in a map, so each event only
+    /// checks its own neighborhood. Rebase events are passed through. For Δt streams, the
+    /// timestamps of the events which pass are re-expressed relative to each pixel's last
+    /// written event, so that `out` decodes to the same absolute times. `out` is not closed.
+    ///
+    /// Returns the number of events dropped.
+    ///
+    /// # Errors
+    /// * If an event cannot be decoded or written
+    /// * [`CodecError::CoordOutOfBounds`] if an event lies outside the plane
+    pub fn denoise_ba<W: Write + 'static>(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        out: &mut Encoder<W>,
+        spatial_radius: u16,
+        temporal_window: DeltaT,
+    ) -> Result<usize, CodecError> {
+        let meta = *self.meta();
+        let absolute_t = meta.codec_version >= 2 && meta.time_mode == TimeMode::AbsoluteT;
+        let radius = usize::from(spatial_radius);
+        let window = BigT::from(temporal_window);
+
+        // The time each pixel's next Δt is measured from, in the input and in `out`
+        let plane = meta.plane;
+        let mut clock = PixelClock::new(&meta);
+        let mut last_written_t: Array3<BigT> =
+            Array3::zeros((plane.h_usize(), plane.w_usize(), plane.c_usize()));
+
+        // The absolute time each pixel last fired, on any channel
+        let mut last_fired: Array2<Option<BigT>> =
+            Array2::from_elem((plane.h_usize(), plane.w_usize()), None);
+        let mut dropped = 0;
+        loop {
+            let mut event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            let Some(t) = clock.advance(&event)? else {
+                if event.coord.is_rebase() {
+                    let time_base = clock.time_base();
+                    last_written_t.mapv_inplace(|t| t.max(time_base));
+                    out.rebase(event.t)?;
+                }
+                continue;
+            };
+            let (x, y) = (event.coord.x_usize(), event.coord.y_usize());
+            let idx = [y, x, event.coord.c_usize()];
+
+            let mut supported = false;
+            for ny in y.saturating_sub(radius)..=(y + radius).min(plane.h_usize() - 1) {
+                for nx in x.saturating_sub(radius)..=(x + radius).min(plane.w_usize() - 1) {
+                    if (nx, ny) == (x, y) {
+                        continue;
+                    }
+                    if let Some(fired) = last_fired[[ny, nx]] {
+                        supported |= t.abs_diff(fired) <= window;
+                    }
+                }
+            }
+            last_fired[[y, x]] = Some(t);
+
+            if !supported {
+                dropped += 1;
+                continue;
+            }
+            if !absolute_t {
+                event.t =
+                    DeltaT::try_from(t.saturating_sub(last_written_t[idx])).unwrap_or(DeltaT::MAX);
+            }
+            last_written_t[idx] = clock.anchor(event.coord).unwrap_or(t);
+            out.ingest_event(event)?;
+        }
+
+        Ok(dropped)
+    }
+
+    /// Merge runs of events on the same pixel into fewer events, writing the result to `out`.
+    ///
+    /// Two consecutive events on a pixel with the same `d` and the same span of ticks integrate
+    /// twice the intensity over twice the span, so they are replaced by a single event with
+    /// `d + 1` spanning both. The intensity per tick is unchanged everywhere, so reconstruction is
+    /// preserved. A merge only happens if `d + 1` does not exceed [`D_MAX`], the merged span does
+    /// not exceed `delta_t_max`, and the first event's timestamp was not rounded up to a
+    /// reference interval boundary. Merged events may merge again with a following event of
+    /// matching `d` and span.
+    ///
+    /// Each pixel's latest event is held back until the pixel's next event shows whether it can
+    /// merge, so events keep their order on each pixel, but not across pixels. Held events are
+    /// written before each rebase event and at the end of the stream. `out` is not closed.
+    ///
+    /// Returns the number of events merged away.
+    ///
+    /// # Errors
+    /// * If an event cannot be decoded or written
+    /// * [`CodecError::CoordOutOfBounds`] if an event lies outside the plane
+    pub fn coalesce_events<W: Write + 'static>(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+        out: &mut Encoder<W>,
+    ) -> Result<usize, CodecError> {
+        let meta = *self.meta();
+        let absolute_t = meta.codec_version >= 2 && meta.time_mode == TimeMode::AbsoluteT;
+        let delta_t_max = BigT::from(meta.delta_t_max);
+        let plane = meta.plane;
+        let mut clock = PixelClock::new(&meta);
+
+        // The event held back on each pixel, with the times its span starts and ends
+        let mut pending: Array3<Option<(Event, BigT, BigT)>> =
+            Array3::from_elem((plane.h_usize(), plane.w_usize(), plane.c_usize()), None);
+
+        // Held events are always written under the base they were read under
+        let write_pending = |out: &mut Encoder<W>,
+                             time_base: BigT,
+                             (mut event, start, end): (Event, BigT, BigT)|
+         -> Result<(), CodecError> {
+            let t = if absolute_t {
+                end - time_base
+            } else {
+                end - start
+            };
+            event.t = DeltaT::try_from(t).unwrap_or(DeltaT::MAX);
+            out.ingest_event(event)
+        };
+        let flush_all = |out: &mut Encoder<W>,
+                         time_base: BigT,
+                         pending: &mut Array3<Option<(Event, BigT, BigT)>>|
+         -> Result<(), CodecError> {
+            let mut held: Vec<_> = pending.iter_mut().filter_map(Option::take).collect();
+            held.sort_by_key(|&(_, _, end)| end);
+            for entry in held {
+                write_pending(out, time_base, entry)?;
+            }
+            Ok(())
+        };
+
+        let mut merged = 0;
+        loop {
+            let event = match self.digest_event(reader) {
+                Ok(event) => event,
+                Err(CodecError::Eof) => break,
+                Err(e) => return Err(e),
+            };
+            if event.coord.is_rebase() {
+                flush_all(out, clock.time_base(), &mut pending)?;
+            }
+            let start = clock.anchor(event.coord).unwrap_or_default();
+            let Some(t) = clock.advance(&event)? else {
+                if event.coord.is_rebase() {
+                    out.rebase(event.t)?;
+                }
+                continue;
+            };
+            let idx = [
+                event.coord.y_usize(),
+                event.coord.x_usize(),
+                event.coord.c_usize(),
+            ];
+
+            let held = &mut pending[idx];
+            match *held {
+                Some((prev, prev_start, prev_end))
+                    if prev.d == event.d
+                        && prev.d < D_MAX
+                        && prev_end == start
+                        && t >= start
+                        && t - start == prev_end - prev_start
+                        && t - prev_start <= delta_t_max =>
+                {
+                    *held = Some((
+                        Event {
+                            d: prev.d + 1,
+                            ..prev
+                        },
+                        prev_start,
+                        t,
+                    ));
+                    merged += 1;
+                }
+                _ => {
+                    if let Some(entry) = held.replace((event, start, t)) {
+                        write_pending(out, clock.time_base(), entry)?;
+                    }
+                }
+            }
+        }
+        flush_all(out, clock.time_base(), &mut pending)?;
+
+        Ok(merged)
+    }
+
     // Read and decode the next event from the input stream
     // #[cfg(feature = "compression")]
     // #[inline]
@@ -230,6 +1153,7 @@ impl<R: Read + Seek> Decoder<R> {
         reader: &mut BitReader<R, BigEndian>,
         position: u64,
     ) -> Result<(), CodecError> {
+        self.marker_read = false;
         self.input.set_input_stream_position(reader, position)
     }
 
@@ -269,6 +1193,182 @@ impl<R: Read + Seek> Decoder<R> {
     }
 }
 
+/// Iterator over the events of a stream, returned by [`Decoder::events`]
+pub struct EventIter<'a, R: Read + Seek> {
+    decoder: &'a mut Decoder<R>,
+    reader: &'a mut BitReader<R, BigEndian>,
+    done: bool,
+}
+
+impl<R: Read + Seek> Iterator for EventIter<'_, R> {
+    type Item = Result<Event, CodecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.decoder.next_event(self.reader) {
+            Ok(Some(event)) => Some(Ok(event)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> std::iter::FusedIterator for EventIter<'_, R> {}
+
+/// The time of each pixel of a stream, advanced one event at a time. A pixel's next Δt is
+/// measured from its anchor: the time of its previous event, rounded up to the next multiple of
+/// `ref_interval` for framed sources, as in the framer. All times are absolute, including the
+/// offsets of the rebase events seen so far.
+struct PixelClock {
+    absolute_t: bool,
+    framed_source: bool,
+    ref_interval: BigT,
+    plane: PlaneSize,
+    anchors: Array3<BigT>,
+    time_base: BigT,
+}
+
+impl PixelClock {
+    fn new(meta: &CodecMetadata) -> Self {
+        let plane = meta.plane;
+        Self {
+            absolute_t: meta.codec_version >= 2 && meta.time_mode == TimeMode::AbsoluteT,
+            framed_source: meta.codec_version >= 1 && is_framed(meta.source_camera),
+            ref_interval: BigT::from(meta.ref_interval.max(1)),
+            plane,
+            anchors: Array3::zeros((plane.h_usize(), plane.w_usize(), plane.c_usize())),
+            time_base: 0,
+        }
+    }
+
+    /// The time the pixel's next Δt is measured from, or `None` if it lies outside the plane
+    fn anchor(&self, coord: Coord) -> Option<BigT> {
+        self.anchors
+            .get([coord.y_usize(), coord.x_usize(), coord.c_usize()])
+            .copied()
+    }
+
+    /// The sum of the offsets of the rebase events seen so far
+    fn time_base(&self) -> BigT {
+        self.time_base
+    }
+
+    /// Advance the pixel of `event` past it, and return the event's absolute time. Reserved
+    /// events (e.g., rebase events) have no time, so return `None`, but a rebase moves the base
+    /// forward, along with any anchor behind it.
+    ///
+    /// # Errors
+    /// [`CodecError::CoordOutOfBounds`] if the event lies outside the plane
+    fn advance(&mut self, event: &Event) -> Result<Option<BigT>, CodecError> {
+        let coord = event.coord;
+        if coord.is_reserved() {
+            if coord.is_rebase() {
+                self.time_base += BigT::from(event.t);
+                let time_base = self.time_base;
+                self.anchors.mapv_inplace(|t| t.max(time_base));
+            }
+            return Ok(None);
+        }
+        let Some(anchor) =
+            self.anchors
+                .get_mut([coord.y_usize(), coord.x_usize(), coord.c_usize()])
+        else {
+            return Err(CodecError::CoordOutOfBounds(
+                coord.x,
+                coord.y,
+                self.plane.width,
+                self.plane.height,
+            ));
+        };
+
+        let t = if self.absolute_t {
+            self.time_base + BigT::from(event.t)
+        } else {
+            *anchor + BigT::from(event.t)
+        };
+        *anchor = if self.framed_source && t % self.ref_interval > 0 {
+            (t / self.ref_interval + 1) * self.ref_interval
+        } else {
+            t
+        };
+        Ok(Some(t))
+    }
+}
+
+/// Check one event against the stream's header, tracking each pixel's last timestamp in `last_t`,
+/// and push any inconsistencies found to `out`. Reserved events (e.g., rebase events) aren't pixel
+/// events, so they're never inconsistent, but a rebase offsets the tracked timestamps.
+fn audit_event(
+    meta: &CodecMetadata,
+    last_t: &mut Array3<DeltaT>,
+    index: u64,
+    event: Event,
+    out: &mut Vec<Inconsistency>,
+) {
+    if event.coord.is_reserved() {
+        if event.coord.is_rebase() {
+            let offset = event.t;
+            last_t.mapv_inplace(|t| t.saturating_sub(offset));
+        }
+        return;
+    }
+
+    let plane = meta.plane;
+    if event.d > D_MAX && !matches!(event.d, D_EMPTY | D_ZERO_INTEGRATION | D_NO_EVENT) {
+        out.push(Inconsistency::DExceedsMax { index, event });
+    }
+
+    let coord = event.coord;
+    if coord.x >= plane.width || coord.y >= plane.height || coord.c_usize() >= plane.c_usize() {
+        // Without a valid coordinate, there's no previous event to measure Δt from
+        out.push(Inconsistency::CoordOutOfBounds { index, event });
+    } else {
+        let delta_t = if meta.codec_version >= 2 && meta.time_mode == TimeMode::AbsoluteT {
+            let idx = [coord.y_usize(), coord.x_usize(), coord.c_usize()];
+            let delta_t = event.t.saturating_sub(last_t[idx]);
+            last_t[idx] = event.t;
+            delta_t
+        } else {
+            event.t
+        };
+        if delta_t > meta.delta_t_max {
+            out.push(Inconsistency::DeltaTExceedsMax {
+                index,
+                event,
+                delta_t,
+            });
+        }
+    }
+}
+
+/// Write a one-dimensional dataset to the group, chunked and compressed
+#[cfg(feature = "hdf5")]
+fn write_hdf5_dataset<T: hdf5::H5Type>(
+    group: &hdf5::Group,
+    name: &str,
+    data: &[T],
+) -> hdf5::Result<()> {
+    let builder = group.new_dataset_builder().with_data(data);
+    if data.is_empty() {
+        // A chunk can't be larger than a fixed-size dataset
+        builder.create(name)?;
+    } else {
+        builder
+            .chunk(data.len().min(HDF5_CHUNK_LEN))
+            .deflate(4)
+            .create(name)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,8 +1377,10 @@ mod tests {
     use crate::codec::raw::stream::{RawInput, RawOutput};
 
     use crate::codec::rate_controller::Crf;
-    use crate::codec::{EncoderOptions, EventOrder};
-    use crate::Coord;
+    use crate::codec::{
+        DeltaTEncoding, EncoderOptions, Endianness, EventOrder, LATEST_CODEC_VERSION,
+    };
+    use crate::{Coord, TimeMode};
     use std::io::{BufReader, BufWriter, Cursor, Write};
 
     fn stock_event() -> Event {
@@ -309,6 +1411,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -346,6 +1451,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -362,6 +1470,10 @@ mod tests {
                         channels: 1,
                     },
                 ),
+                intra_period: None,
+                degradation_block_size: None,
+                enforce_delta_t_max: false,
+                rebase_period: None,
             },
         );
 
@@ -393,6 +1505,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -426,6 +1541,7 @@ mod tests {
         let mut bitreader = BitReader::endian(bufreader, BigEndian);
         let reader = Decoder::new_raw(compression, &mut bitreader).unwrap();
         assert_eq!(reader.input.meta().header_size, 25);
+        assert_eq!(reader.version(), 0);
     }
 
     #[test]
@@ -452,6 +1568,248 @@ mod tests {
         assert_eq!(reader.input.meta().header_size, 33);
     }
 
+    #[test]
+    fn header_unsupported_version() {
+        assert_eq!(
+            supported_codec_versions().last(),
+            Some(&LATEST_CODEC_VERSION)
+        );
+        for &version in supported_codec_versions() {
+            let output = setup_encoded_raw(version);
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*output)), BigEndian);
+            let reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            assert_eq!(reader.meta().codec_version, version);
+        }
+
+        // The version byte follows the 5-byte magic number
+        let mut output = setup_encoded_raw(LATEST_CODEC_VERSION);
+        output[5] = LATEST_CODEC_VERSION + 1;
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*output)), BigEndian);
+        match Decoder::new_raw(RawInput::new(), &mut bitreader) {
+            Err(e @ CodecError::UnsupportedVersion(version)) => {
+                assert_eq!(version, LATEST_CODEC_VERSION + 1);
+                assert!(e
+                    .to_string()
+                    .contains(&format!("{:?}", supported_codec_versions())));
+            }
+            _ => panic!("expected an unsupported version error"),
+        }
+    }
+
+    #[test]
+    fn temporal_jitter() {
+        let plane = PlaneSize::new(2, 1, 1).unwrap();
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    time_mode: TimeMode::DeltaT,
+                    ..Default::default()
+                },
+                BufWriter::new(Vec::new()),
+            ),
+            EncoderOptions::default(plane),
+        );
+
+        // Pixel (0, 0) fires at perfectly regular intervals. Pixel (1, 0) alternates.
+        for i in 0..10 {
+            for (x, t) in [(0, 100), (1, if i % 2 == 0 { 50 } else { 150 })] {
+                encoder
+                    .ingest_event(Event {
+                        coord: Coord { x, y: 0, c: None },
+                        d: 7,
+                        t,
+                    })
+                    .unwrap();
+            }
+        }
+        let output = encoder
+            .close_writer()
+            .unwrap()
+            .unwrap()
+            .into_inner()
+            .unwrap();
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let jitter = reader.temporal_jitter(&mut bitreader).unwrap();
+
+        assert_eq!(jitter.dim(), (1, 2, 1));
+        assert!(jitter[[0, 0, 0]].abs() < 1e-6);
+        assert!((jitter[[0, 1, 0]] - 50.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn temporal_jitter_rebase_and_bounds() {
+        let encode = |plane: PlaneSize, rebase_at: Option<u32>, events: &[Event]| -> Vec<u8> {
+            let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        plane,
+                        time_mode: TimeMode::AbsoluteT,
+                        ..Default::default()
+                    },
+                    BufWriter::new(Vec::new()),
+                ),
+                EncoderOptions::default(plane),
+            );
+            for (i, event) in events.iter().enumerate() {
+                if rebase_at == Some(i as u32) {
+                    encoder.rebase(500).unwrap();
+                }
+                encoder.ingest_event(*event).unwrap();
+            }
+            encoder
+                .close_writer()
+                .unwrap()
+                .unwrap()
+                .into_inner()
+                .unwrap()
+        };
+        let event = |x, t| Event {
+            coord: Coord { x, y: 0, c: None },
+            d: 7,
+            t,
+        };
+
+        // Regular intervals of one reference interval, with the timestamps after the rebase given
+        // relative to the new base
+        let events: Vec<Event> = (1..=10)
+            .map(|k| event(0, if k <= 5 { 255 * k } else { 255 * k - 500 }))
+            .collect();
+        let output = encode(PlaneSize::new(1, 1, 1).unwrap(), Some(5), &events);
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let jitter = reader.temporal_jitter(&mut bitreader).unwrap();
+        assert_eq!(jitter.dim(), (1, 1, 1));
+        assert!(jitter[[0, 0, 0]].abs() < 1e-6);
+
+        // An event outside the declared plane is an error, rather than a panic
+        let declared = encode(PlaneSize::new(2, 1, 1).unwrap(), None, &[]);
+        let wide = encode(PlaneSize::new(4, 1, 1).unwrap(), None, &[event(3, 100)]);
+        let mut bitreader =
+            BitReader::endian(BufReader::new(Cursor::new(declared.clone())), BigEndian);
+        let header_size = Decoder::new_raw(RawInput::new(), &mut bitreader)
+            .unwrap()
+            .meta()
+            .header_size;
+        let mut output = declared[..header_size].to_vec();
+        output.extend_from_slice(&wide[header_size..]);
+        let mut bitreader =
+            BitReader::endian(BufReader::new(Cursor::new(output.clone())), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert!(matches!(
+            reader.temporal_jitter(&mut bitreader),
+            Err(CodecError::CoordOutOfBounds(3, 0, 2, 1))
+        ));
+
+        // The other per-pixel scans share the same clock, and so the same error
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert!(matches!(
+            reader.count_events_in_range(&mut bitreader, 0, BigT::MAX),
+            Err(CodecError::CoordOutOfBounds(3, 0, 2, 1))
+        ));
+    }
+
+    #[test]
+    fn audit_header_consistency() {
+        let encode = |plane: PlaneSize, events: &[Event]| -> Vec<u8> {
+            let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        plane,
+                        time_mode: TimeMode::DeltaT,
+                        delta_t_max: 255,
+                        ..Default::default()
+                    },
+                    BufWriter::new(Vec::new()),
+                ),
+                EncoderOptions::default(plane),
+            );
+            encoder.ingest_events(events).unwrap();
+            encoder
+                .close_writer()
+                .unwrap()
+                .unwrap()
+                .into_inner()
+                .unwrap()
+        };
+
+        let event = |x, d, t| Event {
+            coord: Coord { x, y: 0, c: None },
+            d,
+            t,
+        };
+        let events = [
+            event(0, 7, 100),
+            event(1, 200, 100), // d too large
+            event(0, 7, 300),   // Δt too large
+            event(3, 7, 100),   // outside the declared plane
+            event(1, D_EMPTY, 255),
+            Event::rebase(500), // not a pixel event, so neither its coordinate nor offset count
+        ];
+
+        // The encoder won't write coordinates outside its own plane, so encode the events for a
+        // wider plane and splice them onto the header of a narrower one
+        let declared = encode(PlaneSize::new(2, 1, 1).unwrap(), &[]);
+        let wide = encode(PlaneSize::new(4, 1, 1).unwrap(), &events);
+        let mut bitreader =
+            BitReader::endian(BufReader::new(Cursor::new(declared.clone())), BigEndian);
+        let header_size = Decoder::new_raw(RawInput::new(), &mut bitreader)
+            .unwrap()
+            .meta()
+            .header_size;
+        let mut output = declared[..header_size].to_vec();
+        output.extend_from_slice(&wide[header_size..]);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let inconsistencies = reader.audit_header_consistency(&mut bitreader).unwrap();
+
+        assert_eq!(
+            inconsistencies,
+            vec![
+                Inconsistency::DExceedsMax {
+                    index: 1,
+                    event: events[1]
+                },
+                Inconsistency::DeltaTExceedsMax {
+                    index: 2,
+                    event: events[2],
+                    delta_t: 300
+                },
+                Inconsistency::CoordOutOfBounds {
+                    index: 3,
+                    event: events[3]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn format_info_raw() {
+        let output = setup_encoded_raw(LATEST_CODEC_VERSION);
+        let tmp = Cursor::new(&*output);
+        let bufreader = BufReader::new(tmp);
+        let compression = RawInput::new();
+
+        let mut bitreader = BitReader::endian(bufreader, BigEndian);
+        let reader = Decoder::new_raw(compression, &mut bitreader).unwrap();
+        assert_eq!(reader.version(), LATEST_CODEC_VERSION);
+        assert_eq!(
+            reader.format_info(),
+            FormatInfo {
+                codec_version: LATEST_CODEC_VERSION,
+                endianness: Endianness::Big,
+                compressed: false,
+                checksummed: false,
+                event_size: 9,
+                time_mode: TimeMode::AbsoluteT,
+            }
+        );
+    }
+
     #[test]
     #[cfg(feature = "compression")]
     fn header_v0_compressed() {
@@ -503,4 +1861,801 @@ mod tests {
         let event = reader.digest_event(&mut bitreader).unwrap();
         assert_eq!(event, stock_event());
     }
+
+    fn encode_raw_events(delta_t_encoding: DeltaTEncoding, events: &[Event]) -> Vec<u8> {
+        let compression = RawOutput::new(
+            CodecMetadata {
+                codec_version: LATEST_CODEC_VERSION,
+                header_size: 0,
+                time_mode: TimeMode::DeltaT,
+                plane: PlaneSize {
+                    width: 100,
+                    height: 100,
+                    channels: 1,
+                },
+                tps: 7650,
+                ref_interval: 255,
+                delta_t_max: 255 * 30,
+                event_size: 0,
+                source_camera: Default::default(),
+                adu_interval: 1,
+                delta_t_encoding,
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
+            },
+            BufWriter::new(Vec::new()),
+        );
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            compression,
+            EncoderOptions::default(PlaneSize {
+                width: 100,
+                height: 100,
+                channels: 1,
+            }),
+        );
+        encoder.ingest_events(events).unwrap();
+        let mut writer = encoder.close_writer().unwrap().unwrap();
+        writer.flush().unwrap();
+        writer.into_inner().unwrap()
+    }
+
+    #[test]
+    fn digest_events_into() {
+        let events: Vec<Event> = (0..40_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i,
+                    y: 0,
+                    c: None,
+                },
+                d: 7,
+                t: 100 + u32::from(i),
+            })
+            .collect();
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut buf = [Event::default(); 16];
+        let mut decoded = Vec::new();
+        let mut counts = Vec::new();
+        loop {
+            let count = reader.digest_events_into(&mut bitreader, &mut buf).unwrap();
+            if count == 0 {
+                break;
+            }
+            counts.push(count);
+            decoded.extend_from_slice(&buf[..count]);
+        }
+
+        assert_eq!(counts, vec![16, 16, 8]);
+        assert_eq!(decoded, events);
+    }
+
+    #[test]
+    fn events() {
+        let events: Vec<Event> = (0..40_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i,
+                    y: 0,
+                    c: None,
+                },
+                d: 7,
+                t: 100 + u32::from(i),
+            })
+            .collect();
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let decoded: Vec<Event> = reader
+            .events(&mut bitreader)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, events);
+        assert_eq!(reader.events(&mut bitreader).count(), 0);
+
+        // Lazily decodes only what's consumed
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let header_size = reader.meta().header_size as u64;
+        let event_size = u64::from(reader.meta().event_size);
+        let late = reader
+            .events(&mut bitreader)
+            .map(Result::unwrap)
+            .find(|event| event.t >= 110)
+            .unwrap();
+        assert_eq!(late.coord.x, 10);
+        assert_eq!(
+            reader.get_input_stream_position(&mut bitreader).unwrap(),
+            header_size + 11 * event_size
+        );
+    }
+
+    #[test]
+    fn truncated_stream() {
+        let events: Vec<Event> = (0..20_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i,
+                    y: 0,
+                    c: None,
+                },
+                d: 7,
+                t: 100,
+            })
+            .collect();
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let header_size = reader.meta().header_size as usize;
+        let event_size = reader.meta().event_size as usize;
+
+        // Cut off partway through an event, and cleanly after one but without the marker
+        for len in [10 * event_size + 3, 10 * event_size] {
+            let truncated = &bytes[..header_size + len];
+
+            let mut bitreader =
+                BitReader::endian(BufReader::new(Cursor::new(truncated)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            let decoded: Vec<_> = reader.events(&mut bitreader).collect();
+            assert_eq!(decoded.len(), 11);
+            assert!(matches!(decoded[10], Err(CodecError::Truncated)));
+
+            let mut bitreader =
+                BitReader::endian(BufReader::new(Cursor::new(truncated)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            let mut buf = [Event::default(); 16];
+            assert!(matches!(
+                reader.digest_events_into(&mut bitreader, &mut buf),
+                Err(CodecError::Truncated)
+            ));
+        }
+    }
+
+    #[test]
+    fn digest_events() {
+        let events: Vec<Event> = (0..40_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i,
+                    y: 0,
+                    c: None,
+                },
+                d: 7,
+                t: 100 + u32::from(i),
+            })
+            .collect();
+
+        for encoding in [DeltaTEncoding::Fixed, DeltaTEncoding::Varint] {
+            let bytes = encode_raw_events(encoding, &events);
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            let mut decoded = Vec::new();
+            let mut counts = Vec::new();
+            loop {
+                let count = reader
+                    .digest_events(&mut bitreader, 16, &mut decoded)
+                    .unwrap();
+                counts.push(count);
+                if count < 16 {
+                    break;
+                }
+            }
+            assert_eq!(counts, vec![16, 16, 8]);
+            assert_eq!(decoded, events);
+        }
+
+        // Cut the stream off partway through an event, with no end-of-stream marker
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let header_size = reader.meta().header_size as usize;
+        let event_size = reader.meta().event_size as usize;
+        let truncated = &bytes[..header_size + 10 * event_size + 3];
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(truncated)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut decoded = Vec::new();
+        assert_eq!(
+            reader
+                .digest_events(&mut bitreader, 16, &mut decoded)
+                .unwrap(),
+            10
+        );
+        assert_eq!(decoded, events[..10]);
+        assert_eq!(
+            reader.get_input_stream_position(&mut bitreader).unwrap(),
+            (header_size + 10 * event_size) as u64
+        );
+        assert_eq!(
+            reader
+                .digest_events(&mut bitreader, 16, &mut decoded)
+                .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let mut events: Vec<Event> = (0..20_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i,
+                    y: 0,
+                    c: None,
+                },
+                d: 7,
+                t: 100,
+            })
+            .collect();
+        let decode = |bytes: &[u8]| -> (ValidationReport, u64, u64) {
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(bytes)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            let meta = *reader.meta();
+            // Validation starts from the header, wherever the reader is
+            reader.digest_event(&mut bitreader).unwrap();
+            (
+                reader.validate(&mut bitreader).unwrap(),
+                meta.header_size as u64,
+                u64::from(meta.event_size),
+            )
+        };
+
+        let (report, _, _) = decode(&encode_raw_events(DeltaTEncoding::Fixed, &events));
+        assert_eq!(
+            report,
+            ValidationReport {
+                events_scanned: 20,
+                first_violation: None,
+            }
+        );
+
+        // A varint stream can't seek, but can be validated straight after opening
+        let bytes = encode_raw_events(DeltaTEncoding::Varint, &events);
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let report = reader.validate(&mut bitreader).unwrap();
+        assert_eq!(report.events_scanned, 20);
+        assert_eq!(report.first_violation, None);
+
+        // Only the first of several violations is reported, but every event is scanned
+        events[5].d = 200;
+        events[8].t = 255 * 31;
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+        let (report, header_size, event_size) = decode(&bytes);
+        assert_eq!(report.events_scanned, 20);
+        assert_eq!(
+            report.first_violation,
+            Some(Violation {
+                offset: header_size + 5 * event_size,
+                index: 5,
+                kind: ViolationKind::Inconsistent(Inconsistency::DExceedsMax {
+                    index: 5,
+                    event: events[5],
+                }),
+            })
+        );
+
+        // Cut the stream off partway through an event, with no end-of-stream marker
+        let truncated = &bytes[..(header_size + 12 * event_size + 3) as usize];
+        let (report, _, _) = decode(truncated);
+        assert_eq!(report.events_scanned, 12);
+        assert_eq!(report.first_violation.unwrap().index, 5);
+
+        events[5].d = 7;
+        events[8].t = 100;
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+        let truncated = &bytes[..(header_size + 12 * event_size + 3) as usize];
+        let (report, _, _) = decode(truncated);
+        assert_eq!(report.events_scanned, 12);
+        assert_eq!(
+            report.first_violation,
+            Some(Violation {
+                offset: header_size + 12 * event_size,
+                index: 12,
+                kind: ViolationKind::Truncated,
+            })
+        );
+    }
+
+    #[test]
+    fn seek_to_time() {
+        // Three pixels, each firing every reference interval
+        let events: Vec<Event> = (0..9_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i % 3,
+                    y: 0,
+                    c: None,
+                },
+                d: 7,
+                t: 255,
+            })
+            .collect();
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let header_size = reader.meta().header_size as u64;
+        let event_size = u64::from(reader.meta().event_size);
+
+        // The fourth event is the first to end at 510 ticks
+        let pos = reader.seek_to_time(&mut bitreader, 500).unwrap();
+        assert_eq!(pos, header_size + 3 * event_size);
+        assert_eq!(reader.digest_event(&mut bitreader).unwrap(), events[3]);
+
+        // Seeking back to the start works from anywhere in the stream
+        assert_eq!(reader.seek_to_time(&mut bitreader, 0).unwrap(), header_size);
+        assert_eq!(reader.digest_event(&mut bitreader).unwrap(), events[0]);
+
+        // No event reaches the time, so the stream is left at its end
+        let pos = reader.seek_to_time(&mut bitreader, 10_000).unwrap();
+        assert_eq!(pos, header_size + 9 * event_size);
+        assert!(matches!(
+            reader.digest_event(&mut bitreader),
+            Err(CodecError::Eof)
+        ));
+
+        // Varint-encoded streams can't be seeked
+        let bytes = encode_raw_events(DeltaTEncoding::Varint, &events);
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert!(matches!(
+            reader.seek_to_time(&mut bitreader, 500),
+            Err(CodecError::Seek)
+        ));
+    }
+
+    #[test]
+    fn write_ply() {
+        let events: Vec<Event> = (0..20_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i % 5,
+                    y: i / 5,
+                    c: None,
+                },
+                d: (i % 8) as u8,
+                t: 510,
+            })
+            .collect();
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let path = std::env::temp_dir().join("adder_write_ply_test.ply");
+        let written = reader.write_ply(&mut bitreader, &path, 12).unwrap();
+        assert_eq!(written, 12);
+
+        let ply = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut lines = ply.lines();
+        assert_eq!(lines.next(), Some("ply"));
+        let vertex_count: usize = ply
+            .lines()
+            .find_map(|line| line.strip_prefix("element vertex "))
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(vertex_count, 12);
+
+        let body: Vec<&str> = lines
+            .skip_while(|line| *line != "end_header")
+            .skip(1)
+            .collect();
+        assert_eq!(body.len(), 12);
+        let first: Vec<f64> = body[0]
+            .split_whitespace()
+            .map(|field| field.parse().unwrap())
+            .collect();
+        // x, y, and 510 ticks is 2 reference intervals. The first event has d = 0, so it's blue.
+        assert_eq!(first, vec![0.0, 0.0, 2.0, 0.0, 0.0, 255.0]);
+    }
+
+    #[test]
+    fn varint_delta_t_raw() {
+        let events: Vec<Event> = (0..200_u32)
+            .map(|i| Event {
+                coord: Coord {
+                    x: (i % 100) as u16,
+                    y: (i / 100) as u16,
+                    c: None,
+                },
+                d: (i % 12) as u8,
+                // Mostly small values, with an occasional large one
+                t: if i % 50 == 0 { 7650 * 30 + i } else { i % 120 },
+            })
+            .collect();
+
+        let fixed = encode_raw_events(DeltaTEncoding::Fixed, &events);
+        let varint = encode_raw_events(DeltaTEncoding::Varint, &events);
+        assert!(varint.len() < fixed.len());
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*varint)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert_eq!(reader.meta().delta_t_encoding, DeltaTEncoding::Varint);
+        for event in &events {
+            assert_eq!(reader.digest_event(&mut bitreader).unwrap(), *event);
+        }
+        assert!(matches!(
+            reader.digest_event(&mut bitreader),
+            Err(CodecError::Eof)
+        ));
+
+        // Fixed-stride seeking is disabled for varint streams
+        let header_size = reader.meta().header_size as u64;
+        assert!(reader
+            .set_input_stream_position(&mut bitreader, header_size)
+            .is_err());
+    }
+
+    #[test]
+    fn varint_overflow_rejected() {
+        let event = Event {
+            coord: Coord {
+                x: 1,
+                y: 1,
+                c: None,
+            },
+            d: 7,
+            t: DeltaT::MAX,
+        };
+        let mut bytes = encode_raw_events(DeltaTEncoding::Varint, &[event]);
+
+        // DeltaT::MAX takes all five bytes, with only the low 4 bits of the last one set
+        let max = [0xFF, 0xFF, 0xFF, 0xFF, 0x0F];
+        let pos = bytes.windows(5).position(|w| w == max).unwrap();
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert_eq!(reader.digest_event(&mut bitreader).unwrap(), event);
+
+        // Setting a bit above the top of a DeltaT in the fifth byte overflows
+        bytes[pos + 4] = 0x1F;
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert!(matches!(
+            reader.digest_event(&mut bitreader),
+            Err(CodecError::Deserialize)
+        ));
+    }
+
+    #[test]
+    fn count_events_in_range() {
+        let events: Vec<Event> = (0..80_u32)
+            .map(|i| Event {
+                coord: Coord {
+                    x: (i % 5) as u16,
+                    y: (i % 2) as u16,
+                    c: None,
+                },
+                d: 7,
+                t: 255 * (i % 4 + 1),
+            })
+            .collect();
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        // Accumulate each pixel's absolute timestamps by hand
+        let mut running = [[0_u64; 5]; 2];
+        let absolute: Vec<u64> = events
+            .iter()
+            .map(|event| {
+                let t = &mut running[event.coord.y as usize][event.coord.x as usize];
+                *t += u64::from(event.t);
+                *t
+            })
+            .collect();
+
+        let (t_start, t_end) = (2000, 5000);
+        let expected = absolute
+            .iter()
+            .filter(|t| (t_start..t_end).contains(*t))
+            .count() as u64;
+        assert!(expected > 0 && expected < events.len() as u64);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert_eq!(
+            reader
+                .count_events_in_range(&mut bitreader, t_start, t_end)
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn denoise_ba() {
+        let event = |x, y, t| Event {
+            coord: Coord { x, y, c: None },
+            d: 7,
+            t,
+        };
+        // A cluster of three neighboring events, and one far from anything else
+        let events = [
+            event(10, 10, 100),
+            event(50, 50, 105),
+            event(11, 10, 110),
+            event(10, 11, 120),
+        ];
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut out = Encoder::new_raw(
+            RawOutput::new(*reader.meta(), BufWriter::new(Vec::new())),
+            EncoderOptions::default(reader.meta().plane),
+        );
+        let dropped = reader.denoise_ba(&mut bitreader, &mut out, 1, 50).unwrap();
+        let output = out.close_writer().unwrap().unwrap().into_inner().unwrap();
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut kept = Vec::new();
+        while let Ok(event) = reader.digest_event(&mut bitreader) {
+            kept.push(event);
+        }
+
+        // The isolated event is removed. So is the first event of the cluster, since none of
+        // its neighbors had fired yet.
+        assert_eq!(dropped, 2);
+        assert_eq!(kept, vec![events[2], events[3]]);
+    }
+
+    #[test]
+    fn coalesce_events() {
+        let event = |d, t| Event {
+            coord: Coord {
+                x: 10,
+                y: 10,
+                c: None,
+            },
+            d,
+            t,
+        };
+        // Three identical events, on reference interval boundaries so none are rounded
+        let events = [event(7, 255), event(7, 255), event(7, 255)];
+        let bytes = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut out = Encoder::new_raw(
+            RawOutput::new(*reader.meta(), BufWriter::new(Vec::new())),
+            EncoderOptions::default(reader.meta().plane),
+        );
+        let merged = reader.coalesce_events(&mut bitreader, &mut out).unwrap();
+        let output = out.close_writer().unwrap().unwrap().into_inner().unwrap();
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut coalesced = Vec::new();
+        while let Ok(event) = reader.digest_event(&mut bitreader) {
+            coalesced.push(event);
+        }
+
+        // The first two merge. The merged event spans twice as long as the third, so the third
+        // is left alone.
+        assert_eq!(merged, 1);
+        assert_eq!(coalesced, vec![event(8, 510), event(7, 255)]);
+
+        // Every tick reconstructs to the same intensity as before
+        let per_tick = |events: &[Event]| -> Vec<f64> {
+            events
+                .iter()
+                .flat_map(|e| {
+                    let intensity = crate::d_shift(e.d).unwrap() as f64 / f64::from(e.t);
+                    std::iter::repeat(intensity).take(e.t as usize)
+                })
+                .collect()
+        };
+        assert_eq!(per_tick(&coalesced), per_tick(&events));
+    }
+
+    #[test]
+    fn convert_time_mode_round_trip() {
+        let events: Vec<Event> = (0..60_u32)
+            .map(|i| Event {
+                coord: Coord {
+                    x: (i % 7) as u16,
+                    y: (i % 3) as u16,
+                    c: None,
+                },
+                d: (i % 9) as u8,
+                // Some Δts are multiples of the reference interval, and some aren't
+                t: if i % 4 == 0 {
+                    255 * (i % 3 + 1)
+                } else {
+                    100 + i
+                },
+            })
+            .collect();
+        let original = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let convert = |bytes: &[u8], target: TimeMode| -> Vec<u8> {
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(bytes)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            let writer = reader
+                .convert_time_mode(&mut bitreader, target, BufWriter::new(Vec::new()))
+                .unwrap()
+                .unwrap();
+            writer.into_inner().unwrap()
+        };
+        let absolute = convert(&original, TimeMode::AbsoluteT);
+        assert_ne!(absolute, original);
+
+        // The absolute timestamps increase for each pixel
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*absolute)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert_eq!(reader.meta().time_mode, TimeMode::AbsoluteT);
+        let mut last_t = [[0; 7]; 3];
+        for event in &events {
+            let converted = reader.digest_event(&mut bitreader).unwrap();
+            let (x, y) = (event.coord.x as usize, event.coord.y as usize);
+            assert!(converted.t > last_t[y][x]);
+            last_t[y][x] = converted.t;
+        }
+
+        assert_eq!(convert(&absolute, TimeMode::DeltaT), original);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*original)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert!(matches!(
+            reader.convert_time_mode(&mut bitreader, TimeMode::Mixed, Vec::new()),
+            Err(CodecError::UnsupportedTimeMode(TimeMode::Mixed))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "hdf5")]
+    fn write_hdf5() {
+        let events: Vec<Event> = (0..50_u32)
+            .map(|i| Event {
+                coord: Coord {
+                    x: (i % 100) as u16,
+                    y: (i / 10) as u16,
+                    c: None,
+                },
+                d: (i % 12) as u8,
+                t: 255 + i,
+            })
+            .collect();
+        let encoded = encode_raw_events(DeltaTEncoding::Fixed, &events);
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*encoded)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let path = std::env::temp_dir().join("adder_write_hdf5_test.h5");
+        reader.write_hdf5(&mut bitreader, &path).unwrap();
+
+        let file = hdf5::File::open(&path).unwrap();
+        let x: Vec<u16> = file.dataset("events/x").unwrap().read_raw().unwrap();
+        let y: Vec<u16> = file.dataset("events/y").unwrap().read_raw().unwrap();
+        let c: Vec<u8> = file.dataset("events/c").unwrap().read_raw().unwrap();
+        let d: Vec<u8> = file.dataset("events/d").unwrap().read_raw().unwrap();
+        let t: Vec<DeltaT> = file.dataset("events/t").unwrap().read_raw().unwrap();
+        for len in [x.len(), y.len(), c.len(), d.len(), t.len()] {
+            assert_eq!(len, events.len());
+        }
+        for i in [0, 17, 49] {
+            assert_eq!(x[i], events[i].coord.x);
+            assert_eq!(y[i], events[i].coord.y);
+            assert_eq!(c[i], 0);
+            assert_eq!(d[i], events[i].d);
+            assert_eq!(t[i], events[i].t);
+        }
+        let rebase_index: Vec<u64> = file
+            .dataset("events/rebase_index")
+            .unwrap()
+            .read_raw()
+            .unwrap();
+        assert!(rebase_index.is_empty());
+
+        let group = file.group("events").unwrap();
+        let width: u16 = group.attr("width").unwrap().read_scalar().unwrap();
+        let tps: DeltaT = group.attr("tps").unwrap().read_scalar().unwrap();
+        assert_eq!(width, 100);
+        assert_eq!(tps, 7650);
+
+        drop(file);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "thumbnail")]
+    fn thumbnail() {
+        let plane = PlaneSize::new(128, 48, 3).unwrap();
+        let meta = CodecMetadata {
+            codec_version: LATEST_CODEC_VERSION,
+            plane,
+            ..Default::default()
+        };
+        let frame = Array3::from_shape_fn((48, 128, 3), |(y, x, c)| (x + y + 40 * c) as u8);
+
+        let mut encoder = Encoder::new_raw(
+            RawOutput::new(meta, BufWriter::new(Vec::new())),
+            EncoderOptions::default(plane),
+        );
+        encoder.set_thumbnail(&frame).unwrap();
+        encoder
+            .ingest_event(Event {
+                coord: Coord {
+                    x: 1,
+                    y: 1,
+                    c: Some(2),
+                },
+                d: 7,
+                t: 255,
+            })
+            .unwrap();
+        // Too late, now that the header is written
+        assert!(matches!(
+            encoder.set_thumbnail(&frame),
+            Err(CodecError::Thumbnail(_))
+        ));
+        let bytes = encoder
+            .close_writer()
+            .unwrap()
+            .unwrap()
+            .into_inner()
+            .unwrap();
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let thumbnail = reader.thumbnail().unwrap();
+        assert_eq!(thumbnail.dim(), (24, 64, 3));
+        // Each thumbnail pixel is the mean of a 2x2 block of the frame, up to JPEG loss
+        let mut error = 0.0;
+        for ((y, x, c), &value) in thumbnail.indexed_iter() {
+            let expected = (2 * x + 2 * y + 40 * c) as f64 + 1.0;
+            error += (f64::from(value) - expected).abs();
+        }
+        assert!(error / (thumbnail.len() as f64) < 4.0);
+
+        // The JPEG itself is RGB, so its first channel is the frame's last
+        let mut jpeg = jpeg_decoder::Decoder::new(&*reader.thumbnail);
+        let rgb = jpeg.decode().unwrap();
+        assert_eq!(
+            jpeg.info().unwrap().pixel_format,
+            jpeg_decoder::PixelFormat::RGB24
+        );
+        let mean = |c: usize| {
+            rgb.iter()
+                .skip(c)
+                .step_by(3)
+                .map(|&v| f64::from(v))
+                .sum::<f64>()
+                / (rgb.len() / 3) as f64
+        };
+        assert!((mean(0) - mean(2) - 80.0).abs() < 4.0);
+
+        // The events after the thumbnail are intact
+        assert_eq!(reader.digest_event(&mut bitreader).unwrap().t, 255);
+
+        // Streams without a thumbnail, old or new, have none
+        for codec_version in [6, LATEST_CODEC_VERSION] {
+            let meta = CodecMetadata {
+                codec_version,
+                plane,
+                ..Default::default()
+            };
+            let mut encoder = Encoder::new_raw(
+                RawOutput::new(meta, BufWriter::new(Vec::new())),
+                EncoderOptions::default(plane),
+            );
+            if codec_version < 7 {
+                assert!(matches!(
+                    encoder.set_thumbnail(&frame),
+                    Err(CodecError::UnsupportedVersion(6))
+                ));
+            }
+            let bytes = encoder
+                .close_writer()
+                .unwrap()
+                .unwrap()
+                .into_inner()
+                .unwrap();
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+            let reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            assert!(reader.thumbnail().is_none());
+        }
+    }
 }
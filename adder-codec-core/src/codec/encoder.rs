@@ -1,9 +1,12 @@
 use crate::codec::{
-    CodecError, CodecMetadata, EncoderOptions, EventDrop, EventOrder, WriteCompression,
+    CodecError, CodecMetadata, EncoderOptions, EventDrop, EventOrder, FormatInfo, WriteCompression,
     WriteCompressionEnum,
 };
 use crate::SourceType::*;
-use crate::{Event, EventSingle, SourceCamera, SourceType, EOF_EVENT};
+use crate::{
+    d_shift, is_framed, BigT, DeltaT, Event, EventSingle, PixelMultiMode, SourceCamera, SourceType,
+    TimeMode, D_EMPTY, EOF_EVENT,
+};
 use std::collections::BinaryHeap;
 
 use std::io;
@@ -18,13 +21,19 @@ use crate::codec::compressed::stream::CompressedOutput;
 use crate::codec::empty::stream::EmptyOutput;
 use crate::codec::header::{
     EventStreamHeader, EventStreamHeaderExtensionV0, EventStreamHeaderExtensionV1,
-    EventStreamHeaderExtensionV2, EventStreamHeaderExtensionV3,
+    EventStreamHeaderExtensionV2, EventStreamHeaderExtensionV3, EventStreamHeaderExtensionV4,
+    EventStreamHeaderExtensionV5, EventStreamHeaderExtensionV6, EventStreamHeaderExtensionV7,
 };
+#[cfg(feature = "thumbnail")]
+use crate::codec::thumbnail::encode_thumbnail;
 
 use crate::codec::raw::stream::RawOutput;
 use crate::SourceType::U8;
 use bincode::config::{FixintEncoding, WithOtherEndian, WithOtherIntEncoding};
 use bincode::{DefaultOptions, Options};
+use ndarray::Array2;
+#[cfg(feature = "thumbnail")]
+use ndarray::Array3;
 
 /// Struct for encoding [`Event`]s to a stream
 pub struct Encoder<W: Write> {
@@ -35,12 +44,42 @@ pub struct Encoder<W: Write> {
     >,
     pub options: EncoderOptions,
     state: EncoderState,
+
+    /// The encoded header, until it's written ahead of the first output. Holding it back lets
+    /// [`Encoder::set_thumbnail`] change it after the encoder is created.
+    pending_header: Option<Vec<u8>>,
+
+    /// JPEG thumbnail to embed in the header
+    thumbnail: Vec<u8>,
 }
 
 struct EncoderState {
     current_event_rate: f64,
     last_event_ts: Instant,
     queue: BinaryHeap<Event>,
+
+    /// The reference interval whose events are currently held for [`EventDrop::Budget`]
+    budget_interval: Option<BigT>,
+
+    /// The held events, with the magnitude of their intensity change
+    budget_candidates: Vec<(f64, Event)>,
+
+    /// The last intensity and absolute timestamp of each pixel, for [`EventDrop::Budget`]
+    pixel_history: Vec<(f64, BigT)>,
+
+    /// The number of events dropped in each block since the last
+    /// [`Encoder::take_degradation_map`]
+    dropped: Array2<u32>,
+
+    /// The earliest absolute time at which each pixel's next event may fire, for
+    /// [`EncoderOptions::rebase_period`]
+    pixel_t: Vec<BigT>,
+
+    /// The sum of the offsets of the rebase events written so far
+    rebase_base: BigT,
+
+    /// The absolute time at which to next check whether a rebase is due
+    next_rebase_check: BigT,
 }
 
 impl Default for EncoderState {
@@ -49,6 +88,13 @@ impl Default for EncoderState {
             current_event_rate: 0.0,
             last_event_ts: Instant::now(),
             queue: BinaryHeap::new(),
+            budget_interval: None,
+            budget_candidates: Vec::new(),
+            pixel_history: Vec::new(),
+            dropped: Array2::zeros((0, 0)),
+            pixel_t: Vec::new(),
+            rebase_base: 0,
+            next_rebase_check: 0,
         }
     }
 }
@@ -67,6 +113,8 @@ impl<W: Write + 'static> Encoder<W> {
                 .with_big_endian(),
             options,
             state: EncoderState::default(),
+            pending_header: None,
+            thumbnail: Vec::new(),
         };
         encoder.encode_header().unwrap();
         encoder
@@ -86,6 +134,8 @@ impl<W: Write + 'static> Encoder<W> {
                 .with_big_endian(),
             options,
             state: Default::default(),
+            pending_header: None,
+            thumbnail: Vec::new(),
         };
         encoder.encode_header().unwrap();
         encoder
@@ -103,6 +153,8 @@ impl<W: Write + 'static> Encoder<W> {
                 .with_big_endian(),
             options,
             state: Default::default(),
+            pending_header: None,
+            thumbnail: Vec::new(),
         };
         encoder.encode_header().unwrap();
         encoder
@@ -114,8 +166,19 @@ impl<W: Write + 'static> Encoder<W> {
         self.output.meta()
     }
 
+    /// Returns a summary of the output stream format
+    pub fn format_info(&self) -> FormatInfo {
+        self.output.format_info()
+    }
+
+    /// Returns the codec version of the output stream
+    pub fn version(&self) -> u8 {
+        self.output.version()
+    }
+
+    /// Get the source data representation, based on the source camera
     #[allow(clippy::match_same_arms)]
-    fn get_source_type(&self) -> SourceType {
+    pub fn get_source_type(&self) -> SourceType {
         match self.output.meta().source_camera {
             SourceCamera::FramedU8 => U8,
             SourceCamera::FramedU16 => U16,
@@ -130,13 +193,40 @@ impl<W: Write + 'static> Encoder<W> {
         }
     }
 
+    /// Embed a thumbnail of `frame` in the stream header, as a preview for media browsers. The
+    /// frame has shape (height, width, channels), with 1 or 3 channels in BGR order, and is
+    /// downscaled to 64 pixels wide and JPEG-encoded. Read it back with
+    /// [`crate::codec::decoder::Decoder::thumbnail`].
+    ///
+    /// The header is written ahead of the first event, so this must be called before then.
+    ///
+    /// # Errors
+    /// * [`CodecError::UnsupportedVersion`] if the stream's codec version is older than 7
+    /// * [`CodecError::Thumbnail`] if the header has already been written, or the frame can't be
+    /// encoded
+    #[cfg(feature = "thumbnail")]
+    pub fn set_thumbnail(&mut self, frame: &Array3<u8>) -> Result<(), CodecError> {
+        let codec_version = self.meta().codec_version;
+        if codec_version < 7 {
+            return Err(CodecError::UnsupportedVersion(codec_version));
+        }
+        if self.pending_header.is_none() {
+            return Err(CodecError::Thumbnail(
+                "the header has already been written".to_string(),
+            ));
+        }
+        self.thumbnail = encode_thumbnail(frame)?;
+        self.encode_header()
+    }
+
     /// Signify the end of the file in a unified way
     fn write_eof(&mut self) -> Result<(), CodecError> {
+        self.write_header()?;
         self.output.byte_align()?;
         let output_event: EventSingle;
         let mut buffer = Vec::new();
         if self.output.meta().plane.channels == 1 {
-            output_event = (&EOF_EVENT).into();
+            output_event = EventSingle::try_from(&EOF_EVENT)?;
             self.bincode.serialize_into(&mut buffer, &output_event)?;
         } else {
             self.bincode.serialize_into(&mut buffer, &EOF_EVENT)?;
@@ -146,11 +236,19 @@ impl<W: Write + 'static> Encoder<W> {
 
     /// Flush the `BitWriter`. Does not flush the internal `BufWriter`.
     pub fn flush_writer(&mut self) -> io::Result<()> {
+        self.write_header()?;
         self.output.flush_writer()
     }
 
     /// Close the encoder's writer and return it, consuming the encoder in the process.
     pub fn close_writer(mut self) -> Result<Option<W>, CodecError> {
+        self.write_header()?;
+        if let EventDrop::Budget {
+            events_per_interval,
+        } = self.options.event_drop
+        {
+            self.flush_budget(events_per_interval)?;
+        }
         // self.output.byte_align()?;
         // self.write_eof()?;
         // self.flush_writer()?;
@@ -167,7 +265,7 @@ impl<W: Write + 'static> Encoder<W> {
         // }
     }
 
-    /// Encode the header and its extensions.
+    /// Encode the header and its extensions. It's held back until [`Encoder::write_header`].
     fn encode_header(&mut self) -> Result<(), CodecError> {
         let mut buffer: Vec<u8> = Vec::new();
         let meta = self.output.meta();
@@ -184,11 +282,19 @@ impl<W: Write + 'static> Encoder<W> {
         // Encode the header extensions (for newer versions of the codec)
         buffer = self.encode_header_extension(buffer)?;
 
-        self.output.write_bytes(&buffer)?;
         self.output.meta_mut().header_size = buffer.len();
+        self.pending_header = Some(buffer);
         Ok(())
     }
 
+    /// Write the header, if it hasn't been written yet
+    fn write_header(&mut self) -> io::Result<()> {
+        match self.pending_header.take() {
+            Some(buffer) => self.output.write_bytes(&buffer),
+            None => Ok(()),
+        }
+    }
+
     fn encode_header_extension(&self, mut buffer: Vec<u8>) -> Result<Vec<u8>, CodecError> {
         let meta = self.output.meta();
         self.bincode
@@ -226,12 +332,65 @@ impl<W: Write + 'static> Encoder<W> {
         if meta.codec_version == 3 {
             return Ok(buffer);
         }
+
+        self.bincode.serialize_into(
+            &mut buffer,
+            &EventStreamHeaderExtensionV4 {
+                delta_t_encoding: meta.delta_t_encoding,
+            },
+        )?;
+        if meta.codec_version == 4 {
+            return Ok(buffer);
+        }
+
+        self.bincode.serialize_into(
+            &mut buffer,
+            &EventStreamHeaderExtensionV5 {
+                pixel_multi_mode: meta.pixel_multi_mode,
+            },
+        )?;
+        if meta.codec_version == 5 {
+            return Ok(buffer);
+        }
+
+        self.bincode.serialize_into(
+            &mut buffer,
+            &EventStreamHeaderExtensionV6 {
+                t_fraction_bits: meta.t_fraction_bits,
+            },
+        )?;
+        if meta.codec_version == 6 {
+            return Ok(buffer);
+        }
+
+        self.bincode.serialize_into(
+            &mut buffer,
+            &EventStreamHeaderExtensionV7 {
+                thumbnail_len: self.thumbnail.len() as u32,
+            },
+        )?;
+        buffer.extend_from_slice(&self.thumbnail);
+        if meta.codec_version == 7 {
+            return Ok(buffer);
+        }
         Err(CodecError::BadFile)
     }
 
     /// Ingest an event
+    ///
+    /// The event's channel is normalized for the plane (see [`crate::Coord::normalize`]).
+    ///
+    /// # Errors
+    /// * [`CodecError::InvalidChannel`] if the event's channel does not exist in the plane
+    /// * [`CodecError::CoordOutOfBounds`] if the event's coordinate is outside the plane
+    /// * [`CodecError::DeltaTExceedsMax`] if [`EncoderOptions::enforce_delta_t_max`] is set, the
+    ///   stream is in [`TimeMode::DeltaT`], and the event's Δt exceeds `delta_t_max`. In
+    ///   [`PixelMultiMode::Collapse`] streams, empty events carry the pixel's running time
+    ///   instead, so they aren't checked.
     #[inline(always)]
     pub fn ingest_event(&mut self, event: Event) -> Result<(), CodecError> {
+        let event = self.check_event(event)?;
+
         match self.options.event_drop {
             EventDrop::None => {}
             EventDrop::Manual {
@@ -243,6 +402,7 @@ impl<W: Write + 'static> Encoder<W> {
                 let new_event_rate = alpha * self.state.current_event_rate + (1.0 - alpha) / t_diff;
                 if new_event_rate > target_event_rate {
                     self.state.current_event_rate *= alpha;
+                    self.record_drop(&event);
                     return Ok(()); // skip this event
                 }
                 self.state.last_event_ts = now; // update time
@@ -251,10 +411,193 @@ impl<W: Write + 'static> Encoder<W> {
             EventDrop::Auto => {
                 todo!()
             }
+            EventDrop::Budget {
+                events_per_interval,
+            } => return self.ingest_event_budgeted(event, events_per_interval),
+        }
+
+        self.write_event(event)
+    }
+
+    /// Check that an event can be written to this stream, as described for
+    /// [`Encoder::ingest_event`], and return it with its channel normalized. Reserved events
+    /// (e.g., rebase events) aren't checked.
+    fn check_event(&self, mut event: Event) -> Result<Event, CodecError> {
+        if event.coord.is_valid() {
+            let meta = *self.meta();
+            let plane = meta.plane;
+            event.coord = event
+                .coord
+                .normalize(plane.c())
+                .ok_or(CodecError::InvalidChannel(event.coord.c, plane.c()))?;
+            if event.coord.x >= plane.w() || event.coord.y >= plane.h() {
+                return Err(CodecError::CoordOutOfBounds(
+                    event.coord.x,
+                    event.coord.y,
+                    plane.w(),
+                    plane.h(),
+                ));
+            }
+            if self.options.enforce_delta_t_max
+                && meta.time_mode == TimeMode::DeltaT
+                && event.t > meta.delta_t_max
+                && !(meta.pixel_multi_mode == PixelMultiMode::Collapse && event.d == D_EMPTY)
+            {
+                return Err(CodecError::DeltaTExceedsMax(event.t, meta.delta_t_max));
+            }
+        }
+        Ok(event)
+    }
+
+    /// Write a rebase event, offsetting every pixel's running timestamp by `offset` ticks from
+    /// this point on. In [`TimeMode::AbsoluteT`] streams, the timestamps of subsequent events
+    /// must be given relative to the new base. The offset must not exceed the running timestamp
+    /// of any pixel.
+    ///
+    /// The event bypasses [`EventDrop`] and [`EventOrder`], so any events held back by those are
+    /// not flushed first.
+    ///
+    /// # Errors
+    /// Returns [`CodecError::UnsupportedRebase`] if the output is compressed.
+    pub fn rebase(&mut self, offset: DeltaT) -> Result<(), CodecError> {
+        self.write_header()?;
+        match &mut self.output {
+            #[cfg(feature = "compression")]
+            WriteCompressionEnum::CompressedOutput(_) => Err(CodecError::UnsupportedRebase),
+            WriteCompressionEnum::RawOutput(raw_output) => {
+                raw_output.ingest_event(Event::rebase(offset))
+            }
+            WriteCompressionEnum::EmptyOutput(_) => Ok(()),
+        }
+    }
+
+    /// Hold an event until the end of its reference interval, then write only the
+    /// `events_per_interval` events of that interval with the largest intensity change.
+    fn ingest_event_budgeted(
+        &mut self,
+        event: Event,
+        events_per_interval: usize,
+    ) -> Result<(), CodecError> {
+        let meta = *self.meta();
+        let plane = meta.plane;
+        if self.state.pixel_history.is_empty() {
+            self.state.pixel_history = vec![(0.0, 0); plane.volume()];
+        }
+
+        let idx = (event.coord.y_usize() * plane.w_usize() + event.coord.x_usize())
+            * plane.c_usize()
+            + event.coord.c_usize();
+        let (last_intensity, last_t) = match self.state.pixel_history.get_mut(idx) {
+            Some(history) => history,
+            // Not a pixel we can track, so don't hold it back
+            None => return self.write_event(event),
+        };
+
+        let t = match meta.time_mode {
+            TimeMode::AbsoluteT => BigT::from(event.t),
+            _ => *last_t + BigT::from(event.t),
+        };
+        let delta_t = t.saturating_sub(*last_t);
+        *last_t = t;
+
+        let change = match d_shift(event.d) {
+            Some(shift) if delta_t > 0 => {
+                let intensity = shift as f64 / delta_t as f64;
+                let change = (intensity - *last_intensity).abs();
+                *last_intensity = intensity;
+                change
+            }
+            _ => 0.0,
+        };
+
+        let interval = t / BigT::from(meta.ref_interval.max(1));
+        match self.state.budget_interval {
+            Some(current) if interval > current => {
+                self.flush_budget(events_per_interval)?;
+                self.state.budget_interval = Some(interval);
+            }
+            None => self.state.budget_interval = Some(interval),
+            _ => {}
+        }
+        self.state.budget_candidates.push((change, event));
+        Ok(())
+    }
+
+    /// Write the held events with the largest intensity changes, up to the budget, and drop the
+    /// rest. Ties are broken in favor of the earlier event.
+    fn flush_budget(&mut self, events_per_interval: usize) -> Result<(), CodecError> {
+        let mut candidates = std::mem::take(&mut self.state.budget_candidates);
+        if candidates.len() > events_per_interval {
+            let mut order: Vec<usize> = (0..candidates.len()).collect();
+            order.sort_by(|&a, &b| candidates[b].0.total_cmp(&candidates[a].0));
+            let mut keep = vec![false; candidates.len()];
+            for &i in &order[..events_per_interval] {
+                keep[i] = true;
+            }
+            let mut keep = keep.into_iter();
+            candidates.retain(|(_, event)| {
+                let kept = keep.next().unwrap_or(false);
+                if !kept {
+                    self.record_drop(event);
+                }
+                kept
+            });
+        }
+
+        for (_, event) in candidates {
+            self.write_event(event)?;
         }
+        Ok(())
+    }
 
+    /// Count a dropped event in its block of the degradation map, if it's enabled
+    fn record_drop(&mut self, event: &Event) {
+        let Some(block_size) = self.options.degradation_block_size else {
+            return;
+        };
+        let plane = self.meta().plane;
+        let block_size = usize::from(block_size.get());
+        if self.state.dropped.is_empty() {
+            self.state.dropped = Array2::zeros((
+                plane.h_usize().div_ceil(block_size),
+                plane.w_usize().div_ceil(block_size),
+            ));
+        }
+        if let Some(count) = self.state.dropped.get_mut((
+            event.coord.y_usize() / block_size,
+            event.coord.x_usize() / block_size,
+        )) {
+            *count += 1;
+        }
+    }
+
+    /// Get the number of events dropped by [`EventDrop`] in each block of
+    /// [`EncoderOptions::degradation_block_size`] pixels, since the last call, and reset the
+    /// counts. Call this once per reference interval to get a map for each interval. Under
+    /// [`EventDrop::Budget`], an interval's drops are only counted once an event from a later
+    /// interval arrives.
+    ///
+    /// Returns `None` if the degradation map isn't enabled.
+    pub fn take_degradation_map(&mut self) -> Option<Array2<u32>> {
+        let block_size = usize::from(self.options.degradation_block_size?.get());
+        let plane = self.meta().plane;
+        let dims = (
+            plane.h_usize().div_ceil(block_size),
+            plane.w_usize().div_ceil(block_size),
+        );
+        let map = std::mem::replace(&mut self.state.dropped, Array2::zeros(dims));
+        Some(if map.is_empty() {
+            Array2::zeros(dims)
+        } else {
+            map
+        })
+    }
+
+    /// Write an event to the output, reordering it first if the options call for it
+    fn write_event(&mut self, event: Event) -> Result<(), CodecError> {
+        self.write_header()?;
         match self.options.event_order {
-            EventOrder::Unchanged => self.output.ingest_event(event),
+            EventOrder::Unchanged => self.output_event(event),
             EventOrder::Interleaved => {
                 let dt = event.t;
                 // First, push the event to the queue
@@ -264,7 +607,7 @@ impl<W: Write + 'static> Encoder<W> {
                 if let Some(first_item_addr) = self.state.queue.peek() {
                     if first_item_addr.t < dt.saturating_sub(self.meta().delta_t_max) {
                         if let Some(first_item) = self.state.queue.pop() {
-                            res = self.output.ingest_event(first_item);
+                            res = self.output_event(first_item);
                         }
                     }
                 }
@@ -272,6 +615,74 @@ impl<W: Write + 'static> Encoder<W> {
             }
         }
     }
+
+    /// Pass an event to the output, first writing a rebase event if one is due under
+    /// [`EncoderOptions::rebase_period`]
+    fn output_event(&mut self, mut event: Event) -> Result<(), CodecError> {
+        if let Some(period) = self.options.rebase_period {
+            if !event.coord.is_reserved() {
+                self.rebase_periodically(&mut event, BigT::from(period))?;
+            }
+        }
+        self.output.ingest_event(event)
+    }
+
+    /// Track the earliest time at which each pixel may next fire, and rebase to the earliest of
+    /// them once an event passes the next check time. Absolute timestamps are re-expressed
+    /// relative to the base.
+    fn rebase_periodically(&mut self, event: &mut Event, period: BigT) -> Result<(), CodecError> {
+        let meta = *self.meta();
+        let plane = meta.plane;
+        if self.state.pixel_t.is_empty() {
+            self.state.pixel_t = vec![0; plane.volume()];
+            self.state.next_rebase_check = period;
+        }
+        let idx = (event.coord.y_usize() * plane.w_usize() + event.coord.x_usize())
+            * plane.c_usize()
+            + event.coord.c_usize();
+        let Some(&earliest) = self.state.pixel_t.get(idx) else {
+            return Ok(());
+        };
+
+        // In Collapse mode, a framed pixel's empty events carry its absolute running time
+        let framed_source = is_framed(meta.source_camera);
+        let absolute = (meta.codec_version >= 2 && meta.time_mode == TimeMode::AbsoluteT)
+            || (meta.pixel_multi_mode == PixelMultiMode::Collapse
+                && framed_source
+                && event.d == D_EMPTY);
+        let t = if absolute {
+            BigT::from(event.t)
+        } else {
+            earliest + BigT::from(event.t)
+        };
+
+        if t >= self.state.next_rebase_check {
+            // No pixel can fire before the earliest entry, including this one's, so a base there
+            // never leaves a pixel behind it
+            let floor = self.state.pixel_t.iter().min().copied().unwrap_or(0) / period * period;
+            let max_offset = BigT::from(DeltaT::MAX) / period * period;
+            let offset = floor.saturating_sub(self.state.rebase_base).min(max_offset);
+            if offset > 0 {
+                self.rebase(offset as DeltaT)?;
+                self.state.rebase_base += offset;
+            }
+            self.state.next_rebase_check = t + period;
+        }
+
+        // Δt streams measure a framed pixel's next event from the end of its reference interval
+        let ref_interval = BigT::from(meta.ref_interval.max(1));
+        self.state.pixel_t[idx] = if !absolute && framed_source && t % ref_interval > 0 {
+            (t / ref_interval + 1) * ref_interval
+        } else {
+            t
+        };
+        if absolute {
+            event.t =
+                DeltaT::try_from(t.saturating_sub(self.state.rebase_base)).unwrap_or(DeltaT::MAX);
+        }
+        Ok(())
+    }
+
     // /// Ingest an event
     // #[cfg(feature = "compression")]
     // pub fn ingest_event_debug(&mut self, event: Event) -> Result<Option<Adu>, CodecError> {
@@ -314,14 +725,530 @@ impl<W: Write + 'static> Encoder<W> {
     }
 }
 
+/// Writes every ingested event to two encoders at once, e.g., to record a raw stream alongside a
+/// compressed one without transcoding afterward.
+pub struct TeeEncoder<W1: Write, W2: Write> {
+    first: Encoder<W1>,
+    second: Encoder<W2>,
+}
+
+impl<W1: Write + 'static, W2: Write + 'static> TeeEncoder<W1, W2> {
+    /// Create a new tee from two encoders. They should share the same plane and time parameters,
+    /// or the events accepted by one may be rejected by the other.
+    pub fn new(first: Encoder<W1>, second: Encoder<W2>) -> Self {
+        Self { first, second }
+    }
+
+    /// Get a reference to the first encoder
+    pub fn first(&self) -> &Encoder<W1> {
+        &self.first
+    }
+
+    /// Get a reference to the second encoder
+    pub fn second(&self) -> &Encoder<W2> {
+        &self.second
+    }
+
+    /// Ingest an event into both encoders. The event is checked against both streams (as in
+    /// [`Encoder::ingest_event`]) before either writes it, so an event rejected by one encoder is
+    /// written by neither.
+    ///
+    /// # Errors
+    /// * Any error from checking the event, as for [`Encoder::ingest_event`]
+    /// * If the event can't be written. If the second encoder fails to write it, the first one
+    ///   already has.
+    pub fn ingest_event(&mut self, event: Event) -> Result<(), CodecError> {
+        self.first.check_event(event)?;
+        self.second.check_event(event)?;
+        self.first.ingest_event(event)?;
+        self.second.ingest_event(event)
+    }
+
+    /// Ingest a slice of events into both encoders
+    pub fn ingest_events(&mut self, events: &[Event]) -> Result<(), CodecError> {
+        for event in events {
+            self.ingest_event(*event)?;
+        }
+        Ok(())
+    }
+
+    /// Rebase both encoders by the given time offset
+    pub fn rebase(&mut self, offset: DeltaT) -> Result<(), CodecError> {
+        self.first.rebase(offset)?;
+        self.second.rebase(offset)
+    }
+
+    /// Flush both `BitWriter`s. Does not flush the internal `BufWriter`s.
+    pub fn flush_writer(&mut self) -> io::Result<()> {
+        self.first.flush_writer()?;
+        self.second.flush_writer()
+    }
+
+    /// Close both encoders' writers and return them, consuming the tee in the process.
+    pub fn close_writer(self) -> Result<(Option<W1>, Option<W2>), CodecError> {
+        let first = self.first.close_writer()?;
+        let second = self.second.close_writer()?;
+        Ok((first, second))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::codec::raw::stream::RawOutput;
     use crate::codec::{CodecMetadata, LATEST_CODEC_VERSION};
-    use crate::{Coord, PlaneSize};
+    use crate::{Coord, PixelMultiMode, PlaneSize};
     use bitstream_io::{BigEndian, BitWriter};
     use std::io::BufWriter;
+    use std::num::NonZeroU16;
+
+    #[test]
+    fn event_budget() {
+        use crate::codec::decoder::Decoder;
+        use crate::codec::raw::stream::RawInput;
+        use bitstream_io::BitReader;
+        use std::io::{BufReader, Cursor};
+
+        let plane = PlaneSize::new(4, 1, 1).unwrap();
+        let mut options = EncoderOptions::default(plane);
+        options.event_drop = EventDrop::Budget {
+            events_per_interval: 2,
+        };
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    time_mode: TimeMode::DeltaT,
+                    ref_interval: 100,
+                    delta_t_max: 1000,
+                    ..Default::default()
+                },
+                BufWriter::new(Vec::new()),
+            ),
+            options,
+        );
+
+        // Four candidates in the first interval, with intensity changes increasing with x
+        let mut events: Vec<Event> = (0..4_u16)
+            .map(|x| Event {
+                coord: Coord { x, y: 0, c: None },
+                d: x as u8 + 1,
+                t: 100,
+            })
+            .collect();
+        // A single event in the next interval, which closes out the first
+        events.push(Event {
+            coord: Coord {
+                x: 0,
+                y: 0,
+                c: None,
+            },
+            d: 6,
+            t: 100,
+        });
+        encoder.ingest_events(&events).unwrap();
+        let output = encoder
+            .close_writer()
+            .unwrap()
+            .unwrap()
+            .into_inner()
+            .unwrap();
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut decoded = Vec::new();
+        loop {
+            match reader.digest_event(&mut bitreader) {
+                Ok(event) => decoded.push(event),
+                Err(CodecError::Eof) => break,
+                Err(e) => panic!("{e:?}"),
+            }
+        }
+
+        assert_eq!(decoded, vec![events[2], events[3], events[4]]);
+    }
+
+    #[test]
+    fn intra_period_rejects_zero() {
+        let options = EncoderOptions::default(PlaneSize::new(4, 4, 1).unwrap());
+        assert!(matches!(
+            options.intra_period(0),
+            Err(CodecError::InvalidIntraPeriod)
+        ));
+        assert_eq!(options.intra_period(255).unwrap().intra_period, Some(255));
+    }
+
+    #[test]
+    fn degradation_map() {
+        let plane = PlaneSize::new(8, 8, 1).unwrap();
+        let mut options =
+            EncoderOptions::default(plane).degradation_map(NonZeroU16::new(4).unwrap());
+        options.event_drop = EventDrop::Budget {
+            events_per_interval: 3,
+        };
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    time_mode: TimeMode::DeltaT,
+                    ref_interval: 100,
+                    delta_t_max: 1000,
+                    ..Default::default()
+                },
+                BufWriter::new(Vec::new()),
+            ),
+            options,
+        );
+        assert_eq!(
+            encoder.take_degradation_map().unwrap(),
+            Array2::<u32>::zeros((2, 2))
+        );
+
+        // Small intensity changes fill the top-left block, and one large change lands in each
+        // of the other blocks. Only the large changes fit in the budget.
+        let mut events = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                events.push(Event {
+                    coord: Coord { x, y, c: None },
+                    d: 1,
+                    t: 100,
+                });
+            }
+        }
+        for (x, y) in [(4, 0), (0, 4), (4, 4)] {
+            events.push(Event {
+                coord: Coord { x, y, c: None },
+                d: 8,
+                t: 100,
+            });
+        }
+        // An event in the next interval closes out the first
+        events.push(Event {
+            coord: Coord {
+                x: 7,
+                y: 7,
+                c: None,
+            },
+            d: 1,
+            t: 200,
+        });
+        encoder.ingest_events(&events).unwrap();
+
+        let map = encoder.take_degradation_map().unwrap();
+        assert_eq!(map[[0, 0]], 16);
+        assert_eq!(map[[0, 1]], 0);
+        assert_eq!(map[[1, 0]], 0);
+        assert_eq!(map[[1, 1]], 0);
+
+        // The counts reset after each take
+        assert_eq!(encoder.take_degradation_map().unwrap().sum(), 0);
+
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    ..Default::default()
+                },
+                BufWriter::new(Vec::new()),
+            ),
+            EncoderOptions::default(plane),
+        );
+        assert!(encoder.take_degradation_map().is_none());
+    }
+
+    #[test]
+    fn channel_normalization() {
+        use crate::codec::decoder::Decoder;
+        use crate::codec::raw::stream::RawInput;
+        use bitstream_io::BitReader;
+        use std::io::{BufReader, Cursor};
+
+        fn round_trip(channels: u8, events: &[Event]) -> Vec<Event> {
+            let plane = PlaneSize::new(4, 1, channels).unwrap();
+            let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        plane,
+                        ..Default::default()
+                    },
+                    BufWriter::new(Vec::new()),
+                ),
+                EncoderOptions::default(plane),
+            );
+            encoder.ingest_events(events).unwrap();
+            let output = encoder
+                .close_writer()
+                .unwrap()
+                .unwrap()
+                .into_inner()
+                .unwrap();
+
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            let mut decoded = Vec::new();
+            loop {
+                match reader.digest_event(&mut bitreader) {
+                    Ok(event) => decoded.push(event),
+                    Err(CodecError::Eof) => break,
+                    Err(e) => panic!("{e:?}"),
+                }
+            }
+            decoded
+        }
+
+        let event = |c| Event {
+            coord: Coord { x: 1, y: 0, c },
+            d: 5,
+            t: 100,
+        };
+
+        // Grayscale events are always written without a channel
+        let decoded = round_trip(1, &[event(Some(0)), event(None)]);
+        assert_eq!(decoded, vec![event(None), event(None)]);
+
+        // Color events always carry a channel
+        let decoded = round_trip(3, &[event(None), event(Some(2))]);
+        assert_eq!(decoded, vec![event(Some(0)), event(Some(2))]);
+
+        // Channels outside the plane are rejected
+        for (channels, c) in [(1, Some(1)), (3, Some(3))] {
+            let plane = PlaneSize::new(4, 1, channels).unwrap();
+            let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        plane,
+                        ..Default::default()
+                    },
+                    BufWriter::new(Vec::new()),
+                ),
+                EncoderOptions::default(plane),
+            );
+            assert!(matches!(
+                encoder.ingest_event(event(c)),
+                Err(CodecError::InvalidChannel(channel, n)) if channel == c && n == channels
+            ));
+        }
+    }
+
+    #[test]
+    fn reject_bad_events() {
+        let plane = PlaneSize::new(4, 2, 1).unwrap();
+        let new_encoder = |time_mode, pixel_multi_mode| -> Encoder<BufWriter<Vec<u8>>> {
+            Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        plane,
+                        time_mode,
+                        delta_t_max: 255,
+                        pixel_multi_mode,
+                        ..Default::default()
+                    },
+                    BufWriter::new(Vec::new()),
+                ),
+                EncoderOptions::default(plane).enforce_delta_t_max(),
+            )
+        };
+        let event = |x, y, c, d, t| Event {
+            coord: Coord { x, y, c },
+            d,
+            t,
+        };
+        let mut encoder = new_encoder(TimeMode::DeltaT, PixelMultiMode::Normal);
+
+        // Coordinates outside the plane
+        assert!(matches!(
+            encoder.ingest_event(event(4, 0, None, 7, 100)),
+            Err(CodecError::CoordOutOfBounds(4, 0, 4, 2))
+        ));
+        assert!(matches!(
+            encoder.ingest_event(event(0, 2, None, 7, 100)),
+            Err(CodecError::CoordOutOfBounds(0, 2, 4, 2))
+        ));
+
+        // A color channel on a gray stream
+        assert!(matches!(
+            encoder.ingest_event(event(0, 0, Some(2), 7, 100)),
+            Err(CodecError::InvalidChannel(Some(2), 1))
+        ));
+
+        // Δt beyond delta_t_max
+        assert!(encoder.ingest_event(event(0, 0, None, 7, 255)).is_ok());
+        assert!(matches!(
+            encoder.ingest_event(event(0, 0, None, 7, 256)),
+            Err(CodecError::DeltaTExceedsMax(256, 255))
+        ));
+
+        // Absolute timestamps and collapsed empty events may exceed delta_t_max
+        let mut encoder = new_encoder(TimeMode::AbsoluteT, PixelMultiMode::Normal);
+        assert!(encoder.ingest_event(event(0, 0, None, 7, 1000)).is_ok());
+        let mut encoder = new_encoder(TimeMode::DeltaT, PixelMultiMode::Collapse);
+        assert!(encoder
+            .ingest_event(event(0, 0, None, D_EMPTY, 1000))
+            .is_ok());
+        assert!(encoder.ingest_event(event(0, 0, None, 7, 1000)).is_err());
+
+        // Without enforcement, a long Δt is written as-is
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    time_mode: TimeMode::DeltaT,
+                    delta_t_max: 255,
+                    ..Default::default()
+                },
+                BufWriter::new(Vec::new()),
+            ),
+            EncoderOptions::default(plane),
+        );
+        assert!(encoder.ingest_event(event(0, 0, None, 7, 1000)).is_ok());
+    }
+
+    #[test]
+    fn vec_sink_round_trip() {
+        use crate::codec::decoder::Decoder;
+        use crate::codec::raw::stream::RawInput;
+        use bitstream_io::BitReader;
+        use std::io::{BufReader, Cursor};
+
+        // Any `Write` can back an encoder, so encode straight into memory
+        let plane = PlaneSize::new(3, 2, 1).unwrap();
+        let mut encoder: Encoder<Vec<u8>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    ..Default::default()
+                },
+                Vec::new(),
+            ),
+            EncoderOptions::default(plane),
+        );
+        let events: Vec<Event> = (0..6_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i % 3,
+                    y: i / 3,
+                    c: None,
+                },
+                d: 7,
+                t: 10 * u32::from(i),
+            })
+            .collect();
+        encoder.ingest_events(&events).unwrap();
+        let output = encoder.close_writer().unwrap().unwrap();
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        for event in events {
+            assert_eq!(reader.digest_event(&mut bitreader).unwrap(), event);
+        }
+        assert!(matches!(
+            reader.digest_event(&mut bitreader),
+            Err(CodecError::Eof)
+        ));
+    }
+
+    #[test]
+    fn tee_round_trip() {
+        use crate::codec::decoder::Decoder;
+        use crate::codec::raw::stream::RawInput;
+        use bitstream_io::BitReader;
+        use std::io::{BufReader, Cursor};
+
+        let plane = PlaneSize::new(3, 2, 1).unwrap();
+        let new_encoder = || -> Encoder<Vec<u8>> {
+            Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        plane,
+                        ..Default::default()
+                    },
+                    Vec::new(),
+                ),
+                EncoderOptions::default(plane),
+            )
+        };
+        let mut tee = TeeEncoder::new(new_encoder(), new_encoder());
+        let events: Vec<Event> = (0..6_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i % 3,
+                    y: i / 3,
+                    c: None,
+                },
+                d: 7,
+                t: 10 * u32::from(i),
+            })
+            .collect();
+        tee.ingest_events(&events).unwrap();
+        tee.flush_writer().unwrap();
+        let (first, second) = tee.close_writer().unwrap();
+        let (first, second) = (first.unwrap(), second.unwrap());
+        assert!(!first.is_empty());
+        assert_eq!(first, second);
+
+        for output in [first, second] {
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            for event in &events {
+                assert_eq!(reader.digest_event(&mut bitreader).unwrap(), *event);
+            }
+            assert!(matches!(
+                reader.digest_event(&mut bitreader),
+                Err(CodecError::Eof)
+            ));
+        }
+    }
+
+    #[test]
+    fn tee_rejects_before_writing() {
+        use crate::codec::decoder::Decoder;
+        use crate::codec::raw::stream::RawInput;
+        use bitstream_io::BitReader;
+        use std::io::{BufReader, Cursor};
+
+        let new_encoder = |plane: PlaneSize| -> Encoder<Vec<u8>> {
+            Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        plane,
+                        ..Default::default()
+                    },
+                    Vec::new(),
+                ),
+                EncoderOptions::default(plane),
+            )
+        };
+        let mut tee = TeeEncoder::new(
+            new_encoder(PlaneSize::new(3, 2, 1).unwrap()),
+            new_encoder(PlaneSize::new(2, 2, 1).unwrap()),
+        );
+
+        // Only the second encoder's plane is too narrow, but neither stream gets the event
+        let event = Event {
+            coord: Coord {
+                x: 2,
+                y: 0,
+                c: None,
+            },
+            d: 7,
+            t: 10,
+        };
+        assert!(matches!(
+            tee.ingest_event(event),
+            Err(CodecError::CoordOutOfBounds(2, 0, 2, 2))
+        ));
+
+        let (first, second) = tee.close_writer().unwrap();
+        for output in [first.unwrap(), second.unwrap()] {
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+            assert!(matches!(
+                reader.digest_event(&mut bitreader),
+                Err(CodecError::Eof)
+            ));
+        }
+    }
 
     #[test]
     fn raw() {
@@ -339,6 +1266,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bincode: DefaultOptions::new()
                 .with_fixint_encoding()
@@ -356,6 +1286,8 @@ mod tests {
                 channels: 1,
             }),
             state: EncoderState::default(),
+            pending_header: None,
+            thumbnail: Vec::new(),
         };
         let mut writer = encoder.close_writer().unwrap().unwrap();
         writer.flush().unwrap();
@@ -378,6 +1310,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -392,6 +1327,8 @@ mod tests {
                 channels: 1,
             }),
             state: EncoderState::default(),
+            pending_header: None,
+            thumbnail: Vec::new(),
         };
         let mut writer = encoder.close_writer().unwrap().unwrap();
         writer.flush().unwrap();
@@ -418,6 +1355,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -444,7 +1384,7 @@ mod tests {
         let mut writer = encoder.close_writer().unwrap().unwrap();
         writer.flush().unwrap();
         let output = writer.into_inner().unwrap();
-        assert_eq!(output.len(), 37 + 22); // 37 bytes for the header, 22 bytes for the 2 events
+        assert_eq!(output.len(), 49 + 22); // 49 bytes for the header, 22 bytes for the 2 events
     }
 
     #[test]
@@ -464,6 +1404,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             // frame: Default::default(),
             // adu: Adu::new(),
@@ -479,6 +1422,8 @@ mod tests {
                 .with_big_endian(),
             options: EncoderOptions::default(PlaneSize::default()),
             state: Default::default(),
+            pending_header: None,
+            thumbnail: Vec::new(),
         };
     }
 
@@ -499,6 +1444,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: Default::default(),
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -509,6 +1457,8 @@ mod tests {
                 .with_big_endian(),
             options: EncoderOptions::default(PlaneSize::default()),
             state: Default::default(),
+            pending_header: None,
+            thumbnail: Vec::new(),
         };
     }
 
@@ -529,6 +1479,9 @@ mod tests {
                 event_size: 0,
                 source_camera: Default::default(),
                 adu_interval: Default::default(),
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -1,4 +1,5 @@
-use crate::{PlaneSize, SourceCamera, TimeMode};
+use crate::codec::DeltaTEncoding;
+use crate::{PixelMultiMode, PlaneSize, SourceCamera, TimeMode};
 use serde::{Deserialize, Serialize};
 
 pub(crate) type Magic = [u8; 5];
@@ -45,8 +46,37 @@ pub(crate) struct EventStreamHeaderExtensionV3 {
     pub(crate) adu_interval: u32,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EventStreamHeaderExtensionV4 {
+    pub(crate) delta_t_encoding: DeltaTEncoding,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EventStreamHeaderExtensionV5 {
+    pub(crate) pixel_multi_mode: PixelMultiMode,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EventStreamHeaderExtensionV6 {
+    pub(crate) t_fraction_bits: u8,
+}
+
+/// Followed by `thumbnail_len` bytes of JPEG data. A length of 0 means there is no thumbnail.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct EventStreamHeaderExtensionV7 {
+    pub(crate) thumbnail_len: u32,
+}
+
+/// Upper bound on the size of an embedded thumbnail, so that a corrupt header can't make the
+/// decoder allocate an arbitrary amount of memory
+pub(crate) const MAX_THUMBNAIL_BYTES: usize = 1 << 20;
+
 impl HeaderExtension for EventStreamHeaderExtensionV2 {}
 impl HeaderExtension for EventStreamHeaderExtensionV3 {}
+impl HeaderExtension for EventStreamHeaderExtensionV4 {}
+impl HeaderExtension for EventStreamHeaderExtensionV5 {}
+impl HeaderExtension for EventStreamHeaderExtensionV6 {}
+impl HeaderExtension for EventStreamHeaderExtensionV7 {}
 
 impl EventStreamHeader {
     pub(crate) fn new(
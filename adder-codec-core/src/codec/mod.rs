@@ -1,11 +1,13 @@
 #![warn(missing_docs)]
 
-use crate::codec::header::Magic;
-use crate::{DeltaT, Event, PlaneSize, SourceCamera, TimeMode};
+use crate::codec::header::{Magic, MAGIC_COMPRESSED};
+use crate::{DeltaT, Event, PixelMultiMode, PlaneSize, SourceCamera, TimeMode};
 use bitstream_io::{BigEndian, BitReader};
 use enum_dispatch::enum_dispatch;
+use serde::{Deserialize, Serialize};
 use std::io;
 use std::io::{Read, Seek, Sink, Write};
+use std::num::NonZeroU16;
 
 /// Different options for what to with the events we're given
 #[enum_dispatch(WriteCompression<W>)]
@@ -42,6 +44,19 @@ pub enum EncoderType {
     Empty,
 }
 
+/// How the temporal component of each event is written in a raw stream
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DeltaTEncoding {
+    /// Every timestamp occupies a fixed 4 bytes. Events have a constant size, so the stream
+    /// can be seeked by event index.
+    #[default]
+    Fixed,
+
+    /// Timestamps are written as unsigned LEB128 varints, which takes fewer bytes for small
+    /// values. Events no longer have a constant size, so seeking by position is not supported.
+    Varint,
+}
+
 #[enum_dispatch(ReadCompression<R>)]
 enum ReadCompressionEnum<R: Read + Seek> {
     #[cfg(feature = "compression")]
@@ -66,11 +81,19 @@ mod header;
 pub mod rate_controller;
 /// Raw codec utilities
 pub mod raw;
+#[cfg(feature = "thumbnail")]
+mod thumbnail;
 
 /// Current latest version of the codec.
 ///
 /// This is the version which will be written to the header.
-pub const LATEST_CODEC_VERSION: u8 = 3;
+pub const LATEST_CODEC_VERSION: u8 = 7;
+
+/// The codec versions which this build can read and write, in increasing order
+#[must_use]
+pub fn supported_codec_versions() -> &'static [u8] {
+    &[0, 1, 2, 3, 4, 5, 6, 7]
+}
 
 /// The metadata which stays the same over the course of an ADΔER stream
 #[allow(missing_docs)]
@@ -86,6 +109,19 @@ pub struct CodecMetadata {
     pub event_size: u8,
     pub source_camera: SourceCamera,
     pub adu_interval: usize, // TODO: Allow the adu_interval to be non-constant. Each ADU will encode its own size at its beginning
+    pub delta_t_encoding: DeltaTEncoding,
+
+    /// How the transcoder handled multiple events for a pixel within a single input interval.
+    /// Streams older than codec version 5 don't record this, and are treated as
+    /// [`PixelMultiMode::Normal`].
+    pub pixel_multi_mode: PixelMultiMode,
+
+    /// Number of fractional bits in each timestamp. When nonzero, the event timestamps and the
+    /// `tps`, `ref_interval`, and `delta_t_max` fields are all fixed-point values, in units of
+    /// `2^-t_fraction_bits` of the transcoder's source tick. Readers which ignore this field
+    /// simply see a stream with a finer tick. Streams older than codec version 6 don't record
+    /// this, and are treated as having no fractional bits.
+    pub t_fraction_bits: u8,
 }
 
 impl Default for CodecMetadata {
@@ -101,6 +137,57 @@ impl Default for CodecMetadata {
             event_size: 9,
             source_camera: Default::default(),
             adu_interval: 1,
+            delta_t_encoding: Default::default(),
+            pixel_multi_mode: PixelMultiMode::Normal,
+            t_fraction_bits: 0,
+        }
+    }
+}
+
+/// The byte order of multi-byte values in an ADΔER stream
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Endianness {
+    /// Most significant byte first. All streams are currently written this way.
+    #[default]
+    Big,
+
+    /// Least significant byte first
+    Little,
+}
+
+/// A summary of how an ADΔER stream is formatted, so that tools can describe a stream without
+/// reaching into individual header fields
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct FormatInfo {
+    /// The codec version written in the header
+    pub codec_version: u8,
+
+    /// The byte order of the stream
+    pub endianness: Endianness,
+
+    /// Whether the events are compressed (as opposed to raw)
+    pub compressed: bool,
+
+    /// Whether the stream carries checksums. The format does not define any yet, so this is
+    /// always `false`.
+    pub checksummed: bool,
+
+    /// The size of a single raw event, in bytes
+    pub event_size: u8,
+
+    /// Whether event timestamps are delta or absolute
+    pub time_mode: TimeMode,
+}
+
+impl FormatInfo {
+    fn new(magic: Magic, meta: &CodecMetadata) -> Self {
+        Self {
+            codec_version: meta.codec_version,
+            endianness: Endianness::Big,
+            compressed: magic == MAGIC_COMPRESSED,
+            checksummed: false,
+            event_size: meta.event_size,
+            time_mode: meta.time_mode,
         }
     }
 }
@@ -122,6 +209,16 @@ pub trait WriteCompression<W: Write> {
     /// Returns a mutable reference to the metadata
     fn meta_mut(&mut self) -> &mut CodecMetadata;
 
+    /// Returns a summary of the stream format
+    fn format_info(&self) -> FormatInfo {
+        FormatInfo::new(self.magic(), self.meta())
+    }
+
+    /// Returns the codec version of the stream
+    fn version(&self) -> u8 {
+        self.meta().codec_version
+    }
+
     // fn stream(&mut self) -> &mut W;
 
     /// Write the given bytes to the stream
@@ -164,6 +261,16 @@ pub trait ReadCompression<R: Read> {
     /// Returns a mutable reference to the metadata
     fn meta_mut(&mut self) -> &mut CodecMetadata;
 
+    /// Returns a summary of the stream format
+    fn format_info(&self) -> FormatInfo {
+        FormatInfo::new(self.magic(), self.meta())
+    }
+
+    /// Returns the codec version of the stream
+    fn version(&self) -> u8 {
+        self.meta().codec_version
+    }
+
     /// Read a certain number of bytes from the stream, indicated by the size of the buffer passed.
     fn read_bytes(
         &mut self,
@@ -175,6 +282,19 @@ pub trait ReadCompression<R: Read> {
     /// Read the next event from the stream. Returns `None` if the stream is exhausted.
     fn digest_event(&mut self, reader: &mut BitReader<R, BigEndian>) -> Result<Event, CodecError>;
 
+    /// Read up to `max` events from the stream, appending them to `buf`. Returns the number of
+    /// events read, which is less than `max` only when the stream is exhausted.
+    ///
+    /// The default implementation calls [`ReadCompression::digest_event`] for each event.
+    fn digest_events(
+        &mut self,
+        max: usize,
+        buf: &mut Vec<Event>,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<usize, CodecError> {
+        digest_events_each(self, max, buf, reader)
+    }
+
     // #[cfg(feature = "compression")]
     // fn digest_event_debug(
     //     &mut self,
@@ -194,6 +314,46 @@ pub trait ReadCompression<R: Read> {
 }
 
 // unsafe impl<R: Read> Send for ReadCompression {}
+
+/// Interpret the result of reading one event: `Ok(None)` at the end-of-stream marker. Running
+/// out of data is [`CodecError::Truncated`], unless `marker_read` says that the marker has already
+/// been read, and so the stream ended cleanly.
+pub(crate) fn end_of_stream(
+    result: Result<Event, CodecError>,
+    marker_read: bool,
+) -> Result<Option<Event>, CodecError> {
+    match result {
+        Ok(event) => Ok(Some(event)),
+        Err(CodecError::Eof) => Ok(None),
+        Err(CodecError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            if marker_read {
+                Ok(None)
+            } else {
+                Err(CodecError::Truncated)
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Read up to `max` events one at a time.
+///
+/// # Errors
+/// Returns [`CodecError::Truncated`] if the stream is cut off without an end-of-stream marker.
+pub(crate) fn digest_events_each<R: Read>(
+    input: &mut (impl ReadCompression<R> + ?Sized),
+    max: usize,
+    buf: &mut Vec<Event>,
+    reader: &mut BitReader<R, BigEndian>,
+) -> Result<usize, CodecError> {
+    for count in 0..max {
+        match end_of_stream(input.digest_event(reader), false)? {
+            Some(event) => buf.push(event),
+            None => return Ok(count),
+        }
+    }
+    Ok(max)
+}
 // #[cfg(feature = "compression")]
 // use crate::codec::compressed::adu::frame::Adu;
 #[cfg(feature = "compression")]
@@ -212,6 +372,9 @@ pub enum CodecError {
     #[error("Reached end of file when expected")]
     Eof,
 
+    #[error("Stream ended without an end-of-stream marker")]
+    Truncated,
+
     #[error("Could not deserialize data. EOF reached at unexpected time.")]
     Deserialize,
 
@@ -224,7 +387,10 @@ pub enum CodecError {
     #[error("Attempted to seek to a bad position in the stream")]
     Seek,
 
-    #[error("Unsupported codec version (expected {LATEST_CODEC_VERSION} or lower, found {0})")]
+    #[error(
+        "Unsupported codec version {0} (supported versions are {:?})",
+        supported_codec_versions()
+    )]
     UnsupportedVersion(u8),
 
     #[error("Malformed encoder")]
@@ -252,6 +418,30 @@ pub enum CodecError {
 
     #[error("No more events to read")]
     NoMoreEvents,
+
+    #[error("Unsupported time mode {0:?}")]
+    UnsupportedTimeMode(crate::TimeMode),
+
+    #[error("Rebase events can only be written to raw streams")]
+    UnsupportedRebase,
+
+    #[error("Channel {0:?} does not exist in a plane with {1} channels")]
+    InvalidChannel(Option<u8>, u8),
+
+    #[error("Coordinate ({0}, {1}) is outside a plane of {2}x{3} pixels")]
+    CoordOutOfBounds(u16, u16, u16, u16),
+
+    #[error("Δt {0} exceeds the stream's Δt_max of {1}")]
+    DeltaTExceedsMax(crate::DeltaT, crate::DeltaT),
+
+    #[error("Thumbnail error: {0}")]
+    Thumbnail(String),
+
+    #[error("Intra-frame period must be at least one tick")]
+    InvalidIntraPeriod,
+
+    #[error("Rebase period must be at least one tick")]
+    InvalidRebasePeriod,
 }
 
 /*
@@ -269,6 +459,22 @@ pub struct EncoderOptions {
     pub event_order: EventOrder,
 
     pub crf: Crf,
+
+    /// If set, the transcoder emits an intra-frame every this many ticks. See
+    /// [`EncoderOptions::intra_period`].
+    pub intra_period: Option<DeltaT>,
+
+    /// If set, the encoder counts the events it drops in each square block of this many pixels
+    /// per side. See [`EncoderOptions::degradation_map`].
+    pub degradation_block_size: Option<NonZeroU16>,
+
+    /// Reject events whose Δt exceeds the stream's `delta_t_max`. See
+    /// [`EncoderOptions::enforce_delta_t_max`].
+    pub enforce_delta_t_max: bool,
+
+    /// If set, the encoder writes a rebase event about every this many ticks. See
+    /// [`EncoderOptions::rebase_period`].
+    pub rebase_period: Option<DeltaT>,
 }
 
 impl EncoderOptions {
@@ -277,7 +483,72 @@ impl EncoderOptions {
             event_drop: Default::default(),
             event_order: Default::default(),
             crf: Crf::new(None, plane),
+            intra_period: None,
+            degradation_block_size: None,
+            enforce_delta_t_max: false,
+            rebase_period: None,
+        }
+    }
+
+    /// Emit an intra-frame (I-frame) every `ticks` ticks, for error resilience.
+    ///
+    /// At the start of the first input interval at or after each multiple of `ticks`, every pixel
+    /// flushes the events it has pending, then emits an empty event (`d = D_EMPTY`) timestamped
+    /// at that moment. A decoder which joins the stream
+    /// mid-way can ignore each pixel's events until that pixel's first empty event, and decode
+    /// everything after it independently of the earlier stream.
+    ///
+    /// # Errors
+    /// Returns [`CodecError::InvalidIntraPeriod`] if `ticks` is 0.
+    pub fn intra_period(mut self, ticks: DeltaT) -> Result<Self, CodecError> {
+        if ticks == 0 {
+            return Err(CodecError::InvalidIntraPeriod);
         }
+        self.intra_period = Some(ticks);
+        Ok(self)
+    }
+
+    /// Reject events whose Δt exceeds `delta_t_max` in [`TimeMode::DeltaT`] streams, with
+    /// [`CodecError::DeltaTExceedsMax`]. This is off by default, since the transcoder's events
+    /// may span longer than `delta_t_max` unless it clamps them (e.g., a static pixel's events
+    /// after its first forced event, or intra-frame empty events).
+    #[must_use]
+    pub fn enforce_delta_t_max(mut self) -> Self {
+        self.enforce_delta_t_max = true;
+        self
+    }
+
+    /// Write a rebase event about every `ticks` ticks, so that the timestamps in the stream, and
+    /// the per-pixel times that decoders track, stay small over long recordings.
+    ///
+    /// Each rebase offsets the base by the earliest time at which any pixel may next fire,
+    /// rounded down to a multiple of `ticks`, so the base only advances once every pixel has
+    /// fired past it. In [`TimeMode::AbsoluteT`] streams, events are still given to
+    /// [`encoder::Encoder::ingest_event`] with their absolute timestamps, and the encoder writes
+    /// them relative to the base. Δt timestamps are written unchanged.
+    ///
+    /// Only raw streams support rebase events, so a compressed stream fails with
+    /// [`CodecError::UnsupportedRebase`] at the first rebase. Don't combine this with calls to
+    /// [`encoder::Encoder::rebase`].
+    ///
+    /// # Errors
+    /// Returns [`CodecError::InvalidRebasePeriod`] if `ticks` is 0.
+    pub fn rebase_period(mut self, ticks: DeltaT) -> Result<Self, CodecError> {
+        if ticks == 0 {
+            return Err(CodecError::InvalidRebasePeriod);
+        }
+        self.rebase_period = Some(ticks);
+        Ok(self)
+    }
+
+    /// Track where events are dropped by [`EventDrop`], in square blocks of `block_size` pixels
+    /// per side. Retrieve the counts for each interval with
+    /// [`encoder::Encoder::take_degradation_map`], so that reconstructors can flag the regions
+    /// with low confidence.
+    #[must_use]
+    pub fn degradation_map(mut self, block_size: NonZeroU16) -> Self {
+        self.degradation_block_size = Some(block_size);
+        self
     }
 }
 
@@ -299,6 +570,13 @@ pub enum EventDrop {
     /// TODO: Implement this. Query the actual network bandwidth accoring to some stream handle
     /// and drop events accordingly.
     Auto,
+
+    /// Keep at most this many events per reference interval, for fixed-bandwidth channels.
+    ///
+    /// Events are held until their reference interval ends. Then, only the events with the
+    /// largest change in intensity from their pixel's previous event are written, in their
+    /// original order. Empty events carry no intensity, so they are dropped first.
+    Budget { events_per_interval: usize },
 }
 
 /// Reorder the events according to their firing times
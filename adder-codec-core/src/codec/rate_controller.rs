@@ -1,6 +1,10 @@
 use crate::PlaneSize;
 
 /// Constant Rate Factor lookup table
+///
+/// Level 0 is "lossless visual": both the baseline and maximum contrast thresholds are 0, so any
+/// change in a pixel's intensity produces an event. This maximizes reconstruction fidelity at the
+/// cost of a much higher event rate.
 #[rustfmt::skip]
 pub static CRF: [[f32; 4]; 10] = [
 // baseline C     max C                 C increase velocity             feature radius
@@ -20,6 +24,9 @@ pub static CRF: [[f32; 4]; 10] = [
 /// The default CRF quality level
 pub const DEFAULT_CRF_QUALITY: u8 = 3;
 
+/// The "lossless visual" CRF quality level, which disables contrast thresholding entirely
+pub const CRF_LOSSLESS_QUALITY: u8 = 0;
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Crf {
     /// Constant Rate Factor (CRF) quality setting for the encoder. 0 is lossless, 9 is worst quality.
@@ -98,3 +105,25 @@ impl Crf {
         self.crf_quality
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crf_lossless_disables_thresholding() {
+        let plane = PlaneSize::new(64, 48, 1).unwrap();
+        let crf = Crf::new(Some(CRF_LOSSLESS_QUALITY), plane);
+        let parameters = crf.get_parameters();
+        assert_eq!(parameters.c_thresh_baseline, 0);
+        assert_eq!(parameters.c_thresh_max, 0);
+        assert_eq!(parameters.feature_c_radius, 0);
+        assert_eq!(crf.get_quality(), Some(0));
+
+        // Every other level must threshold at least as coarsely
+        for level in 1..CRF.len() as u8 {
+            let parameters = *Crf::new(Some(level), plane).get_parameters();
+            assert!(parameters.c_thresh_max > 0);
+        }
+    }
+}
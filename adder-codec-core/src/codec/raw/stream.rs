@@ -1,11 +1,15 @@
 // #[cfg(feature = "compression")]
 // use crate::codec::compressed::adu::frame::Adu;
 use crate::codec::header::{Magic, MAGIC_RAW};
-use crate::codec::{CodecError, CodecMetadata, ReadCompression, WriteCompression};
-use crate::{Coord, Event, EventSingle, EOF_PX_ADDRESS};
+use crate::codec::{
+    digest_events_each, CodecError, CodecMetadata, DeltaTEncoding, ReadCompression,
+    WriteCompression,
+};
+use crate::{Coord, CoordSingle, DeltaT, Event, EventSingle, ReservedEvent, D};
 use bincode::config::{FixintEncoding, WithOtherEndian, WithOtherIntEncoding};
 use bincode::{DefaultOptions, Options};
 use bitstream_io::{BigEndian, BitRead, BitReader};
+use log::{error, warn};
 use std::io::{Read, Seek, SeekFrom, Write};
 
 /// Write uncompressed (raw) ADΔER data to a stream.
@@ -75,15 +79,7 @@ impl<W: Write> WriteCompression<W> for RawOutput<W> {
 
     // If `self.writer` is a `BufWriter`, you'll need to flush it yourself after this.
     fn into_writer(&mut self) -> Option<W> {
-        let eof = Event {
-            coord: Coord {
-                x: EOF_PX_ADDRESS,
-                y: EOF_PX_ADDRESS,
-                c: Some(0),
-            },
-            d: 0,
-            t: 0,
-        };
+        let eof = ReservedEvent::Eof.to_event();
         self.bincode.serialize_into(self.stream(), &eof).unwrap();
         self.flush_writer().unwrap();
         self.stream.take()
@@ -99,15 +95,34 @@ impl<W: Write> WriteCompression<W> for RawOutput<W> {
     fn ingest_event(&mut self, event: Event) -> Result<(), CodecError> {
         // NOTE: for speed, the following checks only run in debug builds. It's entirely
         // possibly to encode nonsensical events if you want to.
-        debug_assert!(event.coord.x < self.meta.plane.width || event.coord.x == EOF_PX_ADDRESS);
-        debug_assert!(event.coord.y < self.meta.plane.height || event.coord.y == EOF_PX_ADDRESS);
+        debug_assert!(
+            ReservedEvent::from_event(&event).is_some()
+                || (event.coord.x < self.meta.plane.width
+                    && event.coord.y < self.meta.plane.height)
+        );
 
         // TODO: Switch functionality based on what the deltat mode is!
 
+        if self.meta.delta_t_encoding == DeltaTEncoding::Varint {
+            let mut buffer = Vec::with_capacity(self.meta.event_size as usize + 1);
+            if self.meta.plane.channels == 1 {
+                let output_event = EventSingle::try_from(&event)?;
+                self.bincode
+                    .serialize_into(&mut buffer, &output_event.coord)?;
+            } else {
+                let coord = event.coord;
+                self.bincode.serialize_into(&mut buffer, &coord)?;
+            }
+            buffer.push(event.d);
+            write_varint(&mut buffer, event.t);
+            self.stream().write_all(&buffer)?;
+            return Ok(());
+        }
+
         let output_event: EventSingle;
         if self.meta.plane.channels == 1 {
             // let event_to_write = self.queue.pop()
-            output_event = (&event).into();
+            output_event = EventSingle::try_from(&event)?;
             self.bincode.serialize_into(self.stream(), &output_event)?;
             // bincode::serialize_into(&mut *stream, &output_event, my_options).unwrap();
         } else {
@@ -146,6 +161,62 @@ impl<R: Read + Seek> RawInput<R> {
     }
 }
 
+impl<R: Read + Seek> RawInput<R> {
+    /// Read an event whose timestamp is a LEB128 varint. The fixed-size prefix (coordinate and
+    /// [`D`]) is `event_size - 4` bytes long.
+    fn digest_event_varint(
+        &mut self,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Event, CodecError> {
+        let mut buffer: Vec<u8> = vec![0; self.meta.event_size as usize - 4];
+        reader.read_bytes(&mut buffer)?;
+        let (coord, d): (Coord, D) = if self.meta.plane.channels == 1 {
+            match self
+                .bincode
+                .deserialize_from::<_, (CoordSingle, D)>(&*buffer)
+            {
+                Ok((coord, d)) => (Coord::new_2d(coord.x, coord.y), d),
+                Err(_e) => return Err(CodecError::Deserialize),
+            }
+        } else {
+            match self.bincode.deserialize_from::<_, (Coord, D)>(&*buffer) {
+                Ok(prefix) => prefix,
+                Err(_e) => return Err(CodecError::Deserialize),
+            }
+        };
+
+        // The end-of-stream marker is always written with a fixed-size timestamp, so check for it
+        // before reading the varint
+        if coord.is_eof() {
+            return Err(CodecError::Eof);
+        }
+
+        let t = read_varint(reader)?;
+        Ok(Event { coord, d, t })
+    }
+
+    /// Deserialize a single fixed-size event
+    fn deserialize_event(&self, bytes: &[u8]) -> Result<Event, CodecError> {
+        if self.meta.plane.channels == 1 {
+            match self.bincode.deserialize_from::<_, EventSingle>(bytes) {
+                Ok(ev) => Ok(ev.into()),
+                Err(_e) => Err(CodecError::Deserialize),
+            }
+        } else {
+            match self.bincode.deserialize_from::<_, Event>(bytes) {
+                Ok(ev) => Ok(ev),
+                Err(e) => {
+                    error!(
+                        "Error deserializing event of size {}: {e}",
+                        self.meta.event_size
+                    );
+                    Err(CodecError::Deserialize)
+                }
+            }
+        }
+    }
+}
+
 impl<R: Read + Seek> ReadCompression<R> for RawInput<R> {
     fn magic(&self) -> Magic {
         MAGIC_RAW
@@ -173,29 +244,63 @@ impl<R: Read + Seek> ReadCompression<R> for RawInput<R> {
 
     #[inline]
     fn digest_event(&mut self, reader: &mut BitReader<R, BigEndian>) -> Result<Event, CodecError> {
+        if self.meta.delta_t_encoding == DeltaTEncoding::Varint {
+            return self.digest_event_varint(reader);
+        }
+
         // TODO: Why is the encoded event size wrong?
         let mut buffer: Vec<u8> = vec![0; self.meta.event_size as usize];
         reader.read_bytes(&mut buffer)?;
-        let event: Event = if self.meta.plane.channels == 1 {
-            match self.bincode.deserialize_from::<_, EventSingle>(&*buffer) {
-                Ok(ev) => ev.into(),
-                Err(_e) => return Err(CodecError::Deserialize),
-            }
-        } else {
-            match self.bincode.deserialize_from::<_, Event>(&*buffer) {
-                Ok(ev) => ev,
+        let event = self.deserialize_event(&buffer)?;
+
+        match ReservedEvent::from_event(&event) {
+            Some(ReservedEvent::Eof) => Err(CodecError::Eof),
+            _ => Ok(event),
+        }
+    }
+
+    /// Read up to `max` fixed-size events with a single read, rather than one read per event.
+    ///
+    /// Only whole events are read, so a truncated trailing event is left in the stream and the
+    /// reader stays aligned to an event boundary.
+    fn digest_events(
+        &mut self,
+        max: usize,
+        buf: &mut Vec<Event>,
+        reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<usize, CodecError> {
+        if self.meta.delta_t_encoding == DeltaTEncoding::Varint {
+            return digest_events_each(self, max, buf, reader);
+        }
+
+        let event_size = self.meta.event_size as usize;
+        let start = reader.position_in_bits()?;
+        let end = reader.seek_bits(SeekFrom::End(0))?;
+        reader.seek_bits(SeekFrom::Start(start))?;
+        let available = ((end - start) / 8) as usize / event_size;
+        let count = max.min(available);
+
+        let mut buffer: Vec<u8> = vec![0; count * event_size];
+        reader.read_bytes(&mut buffer)?;
+        let len = buf.len();
+        buf.reserve(count);
+        for (i, bytes) in buffer.chunks_exact(event_size).enumerate() {
+            let event = match self.deserialize_event(bytes) {
+                Ok(event) => event,
                 Err(e) => {
-                    dbg!(self.meta.event_size);
-                    eprintln!("Error deserializing event: {e}");
-                    return Err(CodecError::Deserialize);
+                    buf.truncate(len);
+                    return Err(e);
                 }
+            };
+            if let Some(ReservedEvent::Eof) = ReservedEvent::from_event(&event) {
+                // Leave the reader just past the end-of-stream marker, as digest_event does
+                reader.seek_bits(SeekFrom::Start(start + ((i + 1) * event_size * 8) as u64))?;
+                return Ok(i);
             }
-        };
-
-        if event.coord.is_eof() {
-            return Err(CodecError::Eof);
+            buf.push(event);
         }
-        Ok(event)
+
+        Ok(count)
     }
 
     // #[cfg(feature = "compression")]
@@ -206,13 +311,22 @@ impl<R: Read + Seek> ReadCompression<R> for RawInput<R> {
     //     todo!()
     // }
 
+    /// Seek to the event at the given byte position.
+    ///
+    /// Always fails with [`CodecError::Seek`] for [`DeltaTEncoding::Varint`] streams, since their
+    /// events don't have a fixed stride.
     fn set_input_stream_position(
         &mut self,
         reader: &mut BitReader<R, BigEndian>,
         pos: u64,
     ) -> Result<(), CodecError> {
+        if self.meta.delta_t_encoding == DeltaTEncoding::Varint {
+            warn!("Cannot seek in a stream with varint-encoded timestamps");
+            return Err(CodecError::Seek);
+        }
+
         if (pos - self.meta.header_size as u64) % u64::from(self.meta.event_size) != 0 {
-            eprintln!("Attempted to seek to bad position in stream: {pos}");
+            warn!("Attempted to seek to bad position in stream: {pos}");
             return Err(CodecError::Seek);
         }
 
@@ -223,3 +337,37 @@ impl<R: Read + Seek> ReadCompression<R> for RawInput<R> {
         Ok(())
     }
 }
+
+/// Append `value` to `buffer` as an unsigned LEB128 varint
+fn write_varint(buffer: &mut Vec<u8>, mut value: DeltaT) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer.push(byte);
+            return;
+        }
+        buffer.push(byte | 0x80);
+    }
+}
+
+/// Read an unsigned LEB128 varint from the stream
+fn read_varint<R: Read + Seek>(reader: &mut BitReader<R, BigEndian>) -> Result<DeltaT, CodecError> {
+    let mut value: DeltaT = 0;
+    let mut shift = 0;
+    loop {
+        let byte: u8 = reader.read(8)?;
+        let bits = DeltaT::from(byte & 0x7F);
+        // Reject overlong encodings, including a last byte with bits beyond the top of a DeltaT
+        if shift >= DeltaT::BITS
+            || (shift > DeltaT::BITS - 7 && bits >> (DeltaT::BITS - shift) != 0)
+        {
+            return Err(CodecError::Deserialize);
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
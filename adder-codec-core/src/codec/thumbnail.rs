@@ -0,0 +1,91 @@
+use crate::codec::CodecError;
+use jpeg_decoder::PixelFormat;
+use jpeg_encoder::ColorType;
+use ndarray::Array3;
+
+/// Width, in pixels, of the thumbnails embedded in the stream header. Narrower frames are stored
+/// at their original size.
+pub(crate) const THUMBNAIL_WIDTH: usize = 64;
+
+/// JPEG quality (1-100) of the embedded thumbnails
+const THUMBNAIL_QUALITY: u8 = 85;
+
+/// Downscale a frame of shape (height, width, channels) to [`THUMBNAIL_WIDTH`], preserving its
+/// aspect ratio, and encode it as a JPEG. Each output pixel is the mean of the input pixels it
+/// covers. Color frames are in BGR order, like the transcoder's, and are stored as RGB.
+///
+/// # Errors
+/// Returns [`CodecError::Thumbnail`] if the frame doesn't have 1 or 3 channels, or is empty.
+pub(crate) fn encode_thumbnail(frame: &Array3<u8>) -> Result<Vec<u8>, CodecError> {
+    let (height, width, channels) = frame.dim();
+    let color_type = match channels {
+        1 => ColorType::Luma,
+        3 => ColorType::Rgb,
+        _ => {
+            return Err(CodecError::Thumbnail(format!(
+                "frames must have 1 or 3 channels, not {channels}"
+            )))
+        }
+    };
+    if height == 0 || width == 0 {
+        return Err(CodecError::Thumbnail("frame is empty".to_string()));
+    }
+
+    let out_width = width.min(THUMBNAIL_WIDTH);
+    let out_height = ((height * out_width + width / 2) / width).max(1);
+    let mut data = Vec::with_capacity(out_height * out_width * channels);
+    for y in 0..out_height {
+        let (y_start, y_end) = (y * height / out_height, (y + 1) * height / out_height);
+        for x in 0..out_width {
+            let (x_start, x_end) = (x * width / out_width, (x + 1) * width / out_width);
+            // Reversing the channels turns BGR into RGB
+            for c in (0..channels).rev() {
+                let mut sum = 0_u32;
+                for in_y in y_start..y_end {
+                    for in_x in x_start..x_end {
+                        sum += u32::from(frame[[in_y, in_x, c]]);
+                    }
+                }
+                let count = ((y_end - y_start) * (x_end - x_start)) as u32;
+                data.push(((sum + count / 2) / count) as u8);
+            }
+        }
+    }
+
+    let mut bytes = Vec::new();
+    jpeg_encoder::Encoder::new(&mut bytes, THUMBNAIL_QUALITY)
+        .encode(&data, out_width as u16, out_height as u16, color_type)
+        .map_err(|e| CodecError::Thumbnail(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Decode a JPEG thumbnail to an array of shape (height, width, channels). Color thumbnails are
+/// returned in BGR order, like the frames they were made from.
+///
+/// # Errors
+/// Returns [`CodecError::Thumbnail`] if the data isn't a JPEG with 1 or 3 channels of 8 bits.
+pub(crate) fn decode_thumbnail(bytes: &[u8]) -> Result<Array3<u8>, CodecError> {
+    let mut decoder = jpeg_decoder::Decoder::new(bytes);
+    let data = decoder
+        .decode()
+        .map_err(|e| CodecError::Thumbnail(e.to_string()))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| CodecError::Thumbnail("missing image info".to_string()))?;
+    let channels = match info.pixel_format {
+        PixelFormat::L8 => 1,
+        PixelFormat::RGB24 => 3,
+        format => {
+            return Err(CodecError::Thumbnail(format!(
+                "unsupported pixel format {format:?}"
+            )))
+        }
+    };
+    let mut thumbnail = Array3::from_shape_vec(
+        (usize::from(info.height), usize::from(info.width), channels),
+        data,
+    )
+    .map_err(|e| CodecError::Thumbnail(e.to_string()))?;
+    thumbnail.invert_axis(ndarray::Axis(2));
+    Ok(thumbnail.as_standard_layout().into_owned())
+}
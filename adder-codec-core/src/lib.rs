@@ -11,14 +11,14 @@ pub use bitstream_io;
 use bitstream_io::{BigEndian, BitReader};
 use std::cmp::Ordering;
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::ops::Add;
 
 use thiserror::Error;
 
 /// Error type for the `PlaneSize` struct
 #[allow(missing_docs)]
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum PlaneError {
     #[error(
         "plane dimensions invalid. All must be positive. Found {width:?}, {height:?}, {channels:?}"
@@ -176,6 +176,132 @@ impl PlaneSize {
     }
 }
 
+/// Error type for the [`TimeParams`] struct
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TimeParamsError {
+    #[error(
+        "ticks per second and reference interval must be positive. Found {tps}, {ref_interval}"
+    )]
+    ZeroTicks { tps: DeltaT, ref_interval: DeltaT },
+
+    #[error("frame rate must be positive and finite. Found {0}")]
+    InvalidFps(f32),
+
+    #[error("Δt_max ({delta_t_max}) must be at least the reference interval ({ref_interval})")]
+    DeltaTMaxTooSmall {
+        ref_interval: DeltaT,
+        delta_t_max: DeltaT,
+    },
+
+    #[error("time parameters overflow a timestamp")]
+    Overflow,
+}
+
+/// The time parameters of an ADΔER stream: its tick rate, the number of ticks in each source
+/// interval, and the longest time a pixel may go without firing.
+///
+/// A source's frame rate, `tps`, and `ref_interval` are tied together, as are `ref_interval`,
+/// `delta_t_max`, and the Δt_max multiplier. Constructing them all here keeps the derived values
+/// consistent, however the caller happens to specify them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeParams {
+    tps: DeltaT,
+    ref_interval: DeltaT,
+    delta_t_max: DeltaT,
+}
+
+impl TimeParams {
+    /// Create a new `TimeParams` with the given ticks per second, reference interval, and
+    /// Δt_max, in ticks
+    pub fn new(
+        tps: DeltaT,
+        ref_interval: DeltaT,
+        delta_t_max: DeltaT,
+    ) -> Result<Self, TimeParamsError> {
+        if tps == 0 || ref_interval == 0 {
+            return Err(TimeParamsError::ZeroTicks { tps, ref_interval });
+        }
+        if delta_t_max < ref_interval {
+            return Err(TimeParamsError::DeltaTMaxTooSmall {
+                ref_interval,
+                delta_t_max,
+            });
+        }
+        Ok(Self {
+            tps,
+            ref_interval,
+            delta_t_max,
+        })
+    }
+
+    /// Create a new `TimeParams` with the given ticks per second and reference interval, and a
+    /// Δt_max of `delta_t_max_mult` reference intervals
+    pub fn from_tps(
+        tps: DeltaT,
+        ref_interval: DeltaT,
+        delta_t_max_mult: u32,
+    ) -> Result<Self, TimeParamsError> {
+        let delta_t_max = ref_interval
+            .checked_mul(delta_t_max_mult)
+            .ok_or(TimeParamsError::Overflow)?;
+        Self::new(tps, ref_interval, delta_t_max)
+    }
+
+    /// Create a new `TimeParams` for a source running at `fps`, where each source interval spans
+    /// `ref_interval` ticks, and a Δt_max of `delta_t_max_mult` reference intervals. The ticks
+    /// per second are rounded to the nearest whole tick.
+    pub fn from_fps(
+        fps: f32,
+        ref_interval: DeltaT,
+        delta_t_max_mult: u32,
+    ) -> Result<Self, TimeParamsError> {
+        if !fps.is_finite() || fps <= 0.0 {
+            return Err(TimeParamsError::InvalidFps(fps));
+        }
+        let tps = (f64::from(fps) * f64::from(ref_interval)).round();
+        if tps > f64::from(DeltaT::MAX) {
+            return Err(TimeParamsError::Overflow);
+        }
+        Self::from_tps(tps as DeltaT, ref_interval, delta_t_max_mult)
+    }
+
+    /// Ticks per second
+    pub fn tps(&self) -> DeltaT {
+        self.tps
+    }
+
+    /// Ticks per source interval
+    pub fn ref_interval(&self) -> DeltaT {
+        self.ref_interval
+    }
+
+    /// The maximum number of ticks between a pixel's events
+    pub fn delta_t_max(&self) -> DeltaT {
+        self.delta_t_max
+    }
+
+    /// The number of whole reference intervals in Δt_max
+    pub fn delta_t_max_mult(&self) -> u32 {
+        self.delta_t_max / self.ref_interval
+    }
+
+    /// The frame rate of the source
+    pub fn fps(&self) -> f64 {
+        f64::from(self.tps) / f64::from(self.ref_interval)
+    }
+
+    /// Ticks per output frame, when framing at `output_fps`. With no output frame rate, each
+    /// frame spans one reference interval. An output frame rate above the tick rate can't be
+    /// honored, so such frames span a single tick rather than zero.
+    pub fn tpf(&self, output_fps: Option<f32>) -> DeltaT {
+        match output_fps {
+            Some(output_fps) => ((self.tps as f32 / output_fps) as DeltaT).max(1),
+            None => self.ref_interval,
+        }
+    }
+}
+
 /// Decimation value; a pixel's sensitivity.
 pub type D = u8;
 
@@ -203,7 +329,7 @@ pub enum Mode {
     Continuous,
 }
 
-#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
 pub enum PixelMultiMode {
     Normal,
 
@@ -233,8 +359,58 @@ make_d_shift_array!(D_SHIFT, UDshift);
 make_d_shift_array!(D_SHIFT_F64, f64);
 make_d_shift_array!(D_SHIFT_F32, f32);
 
-/// The maximum intensity representation for input data. Currently 255 for 8-bit framed input.
-pub const MAX_INTENSITY: f32 = 255.0; // TODO: make variable, dependent on input bit depth
+/// The intensity to integrate for a given [`D`] value, or `None` if `d` is greater than [`D_MAX`].
+///
+/// Special values like [`D_EMPTY`], and corrupted values from an untrusted stream, give `None`
+/// rather than panicking as indexing [`D_SHIFT`] directly would.
+#[must_use]
+#[inline]
+pub fn d_shift(d: D) -> Option<UDshift> {
+    D_SHIFT.get(d as usize).copied()
+}
+
+/// The range of [`D`] values a transcoder may emit, and the intensity each one integrates. A
+/// smaller `d_max` caps how much intensity a pixel can integrate before it must fire.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DConfig {
+    /// The largest [`D`] value to emit
+    pub d_max: D,
+
+    /// The intensity to integrate for each [`D`] value in `0..=d_max`
+    pub d_shift: Vec<UDshift>,
+}
+
+impl DConfig {
+    /// Derive the shift table for `d_max`, which is clamped to [`D_MAX`]
+    #[must_use]
+    pub fn new(d_max: D) -> Self {
+        let d_max = d_max.min(D_MAX);
+        Self {
+            d_max,
+            d_shift: D_SHIFT[..=d_max as usize].to_vec(),
+        }
+    }
+
+    /// The intensity to integrate for a given [`D`] value, or `None` if `d` is greater than
+    /// `d_max`. See [`d_shift`].
+    #[must_use]
+    #[inline]
+    pub fn d_shift(&self, d: D) -> Option<UDshift> {
+        self.d_shift.get(d as usize).copied()
+    }
+}
+
+impl Default for DConfig {
+    fn default() -> Self {
+        Self::new(D_MAX)
+    }
+}
+
+/// The maximum intensity representation for 8-bit framed input.
+#[deprecated(
+    note = "please use `SourceType::max_intensity`, which depends on the source bit depth"
+)]
+pub const MAX_INTENSITY: f32 = 255.0;
 
 /// The default [`D`] value for every pixel at the beginning of transcode
 pub const D_START: D = 7;
@@ -258,6 +434,11 @@ pub type PixelAddress = u16;
 /// Special pixel address when signifying the end of a sequence of [Events](Event)
 pub const EOF_PX_ADDRESS: PixelAddress = u16::MAX;
 
+/// Special y-address which, paired with an x-address of [`EOF_PX_ADDRESS`], marks a rebase
+/// event. Its `t` is the number of ticks by which every pixel's running timestamp is offset from
+/// that point on in the stream.
+pub const REBASE_PX_ADDRESS: PixelAddress = u16::MAX - 1;
+
 /// Pixel channel address in the ADΔER model
 #[repr(packed)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -268,7 +449,8 @@ pub struct Coord {
     /// Pixel y-coordinate
     pub y: PixelAddress,
 
-    /// Pixel channel, if present
+    /// Pixel channel. By convention, this is `None` in single-channel (grayscale) planes and
+    /// `Some` in color planes. See [`Coord::normalize`].
     pub c: Option<u8>,
 }
 
@@ -338,6 +520,23 @@ impl Coord {
         self.c.is_some()
     }
 
+    /// Returns the coordinate with its channel following the convention for a plane with
+    /// `channels` channels: `None` if there is one channel, and `Some` otherwise. A missing
+    /// channel is taken to be channel 0.
+    ///
+    /// Returns `None` if the channel does not exist in such a plane.
+    #[must_use]
+    pub fn normalize(self, channels: u8) -> Option<Coord> {
+        let c = self.c.unwrap_or(0);
+        if c >= channels {
+            return None;
+        }
+        Some(Coord {
+            c: if channels == 1 { None } else { Some(c) },
+            ..self
+        })
+    }
+
     /// Returns true if the coordinate is valid
     pub fn is_valid(&self) -> bool {
         self.x != EOF_PX_ADDRESS && self.y != EOF_PX_ADDRESS
@@ -348,6 +547,17 @@ impl Coord {
         self.x == EOF_PX_ADDRESS && self.y == EOF_PX_ADDRESS
     }
 
+    /// Returns true if the coordinate is the rebase coordinate
+    pub fn is_rebase(&self) -> bool {
+        self.x == EOF_PX_ADDRESS && self.y == REBASE_PX_ADDRESS
+    }
+
+    /// Returns true if the coordinate lies in the reserved namespace of special events. See
+    /// [`ReservedEvent`].
+    pub fn is_reserved(&self) -> bool {
+        self.x == EOF_PX_ADDRESS
+    }
+
     /// Is this coordinate at the border of the image?
     pub fn is_border(&self, width: usize, height: usize, cs: usize) -> bool {
         self.x_usize() < cs
@@ -393,19 +603,28 @@ pub struct EventSingle {
     pub t: DeltaT,
 }
 
-impl From<&Event> for EventSingle {
-    fn from(event: &Event) -> Self {
-        EventSingle {
+/// Drops the channel, which must be absent or 0. Events of multi-channel streams can't be
+/// represented as an [`EventSingle`] without losing their channel, so they're rejected instead.
+impl TryFrom<&Event> for EventSingle {
+    type Error = CodecError;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.coord.c.unwrap_or(0) != 0 {
+            return Err(CodecError::InvalidChannel(event.coord.c, 1));
+        }
+        Ok(EventSingle {
             coord: CoordSingle {
                 x: event.coord.x,
                 y: event.coord.y,
             },
             d: event.d,
             t: event.t,
-        }
+        })
     }
 }
 
+/// The channel is `None`, following the convention for single-channel planes (see
+/// [`Coord::normalize`]).
 impl From<EventSingle> for Event {
     fn from(event: EventSingle) -> Self {
         Event {
@@ -420,6 +639,64 @@ impl From<EventSingle> for Event {
     }
 }
 
+impl Event {
+    /// Create a rebase event, which offsets every pixel's running timestamp by `offset` ticks.
+    /// Decoders pass it through like any other event; check for it with [`Coord::is_rebase`].
+    pub fn rebase(offset: DeltaT) -> Self {
+        ReservedEvent::Rebase(offset).to_event()
+    }
+}
+
+/// A special event in the reserved coordinate namespace, rather than a pixel's event.
+///
+/// Reserved events all have an x-address of [`EOF_PX_ADDRESS`], which no real pixel can have,
+/// and are told apart by their y-address. Every reserved y-address is defined here, so that new
+/// kinds of special events can't collide with each other or with pixel data.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReservedEvent {
+    /// Marks the end of the stream. Has a y-address of [`EOF_PX_ADDRESS`].
+    Eof,
+
+    /// Offsets every pixel's running timestamp by the given number of ticks from this point on
+    /// in the stream. Has a y-address of [`REBASE_PX_ADDRESS`].
+    Rebase(DeltaT),
+}
+
+impl ReservedEvent {
+    /// Identify the reserved event which `event` encodes. Returns `None` for pixel events, and
+    /// for reserved coordinates which don't name a known kind of event.
+    #[must_use]
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let coord = event.coord;
+        if !coord.is_reserved() {
+            return None;
+        }
+        match coord.y {
+            EOF_PX_ADDRESS => Some(ReservedEvent::Eof),
+            REBASE_PX_ADDRESS => Some(ReservedEvent::Rebase(event.t)),
+            _ => None,
+        }
+    }
+
+    /// The event which encodes this reserved event in a stream
+    #[must_use]
+    pub const fn to_event(self) -> Event {
+        let (y, t) = match self {
+            ReservedEvent::Eof => (EOF_PX_ADDRESS, 0),
+            ReservedEvent::Rebase(offset) => (REBASE_PX_ADDRESS, offset),
+        };
+        Event {
+            coord: Coord {
+                x: EOF_PX_ADDRESS,
+                y,
+                c: Some(0),
+            },
+            d: 0,
+            t,
+        }
+    }
+}
+
 impl Ord for Event {
     fn cmp(&self, other: &Self) -> Ordering {
         let b = other.t;
@@ -446,15 +723,36 @@ pub enum SourceType {
     F64,
 }
 
-const EOF_EVENT: Event = Event {
-    coord: Coord {
-        x: EOF_PX_ADDRESS,
-        y: EOF_PX_ADDRESS,
-        c: Some(0),
-    },
-    d: 0,
-    t: 0,
-};
+impl SourceType {
+    /// The number of bits in this type's container
+    #[must_use]
+    pub fn container_bits(&self) -> u8 {
+        match self {
+            SourceType::U8 => 8,
+            SourceType::U16 => 16,
+            SourceType::U32 | SourceType::F32 => 32,
+            SourceType::U64 | SourceType::F64 => 64,
+        }
+    }
+
+    /// The maximum intensity of a source of this type whose samples only use the low `bit_depth`
+    /// bits of the container (e.g., 10- or 12-bit data packed in a `u16`). If `bit_depth` is
+    /// `None`, the full width of the container is assumed. Float sources are normalized to 1.0.
+    #[must_use]
+    pub fn max_intensity(&self, bit_depth: Option<u8>) -> f64 {
+        match self {
+            SourceType::F32 | SourceType::F64 => 1.0,
+            _ => {
+                let bits = bit_depth
+                    .unwrap_or(self.container_bits())
+                    .clamp(1, self.container_bits());
+                ((1_u128 << bits) - 1) as f64
+            }
+        }
+    }
+}
+
+const EOF_EVENT: Event = ReservedEvent::Eof.to_event();
 
 /// Helper function for opening a file as a raw or compressed input ADΔER stream
 pub fn open_file_decoder(
@@ -492,6 +790,75 @@ pub fn open_file_decoder(
     Ok((stream, bitreader))
 }
 
+/// An input stream that can't be seeked, such as stdin or a network socket.
+///
+/// Wraps any [`Read`] so it can back a [`Decoder`]. Querying the current position works, but any
+/// other seek fails, so [`Decoder::set_input_stream_position`] and
+/// [`Decoder::get_eof_position`] return errors for these streams.
+pub struct UnseekableReader {
+    reader: BufReader<Box<dyn Read>>,
+    position: u64,
+}
+
+impl UnseekableReader {
+    /// Wrap the given reader
+    pub fn new<R: Read + 'static>(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(Box::new(reader)),
+            position: 0,
+        }
+    }
+}
+
+impl Read for UnseekableReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl BufRead for UnseekableReader {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.reader.consume(amt);
+        self.position += amt as u64;
+    }
+}
+
+impl Seek for UnseekableReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match pos {
+            SeekFrom::Current(0) => Ok(self.position),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "cannot seek in an unseekable input stream",
+            )),
+        }
+    }
+}
+
+/// Helper function for opening any reader, such as stdin, as a raw input ADΔER stream.
+///
+/// Unlike [`open_file_decoder`], this can't fall back to decoding a compressed stream, since the
+/// header bytes have already been consumed by the time the raw decoder rejects them.
+pub fn open_reader_decoder<R: Read + 'static>(
+    reader: R,
+) -> Result<
+    (
+        Decoder<UnseekableReader>,
+        BitReader<UnseekableReader, BigEndian>,
+    ),
+    CodecError,
+> {
+    let mut bitreader = BitReader::endian(UnseekableReader::new(reader), BigEndian);
+    let stream = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+    Ok((stream, bitreader))
+}
+
 /// An ADΔER event representation
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
@@ -535,8 +902,29 @@ impl From<Event> for EventCoordless {
 impl Add<EventCoordless> for EventCoordless {
     type Output = EventCoordless;
 
-    fn add(self, _rhs: EventCoordless) -> EventCoordless {
-        todo!()
+    /// Combine two events into one spanning both of their times, whose [`D`] is the log2 of
+    /// their summed integrated intensities (rounded down, and capped at [`D_MAX`]). Empty events
+    /// contribute no intensity. Adding a zero event leaves the other unchanged.
+    fn add(self, rhs: EventCoordless) -> EventCoordless {
+        use num_traits::Zero;
+        if self.is_zero() {
+            return rhs;
+        }
+        if rhs.is_zero() {
+            return self;
+        }
+
+        let intensity = |d: D| D_SHIFT_F64.get(d as usize).copied().unwrap_or(0.0);
+        let total = intensity(self.d) + intensity(rhs.d);
+        let d = if total > 0.0 {
+            (total.log2() as D).min(D_MAX)
+        } else {
+            D_EMPTY
+        };
+        EventCoordless {
+            d,
+            t: self.t.saturating_add(rhs.t),
+        }
     }
 }
 
@@ -554,6 +942,57 @@ impl num_traits::Zero for EventCoordless {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_open_reader_decoder() {
+        use crate::codec::encoder::Encoder;
+        use crate::codec::raw::stream::RawOutput;
+        use crate::codec::{CodecMetadata, EncoderOptions};
+        use std::io::{BufWriter, Cursor};
+
+        let plane = PlaneSize::new(2, 1, 1).unwrap();
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    ..Default::default()
+                },
+                BufWriter::new(Vec::new()),
+            ),
+            EncoderOptions::default(plane),
+        );
+        let events: Vec<Event> = (0..2)
+            .map(|x| Event {
+                coord: Coord::new(x, 0, None),
+                d: 7,
+                t: 100 * (u32::from(x) + 1),
+            })
+            .collect();
+        encoder.ingest_events(&events).unwrap();
+        let output = encoder
+            .close_writer()
+            .unwrap()
+            .unwrap()
+            .into_inner()
+            .unwrap();
+
+        let (mut decoder, mut bitreader) = open_reader_decoder(Cursor::new(output)).unwrap();
+        let header_size = decoder.meta().header_size as u64;
+        assert_eq!(
+            decoder.get_input_stream_position(&mut bitreader).unwrap(),
+            header_size
+        );
+        for event in events {
+            assert_eq!(decoder.digest_event(&mut bitreader).unwrap(), event);
+        }
+        assert!(matches!(
+            decoder.digest_event(&mut bitreader),
+            Err(CodecError::Eof)
+        ));
+        assert!(decoder
+            .set_input_stream_position(&mut bitreader, header_size)
+            .is_err());
+    }
+
     #[test]
     fn test_dshift_arrays() {
         assert_eq!(D_SHIFT[0], 1);
@@ -567,6 +1006,28 @@ mod tests {
         assert_eq!(D_SHIFT_F32[127], D_SHIFT[127] as f32);
     }
 
+    #[test]
+    fn test_d_shift() {
+        assert_eq!(d_shift(0), Some(1));
+        assert_eq!(d_shift(D_MAX), Some(1_u128 << D_MAX));
+        assert_eq!(d_shift(D_MAX + 1), None);
+        assert_eq!(d_shift(D_ZERO_INTEGRATION), None);
+        assert_eq!(d_shift(D_EMPTY), None);
+    }
+
+    #[test]
+    fn test_d_config() {
+        let config = DConfig::new(12);
+        assert_eq!(config.d_max, 12);
+        assert_eq!(config.d_shift.len(), 13);
+        assert_eq!(config.d_shift(12), Some(1 << 12));
+        assert_eq!(config.d_shift(13), None);
+
+        // Clamped to the full range
+        assert_eq!(DConfig::new(D_EMPTY), DConfig::default());
+        assert_eq!(DConfig::default().d_shift.as_slice(), &D_SHIFT[..]);
+    }
+
     #[test]
     fn test_plane_size() {
         let plane_size = PlaneSize::new(1, 1, 1).unwrap();
@@ -650,4 +1111,167 @@ mod tests {
         assert!(!coord.is_valid());
         assert!(coord.is_eof());
     }
+
+    #[test]
+    fn test_reserved_events() {
+        for reserved in [
+            ReservedEvent::Eof,
+            ReservedEvent::Rebase(0),
+            ReservedEvent::Rebase(1234),
+        ] {
+            let event = reserved.to_event();
+            assert!(event.coord.is_reserved());
+            assert_eq!(ReservedEvent::from_event(&event), Some(reserved));
+        }
+        assert!(ReservedEvent::Eof.to_event().coord.is_eof());
+        assert!(ReservedEvent::Rebase(5).to_event().coord.is_rebase());
+        assert_eq!(Event::rebase(5), ReservedEvent::Rebase(5).to_event());
+
+        // The largest real pixel address is not reserved
+        let max = Event {
+            coord: Coord::new(EOF_PX_ADDRESS - 1, EOF_PX_ADDRESS - 1, Some(2)),
+            d: 7,
+            t: 100,
+        };
+        assert!(!max.coord.is_reserved());
+        assert_eq!(ReservedEvent::from_event(&max), None);
+
+        // Nor is a real pixel whose y-address happens to match a reserved one
+        let pixel = Event {
+            coord: Coord::new(0, REBASE_PX_ADDRESS, None),
+            d: 7,
+            t: 100,
+        };
+        assert_eq!(ReservedEvent::from_event(&pixel), None);
+
+        // An unassigned reserved address isn't a known event
+        let unknown = Event {
+            coord: Coord::new(EOF_PX_ADDRESS, 0, Some(0)),
+            d: 0,
+            t: 0,
+        };
+        assert!(unknown.coord.is_reserved());
+        assert_eq!(ReservedEvent::from_event(&unknown), None);
+    }
+
+    #[test]
+    fn test_coord_normalize() {
+        let gray = Coord::new(1, 2, None);
+        assert_eq!(gray.normalize(1), Some(gray));
+        assert_eq!(Coord::new(1, 2, Some(0)).normalize(1), Some(gray));
+        assert_eq!(Coord::new(1, 2, Some(1)).normalize(1), None);
+
+        let color = Coord::new(1, 2, Some(0));
+        assert_eq!(gray.normalize(3), Some(color));
+        assert_eq!(color.normalize(3), Some(color));
+        assert_eq!(
+            Coord::new(1, 2, Some(2)).normalize(3),
+            Some(Coord::new(1, 2, Some(2)))
+        );
+        assert_eq!(Coord::new(1, 2, Some(3)).normalize(3), None);
+    }
+
+    #[test]
+    fn test_event_single_channel() {
+        use crate::codec::encoder::Encoder;
+        use crate::codec::raw::stream::RawOutput;
+        use crate::codec::{CodecMetadata, EncoderOptions};
+        use std::io::{BufWriter, Cursor};
+
+        for c in [None, Some(0)] {
+            let event = Event {
+                coord: Coord::new(3, 4, c),
+                d: 7,
+                t: 100,
+            };
+            let single = EventSingle::try_from(&event).unwrap();
+            assert_eq!(
+                Event::from(single),
+                Event {
+                    coord: Coord::new(3, 4, None),
+                    ..event
+                }
+            );
+        }
+
+        // The channel can't be dropped silently
+        let event = Event {
+            coord: Coord::new(3, 4, Some(2)),
+            d: 7,
+            t: 100,
+        };
+        assert!(matches!(
+            EventSingle::try_from(&event),
+            Err(CodecError::InvalidChannel(Some(2), 1))
+        ));
+
+        // A color stream keeps it
+        let plane = PlaneSize::new(8, 8, 3).unwrap();
+        let mut encoder: Encoder<BufWriter<Vec<u8>>> = Encoder::new_raw(
+            RawOutput::new(
+                CodecMetadata {
+                    plane,
+                    ..Default::default()
+                },
+                BufWriter::new(Vec::new()),
+            ),
+            EncoderOptions::default(plane),
+        );
+        encoder.ingest_event(event).unwrap();
+        let output = encoder
+            .close_writer()
+            .unwrap()
+            .unwrap()
+            .into_inner()
+            .unwrap();
+        let (mut decoder, mut bitreader) = open_reader_decoder(Cursor::new(output)).unwrap();
+        assert_eq!(decoder.digest_event(&mut bitreader).unwrap(), event);
+    }
+
+    #[test]
+    fn test_time_params() {
+        let from_fps = TimeParams::from_fps(30.0, 255, 4).unwrap();
+        let from_tps = TimeParams::from_tps(7650, 255, 4).unwrap();
+        let explicit = TimeParams::new(7650, 255, 1020).unwrap();
+        assert_eq!(from_fps, from_tps);
+        assert_eq!(from_fps, explicit);
+        assert_eq!(from_fps.tps(), 7650);
+        assert_eq!(from_fps.ref_interval(), 255);
+        assert_eq!(from_fps.delta_t_max(), 1020);
+        assert_eq!(from_fps.delta_t_max_mult(), 4);
+        assert!((from_fps.fps() - 30.0).abs() < 1e-9);
+        assert_eq!(from_fps.tpf(None), 255);
+        assert_eq!(from_fps.tpf(Some(60.0)), 127);
+        assert_eq!(from_fps.tpf(Some(1e6)), 1);
+
+        // Non-integer frame rates snap to the nearest tick
+        assert_eq!(
+            TimeParams::from_fps(29.97, 1001, 2).unwrap(),
+            TimeParams::from_tps(30000, 1001, 2).unwrap()
+        );
+
+        assert_eq!(
+            TimeParams::from_fps(0.0, 255, 4),
+            Err(TimeParamsError::InvalidFps(0.0))
+        );
+        assert!(TimeParams::from_fps(f32::NAN, 255, 4).is_err());
+        assert_eq!(
+            TimeParams::from_tps(7650, 0, 4),
+            Err(TimeParamsError::ZeroTicks {
+                tps: 7650,
+                ref_interval: 0
+            })
+        );
+        assert_eq!(
+            TimeParams::from_tps(7650, 255, 0),
+            Err(TimeParamsError::DeltaTMaxTooSmall {
+                ref_interval: 255,
+                delta_t_max: 0
+            })
+        );
+        assert_eq!(
+            TimeParams::from_tps(7650, u32::MAX, 2),
+            Err(TimeParamsError::Overflow)
+        );
+    }
 }
@@ -0,0 +1,133 @@
+extern crate adder_codec_core;
+
+use adder_codec_core::codec::decoder::Decoder;
+use adder_codec_core::codec::encoder::Encoder;
+use adder_codec_core::codec::raw::stream::{RawInput, RawOutput};
+use adder_codec_core::codec::{
+    CodecError, CodecMetadata, DeltaTEncoding, EncoderOptions, LATEST_CODEC_VERSION,
+};
+use adder_codec_core::{Coord, Event, PlaneSize, TimeMode, D_EMPTY, D_MAX};
+use bitstream_io::{BigEndian, BitReader};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestError, TestRunner};
+use std::io::{BufReader, Cursor};
+
+/// Random stream parameters, and a sequence of events which are valid for them
+fn stream_strategy() -> impl Strategy<Value = (CodecMetadata, Vec<Event>)> {
+    (
+        1_u16..=64,
+        1_u16..=64,
+        prop_oneof![Just(1_u8), Just(3_u8)],
+        2_u8..=LATEST_CODEC_VERSION,
+        prop_oneof![Just(TimeMode::DeltaT), Just(TimeMode::AbsoluteT)],
+        1_u32..=1_000_000,
+    )
+        .prop_flat_map(|(width, height, channels, codec_version, time_mode, dtm)| {
+            let meta = CodecMetadata {
+                codec_version,
+                time_mode,
+                plane: PlaneSize::new(width, height, channels).unwrap(),
+                delta_t_max: dtm,
+                ..Default::default()
+            };
+            let event = (
+                0..width,
+                0..height,
+                0..channels,
+                prop_oneof![0..=D_MAX, Just(D_EMPTY)],
+                0..=dtm,
+            )
+                .prop_map(move |(x, y, c, d, t)| Event {
+                    coord: Coord {
+                        x,
+                        y,
+                        // Single-channel streams don't store the channel
+                        c: if channels == 1 { None } else { Some(c) },
+                    },
+                    d,
+                    t,
+                });
+            (Just(meta), prop::collection::vec(event, 0..200))
+        })
+}
+
+fn encode(meta: CodecMetadata, events: &[Event]) -> Vec<u8> {
+    let mut encoder: Encoder<Vec<u8>> = Encoder::new_raw(
+        RawOutput::new(meta, Vec::new()),
+        EncoderOptions::default(meta.plane),
+    );
+    encoder.ingest_events(events).unwrap();
+    encoder.close_writer().unwrap().unwrap()
+}
+
+proptest! {
+    #[test]
+    fn raw_round_trip((meta, events) in stream_strategy()) {
+        let output = encode(meta, &events);
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(output)), BigEndian);
+        let mut decoder = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+
+        for event in &events {
+            prop_assert_eq!(decoder.digest_event(&mut bitreader).unwrap(), *event);
+        }
+        prop_assert!(matches!(
+            decoder.digest_event(&mut bitreader),
+            Err(CodecError::Eof)
+        ));
+
+        // Seeking to any event boundary must land on that event. Go backwards, so every seek
+        // moves away from where the last read left off.
+        let header_size = decoder.meta().header_size as u64;
+        let event_size = u64::from(decoder.meta().event_size);
+        for (i, event) in events.iter().enumerate().rev() {
+            decoder
+                .set_input_stream_position(&mut bitreader, header_size + i as u64 * event_size)
+                .unwrap();
+            prop_assert_eq!(decoder.digest_event(&mut bitreader).unwrap(), *event);
+        }
+    }
+}
+
+/// Demonstrates how a failing property shrinks to a minimal case. The property here is
+/// deliberately false: a varint timestamp only fits in a single byte below 128, so the shrinker
+/// should home in on exactly that boundary.
+#[test]
+fn varint_timestamp_size_shrinks_to_boundary() {
+    let plane = PlaneSize::new(1, 1, 1).unwrap();
+    let meta = CodecMetadata {
+        plane,
+        delta_t_encoding: DeltaTEncoding::Varint,
+        delta_t_max: 100_000,
+        ..Default::default()
+    };
+    let encoded_len = |t| {
+        encode(
+            meta,
+            &[Event {
+                coord: Coord {
+                    x: 0,
+                    y: 0,
+                    c: None,
+                },
+                d: 7,
+                t,
+            }],
+        )
+        .len()
+    };
+    let baseline = encoded_len(0);
+
+    let mut runner = TestRunner::new(Config {
+        failure_persistence: None,
+        ..Config::default()
+    });
+    let result = runner.run(&(0..100_000_u32), |t| {
+        prop_assert_eq!(encoded_len(t), baseline);
+        Ok(())
+    });
+
+    match result {
+        Err(TestError::Fail(_, t)) => assert_eq!(t, 128),
+        other => panic!("expected the property to fail, got {other:?}"),
+    }
+}
@@ -0,0 +1,44 @@
+//! Event data and framers shared by the framer benchmarks and their smoke test
+
+use adder_codec_core::SourceCamera::FramedU8;
+use adder_codec_core::SourceType::U8;
+use adder_codec_core::{Coord, Event, PlaneSize, TimeMode};
+use adder_codec_rs::framer::driver::FramerMode::INSTANTANEOUS;
+use adder_codec_rs::framer::driver::{FrameSequence, Framer, FramerBuilder};
+
+pub const WIDTH: u16 = 1920;
+pub const HEIGHT: u16 = 1080;
+pub const CHUNK_ROWS: usize = 64;
+
+/// One event for every pixel of a 1080p frame, bucketed by framer chunk
+pub fn frame_of_events() -> Vec<Vec<Event>> {
+    let mut buckets = vec![Vec::new(); (HEIGHT as usize + CHUNK_ROWS - 1) / CHUNK_ROWS];
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            buckets[y as usize / CHUNK_ROWS].push(Event {
+                coord: Coord::new(x, y, None),
+                d: 5 + (x % 3) as u8,
+                t: 1000,
+            });
+        }
+    }
+    buckets
+}
+
+pub fn new_framer() -> FrameSequence<u8> {
+    FramerBuilder::new(PlaneSize::new(WIDTH, HEIGHT, 1).unwrap(), CHUNK_ROWS)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap()
+}
+
+/// A framer which has ingested two frames' worth of events, so it has complete frames to write
+pub fn filled_framer(events: &[Vec<Event>]) -> FrameSequence<u8> {
+    let mut framer = new_framer();
+    framer.ingest_events_events(events.to_vec());
+    framer.ingest_events_events(events.to_vec());
+    framer
+}
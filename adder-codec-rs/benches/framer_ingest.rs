@@ -0,0 +1,74 @@
+mod common;
+
+use adder_codec_core::Event;
+use adder_codec_rs::framer::driver::Framer;
+use common::{filled_framer, frame_of_events, new_framer};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use std::io::sink;
+
+fn ingest_events_events(c: &mut Criterion) {
+    let events = frame_of_events();
+    let mut group = c.benchmark_group("reconstruction");
+    group.throughput(Throughput::Elements(
+        events.iter().map(Vec::len).sum::<usize>() as u64,
+    ));
+    group.bench_function("ingest_events_events 1080p", |b| {
+        b.iter_batched(
+            || (new_framer(), events.clone()),
+            |(mut framer, events)| framer.ingest_events_events(events),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// Compare ingesting events one at a time, as the CLI tools do, with ingesting them as a batch
+fn ingest_event_batch(c: &mut Criterion) {
+    // Five frames' worth of events, about 10 million in all
+    let events: Vec<Event> = frame_of_events().concat().repeat(5);
+    let mut group = c.benchmark_group("reconstruction");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(events.len() as u64));
+    group.bench_function("ingest_event 1080p", |b| {
+        b.iter_batched(
+            new_framer,
+            |mut framer| {
+                let mut filled = false;
+                for mut event in events.iter().copied() {
+                    filled = framer.ingest_event(&mut event, None);
+                }
+                filled
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("ingest_event_batch 1080p", |b| {
+        b.iter_batched(
+            new_framer,
+            |mut framer| framer.ingest_event_batch(&events),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+fn write_multi_frame_bytes(c: &mut Criterion) {
+    let events = frame_of_events();
+    let mut group = c.benchmark_group("reconstruction");
+    group.bench_function("write_multi_frame_bytes 1080p", |b| {
+        b.iter_batched(
+            || filled_framer(&events),
+            |mut framer| framer.write_multi_frame_bytes(&mut sink()).unwrap(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    ingest_events_events,
+    ingest_event_batch,
+    write_multi_frame_bytes
+);
+criterion_main!(benches);
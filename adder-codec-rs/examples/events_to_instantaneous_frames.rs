@@ -41,42 +41,44 @@ fn main() {
         )
         .mode(INSTANTANEOUS)
         .source(reader.get_source_type(), reader.meta().source_camera)
-        .finish();
+        .pixel_multi_mode(reader.meta().pixel_multi_mode)
+        .finish()
+        .unwrap();
 
     let mut now = Instant::now();
     let mut frame_count = 0;
-    loop {
-        match reader.digest_event(&mut bitreader) {
-            Ok(mut event) => {
-                if frame_sequence.ingest_event(&mut event, None) {
-                    match frame_sequence.write_multi_frame_bytes(&mut output_stream) {
-                        Ok(0) => {
-                            panic!("Should have frame, but didn't")
-                        }
-                        Ok(frames_returned) => {
-                            frame_count += frames_returned;
-                            print!(
-                                "\rOutput frame {}. Got {} frames in  {}ms\t",
-                                frame_count,
-                                frames_returned,
-                                now.elapsed().as_millis()
-                            );
-                            io::stdout().flush().unwrap();
-                            now = Instant::now();
-                        }
-                        Err(e) => {
-                            eprintln!("Error writing frame: {e}");
-                            break;
-                        }
-                    }
-                }
-            }
-            Err(_e) => {
-                eprintln!("\nExiting");
+    for event in reader.events(&mut bitreader) {
+        let mut event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("\nError reading event: {e}");
                 break;
             }
+        };
+        if frame_sequence.ingest_event(&mut event, None) {
+            match frame_sequence.write_multi_frame_bytes(&mut output_stream) {
+                Ok(0) => {
+                    panic!("Should have frame, but didn't")
+                }
+                Ok(frames_returned) => {
+                    frame_count += frames_returned;
+                    print!(
+                        "\rOutput frame {}. Got {} frames in  {}ms\t",
+                        frame_count,
+                        frames_returned,
+                        now.elapsed().as_millis()
+                    );
+                    io::stdout().flush().unwrap();
+                    now = Instant::now();
+                }
+                Err(e) => {
+                    eprintln!("Error writing frame: {e}");
+                    break;
+                }
+            }
         }
     }
+    eprintln!("\nExiting");
 
     output_stream.flush().unwrap();
 }
@@ -27,7 +27,9 @@ use adder_codec_core::{open_file_decoder, Event, Intensity, SourceCamera, TimeMo
 use adder_codec_rs::framer::driver::FramerMode::INSTANTANEOUS;
 use adder_codec_rs::framer::driver::{FrameSequence, Framer, FramerBuilder};
 use adder_codec_rs::transcoder::source::framed::Framed;
-use adder_codec_rs::utils::cv::{calculate_quality_metrics, handle_color, QualityMetrics};
+use adder_codec_rs::utils::cv::{
+    calculate_quality_metrics_in, handle_color, LumaWeights, QualityMetrics,
+};
 use adder_codec_rs::utils::viz::ShowFeatureMode::Off;
 use bitstream_io::{BigEndian, BitReader};
 use ndarray::{Array3, ArrayBase, Ix3, OwnedRepr};
@@ -254,9 +256,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 .mode(INSTANTANEOUS)
                 .view_mode(FramedViewMode::Intensity)
                 .detect_features(false)
-                .source(stream.get_source_type(), meta.source_camera);
+                .source(stream.get_source_type(), meta.source_camera)
+                .pixel_multi_mode(meta.pixel_multi_mode);
+
+            let mut frame_sequence: FrameSequence<u8> = framer_builder.clone().finish()?;
 
-            let mut frame_sequence: FrameSequence<u8> = framer_builder.clone().finish();
+            // The reconstruction has the channel order of the transcoder's intensities
+            let input_space = source.get_input_color_space();
+            let recon_space = source.get_video_ref().get_color_space();
 
             let video = &mut source.get_video_mut();
             if let Some(handle) = &mut video.state.feature_log_handle {
@@ -282,19 +289,22 @@ async fn main() -> Result<(), Box<dyn Error>> {
                             break;
                         }
                     };
-                    let mut recon_image = match recon_image {
+                    let recon_image = match recon_image {
                         None => continue,
                         Some(a) => a,
                     };
 
                     let (_, frame) = cap.decode()?;
-                    let input_frame = handle_color(frame, args.color_input)?;
+                    let input_frame =
+                        handle_color(frame, args.color_input, LumaWeights::default())?;
 
                     // Get the quality metrics compared to the source video
                     #[rustfmt::skip]
-                        let metrics = calculate_quality_metrics(
+                        let metrics = calculate_quality_metrics_in(
                         &input_frame,
-                        &mut recon_image,
+                        input_space,
+                        &recon_image,
+                        recon_space,
                         QualityMetrics {
                             mse: Some(0.0),
                             psnr: Some(0.0),
@@ -0,0 +1,154 @@
+use crate::framer::driver::OverflowMode;
+use crate::framer::scale_intensity::FrameValue;
+use adder_codec_core::BigT;
+
+/// Accumulates the intensity a pixel receives over time, as a
+/// [`Framer`](crate::framer::driver::Framer) does in
+/// [INTEGRATION](crate::framer::driver::FramerMode::INTEGRATION) mode.
+///
+/// Each addition is an intensity already integrated over its `dt` ticks. A sum which overflows
+/// `T` is handled according to the [`OverflowMode`], so under [`OverflowMode::Saturate`] the
+/// value clamps at `T::max_f32()`. With a [`delta_t_max`](Self::delta_t_max) cap, additions
+/// made once that many ticks have been accumulated are ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntensityAccumulator<T> {
+    value: Option<T>,
+    ticks: BigT,
+    delta_t_max: Option<BigT>,
+    overflow_mode: OverflowMode,
+    overflowed: bool,
+}
+
+impl<T: FrameValue<Output = T> + Copy> IntensityAccumulator<T> {
+    /// Create an empty accumulator
+    #[must_use]
+    pub fn new(overflow_mode: OverflowMode) -> Self {
+        Self::resume(None, overflow_mode)
+    }
+
+    /// Continue accumulating onto `value`, e.g., a frame pixel which earlier events have
+    /// already contributed to
+    #[must_use]
+    pub fn resume(value: Option<T>, overflow_mode: OverflowMode) -> Self {
+        Self {
+            value,
+            ticks: 0,
+            delta_t_max: None,
+            overflow_mode,
+            overflowed: false,
+        }
+    }
+
+    /// Ignore any intensity added once `delta_t_max` ticks have been accumulated
+    #[must_use]
+    pub fn delta_t_max(mut self, delta_t_max: BigT) -> Self {
+        self.delta_t_max = Some(delta_t_max);
+        self
+    }
+
+    /// Add an `intensity` integrated over `dt` ticks. Returns `false` if it was ignored because
+    /// of the `delta_t_max` cap, or if the sum overflowed under [`OverflowMode::Error`]. In the
+    /// latter case, the value is left as it was.
+    pub fn add(&mut self, intensity: T, dt: BigT) -> bool {
+        if self.delta_t_max.map_or(false, |cap| self.ticks >= cap) {
+            return false;
+        }
+        self.ticks += dt;
+        match self.value {
+            None => self.value = Some(intensity),
+            Some(value) => match value.accumulate(intensity, self.overflow_mode) {
+                Some(sum) => self.value = Some(sum),
+                None => {
+                    self.overflowed = true;
+                    return false;
+                }
+            },
+        }
+        true
+    }
+
+    /// The accumulated intensity, or `None` if nothing has been added
+    #[must_use]
+    pub fn value(&self) -> Option<T> {
+        self.value
+    }
+
+    /// The number of ticks accumulated since the last reset
+    #[must_use]
+    pub fn ticks(&self) -> BigT {
+        self.ticks
+    }
+
+    /// Whether a sum has overflowed under [`OverflowMode::Error`] since the last reset
+    #[must_use]
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Clear the accumulated intensity and ticks. The overflow mode and cap are kept.
+    pub fn reset(&mut self) {
+        self.value = None;
+        self.ticks = 0;
+        self.overflowed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulates() {
+        let mut acc = IntensityAccumulator::<u16>::new(OverflowMode::Saturate);
+        assert_eq!(acc.value(), None);
+        assert!(acc.add(100, 10));
+        assert!(acc.add(250, 25));
+        assert_eq!(acc.value(), Some(350));
+        assert_eq!(acc.ticks(), 35);
+
+        let mut acc = IntensityAccumulator::resume(Some(5_u8), OverflowMode::Saturate);
+        acc.add(7, 1);
+        assert_eq!(acc.value(), Some(12));
+    }
+
+    #[test]
+    fn test_clamps() {
+        let mut acc = IntensityAccumulator::<u8>::new(OverflowMode::Saturate);
+        acc.add(200, 10);
+        assert!(acc.add(200, 10));
+        assert_eq!(f32::from(acc.value().unwrap()), u8::max_f32());
+        assert!(!acc.overflowed());
+
+        let mut acc = IntensityAccumulator::<u8>::new(OverflowMode::Error);
+        acc.add(200, 10);
+        assert!(!acc.add(200, 10));
+        assert_eq!(acc.value(), Some(200));
+        assert!(acc.overflowed());
+    }
+
+    #[test]
+    fn test_delta_t_max_cap() {
+        let mut acc = IntensityAccumulator::<u16>::new(OverflowMode::Saturate).delta_t_max(20);
+        assert!(acc.add(10, 15));
+        assert!(acc.add(10, 15));
+        assert!(!acc.add(10, 15));
+        assert_eq!(acc.value(), Some(20));
+        assert_eq!(acc.ticks(), 30);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut acc = IntensityAccumulator::<u8>::new(OverflowMode::Error).delta_t_max(20);
+        acc.add(200, 20);
+        acc.add(200, 0);
+        acc.reset();
+        assert_eq!(acc.value(), None);
+        assert_eq!(acc.ticks(), 0);
+        assert!(!acc.overflowed());
+
+        // The cap still applies after a reset
+        assert!(acc.add(1, 20));
+        assert!(!acc.add(1, 1));
+        assert_eq!(acc.value(), Some(1));
+    }
+}
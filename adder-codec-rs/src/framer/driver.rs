@@ -1,6 +1,9 @@
-use crate::framer::scale_intensity::{FrameValue, SaeTime};
+use crate::framer::accumulator::IntensityAccumulator;
+use crate::framer::ring_buffer::RingFrameBuffer;
+use crate::framer::scale_intensity::{practical_d_max, FrameElementType, FrameValue, SaeTime};
 use bincode::config::{BigEndian, FixintEncoding, WithOtherEndian, WithOtherIntEncoding};
 use bincode::{DefaultOptions, Options};
+use log::{debug, warn};
 use rayon::iter::ParallelIterator;
 
 use std::collections::VecDeque;
@@ -8,10 +11,10 @@ use std::error::Error;
 use std::fmt;
 
 use adder_codec_core::{
-    BigT, Coord, DeltaT, Event, PlaneSize, SourceCamera, SourceType, TimeMode, D_EMPTY,
+    BigT, Coord, DeltaT, Event, PixelMultiMode, PlaneSize, SourceCamera, SourceType, TimeMode,
+    TimeParams, TimeParamsError, D_EMPTY,
 };
-use std::fs::File;
-use std::io::BufWriter;
+use std::io::{Read, Write};
 
 // Want one main framer with the same functions
 // Want additional functions
@@ -30,6 +33,98 @@ pub enum FramerMode {
     INTEGRATION,
 }
 
+/// How a `Framer` in [INTEGRATION](FramerMode::INTEGRATION) mode should attribute the energy of
+/// an event whose span crosses one or more frame boundaries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum IntegrationBoundary {
+    /// The event's full energy is integrated into the frame in which the event ends.
+    #[default]
+    Whole,
+
+    /// The event's energy is split across every frame it spans, weighted by the fraction of its
+    /// `delta_t` which falls in each frame. Total integrated energy is conserved even when the
+    /// output frame length doesn't evenly divide the source timing.
+    Proportional,
+}
+
+/// Which frame an event belongs to when its timestamp lands exactly on a frame boundary.
+///
+/// A pixel's running timestamp `t` (the end of its latest event's integration) places the event
+/// in an output frame of `tpf` ticks. The modes differ only when `t` is an exact multiple of
+/// `tpf`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BoundaryMode {
+    /// Frame `k` spans `(k * tpf, (k + 1) * tpf]`, so an event ending at exactly `tpf` belongs
+    /// to frame 0. The event finished integrating within that frame's period.
+    #[default]
+    Inclusive,
+
+    /// Frame `k` spans `[k * tpf, (k + 1) * tpf)`, so an event ending at exactly `tpf` belongs
+    /// to frame 1. The event's intensity is first observable at the start of that frame.
+    Exclusive,
+}
+
+/// How a `Framer` in [INTEGRATION](FramerMode::INTEGRATION) mode should handle a pixel whose
+/// accumulated intensity exceeds the range of the output type `T`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum OverflowMode {
+    /// Clamp the accumulated intensity to `T`'s maximum value.
+    #[default]
+    Saturate,
+
+    /// Leave the pixel at its last in-range value and report a
+    /// [`FrameSequenceError::IntensityOverflow`] from
+    /// [`try_ingest_event`](FrameSequence::try_ingest_event).
+    Error,
+
+    /// Wrap around modulo `T::MAX + 1`, as with [`u8::wrapping_add`]. Only useful if you know the
+    /// true value can be recovered, e.g., by tracking the number of wraps yourself. For HDR
+    /// content, prefer choosing a wider `T`.
+    Wrap,
+}
+
+/// What to do with a frame's unfilled pixels when it's popped before it's filled. See
+/// [`FrameSequence::force_pop_next_frame`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ForcePopPolicy {
+    /// Fill each unfilled pixel with the last intensity ingested for it
+    FillWithLast,
+
+    /// Fill each unfilled pixel with 0 (i.e., `T::default()`)
+    FillWithZero,
+
+    /// Leave the frame buffered, and return [`FrameSequenceError::UnfilledFrame`]
+    Error,
+}
+
+/// The order in which a frame's pixel values are serialized by
+/// [`write_frame_bytes`](FrameSequence::write_frame_bytes).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ChannelLayout {
+    /// Pixel-major order: all channels of a pixel are written before moving to the next pixel.
+    #[default]
+    Interleaved,
+
+    /// Channel-major order: every pixel of channel 0 is written, then every pixel of channel 1,
+    /// and so on.
+    Planar,
+}
+
+/// Suggest a `chunk_rows` for [`FramerBuilder::new`] such that the working set of one chunk fits
+/// in an L2 cache of `l2_cache_bytes`, for better locality when chunks are processed in parallel.
+///
+/// `element_size` is the size of the output intensity type `T` (e.g., 1 for `u8`). The working
+/// set of each pixel is estimated as its `Option<T>` in the next frame (taken to be
+/// `2 * element_size`), its last intensity `T`, and its 8-byte timestamp and last-filled-frame
+/// trackers. Only the next frame is counted, since that's the one most events land in. The
+/// suggestion is at least 1 row and at most the plane's height.
+#[must_use]
+pub fn suggest_chunk_rows(plane: PlaneSize, l2_cache_bytes: usize, element_size: usize) -> usize {
+    let px_bytes = 3 * element_size + std::mem::size_of::<BigT>() + std::mem::size_of::<i64>();
+    let row_bytes = plane.w_usize() * plane.c_usize() * px_bytes;
+    (l2_cache_bytes / row_bytes).clamp(1, plane.h_usize().max(1))
+}
+
 /// Builder for a Framer.
 #[derive(Clone)]
 pub struct FramerBuilder {
@@ -37,15 +132,24 @@ pub struct FramerBuilder {
     tps: DeltaT,
     output_fps: Option<f32>,
     mode: FramerMode,
+    integration_boundary: IntegrationBoundary,
+    boundary_mode: BoundaryMode,
+    channel_layout: ChannelLayout,
+    overflow_mode: OverflowMode,
     view_mode: FramedViewMode,
     source: SourceType,
+    source_bit_depth: Option<u8>,
     codec_version: u8,
     source_camera: SourceCamera,
     time_mode: TimeMode,
+    pixel_multi_mode: PixelMultiMode,
     ref_interval: DeltaT,
     delta_t_max: DeltaT,
     detect_features: bool,
     buffer_limit: Option<u32>,
+    min_fill_fraction: Option<f32>,
+    max_pending_frames: Option<usize>,
+    start_offset: BigT,
 
     /// The number of rows to process in each chunk (thread).
     pub chunk_rows: usize,
@@ -61,19 +165,28 @@ impl FramerBuilder {
             tps: 150_000,
             output_fps: None,
             mode: FramerMode::INSTANTANEOUS,
+            integration_boundary: IntegrationBoundary::default(),
+            boundary_mode: BoundaryMode::default(),
+            channel_layout: ChannelLayout::default(),
+            overflow_mode: OverflowMode::default(),
             view_mode: FramedViewMode::Intensity,
             source: SourceType::U8,
+            source_bit_depth: None,
             codec_version: 3,
             source_camera: SourceCamera::default(),
             time_mode: TimeMode::default(),
+            pixel_multi_mode: PixelMultiMode::Normal,
             ref_interval: 5000,
             delta_t_max: 5000,
             detect_features: false,
             buffer_limit: None,
+            min_fill_fraction: None,
+            max_pending_frames: None,
+            start_offset: 0,
         }
     }
 
-    /// Set the time parameters.
+    /// Set the time parameters. They are validated as [`TimeParams`] when the framer is built.
     #[must_use]
     pub fn time_parameters(
         mut self,
@@ -89,11 +202,32 @@ impl FramerBuilder {
         self
     }
 
+    /// Set the time parameters from a [`TimeParams`], which has already been validated.
+    #[must_use]
+    pub fn time_params(self, params: TimeParams, output_fps: Option<f32>) -> FramerBuilder {
+        self.time_parameters(
+            params.tps(),
+            params.ref_interval(),
+            params.delta_t_max(),
+            output_fps,
+        )
+    }
+
     pub fn buffer_limit(mut self, buffer_limit: Option<u32>) -> FramerBuilder {
         self.buffer_limit = buffer_limit;
         self
     }
 
+    /// Consider the next frame ready once this fraction of its pixels (across all chunks) have
+    /// been filled, rather than waiting for every pixel. Useful for live or lossy sources, where
+    /// some pixels may never fire. When the frame is popped, its unfilled pixels take the last
+    /// intensity recorded for them (initially `T::default()`). The fraction must lie in (0, 1].
+    #[must_use]
+    pub fn min_fill_fraction(mut self, fraction: f32) -> FramerBuilder {
+        self.min_fill_fraction = Some(fraction);
+        self
+    }
+
     /// Set the framer mode.
     #[must_use]
     pub fn mode(mut self, mode: FramerMode) -> FramerBuilder {
@@ -101,6 +235,57 @@ impl FramerBuilder {
         self
     }
 
+    /// Set how events spanning a frame boundary are integrated. Only used in
+    /// [INTEGRATION](FramerMode::INTEGRATION) mode.
+    #[must_use]
+    pub fn integration_boundary(mut self, boundary: IntegrationBoundary) -> FramerBuilder {
+        self.integration_boundary = boundary;
+        self
+    }
+
+    /// Set which frame an event belongs to when it ends exactly on a frame boundary.
+    #[must_use]
+    pub fn boundary_mode(mut self, boundary_mode: BoundaryMode) -> FramerBuilder {
+        self.boundary_mode = boundary_mode;
+        self
+    }
+
+    /// Set how accumulated intensities which overflow the output type are handled. Only used in
+    /// [INTEGRATION](FramerMode::INTEGRATION) mode.
+    #[must_use]
+    pub fn overflow_mode(mut self, overflow_mode: OverflowMode) -> FramerBuilder {
+        self.overflow_mode = overflow_mode;
+        self
+    }
+
+    /// Cap the number of frames each chunk may buffer before they are popped. An event which
+    /// would grow a chunk's buffer beyond `max_pending_frames` only fills the frames that fit,
+    /// and [`FrameSequence::try_ingest_event`] reports
+    /// [`FrameSequenceError::TooManyPendingFrames`]. This bounds the framer's memory use for
+    /// pathological streams (e.g., a timestamp far in the future). Must be greater than 0.
+    #[must_use]
+    pub fn max_pending_frames(mut self, max_pending_frames: usize) -> FramerBuilder {
+        self.max_pending_frames = Some(max_pending_frames);
+        self
+    }
+
+    /// Align the output frames to an external timeline, so that frame `k` starts at
+    /// `start_offset + k * tpf` ticks rather than at `k * tpf`. Events before the offset only
+    /// advance their pixels' timestamps. Useful for syncing the reconstruction with, e.g., an
+    /// audio track or another camera.
+    #[must_use]
+    pub fn start_offset_ticks(mut self, t: BigT) -> FramerBuilder {
+        self.start_offset = t;
+        self
+    }
+
+    /// Set the channel layout used when writing out frame bytes.
+    #[must_use]
+    pub fn channel_layout(mut self, layout: ChannelLayout) -> FramerBuilder {
+        self.channel_layout = layout;
+        self
+    }
+
     /// Set the view mode.
     #[must_use]
     pub fn view_mode(mut self, mode: FramedViewMode) -> FramerBuilder {
@@ -116,6 +301,15 @@ impl FramerBuilder {
         self
     }
 
+    /// Set the number of significant bits in each source sample, for sources which pack fewer
+    /// bits than their [`SourceType`] holds (e.g., 10- or 12-bit data in a `u16`). Without this,
+    /// intensities are scaled as if the source used the full range of its type.
+    #[must_use]
+    pub fn source_bit_depth(mut self, bit_depth: u8) -> FramerBuilder {
+        self.source_bit_depth = Some(bit_depth);
+        self
+    }
+
     /// Set the codec version and time mode.
     #[must_use]
     pub fn codec_version(mut self, codec_version: u8, time_mode: TimeMode) -> FramerBuilder {
@@ -124,10 +318,23 @@ impl FramerBuilder {
         self
     }
 
-    /// Build a [`Framer`].
-    /// TODO: Make this return a result
+    /// Set the [`PixelMultiMode`] the events were transcoded with. This should match the
+    /// `pixel_multi_mode` recorded in the stream's header.
     #[must_use]
-    pub fn finish<T>(self) -> FrameSequence<T>
+    pub fn pixel_multi_mode(mut self, pixel_multi_mode: PixelMultiMode) -> FramerBuilder {
+        self.pixel_multi_mode = pixel_multi_mode;
+        self
+    }
+
+    /// Build a [`Framer`].
+    ///
+    /// # Errors
+    /// * [`FrameSequenceError::InvalidChunkRows`] if `chunk_rows` is 0
+    /// * [`FrameSequenceError::EmptyPlane`] if the plane has no rows
+    /// * [`FrameSequenceError::InvalidTimeParams`] if the time parameters are inconsistent
+    /// * [`FrameSequenceError::InvalidMaxPendingFrames`] if `max_pending_frames` is 0
+    /// * [`FrameSequenceError::InvalidFillFraction`] if `min_fill_fraction` is outside (0, 1]
+    pub fn finish<T>(self) -> Result<FrameSequence<T>, FrameSequenceError>
     where
         T: FrameValue<Output = T>
             + Default
@@ -153,7 +360,12 @@ pub trait Framer {
     /// The type of the output frame.
     type Output;
     /// Create a new [`Framer`] with the given [`FramerBuilder`].
-    fn new(builder: FramerBuilder) -> Self;
+    ///
+    /// # Errors
+    /// Returns an error if the builder's parameters are invalid.
+    fn new(builder: FramerBuilder) -> Result<Self, FrameSequenceError>
+    where
+        Self: Sized;
 
     /// Ingest an ADΔER event. Will process differently depending on choice of [`FramerMode`].
     ///
@@ -166,6 +378,10 @@ pub trait Framer {
     fn ingest_event(&mut self, event: &mut Event, last_event: Option<Event>) -> bool;
 
     /// Ingest a vector of a vector of ADΔER events.
+    ///
+    /// The buckets don't record the order of events across chunks, so any rebase events in them
+    /// are applied after the whole batch is ingested. This places Δt events exactly, but
+    /// [`TimeMode::AbsoluteT`] events which follow a rebase must be in a later batch.
     fn ingest_events_events(&mut self, events: Vec<Vec<Event>>) -> bool;
     /// For all frames left that we haven't written out yet, for any None pixels, set them to the
     /// last recorded intensity for that pixel.
@@ -194,6 +410,36 @@ pub enum FrameSequenceError {
 
     /// An impossible "fill count" encountered
     BadFillCount,
+
+    /// An integrated intensity exceeded the range of the output type
+    IntensityOverflow,
+
+    /// A chunk's frame buffer reached its maximum number of pending frames
+    TooManyPendingFrames,
+
+    /// A frame stream doesn't begin with a valid [`FrameStreamHeader`]
+    BadFrameHeader,
+
+    /// A frame was popped before all of its pixels were filled
+    UnfilledFrame,
+
+    /// The number of rows in each chunk must be greater than 0
+    InvalidChunkRows,
+
+    /// The plane has no rows to divide into chunks
+    EmptyPlane,
+
+    /// The time parameters are invalid
+    InvalidTimeParams(TimeParamsError),
+
+    /// Events were bucketed into chunks which don't match the framer's division of rows
+    MisalignedChunks,
+
+    /// The maximum number of pending frames must be greater than 0
+    InvalidMaxPendingFrames,
+
+    /// The minimum fill fraction must lie in (0, 1]
+    InvalidFillFraction,
 }
 
 impl fmt::Display for FrameSequenceError {
@@ -203,10 +449,67 @@ impl fmt::Display for FrameSequenceError {
             FrameSequenceError::UninitializedFrame => write!(f, "Uninitialized frame"),
             FrameSequenceError::UninitializedFrameChunk => write!(f, "Uninitialized frame chunk"),
             FrameSequenceError::BadFillCount => write!(f, "Bad fill count"),
+            FrameSequenceError::IntensityOverflow => write!(f, "Integrated intensity overflow"),
+            FrameSequenceError::TooManyPendingFrames => write!(f, "Too many pending frames"),
+            FrameSequenceError::BadFrameHeader => write!(f, "Bad frame stream header"),
+            FrameSequenceError::UnfilledFrame => write!(f, "Frame popped before being filled"),
+            FrameSequenceError::InvalidChunkRows => write!(f, "Chunk rows must be greater than 0"),
+            FrameSequenceError::EmptyPlane => write!(f, "Plane has no rows"),
+            FrameSequenceError::InvalidTimeParams(e) => write!(f, "Invalid time parameters: {e}"),
+            FrameSequenceError::MisalignedChunks => {
+                write!(f, "Event buckets don't match the framer's chunk rows")
+            }
+            FrameSequenceError::InvalidMaxPendingFrames => {
+                write!(f, "Max pending frames must be greater than 0")
+            }
+            FrameSequenceError::InvalidFillFraction => {
+                write!(f, "Min fill fraction must be in (0, 1]")
+            }
         }
     }
 }
 
+/// Identifies a frame stream which begins with a [`FrameStreamHeader`]
+pub const FRAME_STREAM_MAGIC: [u8; 5] = *b"adfrm";
+
+/// An optional header for the frame byte stream written by
+/// [`write_frame_bytes`](FrameSequence::write_frame_bytes), describing its layout. Written by
+/// [`write_frame_header`](FrameSequence::write_frame_header) and read by [`read_frame_header`].
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameStreamHeader {
+    /// Always [`FRAME_STREAM_MAGIC`]
+    pub magic: [u8; 5],
+    /// The width of each frame, in pixels
+    pub width: u16,
+    /// The height of each frame, in pixels
+    pub height: u16,
+    /// The number of channels in each frame
+    pub channels: u8,
+    /// The type of each pixel value
+    pub element_type: FrameElementType,
+    /// The order in which each frame's pixel values are written
+    pub channel_layout: ChannelLayout,
+    /// The frame rate
+    pub fps: f32,
+}
+
+/// Read the [`FrameStreamHeader`] at the start of a frame byte stream. The frames follow it
+/// immediately.
+///
+/// # Errors
+/// * If the header cannot be read
+/// * If the stream doesn't begin with [`FRAME_STREAM_MAGIC`]
+pub fn read_frame_header<R: Read>(reader: &mut R) -> Result<FrameStreamHeader, Box<dyn Error>> {
+    let header: FrameStreamHeader = DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_big_endian()
+        .deserialize_from(reader)?;
+    if header.magic != FRAME_STREAM_MAGIC {
+        return Err(FrameSequenceError::BadFrameHeader.into());
+    }
+    Ok(header)
+}
+
 impl From<FrameSequenceError> for Box<dyn std::error::Error> {
     fn from(value: FrameSequenceError) -> Self {
         value.to_string().into()
@@ -222,6 +525,7 @@ impl From<FrameSequenceError> for Box<dyn std::error::Error> {
 // impl std::error::Error for FrameSequenceError {}
 
 /// The state of a [`FrameSequence`]
+#[derive(Clone)]
 pub struct FrameSequenceState {
     /// The number of frames written to the output so far
     frames_written: i64,
@@ -230,20 +534,58 @@ pub struct FrameSequenceState {
     /// Ticks per output frame
     pub tpf: DeltaT,
     pub(crate) source: SourceType,
+    source_bit_depth: Option<u8>,
     codec_version: u8,
     source_camera: SourceCamera,
+    tps: DeltaT,
     ref_interval: DeltaT,
     source_dtm: DeltaT,
     view_mode: FramedViewMode,
     time_mode: TimeMode,
+    pixel_multi_mode: PixelMultiMode,
+    mode: FramerMode,
+    integration_boundary: IntegrationBoundary,
+    boundary_mode: BoundaryMode,
+    overflow_mode: OverflowMode,
+    max_pending_frames: Option<usize>,
+
+    /// The common offset of every pixel's running timestamp, accumulated from rebase events
+    time_base: BigT,
+
+    /// The timestamp at which frame 0 starts
+    start_offset: BigT,
 }
 
 impl FrameSequenceState {
     pub fn reset(&mut self) {
         self.frames_written = 0;
     }
+
+    /// The index of the frame which a pixel's running timestamp `ts` falls in, according to the
+    /// [`BoundaryMode`]. Negative for timestamps before the start offset.
+    fn frame_for_ts(&self, ts: BigT) -> i64 {
+        let ts = match self.boundary_mode {
+            BoundaryMode::Inclusive => ts.saturating_sub(1),
+            BoundaryMode::Exclusive => ts,
+        };
+        (ts as i64 - self.start_offset as i64).div_euclid(i64::from(self.tpf))
+    }
+
+    /// The timestamp at which frame `frame` starts
+    fn frame_start_ts(&self, frame: i64) -> BigT {
+        debug_assert!(frame >= 0);
+        self.start_offset + frame as BigT * BigT::from(self.tpf)
+    }
+
+    /// The factor that maps the source's declared bit depth onto the full range of its
+    /// [`SourceType`]. Every [`FrameValue`] conversion is linear in the ticks it normalizes over,
+    /// so we apply this by scaling those ticks.
+    fn intensity_scale(&self) -> f64 {
+        self.source.max_intensity(None) / self.source.max_intensity(self.source_bit_depth)
+    }
 }
 
+#[derive(Clone)]
 pub struct FeatureInterval {
     end_ts: BigT,
     pub features: Vec<Coord>,
@@ -251,6 +593,7 @@ pub struct FeatureInterval {
 
 /// A sequence of frames, each of which is a 3D array of [`FrameValue`]s
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct FrameSequence<T> {
     /// The state of the frame sequence
     pub state: FrameSequenceState,
@@ -260,10 +603,14 @@ pub struct FrameSequence<T> {
     pub(crate) last_filled_tracker: Vec<Array3<i64>>,
     pub(crate) last_frame_intensity_tracker: Vec<Array3<T>>,
     chunk_filled_tracker: Vec<bool>,
+    chunk_overflow_tracker: Vec<bool>,
+    chunk_pending_tracker: Vec<bool>,
     pub(crate) mode: FramerMode,
+    channel_layout: ChannelLayout,
     pub(crate) detect_features: bool,
     pub(crate) features: VecDeque<FeatureInterval>,
     buffer_limit: Option<u32>,
+    min_fill_fraction: Option<f32>,
 
     pub(crate) running_intensities: Array3<u8>,
 
@@ -272,12 +619,30 @@ pub struct FrameSequence<T> {
     bincode: WithOtherEndian<WithOtherIntEncoding<DefaultOptions, FixintEncoding>, BigEndian>,
 }
 
-use ndarray::{Array, Array3};
+/// A copy of everything in a [`FrameSequence`] that changes as events are ingested: its frame
+/// buffers, per-pixel trackers, and state. Taken by [`FrameSequence::snapshot`] and applied by
+/// [`FrameSequence::restore`].
+#[derive(Clone)]
+pub struct FrameSequenceSnapshot<T> {
+    state: FrameSequenceState,
+    frames: Vec<VecDeque<Frame<Option<T>>>>,
+    frame_idx_offsets: Vec<i64>,
+    pixel_ts_tracker: Vec<Array3<BigT>>,
+    last_filled_tracker: Vec<Array3<i64>>,
+    last_frame_intensity_tracker: Vec<Array3<T>>,
+    chunk_filled_tracker: Vec<bool>,
+    chunk_overflow_tracker: Vec<bool>,
+    chunk_pending_tracker: Vec<bool>,
+    features: VecDeque<FeatureInterval>,
+    running_intensities: Array3<u8>,
+}
+
+use ndarray::{concatenate, Array, Array3, Array4, Axis};
 
 use crate::transcoder::source::video::FramedViewMode;
 use crate::utils::cv::is_feature;
 use rayon::prelude::IntoParallelIterator;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 impl<
         T: Clone
@@ -292,16 +657,32 @@ impl<
     > Framer for FrameSequence<T>
 {
     type Output = T;
-    fn new(builder: FramerBuilder) -> Self {
+    fn new(builder: FramerBuilder) -> Result<Self, FrameSequenceError> {
         let plane = &builder.plane;
 
         let chunk_rows = builder.chunk_rows;
-        assert!(chunk_rows > 0);
+        if chunk_rows == 0 {
+            return Err(FrameSequenceError::InvalidChunkRows);
+        }
 
         let num_chunks: usize = ((builder.plane.h()) as f64 / chunk_rows as f64).ceil() as usize;
+        if num_chunks == 0 {
+            return Err(FrameSequenceError::EmptyPlane);
+        }
+
+        if builder.max_pending_frames == Some(0) {
+            return Err(FrameSequenceError::InvalidMaxPendingFrames);
+        }
+        if let Some(fraction) = builder.min_fill_fraction {
+            if !(fraction > 0.0 && fraction <= 1.0) {
+                return Err(FrameSequenceError::InvalidFillFraction);
+            }
+        }
+
+        // The last chunk may hold fewer rows than the others, but always spans the full width
+        // and channel depth of the plane
         let last_chunk_rows = builder.plane.h_usize() - (num_chunks - 1) * chunk_rows;
 
-        assert!(num_chunks > 0);
         let array: Array3<Option<T>> =
             Array3::<Option<T>>::default((chunk_rows, plane.w_usize(), plane.c_usize()));
         let last_array: Array3<Option<T>> =
@@ -346,25 +727,33 @@ impl<
             }
         }
 
-        let tpf = if let Some(output_fps) = builder.output_fps {
-            (builder.tps as f32 / output_fps) as u32
-        } else {
-            builder.ref_interval
-        };
+        let time_params = TimeParams::new(builder.tps, builder.ref_interval, builder.delta_t_max)
+            .map_err(FrameSequenceError::InvalidTimeParams)?;
+        let tpf = time_params.tpf(builder.output_fps);
 
         // Array3::<Option<T>>::new(num_rows, num_cols, num_channels);
-        FrameSequence {
+        Ok(FrameSequence {
             state: FrameSequenceState {
                 plane: *plane,
                 frames_written: 0,
                 view_mode: builder.view_mode,
                 tpf,
                 source: builder.source,
+                source_bit_depth: builder.source_bit_depth,
                 codec_version: builder.codec_version,
                 source_camera: builder.source_camera,
+                tps: builder.tps,
                 ref_interval: builder.ref_interval,
                 source_dtm: builder.delta_t_max,
                 time_mode: builder.time_mode,
+                pixel_multi_mode: builder.pixel_multi_mode,
+                mode: builder.mode,
+                integration_boundary: builder.integration_boundary,
+                boundary_mode: builder.boundary_mode,
+                overflow_mode: builder.overflow_mode,
+                max_pending_frames: builder.max_pending_frames,
+                time_base: 0,
+                start_offset: builder.start_offset,
             },
             frames,
             frame_idx_offsets: vec![0; num_chunks],
@@ -372,7 +761,10 @@ impl<
             last_filled_tracker,
             last_frame_intensity_tracker,
             chunk_filled_tracker: vec![false; num_chunks],
+            chunk_overflow_tracker: vec![false; num_chunks],
+            chunk_pending_tracker: vec![false; num_chunks],
             mode: builder.mode,
+            channel_layout: builder.channel_layout,
             running_intensities: Array::zeros((
                 builder.plane.h_usize(),
                 builder.plane.w_usize(),
@@ -380,6 +772,7 @@ impl<
             )),
             detect_features: builder.detect_features,
             buffer_limit: builder.buffer_limit,
+            min_fill_fraction: builder.min_fill_fraction,
             features: VecDeque::with_capacity(
                 (builder.delta_t_max / builder.ref_interval) as usize,
             ),
@@ -387,7 +780,7 @@ impl<
             bincode: DefaultOptions::new()
                 .with_fixint_encoding()
                 .with_big_endian(),
-        }
+        })
     }
 
     ///
@@ -408,7 +801,8 @@ impl<
     ///             .time_parameters(50000, 1000, 1000, Some(50.0))
     ///             .mode(INSTANTANEOUS)
     ///             .source(U8, FramedU8)
-    ///             .finish();
+    ///             .finish()
+    ///             .unwrap();
     /// let mut event: Event = Event {
     ///         coord: Coord {
     ///             x: 5,
@@ -423,6 +817,11 @@ impl<
     /// assert_eq!(*elem, Some(32));
     /// ```
     fn ingest_event(&mut self, event: &mut Event, last_event: Option<Event>) -> bool {
+        if event.coord.is_rebase() {
+            self.rebase(event.t.into());
+            return self.is_frame_0_filled();
+        }
+
         let channel = event.coord.c.unwrap_or(0);
         let chunk_num = event.coord.y as usize / self.chunk_rows;
 
@@ -450,6 +849,8 @@ impl<
             frame_idx_offset,
             last_filled_frame_ref,
             last_frame_intensity_ref,
+            &mut self.chunk_overflow_tracker[chunk_num],
+            &mut self.chunk_pending_tracker[chunk_num],
             &self.state,
             self.buffer_limit,
         );
@@ -536,6 +937,9 @@ impl<
             }
         }
 
+        if self.min_fill_fraction.is_some() {
+            return self.is_frame_0_filled();
+        }
         for chunk in &self.chunk_filled_tracker {
             if !chunk {
                 return false;
@@ -548,6 +952,23 @@ impl<
     fn ingest_events_events(&mut self, mut events: Vec<Vec<Event>>) -> bool {
         // Make sure that the chunk division is aligned between the source and the framer
         assert_eq!(events.len(), self.frames.len());
+        debug_assert!(self.check_chunk_alignment(&events).is_ok());
+
+        let mut rebase_offset: BigT = 0;
+        for bucket in &mut events {
+            bucket.retain(|event| {
+                if event.coord.is_rebase() {
+                    rebase_offset += BigT::from(event.t);
+                }
+                !event.coord.is_reserved()
+            });
+        }
+
+        // Every event in a bucket belongs to that bucket's chunk, so its row offset is known
+        // up front
+        let chunk_rows = self.chunk_rows;
+        let state = &self.state;
+        let buffer_limit = self.buffer_limit;
 
         (
             &mut events,
@@ -557,22 +978,30 @@ impl<
             &mut self.frame_idx_offsets,
             &mut self.last_filled_tracker,
             &mut self.last_frame_intensity_tracker,
+            &mut self.chunk_overflow_tracker,
+            &mut self.chunk_pending_tracker,
         )
             .into_par_iter()
+            .enumerate()
             .for_each(
                 |(
-                    a,
-                    frame_chunk,
-                    chunk_filled,
-                    chunk_ts_tracker,
-                    frame_idx_offset,
-                    chunk_last_filled_tracker,
-                    last_frame_intensity_tracker,
+                    chunk_num,
+                    (
+                        a,
+                        frame_chunk,
+                        chunk_filled,
+                        chunk_ts_tracker,
+                        frame_idx_offset,
+                        chunk_last_filled_tracker,
+                        last_frame_intensity_tracker,
+                        chunk_overflowed,
+                        chunk_pending_exceeded,
+                    ),
                 )| {
+                    let row_offset = (chunk_num * chunk_rows) as u16;
                     for event in a {
                         let channel = event.coord.c.unwrap_or(0);
-                        let chunk_num = event.coord.y as usize / self.chunk_rows;
-                        event.coord.y -= (chunk_num * self.chunk_rows) as u16; // Modify the coordinate here, so it gets ingested at the right place
+                        event.coord.y -= row_offset; // Modify the coordinate here, so it gets ingested at the right place
                         let last_filled_frame_ref = &mut chunk_last_filled_tracker
                             [[event.coord.y.into(), event.coord.x.into(), channel.into()]];
                         let running_ts_ref = &mut chunk_ts_tracker
@@ -587,8 +1016,10 @@ impl<
                             frame_idx_offset,
                             last_filled_frame_ref,
                             last_frame_intensity_ref,
-                            &self.state,
-                            self.buffer_limit,
+                            chunk_overflowed,
+                            chunk_pending_exceeded,
+                            state,
+                            buffer_limit,
                         );
                         *chunk_filled = filled;
 
@@ -606,6 +1037,9 @@ impl<
                 },
             );
 
+        if rebase_offset > 0 {
+            self.rebase(rebase_offset);
+        }
         self.is_frame_0_filled()
     }
 
@@ -639,7 +1073,7 @@ impl<
                 self.chunk_filled_tracker[chunk_num] = true;
             }
         } else {
-            eprintln!("marking not filled...");
+            debug!("No frames to flush. Marking chunk 0 not filled");
             self.chunk_filled_tracker[0] = false;
         }
 
@@ -715,6 +1149,157 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
         self.pixel_ts_tracker.len()
     }
 
+    /// Ingest an ADΔER event, as with [`Framer::ingest_event`], but report an error if an
+    /// integrated intensity overflowed `T` under [`OverflowMode::Error`], or if a chunk hit its
+    /// [`max_pending_frames`](FramerBuilder::max_pending_frames).
+    ///
+    /// # Errors
+    /// * [`FrameSequenceError::IntensityOverflow`] if a pixel's accumulated intensity exceeded
+    ///   the range of `T`. The pixel keeps its last in-range value.
+    /// * [`FrameSequenceError::TooManyPendingFrames`] if the event spanned frames beyond the
+    ///   buffer cap. Those frames were not filled.
+    pub fn try_ingest_event(
+        &mut self,
+        event: &mut Event,
+        last_event: Option<Event>,
+    ) -> Result<bool, FrameSequenceError>
+    where
+        Self: Framer,
+    {
+        let filled = self.ingest_event(event, last_event);
+        self.check_overflow()?;
+        self.check_pending_frames()?;
+        Ok(filled)
+    }
+
+    /// Ingest a vector of event buckets, as with [`Framer::ingest_events_events`], but first
+    /// verify that the buckets line up with the framer's chunks. Also reports the errors of
+    /// [`FrameSequence::try_ingest_event`].
+    ///
+    /// # Errors
+    /// * [`FrameSequenceError::MisalignedChunks`] if the buckets don't line up with the chunks.
+    ///   No events are ingested.
+    /// * [`FrameSequenceError::IntensityOverflow`] if a pixel's accumulated intensity exceeded
+    ///   the range of `T`
+    /// * [`FrameSequenceError::TooManyPendingFrames`] if an event spanned frames beyond the
+    ///   buffer cap
+    pub fn try_ingest_events_events(
+        &mut self,
+        events: Vec<Vec<Event>>,
+    ) -> Result<bool, FrameSequenceError>
+    where
+        Self: Framer,
+    {
+        self.check_chunk_alignment(&events)?;
+        let filled = self.ingest_events_events(events);
+        self.check_overflow()?;
+        self.check_pending_frames()?;
+        Ok(filled)
+    }
+
+    /// Ingest a batch of events in parallel. The events are grouped by chunk in a single pass,
+    /// keeping their order within each chunk, then each chunk's group is ingested as with
+    /// [`Framer::ingest_events_events`]. Events outside the plane, or on a channel it doesn't
+    /// have, are silently ignored. Features are not detected.
+    ///
+    /// The batch is split at each rebase event, and the rebase is applied once the events
+    /// before it are ingested, so the batch may hold [`TimeMode::AbsoluteT`] events on either
+    /// side of a rebase. The events are taken by shared reference, since each is copied into
+    /// its chunk's group anyway.
+    ///
+    /// Returns `true` if frame 0 is now filled
+    pub fn ingest_event_batch(&mut self, events: &[Event]) -> bool
+    where
+        Self: Framer,
+    {
+        let plane = self.state.plane;
+        let mut filled = self.is_frame_0_filled();
+        for segment in events.split_inclusive(|event| event.coord.is_rebase()) {
+            let mut buckets = vec![Vec::new(); self.frames.len()];
+            for event in segment {
+                let bucket = if event.coord.is_rebase() {
+                    // Applied after the rest of the segment, which is exactly where it belongs
+                    buckets.first_mut()
+                } else if event.coord.x_usize() < plane.w_usize()
+                    && event.coord.c_usize() < plane.c_usize()
+                {
+                    buckets.get_mut(event.coord.y_usize() / self.chunk_rows)
+                } else {
+                    None
+                };
+                if let Some(bucket) = bucket {
+                    bucket.push(*event);
+                }
+            }
+            filled = self.ingest_events_events(buckets);
+        }
+        filled
+    }
+
+    /// Check that there is one bucket of events per chunk, and that every event in a bucket
+    /// falls within that chunk's rows. Reserved events (e.g., rebase events) may be in any bucket.
+    ///
+    /// # Errors
+    /// * [`FrameSequenceError::MisalignedChunks`] if the buckets don't line up with the chunks
+    pub fn check_chunk_alignment(&self, events: &[Vec<Event>]) -> Result<(), FrameSequenceError> {
+        if events.len() != self.frames.len() {
+            return Err(FrameSequenceError::MisalignedChunks);
+        }
+        let height = self.state.plane.h_usize();
+        for (chunk_num, bucket) in events.iter().enumerate() {
+            let start = chunk_num * self.chunk_rows;
+            let end = (start + self.chunk_rows).min(height);
+            if bucket.iter().any(|event| {
+                !event.coord.is_reserved() && !(start..end).contains(&(event.coord.y as usize))
+            }) {
+                return Err(FrameSequenceError::MisalignedChunks);
+            }
+        }
+        Ok(())
+    }
+
+    /// Offset every pixel's running timestamp by a common `offset`, keeping the tracked values
+    /// small over long streams. This is what ingesting a rebase event (see
+    /// [`Event::rebase`]) does. Frame timing is unaffected, since the offset is added back when
+    /// placing events in frames.
+    ///
+    /// The offset should not exceed the running timestamp of any pixel. Pixels which lag behind
+    /// it are clamped to the new base.
+    pub fn rebase(&mut self, offset: BigT) {
+        for chunk in &mut self.pixel_ts_tracker {
+            chunk.mapv_inplace(|ts| ts.saturating_sub(offset));
+        }
+        self.state.time_base += offset;
+    }
+
+    /// Check whether any integrated intensity has overflowed `T` (under [`OverflowMode::Error`])
+    /// since the last check, and reset the overflow status.
+    ///
+    /// # Errors
+    /// * [`FrameSequenceError::IntensityOverflow`] if an overflow occurred
+    pub fn check_overflow(&mut self) -> Result<(), FrameSequenceError> {
+        let overflowed = self.chunk_overflow_tracker.iter().any(|o| *o);
+        self.chunk_overflow_tracker.fill(false);
+        if overflowed {
+            return Err(FrameSequenceError::IntensityOverflow);
+        }
+        Ok(())
+    }
+
+    /// Check whether any chunk hit its [`max_pending_frames`](FramerBuilder::max_pending_frames)
+    /// since the last check, and reset the status.
+    ///
+    /// # Errors
+    /// * [`FrameSequenceError::TooManyPendingFrames`] if a chunk's buffer was full
+    pub fn check_pending_frames(&mut self) -> Result<(), FrameSequenceError> {
+        let exceeded = self.chunk_pending_tracker.iter().any(|e| *e);
+        self.chunk_pending_tracker.fill(false);
+        if exceeded {
+            return Err(FrameSequenceError::TooManyPendingFrames);
+        }
+        Ok(())
+    }
+
     /// Get the reference for the pixel at the given coordinates
     /// # Arguments
     /// * `y` - The y coordinate of the pixel
@@ -748,6 +1333,7 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
     /// * `Option<&T>` - The reference to the pixel value
     /// # Errors
     /// * If the frame at the given index has not been initialized
+    /// * [`FrameSequenceError::InvalidIndex`] if `y`, `x`, or `c` is outside the plane
     pub fn px_at_frame(
         &self,
         y: usize,
@@ -755,10 +1341,16 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
         c: usize,
         frame_idx: usize,
     ) -> Result<&Option<T>, FrameSequenceError> {
+        let plane = &self.state.plane;
+        if y >= plane.h_usize() || x >= plane.w_usize() || c >= plane.c_usize() {
+            return Err(FrameSequenceError::InvalidIndex);
+        }
         let chunk_num = y / self.chunk_rows;
         let local_row = y - (chunk_num * self.chunk_rows);
-        match self.frames.len() {
-            a if frame_idx < a => Ok(&self.frames[chunk_num][frame_idx].array[[local_row, x, c]]),
+        match self.frames.get(chunk_num) {
+            Some(chunk) if frame_idx < chunk.len() => {
+                Ok(&chunk[frame_idx].array[[local_row, x, c]])
+            }
             _ => Err(FrameSequenceError::InvalidIndex),
         }
     }
@@ -812,6 +1404,15 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
             }
         }
 
+        if let Some(min_fill_fraction) = self.min_fill_fraction {
+            let (filled, total) = self.frames.iter().fold((0, 0), |(filled, total), chunk| {
+                (filled + chunk[0].filled_count, total + chunk[0].array.len())
+            });
+            if filled as f64 >= f64::from(min_fill_fraction) * total as f64 {
+                return true;
+            }
+        }
+
         for chunk in self.chunk_filled_tracker.iter() {
             if !chunk {
                 return false;
@@ -820,6 +1421,103 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
         true
     }
 
+    /// Compare the currently buffered frames of two frame sequences, pixel by pixel. Pixels
+    /// that have not been written to are treated as 0.
+    ///
+    /// Returns `true` if both sequences buffer the same number of frames for each chunk, and
+    /// every pair of pixels differs by no more than `tol`. Useful for regression testing.
+    #[must_use]
+    pub fn frames_approx_equal(&self, other: &FrameSequence<T>, tol: f64) -> bool
+    where
+        T: Into<f64>,
+    {
+        if self.frames.len() != other.frames.len() {
+            return false;
+        }
+        let value = |px: &Option<T>| -> f64 { px.map_or(0.0, Into::into) };
+
+        self.frames
+            .iter()
+            .zip(other.frames.iter())
+            .all(|(chunk, other_chunk)| {
+                chunk.len() == other_chunk.len()
+                    && chunk
+                        .iter()
+                        .zip(other_chunk.iter())
+                        .all(|(frame, other_frame)| {
+                            frame.array.shape() == other_frame.array.shape()
+                                && frame
+                                    .array
+                                    .iter()
+                                    .zip(other_frame.array.iter())
+                                    .all(|(a, b)| (value(a) - value(b)).abs() <= tol)
+                        })
+            })
+    }
+
+    /// The total intensity of a buffered frame: the sum of all its pixel values, where pixels
+    /// that have not been written to count as 0. Useful for auto-exposure and scene-cut
+    /// detection.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_idx`: the index of the frame, where 0 is the next frame to be popped
+    ///
+    /// # Errors
+    /// * If any chunk doesn't buffer the frame at the given index
+    pub fn frame_energy(&self, frame_idx: usize) -> Result<f64, FrameSequenceError>
+    where
+        T: Into<f64>,
+    {
+        self.frames.iter().try_fold(0.0, |energy, chunk| {
+            let frame = chunk
+                .get(frame_idx)
+                .ok_or(FrameSequenceError::InvalidIndex)?;
+            Ok(energy
+                + frame
+                    .array
+                    .iter()
+                    .map(|px| px.clone().map_or(0.0, Into::into))
+                    .sum::<f64>())
+        })
+    }
+
+    /// Capture the sequence's current frame buffers, trackers, and state, so that an ingest
+    /// sequence can later be replayed from exactly this point with [`FrameSequence::restore`].
+    #[must_use]
+    pub fn snapshot(&self) -> FrameSequenceSnapshot<T> {
+        FrameSequenceSnapshot {
+            state: self.state.clone(),
+            frames: self.frames.clone(),
+            frame_idx_offsets: self.frame_idx_offsets.clone(),
+            pixel_ts_tracker: self.pixel_ts_tracker.clone(),
+            last_filled_tracker: self.last_filled_tracker.clone(),
+            last_frame_intensity_tracker: self.last_frame_intensity_tracker.clone(),
+            chunk_filled_tracker: self.chunk_filled_tracker.clone(),
+            chunk_overflow_tracker: self.chunk_overflow_tracker.clone(),
+            chunk_pending_tracker: self.chunk_pending_tracker.clone(),
+            features: self.features.clone(),
+            running_intensities: self.running_intensities.clone(),
+        }
+    }
+
+    /// Return the sequence to the state captured by [`FrameSequence::snapshot`]. The snapshot
+    /// must have been taken from this sequence, or one built with the same [`FramerBuilder`].
+    pub fn restore(&mut self, snap: FrameSequenceSnapshot<T>) {
+        debug_assert_eq!(snap.frames.len(), self.frames.len());
+        self.state = snap.state;
+        self.frames = snap.frames;
+        self.frame_idx_offsets = snap.frame_idx_offsets;
+        self.pixel_ts_tracker = snap.pixel_ts_tracker;
+        self.last_filled_tracker = snap.last_filled_tracker;
+        self.last_frame_intensity_tracker = snap.last_frame_intensity_tracker;
+        self.chunk_filled_tracker = snap.chunk_filled_tracker;
+        self.chunk_overflow_tracker = snap.chunk_overflow_tracker;
+        self.chunk_pending_tracker = snap.chunk_pending_tracker;
+        self.features = snap.features;
+        self.running_intensities = snap.running_intensities;
+    }
+
     /// Get the instantaneous intensity for each pixel
     pub fn get_running_intensities(&self) -> &Array3<u8> {
         &self.running_intensities
@@ -850,6 +1548,21 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
         self.features.pop_front()
     }
 
+    /// Borrow the chunks of a buffered frame without popping it
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_idx`: the index of the frame, where 0 is the next frame to be popped
+    ///
+    /// returns: the chunks of the frame, or `None` if any chunk doesn't buffer that frame
+    #[must_use]
+    pub fn peek_frame(&self, frame_idx: usize) -> Option<Vec<&Array3<Option<T>>>> {
+        self.frames
+            .iter()
+            .map(|chunk| chunk.get(frame_idx).map(|frame| &frame.array))
+            .collect()
+    }
+
     /// Pop the next frame for all chunks
     ///
     /// returns: the frame
@@ -862,7 +1575,7 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
                     ret.push(frame);
                 }
                 None => {
-                    println!("Couldn't pop chunk {chunk_num}!");
+                    warn!("Couldn't pop chunk {chunk_num}!");
                 }
             }
         }
@@ -871,6 +1584,95 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
         Some(ret)
     }
 
+    /// Pop the next frame for all chunks, whether or not it's filled. Any unfilled pixels are
+    /// handled according to `policy`.
+    ///
+    /// returns: the frame
+    /// # Errors
+    /// * If `policy` is [`ForcePopPolicy::Error`] and the frame isn't filled. The frame is left
+    /// buffered.
+    pub fn force_pop_next_frame(
+        &mut self,
+        policy: ForcePopPolicy,
+    ) -> Result<Vec<Array3<Option<T>>>, FrameSequenceError> {
+        for (chunk, last_intensities) in self
+            .frames
+            .iter_mut()
+            .zip(self.last_frame_intensity_tracker.iter())
+        {
+            let frame = match chunk.front_mut() {
+                Some(frame) if frame.filled_count < frame.array.len() => frame,
+                _ => continue,
+            };
+            for (px, last) in frame.array.iter_mut().zip(last_intensities.iter()) {
+                if px.is_none() {
+                    *px = match policy {
+                        ForcePopPolicy::FillWithLast => Some(last.clone()),
+                        ForcePopPolicy::FillWithZero => Some(T::default()),
+                        ForcePopPolicy::Error => return Err(FrameSequenceError::UnfilledFrame),
+                    };
+                }
+            }
+        }
+        Ok(self.pop_next_frame().unwrap_or_default())
+    }
+
+    /// Pop every filled frame, stitch its chunks into a full frame, and push it into `ring`.
+    /// Pixels which have not been written to are `T::default()`.
+    ///
+    /// returns: the number of frames pushed
+    /// # Errors
+    /// * If a frame chunk has not been initialized
+    pub fn push_filled_frames(
+        &mut self,
+        ring: &mut RingFrameBuffer<Array3<T>>,
+    ) -> Result<usize, FrameSequenceError> {
+        let mut count = 0;
+        while self.is_frame_0_filled() {
+            let mut chunks = Vec::with_capacity(self.frames.len());
+            for chunk_num in 0..self.frames.len() {
+                match self.pop_next_frame_for_chunk(chunk_num) {
+                    Some(arr) => chunks.push(arr.mapv(Option::unwrap_or_default)),
+                    None => return Err(FrameSequenceError::UninitializedFrameChunk),
+                }
+            }
+            self.state.frames_written += 1;
+
+            let views: Vec<_> = chunks.iter().map(Array3::view).collect();
+            // The chunks all share the frame's width and channel count
+            let frame = concatenate(Axis(0), &views).expect("frame chunks have matching shapes");
+            ring.push(frame);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Pop the next frame for all chunks as a normalized tensor in NHWC layout, for feeding
+    /// reconstructed frames to ML models.
+    ///
+    /// The chunks are stitched back together into the full frame height. Each value is divided
+    /// by the maximum value of `T`, so the output lies in `[0, 1]`. Pixels that have not been
+    /// written to are 0.
+    ///
+    /// returns: a tensor of shape `[1, h, w, c]`, or `None` if any chunk could not be popped
+    pub fn pop_next_frame_nhwc(&mut self) -> Option<Array4<f32>>
+    where
+        T: Into<f64>,
+    {
+        let num_chunks = self.frames.len();
+        let chunks = self.pop_next_frame()?;
+        if chunks.is_empty() || chunks.len() != num_chunks {
+            return None;
+        }
+
+        let views: Vec<_> = chunks.iter().map(Array3::view).collect();
+        let stitched = concatenate(Axis(0), &views).ok()?;
+        let max = f64::from(T::max_f32());
+        let tensor =
+            stitched.mapv(|px| px.map_or(0.0, |v| (v.into() / max).clamp(0.0, 1.0) as f32));
+        Some(tensor.insert_axis(Axis(0)))
+    }
+
     /// Pop the next frame from the given chunk
     ///
     /// # Arguments
@@ -879,6 +1681,20 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
     ///
     /// returns: the chunk of frame values
     pub fn pop_next_frame_for_chunk(&mut self, chunk_num: usize) -> Option<Array3<Option<T>>> {
+        if self.min_fill_fraction.is_some() {
+            // The frame may be popped before every pixel fired, so fill in the stragglers
+            if let Some(frame) = self.frames[chunk_num].front_mut() {
+                for (px, last) in frame
+                    .array
+                    .iter_mut()
+                    .zip(self.last_frame_intensity_tracker[chunk_num].iter())
+                {
+                    if px.is_none() {
+                        *px = Some(last.clone());
+                    }
+                }
+            }
+        }
         self.frames[chunk_num].rotate_left(1);
         match self.frames[chunk_num].pop_back() {
             Some(a) => {
@@ -902,22 +1718,48 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
         }
     }
 
+    /// Write a [`FrameStreamHeader`] describing the frames written by
+    /// [`write_frame_bytes`](Self::write_frame_bytes). Call this once, before writing any frames,
+    /// so that a reader can recover the stream's layout with [`read_frame_header`].
+    /// # Errors
+    /// * If the header cannot be written
+    pub fn write_frame_header<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        let header = FrameStreamHeader {
+            magic: FRAME_STREAM_MAGIC,
+            width: self.state.plane.w(),
+            height: self.state.plane.h(),
+            channels: self.state.plane.c(),
+            element_type: T::ELEMENT_TYPE,
+            channel_layout: self.channel_layout,
+            fps: self.state.tps as f32 / self.state.tpf as f32,
+        };
+        self.bincode.serialize_into(writer, &header)?;
+        Ok(())
+    }
+
     /// Write out the next frame to the given writer
     /// # Arguments
-    /// * `writer` - The writer to write the frame to
+    /// * `writer` - The writer to write the frame to, such as a file, a socket, or a `Vec<u8>`
     /// # Returns
     /// * `Result<(), FrameSequenceError>` - Whether or not the write was successful
     /// # Errors
     /// * If the frame chunk has not been initialized
     /// * If the data cannot be written
-    pub fn write_frame_bytes(
-        &mut self,
-        writer: &mut BufWriter<File>,
-    ) -> Result<(), Box<dyn Error>> {
+    pub fn write_frame_bytes<W: Write>(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>> {
         let none_val = T::default();
+        let mut chunks = Vec::with_capacity(self.frames.len());
         for chunk_num in 0..self.frames.len() {
             match self.pop_next_frame_for_chunk(chunk_num) {
-                Some(arr) => {
+                Some(arr) => chunks.push(arr),
+                None => {
+                    return Err(FrameSequenceError::UninitializedFrameChunk.into());
+                }
+            }
+        }
+
+        match self.channel_layout {
+            ChannelLayout::Interleaved => {
+                for arr in &chunks {
                     for px in arr.iter() {
                         self.bincode.serialize_into(
                             &mut *writer,
@@ -928,13 +1770,27 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
                         )?;
                     }
                 }
-                None => {
-                    return Err(FrameSequenceError::UninitializedFrameChunk.into());
+            }
+            ChannelLayout::Planar => {
+                // Each channel plane spans every chunk, so we have to walk all the chunks once
+                // per channel
+                for c in 0..self.state.plane.c_usize() {
+                    for arr in &chunks {
+                        for px in arr.index_axis(Axis(2), c).iter() {
+                            self.bincode.serialize_into(
+                                &mut *writer,
+                                match px {
+                                    Some(event) => event,
+                                    None => &none_val,
+                                },
+                            )?;
+                        }
+                    }
                 }
             }
         }
         self.state.frames_written += 1;
-        dbg!(self.state.frames_written);
+        debug!("Wrote frame {}", self.state.frames_written);
         Ok(())
     }
 
@@ -945,12 +1801,12 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
     /// * `Result<(), FrameSequenceError>` - Whether or not the write was successful
     /// # Errors
     /// * If a frame could not be written
-    pub fn write_multi_frame_bytes(
+    pub fn write_multi_frame_bytes<W: Write>(
         &mut self,
-        writer: &mut BufWriter<File>,
+        writer: &mut W,
     ) -> Result<i32, Box<dyn Error>> {
         let mut frame_count = 0;
-        while self.is_frame_filled(0)? {
+        while self.is_frame_0_filled() {
             self.write_frame_bytes(writer)?;
             frame_count += 1;
         }
@@ -959,8 +1815,17 @@ impl<T: Clone + Default + FrameValue<Output = T> + Serialize> FrameSequence<T> {
 }
 
 // TODO: refactor this garbage
+#[allow(clippy::too_many_arguments)]
 fn ingest_event_for_chunk<
-    T: Clone + Default + FrameValue<Output = T> + Copy + Serialize + Send + Sync + Into<f64>,
+    T: Clone
+        + Default
+        + FrameValue<Output = T>
+        + Copy
+        + Serialize
+        + Send
+        + Sync
+        + num_traits::identities::Zero
+        + Into<f64>,
 >(
     event: &mut Event,
     frame_chunk: &mut VecDeque<Frame<Option<T>>>,
@@ -968,6 +1833,8 @@ fn ingest_event_for_chunk<
     frame_idx_offset: &mut i64,
     last_filled_frame_ref: &mut i64,
     last_frame_intensity_ref: &mut T,
+    overflowed: &mut bool,
+    pending_exceeded: &mut bool,
     state: &FrameSequenceState,
     buffer_limit: Option<u32>,
 ) -> (bool, bool) {
@@ -977,6 +1844,18 @@ fn ingest_event_for_chunk<
     let prev_last_filled_frame = *last_filled_frame_ref;
     let prev_running_ts = *running_ts_ref;
 
+    let framed_source = match state.source_camera {
+        SourceCamera::FramedU8
+        | SourceCamera::FramedU16
+        | SourceCamera::FramedU32
+        | SourceCamera::FramedU64
+        | SourceCamera::FramedF32
+        | SourceCamera::FramedF64 => true,
+        SourceCamera::Dvs | SourceCamera::DavisU8 | SourceCamera::Atis | SourceCamera::Asint => {
+            false
+        } // TODO: switch statement on the transcode MODE (frame-perfect or continuous), not just the source
+    };
+
     if state.codec_version >= 2 && state.time_mode == TimeMode::AbsoluteT {
         if prev_running_ts >= event.t as BigT {
             return (
@@ -985,18 +1864,50 @@ fn ingest_event_for_chunk<
             );
         }
         *running_ts_ref = event.t as BigT;
+    } else if state.pixel_multi_mode == PixelMultiMode::Collapse
+        && framed_source
+        && event.d == D_EMPTY
+    {
+        // In Collapse mode, the transcoder follows a collapsed event with an empty event whose
+        // timestamp is the pixel's absolute running time, already aligned to the end of the
+        // input interval. Treating it as a delta would count the interval remainder on top of
+        // the snap we already applied after the collapsed event.
+        if prev_running_ts >= event.t as BigT {
+            return (
+                frame_chunk[0].filled_count == frame_chunk[0].array.len(),
+                false,
+            );
+        }
+        *running_ts_ref = event.t as BigT;
     } else {
         *running_ts_ref += u64::from(event.t);
     }
 
-    if ((running_ts_ref.saturating_sub(1)) as i64 / i64::from(state.tpf)) > *last_filled_frame_ref {
+    if state.mode == FramerMode::INTEGRATION {
+        grew = integrate_event_for_chunk(
+            event,
+            frame_chunk,
+            prev_running_ts + state.time_base,
+            *running_ts_ref + state.time_base,
+            frame_idx_offset,
+            last_filled_frame_ref,
+            last_frame_intensity_ref,
+            overflowed,
+            pending_exceeded,
+            state,
+        );
+    } else if state.frame_for_ts(*running_ts_ref + state.time_base) > *last_filled_frame_ref {
         // Set the frame's value from the event
 
         if event.d != D_EMPTY {
             // If d == 0xFF, then the event was empty, and we simply repeat the last non-empty
             // event's intensity. Else we reset the intensity here.
-            let practical_d_max =
-                fast_math::log2_raw(T::max_f32() * (state.source_dtm / state.ref_interval) as f32);
+            let practical_d_max = practical_d_max(
+                state.source,
+                state.source_bit_depth,
+                state.source_dtm,
+                state.ref_interval,
+            );
             if state.codec_version >= 2
                 && state.time_mode == TimeMode::AbsoluteT
                 && state.view_mode != FramedViewMode::SAE
@@ -1009,7 +1920,7 @@ fn ingest_event_for_chunk<
             *last_frame_intensity_ref = T::get_frame_value(
                 event,
                 state.source,
-                state.ref_interval as f64,
+                state.ref_interval as f64 * state.intensity_scale(),
                 practical_d_max,
                 state.source_dtm,
                 state.view_mode,
@@ -1020,22 +1931,12 @@ fn ingest_event_for_chunk<
             );
         }
 
-        *last_filled_frame_ref = (running_ts_ref.saturating_sub(1)) as i64 / i64::from(state.tpf);
+        *last_filled_frame_ref = state.frame_for_ts(*running_ts_ref + state.time_base);
 
         // Grow the frames vec if necessary
         match *last_filled_frame_ref - *frame_idx_offset {
             a if a > 0 => {
-                let array: Array3<Option<T>> =
-                    Array3::<Option<T>>::default(frame_chunk[0].array.raw_dim());
-                frame_chunk.append(&mut VecDeque::from(vec![
-                    Frame {
-                        array,
-                        filled_count: 0
-                    };
-                    a as usize
-                ]));
-                *frame_idx_offset += a;
-                grew = true;
+                grew = grow_frame_chunk(frame_chunk, frame_idx_offset, a, pending_exceeded, state);
             }
             a if a < 0 => {
                 // We can get here if we've forcibly popped a frame before it's ready.
@@ -1053,8 +1954,14 @@ fn ingest_event_for_chunk<
             _ => {}
         }
 
+        // Frames beyond the end of the buffer were not allocated (see `max_pending_frames`)
+        let fill_end =
+            (*last_filled_frame_ref).min(state.frames_written - 1 + frame_chunk.len() as i64);
+        // Only count the frames actually filled, so that the pixel's next event fills the rest
+        // once they're allocated
+        *last_filled_frame_ref = fill_end.max(prev_last_filled_frame);
         let mut px: &mut Option<T>;
-        for i in prev_last_filled_frame..*last_filled_frame_ref {
+        for i in prev_last_filled_frame..fill_end {
             if i - state.frames_written + 1 >= 0 {
                 px = &mut frame_chunk[(i - state.frames_written + 1) as usize].array
                     [[event.coord.y.into(), event.coord.x.into(), channel.into()]];
@@ -1072,23 +1979,13 @@ fn ingest_event_for_chunk<
     // If framed video source, we can take advantage of scheme that reduces event rate by half
     if state.codec_version >= 1
         // && state.time_mode == TimeMode::DeltaT
-        && match state.source_camera {
-            SourceCamera::FramedU8
-            | SourceCamera::FramedU16
-            | SourceCamera::FramedU32
-            | SourceCamera::FramedU64
-            | SourceCamera::FramedF32
-            | SourceCamera::FramedF64 => true,
-            SourceCamera::Dvs
-            | SourceCamera::DavisU8
-            | SourceCamera::Atis
-            | SourceCamera::Asint => false,
-            // TODO: switch statement on the transcode MODE (frame-perfect or continuous), not just the source
-        }
-        && *running_ts_ref % u64::from(state.ref_interval) > 0
+        && framed_source
+        && (*running_ts_ref + state.time_base) % u64::from(state.ref_interval) > 0
     {
-        *running_ts_ref =
-            ((*running_ts_ref / u64::from(state.ref_interval)) + 1) * u64::from(state.ref_interval);
+        let absolute_ts = *running_ts_ref + state.time_base;
+        *running_ts_ref = ((absolute_ts / u64::from(state.ref_interval)) + 1)
+            * u64::from(state.ref_interval)
+            - state.time_base;
     }
 
     if let Some(buffer_limit) = buffer_limit {
@@ -1099,7 +1996,8 @@ fn ingest_event_for_chunk<
         }
     }
 
-    debug_assert!(*last_filled_frame_ref >= 0);
+    // Pixels which have only fired before the start offset haven't filled any frame yet
+    debug_assert!(*last_filled_frame_ref >= -1);
     if frame_chunk[0].filled_count > frame_chunk[0].array.len() {
         frame_chunk[0].filled_count = frame_chunk[0].array.len();
     }
@@ -1109,3 +2007,145 @@ fn ingest_event_for_chunk<
         grew,
     )
 }
+
+/// Append `count` empty frames to a chunk's buffer, or as many as fit under the configured
+/// `max_pending_frames` (setting `pending_exceeded` if any don't fit). Returns `true` if the
+/// buffer grew.
+fn grow_frame_chunk<T: Clone + Default>(
+    frame_chunk: &mut VecDeque<Frame<Option<T>>>,
+    frame_idx_offset: &mut i64,
+    count: i64,
+    pending_exceeded: &mut bool,
+    state: &FrameSequenceState,
+) -> bool {
+    let mut count = count as usize;
+    if let Some(max_pending_frames) = state.max_pending_frames {
+        let room = max_pending_frames.saturating_sub(frame_chunk.len());
+        if count > room {
+            *pending_exceeded = true;
+            count = room;
+        }
+    }
+    if count == 0 {
+        return false;
+    }
+
+    let array: Array3<Option<T>> = Array3::<Option<T>>::default(frame_chunk[0].array.raw_dim());
+    frame_chunk.append(&mut VecDeque::from(vec![
+        Frame {
+            array,
+            filled_count: 0
+        };
+        count
+    ]));
+    *frame_idx_offset += count as i64;
+    true
+}
+
+/// Integrate the energy of an event over every frame its span (`prev_running_ts` to
+/// `running_ts`) overlaps. Returns `true` if the frame buffer grew. Sets `overflowed` if an
+/// accumulated intensity exceeded `T`'s range in [`OverflowMode::Error`] mode.
+#[allow(clippy::too_many_arguments)]
+fn integrate_event_for_chunk<
+    T: Clone
+        + Default
+        + FrameValue<Output = T>
+        + Copy
+        + Serialize
+        + Send
+        + Sync
+        + num_traits::identities::Zero
+        + Into<f64>,
+>(
+    event: &mut Event,
+    frame_chunk: &mut VecDeque<Frame<Option<T>>>,
+    prev_running_ts: BigT,
+    running_ts: BigT,
+    frame_idx_offset: &mut i64,
+    last_filled_frame_ref: &mut i64,
+    last_frame_intensity_ref: &mut T,
+    overflowed: &mut bool,
+    pending_exceeded: &mut bool,
+    state: &FrameSequenceState,
+) -> bool {
+    let channel = event.coord.c.unwrap_or(0);
+    let tpf = BigT::from(state.tpf);
+    let mut grew = false;
+
+    if running_ts <= prev_running_ts {
+        return false;
+    }
+    let first_frame = (prev_running_ts as i64 - state.start_offset as i64).div_euclid(tpf as i64);
+    let last_frame = state.frame_for_ts(running_ts);
+
+    // Grow the frames vec if necessary
+    if last_frame - *frame_idx_offset > 0 {
+        let a = last_frame - *frame_idx_offset;
+        grew = grow_frame_chunk(frame_chunk, frame_idx_offset, a, pending_exceeded, state);
+    }
+
+    // The integrated event always spans exactly the ticks since the pixel's last event
+    let delta_t = running_ts - prev_running_ts;
+    event.t = delta_t as DeltaT;
+    let practical_d_max = practical_d_max(
+        state.source,
+        state.source_bit_depth,
+        state.source_dtm,
+        state.ref_interval,
+    );
+    let frame_value = |ticks: BigT, event: &Event| -> T {
+        if event.d == D_EMPTY || ticks == 0 {
+            return T::zero();
+        }
+        T::get_frame_value(
+            event,
+            state.source,
+            ticks as f64 * state.intensity_scale(),
+            practical_d_max,
+            state.source_dtm,
+            state.view_mode,
+            None,
+        )
+    };
+
+    // Frames beyond the end of the buffer were not allocated (see `max_pending_frames`)
+    let fill_end = last_frame.min(state.frames_written + frame_chunk.len() as i64 - 1);
+    for frame_num in first_frame..=fill_end {
+        let idx = frame_num - state.frames_written;
+        if idx < 0 || idx as usize >= frame_chunk.len() {
+            // This frame has already been popped
+            continue;
+        }
+        let frame_start = state.frame_start_ts(frame_num);
+        let frame_end = frame_start + tpf;
+
+        let (contribution, ticks) = match state.integration_boundary {
+            IntegrationBoundary::Proportional => {
+                let overlap = running_ts.min(frame_end) - prev_running_ts.max(frame_start);
+                (frame_value(overlap, event), overlap)
+            }
+            IntegrationBoundary::Whole if frame_num == last_frame => {
+                (frame_value(delta_t, event), delta_t)
+            }
+            IntegrationBoundary::Whole => (T::zero(), 0),
+        };
+
+        let frame = &mut frame_chunk[idx as usize];
+        let px = &mut frame.array[[event.coord.y.into(), event.coord.x.into(), channel.into()]];
+        if px.is_none() {
+            frame.filled_count += 1;
+        }
+        let mut accumulator = IntensityAccumulator::resume(*px, state.overflow_mode);
+        accumulator.add(contribution, ticks);
+        *px = accumulator.value();
+        *overflowed |= accumulator.overflowed();
+    }
+
+    if event.d != D_EMPTY {
+        // Remember the full-frame integration of this event, for filling empty frames later
+        *last_frame_intensity_ref = frame_value(tpf, event);
+    }
+    *last_filled_frame_ref = (*last_filled_frame_ref).max(last_frame);
+
+    grew
+}
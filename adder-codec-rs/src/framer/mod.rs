@@ -3,3 +3,12 @@ pub mod driver;
 
 /// Tools for casting events to intensity values
 pub mod scale_intensity;
+
+/// Accumulating pixel intensities over time
+pub mod accumulator;
+
+/// Helpers for reconstructing framed video from ADΔER streams in memory
+pub mod reconstruct;
+
+/// A bounded buffer of the most recent reconstructed frames
+pub mod ring_buffer;
@@ -0,0 +1,302 @@
+use crate::framer::driver::{FrameSequence, Framer, FramerBuilder, FramerMode};
+use adder_codec_core::codec::decoder::Decoder;
+use adder_codec_core::{BigT, Coord, PlaneSize};
+use bitstream_io::{BigEndian, BitReader};
+use ndarray::{concatenate, Array3, ArrayView3, Axis};
+use std::error::Error;
+use std::io::{Read, Seek};
+
+/// Reconstruct every fully-filled frame of an ADΔER stream, in memory.
+///
+/// A [`FrameSequence`] is built from the stream's header, and events are decoded from the
+/// current stream position until the end of the stream. Frames are reconstructed in
+/// [INSTANTANEOUS](FramerMode::INSTANTANEOUS) mode at `output_fps`. Trailing frames which are not
+/// completely filled when the stream ends are not returned.
+///
+/// **Note:** this loads _every_ reconstructed frame into memory at once, so it's only suitable
+/// for short streams or small resolutions. For anything larger, write the frames out
+/// incrementally with [`FrameSequence::write_multi_frame_bytes`].
+///
+/// # Errors
+/// * If a decoded event can't be read from the stream
+/// * If the reconstructed frame chunks can't be assembled into a single frame
+pub fn reconstruct_frames<R: Read + Seek>(
+    stream: &mut Decoder<R>,
+    bitreader: &mut BitReader<R, BigEndian>,
+    output_fps: f64,
+) -> Result<Vec<Array3<u8>>, Box<dyn Error>> {
+    let mut frame_sequence: FrameSequence<u8> =
+        framer_for_stream(stream, stream.meta().plane, output_fps).finish()?;
+    collect_frames(stream, bitreader, &mut frame_sequence, None)
+}
+
+/// Reconstruct only the segment `[t_start, t_end)` of an ADΔER stream, in memory, at
+/// `output_fps`. Since events carry continuous timing, `output_fps` may be far above the
+/// stream's native frame rate, e.g., for slow-motion playback.
+///
+/// The first frame starts exactly at `t_start` ticks, and frames are returned until one reaches
+/// `t_end`, so there are `ceil((t_end - t_start) / tpf)` frames at most. Events before `t_start`
+/// are still decoded, but only to establish each pixel's timing. If `output_fps` exceeds the
+/// stream's ticks per second, each frame spans a single tick.
+///
+/// # Errors
+/// * If a decoded event can't be read from the stream
+/// * If the reconstructed frame chunks can't be assembled into a single frame
+pub fn reconstruct_segment<R: Read + Seek>(
+    stream: &mut Decoder<R>,
+    bitreader: &mut BitReader<R, BigEndian>,
+    t_start: BigT,
+    t_end: BigT,
+    output_fps: f64,
+) -> Result<Vec<Array3<u8>>, Box<dyn Error>> {
+    let mut frame_sequence: FrameSequence<u8> =
+        framer_for_stream(stream, stream.meta().plane, output_fps)
+            .start_offset_ticks(t_start)
+            .finish()?;
+    let tpf = BigT::from(frame_sequence.state.tpf);
+    let frame_count = t_end.saturating_sub(t_start).div_ceil(tpf) as usize;
+    if frame_count == 0 {
+        return Ok(Vec::new());
+    }
+    collect_frames(stream, bitreader, &mut frame_sequence, Some(frame_count))
+}
+
+/// Reconstruct the intensity timeline of a single pixel of an ADΔER stream at `output_fps`,
+/// for debugging. Only that pixel's events are framed, so this is much cheaper than
+/// reconstructing every frame. As with [`reconstruct_frames`], a trailing frame which is not
+/// filled when the stream ends is not returned.
+///
+/// # Errors
+/// * If the coordinate is outside the stream's plane
+/// * If a decoded event can't be read from the stream
+pub fn reconstruct_pixel<R: Read + Seek>(
+    stream: &mut Decoder<R>,
+    bitreader: &mut BitReader<R, BigEndian>,
+    x: u16,
+    y: u16,
+    c: u8,
+    output_fps: f64,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let plane = stream.meta().plane;
+    if x >= plane.w() || y >= plane.h() || c >= plane.c() {
+        return Err(format!("Pixel ({x}, {y}, {c}) is outside the plane").into());
+    }
+
+    let mut frame_sequence: FrameSequence<u8> =
+        framer_for_stream(stream, PlaneSize::new(1, 1, 1)?, output_fps).finish()?;
+    let mut timeline = Vec::new();
+    for event in stream.events(bitreader) {
+        let mut event = event?;
+        // Rebase events apply to every pixel, so they're passed on as they are
+        if !event.coord.is_rebase() {
+            if event.coord.x != x || event.coord.y != y || event.coord.c_usize() != usize::from(c) {
+                continue;
+            }
+            event.coord = Coord::new(0, 0, None);
+        }
+        if frame_sequence.ingest_event(&mut event, None) {
+            while frame_sequence.is_frame_filled(0)? {
+                if let Some(chunks) = frame_sequence.pop_next_frame() {
+                    timeline.push(chunks[0][[0, 0, 0]].unwrap_or(0));
+                }
+            }
+        }
+    }
+
+    Ok(timeline)
+}
+
+/// A framer for reconstructing `stream`, over `plane`, in
+/// [INSTANTANEOUS](FramerMode::INSTANTANEOUS) mode
+fn framer_for_stream<R: Read + Seek>(
+    stream: &Decoder<R>,
+    plane: PlaneSize,
+    output_fps: f64,
+) -> FramerBuilder {
+    let meta = *stream.meta();
+    FramerBuilder::new(plane, 64)
+        .codec_version(meta.codec_version, meta.time_mode)
+        .time_parameters(
+            meta.tps,
+            meta.ref_interval,
+            meta.delta_t_max,
+            Some(output_fps as f32),
+        )
+        .mode(FramerMode::INSTANTANEOUS)
+        .source(stream.get_source_type(), meta.source_camera)
+        .pixel_multi_mode(meta.pixel_multi_mode)
+}
+
+/// Decode events until the end of the stream, or until `max_frames` frames are filled, and
+/// return the filled frames
+fn collect_frames<R: Read + Seek>(
+    stream: &mut Decoder<R>,
+    bitreader: &mut BitReader<R, BigEndian>,
+    frame_sequence: &mut FrameSequence<u8>,
+    max_frames: Option<usize>,
+) -> Result<Vec<Array3<u8>>, Box<dyn Error>> {
+    let mut frames = Vec::new();
+    for event in stream.events(bitreader) {
+        let mut event = event?;
+        if frame_sequence.ingest_event(&mut event, None) {
+            while frame_sequence.is_frame_filled(0)? {
+                if let Some(chunks) = frame_sequence.pop_next_frame() {
+                    frames.push(assemble_frame(&chunks)?);
+                    if max_frames == Some(frames.len()) {
+                        return Ok(frames);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(frames)
+}
+
+/// Encode reconstructed frames, such as those from [`reconstruct_frames`], to an H.264 video
+/// file at `fps`, in one step. The container format is inferred from the extension of `path`
+/// (e.g., `.mp4`).
+///
+/// Each frame is `(height, width, channels)`, with 1 (grayscale) or 3 (RGB) channels. Grayscale
+/// frames are encoded as RGB frames with equal channels. Since the video is encoded as YUV 4:2:0,
+/// the width and height must both be even.
+///
+/// # Errors
+/// * If `frames` is empty, the frames don't all have the same shape, or their shape can't be
+///   encoded
+/// * If `fps` is not positive
+/// * If the encoder can't be opened or fails to encode a frame
+#[cfg(feature = "mp4-export")]
+pub fn write_mp4<P: AsRef<std::path::Path>>(
+    frames: &[Array3<u8>],
+    path: P,
+    fps: f64,
+) -> Result<(), Box<dyn Error>> {
+    use video_rs_adder_dep::{Encoder, EncoderSettings, Locator, Time};
+
+    let Some(first) = frames.first() else {
+        return Err("No frames to encode".into());
+    };
+    let (height, width, channels) = first.dim();
+    if channels != 1 && channels != 3 {
+        return Err(format!("Frames must have 1 or 3 channels, found {channels}").into());
+    }
+    if width % 2 != 0 || height % 2 != 0 {
+        return Err(format!("Frame dimensions must be even, found {width}x{height}").into());
+    }
+    if !fps.is_finite() || fps <= 0.0 {
+        return Err(format!("Frame rate must be positive, found {fps}").into());
+    }
+
+    let destination = Locator::Path(path.as_ref().to_path_buf());
+    let settings = EncoderSettings::for_h264_yuv420p(width, height, false);
+    let mut encoder = Encoder::new(&destination, settings)?;
+
+    let frame_duration = Time::from_secs_f64(1.0 / fps);
+    let mut position = Time::zero();
+    for frame in frames {
+        if frame.dim() != (height, width, channels) {
+            return Err(format!(
+                "Frames must all have the same shape, found {:?} and {:?}",
+                frame.dim(),
+                (height, width, channels)
+            )
+            .into());
+        }
+        if channels == 1 {
+            let rgb = frame
+                .broadcast((height, width, 3))
+                .ok_or("Can't broadcast a grayscale frame to RGB")?
+                .to_owned();
+            encoder.encode(&rgb, &position)?;
+        } else {
+            encoder.encode(frame, &position)?;
+        }
+        position = position.aligned_with(&frame_duration).add();
+    }
+    encoder.finish()?;
+    Ok(())
+}
+
+/// A summary of how two ADΔER streams differ, as reconstructed frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffReport {
+    /// The number of frames compared. If the streams reconstruct to different numbers of frames,
+    /// only the frames they share are compared.
+    pub frames_compared: usize,
+
+    /// The mean absolute per-pixel intensity difference, over every compared frame
+    pub mean_abs_diff: f64,
+
+    /// The largest absolute per-pixel intensity difference in any compared frame
+    pub max_abs_diff: u8,
+
+    /// The index of the frame with the largest mean absolute difference, or `None` if the
+    /// compared frames are identical
+    pub max_divergence_frame: Option<usize>,
+}
+
+/// Reconstruct two ADΔER streams at the same frame rate, and measure how much they differ.
+///
+/// Useful for quantifying the effect of changing transcode parameters. Both streams are read
+/// from their current positions to the end, as in [`reconstruct_frames`].
+///
+/// # Errors
+/// * If the streams don't have the same plane dimensions
+/// * If either stream can't be reconstructed
+pub fn diff_streams<RA: Read + Seek, RB: Read + Seek>(
+    a: &mut Decoder<RA>,
+    a_bitreader: &mut BitReader<RA, BigEndian>,
+    b: &mut Decoder<RB>,
+    b_bitreader: &mut BitReader<RB, BigEndian>,
+    output_fps: f64,
+) -> Result<DiffReport, Box<dyn Error>> {
+    let (plane_a, plane_b) = (a.meta().plane, b.meta().plane);
+    if (plane_a.w(), plane_a.h(), plane_a.c()) != (plane_b.w(), plane_b.h(), plane_b.c()) {
+        return Err(format!("Streams have different planes: {plane_a:?} and {plane_b:?}").into());
+    }
+
+    let frames_a = reconstruct_frames(a, a_bitreader, output_fps)?;
+    let frames_b = reconstruct_frames(b, b_bitreader, output_fps)?;
+
+    let mut report = DiffReport {
+        frames_compared: frames_a.len().min(frames_b.len()),
+        mean_abs_diff: 0.0,
+        max_abs_diff: 0,
+        max_divergence_frame: None,
+    };
+    let mut total_diff: u64 = 0;
+    let mut max_frame_diff: u64 = 0;
+    for (frame_idx, (frame_a, frame_b)) in frames_a.iter().zip(frames_b.iter()).enumerate() {
+        let mut frame_diff: u64 = 0;
+        for (px_a, px_b) in frame_a.iter().zip(frame_b.iter()) {
+            let diff = px_a.abs_diff(*px_b);
+            frame_diff += u64::from(diff);
+            report.max_abs_diff = report.max_abs_diff.max(diff);
+        }
+        total_diff += frame_diff;
+
+        // Every frame has the same number of pixels, so the frame with the largest total
+        // difference also has the largest mean difference
+        if frame_diff > max_frame_diff {
+            max_frame_diff = frame_diff;
+            report.max_divergence_frame = Some(frame_idx);
+        }
+    }
+
+    let px_compared = report.frames_compared * plane_a.volume();
+    if px_compared > 0 {
+        report.mean_abs_diff = total_diff as f64 / px_compared as f64;
+    }
+    Ok(report)
+}
+
+/// Stack the row chunks of a frame into a single array, filling any unset pixels with 0
+fn assemble_frame(chunks: &[Array3<Option<u8>>]) -> Result<Array3<u8>, Box<dyn Error>> {
+    let chunks: Vec<Array3<u8>> = chunks
+        .iter()
+        .map(|chunk| chunk.mapv(|px| px.unwrap_or(0)))
+        .collect();
+    let views: Vec<ArrayView3<u8>> = chunks.iter().map(Array3::view).collect();
+    Ok(concatenate(Axis(0), &views)?)
+}
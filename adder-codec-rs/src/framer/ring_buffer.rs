@@ -0,0 +1,113 @@
+/// A fixed-capacity buffer of the most recent reconstructed frames, for live displays that let
+/// the user scrub back through the last few seconds of video.
+///
+/// Once the buffer is full, pushing a new frame overwrites the oldest one, so memory use is
+/// bounded by the capacity. Any frame still held can be accessed in O(1) by its age.
+#[derive(Debug, Clone)]
+pub struct RingFrameBuffer<F> {
+    frames: Vec<F>,
+    capacity: usize,
+
+    /// The index in `frames` of the oldest frame, once the buffer is full
+    head: usize,
+}
+
+impl<F> RingFrameBuffer<F> {
+    /// Create an empty buffer which holds at most `capacity` frames
+    ///
+    /// # Panics
+    /// If `capacity` is 0
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "RingFrameBuffer capacity must be positive");
+        Self {
+            frames: Vec::with_capacity(capacity),
+            capacity,
+            head: 0,
+        }
+    }
+
+    /// Add a new frame. If the buffer is full, the oldest frame is removed and returned.
+    pub fn push(&mut self, frame: F) -> Option<F> {
+        if self.frames.len() < self.capacity {
+            self.frames.push(frame);
+            return None;
+        }
+        let oldest = std::mem::replace(&mut self.frames[self.head], frame);
+        self.head = (self.head + 1) % self.capacity;
+        Some(oldest)
+    }
+
+    /// Get a frame by its age, where 0 is the most recent frame
+    #[must_use]
+    pub fn get(&self, age: usize) -> Option<&F> {
+        if age >= self.frames.len() {
+            return None;
+        }
+        let newest = (self.head + self.frames.len() - 1) % self.frames.len();
+        let idx = (newest + self.frames.len() - age) % self.frames.len();
+        self.frames.get(idx)
+    }
+
+    /// Get the most recent frame
+    #[must_use]
+    pub fn latest(&self) -> Option<&F> {
+        self.get(0)
+    }
+
+    /// Iterate over the frames held, from oldest to newest
+    pub fn iter(&self) -> impl Iterator<Item = &F> {
+        let (newer, older) = self.frames.split_at(self.head);
+        older.iter().chain(newer.iter())
+    }
+
+    /// The number of frames currently held
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether no frames are held
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The maximum number of frames held
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Remove every frame
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.head = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_most_recent() {
+        let n = 5;
+        let mut buffer = RingFrameBuffer::new(n);
+        for i in 0..n + 3 {
+            let evicted = buffer.push(i);
+            assert_eq!(evicted, i.checked_sub(n));
+        }
+
+        assert_eq!(buffer.len(), n);
+        assert_eq!(
+            buffer.iter().copied().collect::<Vec<_>>(),
+            vec![3, 4, 5, 6, 7]
+        );
+        assert_eq!(buffer.latest(), Some(&7));
+        for age in 0..n {
+            assert_eq!(buffer.get(age), Some(&(7 - age)));
+        }
+        assert_eq!(buffer.get(n), None);
+    }
+}
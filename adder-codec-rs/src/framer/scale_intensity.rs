@@ -1,13 +1,32 @@
+use crate::framer::driver::OverflowMode;
 use crate::transcoder::source::video::FramedViewMode;
-use adder_codec_core::{
-    DeltaT, Event, EventCoordless, Intensity, SourceType, D_SHIFT, D_SHIFT_F64,
-};
+use adder_codec_core::{d_shift, DeltaT, Event, EventCoordless, Intensity, SourceType};
+use serde::{Deserialize, Serialize};
+
+/// The type of each pixel value in a serialized frame. See
+/// [`FrameStreamHeader`](crate::framer::driver::FrameStreamHeader).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameElementType {
+    /// An [`EventCoordless`]
+    EventCoordless,
+    /// A `u8`
+    U8,
+    /// A `u16`
+    U16,
+    /// A `u32`
+    U32,
+    /// A `u64`
+    U64,
+}
 
 /// A trait for types that can be used as the value of a pixel in a `Frame`.
 pub trait FrameValue {
     /// The type of the output intensity value
     type Output;
 
+    /// How the value is identified in a serialized frame stream's header
+    const ELEMENT_TYPE: FrameElementType;
+
     /// Get the frame-normalized intensity value of an event
     fn get_frame_value(
         event: &Event,
@@ -21,6 +40,12 @@ pub trait FrameValue {
 
     /// The maximum value of the type, as an f32
     fn max_f32() -> f32;
+
+    /// Add `rhs` to an accumulated value, handling overflow according to `mode`. Returns `None`
+    /// if the sum overflows in [`OverflowMode::Error`] mode.
+    fn accumulate(self, rhs: Self, mode: OverflowMode) -> Option<Self>
+    where
+        Self: Sized;
 }
 
 pub struct SaeTime {
@@ -30,6 +55,7 @@ pub struct SaeTime {
 
 impl FrameValue for EventCoordless {
     type Output = EventCoordless;
+    const ELEMENT_TYPE: FrameElementType = FrameElementType::EventCoordless;
     fn get_frame_value(
         event: &Event,
         _source_type: SourceType,
@@ -48,10 +74,15 @@ impl FrameValue for EventCoordless {
     fn max_f32() -> f32 {
         1.0
     }
+
+    fn accumulate(self, rhs: Self, _mode: OverflowMode) -> Option<Self> {
+        Some(self + rhs)
+    }
 }
 
 impl FrameValue for u8 {
     type Output = u8;
+    const ELEMENT_TYPE: FrameElementType = FrameElementType::U8;
 
     #[inline(always)]
     fn get_frame_value(
@@ -105,10 +136,19 @@ impl FrameValue for u8 {
     fn max_f32() -> f32 {
         f32::from(u8::MAX)
     }
+
+    fn accumulate(self, rhs: Self, mode: OverflowMode) -> Option<Self> {
+        match mode {
+            OverflowMode::Saturate => Some(self.saturating_add(rhs)),
+            OverflowMode::Error => self.checked_add(rhs),
+            OverflowMode::Wrap => Some(self.wrapping_add(rhs)),
+        }
+    }
 }
 
 impl FrameValue for u16 {
     type Output = u16;
+    const ELEMENT_TYPE: FrameElementType = FrameElementType::U16;
 
     fn get_frame_value(
         event: &Event,
@@ -158,10 +198,19 @@ impl FrameValue for u16 {
     fn max_f32() -> f32 {
         f32::from(u16::MAX)
     }
+
+    fn accumulate(self, rhs: Self, mode: OverflowMode) -> Option<Self> {
+        match mode {
+            OverflowMode::Saturate => Some(self.saturating_add(rhs)),
+            OverflowMode::Error => self.checked_add(rhs),
+            OverflowMode::Wrap => Some(self.wrapping_add(rhs)),
+        }
+    }
 }
 
 impl FrameValue for u32 {
     type Output = u32;
+    const ELEMENT_TYPE: FrameElementType = FrameElementType::U32;
 
     fn get_frame_value(
         event: &Event,
@@ -209,10 +258,19 @@ impl FrameValue for u32 {
     fn max_f32() -> f32 {
         u32::MAX as f32
     }
+
+    fn accumulate(self, rhs: Self, mode: OverflowMode) -> Option<Self> {
+        match mode {
+            OverflowMode::Saturate => Some(self.saturating_add(rhs)),
+            OverflowMode::Error => self.checked_add(rhs),
+            OverflowMode::Wrap => Some(self.wrapping_add(rhs)),
+        }
+    }
 }
 
 impl FrameValue for u64 {
     type Output = u64;
+    const ELEMENT_TYPE: FrameElementType = FrameElementType::U64;
 
     fn get_frame_value(
         event: &Event,
@@ -258,23 +316,66 @@ impl FrameValue for u64 {
     fn max_f32() -> f32 {
         u64::MAX as f32
     }
+
+    fn accumulate(self, rhs: Self, mode: OverflowMode) -> Option<Self> {
+        match mode {
+            OverflowMode::Saturate => Some(self.saturating_add(rhs)),
+            OverflowMode::Error => self.checked_add(rhs),
+            OverflowMode::Wrap => Some(self.wrapping_add(rhs)),
+        }
+    }
+}
+
+/// The largest [`D`](adder_codec_core::D) that a pixel of a `source_type` source can practically
+/// reach, by integrating the source's maximum intensity for `delta_t_max / ref_time` intervals.
+/// [`FramedViewMode::D`] frames are scaled by this. See [`SourceType::max_intensity`] for
+/// `bit_depth`.
+#[must_use]
+pub fn practical_d_max(
+    source_type: SourceType,
+    bit_depth: Option<u8>,
+    delta_t_max: DeltaT,
+    ref_time: DeltaT,
+) -> f32 {
+    fast_math::log2_raw(
+        source_type.max_intensity(bit_depth) as f32 * (delta_t_max / ref_time) as f32,
+    )
 }
 
 /// Convert an event to an intensity value.
 #[must_use]
 pub fn event_to_intensity(event: &Event) -> Intensity {
-    match event.d as usize {
-        a if a >= D_SHIFT.len() => f64::from(0),
-        _ => match event.t {
-            0 => D_SHIFT_F64[event.d as usize], // treat it as dt = 1
-            _ => D_SHIFT_F64[event.d as usize] / f64::from(event.t),
+    match d_shift(event.d) {
+        None => f64::from(0),
+        Some(shift) => match event.t {
+            0 => shift as Intensity, // treat it as dt = 1
+            _ => shift as Intensity / f64::from(event.t),
         },
     }
 }
 
 fn _eventcoordless_to_intensity(event: EventCoordless) -> Intensity {
-    match event.d as usize {
-        a if a >= D_SHIFT.len() => f64::from(0),
-        _ => D_SHIFT[event.d as usize] as Intensity / f64::from(event.t),
+    match d_shift(event.d) {
+        None => f64::from(0),
+        Some(shift) => shift as Intensity / f64::from(event.t),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_practical_d_max() {
+        // 30 ref intervals per delta_t_max
+        let u8_d_max = practical_d_max(SourceType::U8, None, 7650, 255);
+        let u16_d_max = practical_d_max(SourceType::U16, None, 7650, 255);
+        assert!((u8_d_max - (255.0_f32 * 30.0).log2()).abs() < 0.2);
+        assert!((u16_d_max - (65535.0_f32 * 30.0).log2()).abs() < 0.2);
+        assert!(u16_d_max > u8_d_max + 7.5);
+
+        // 10-bit samples in a 16-bit container
+        let u10_d_max = practical_d_max(SourceType::U16, Some(10), 7650, 255);
+        assert!((u10_d_max - (1023.0_f32 * 30.0).log2()).abs() < 0.2);
     }
 }
@@ -1,8 +1,9 @@
 use adder_codec_core::Mode::{Continuous, FramePerfect};
+use adder_codec_core::{d_shift, UDshift, D_EMPTY, D_MAX, D_ZERO_INTEGRATION};
 use adder_codec_core::{
-    AbsoluteT, Coord, DeltaT, Event, Mode, PixelMultiMode, TimeMode, D, D_SHIFT_F32,
+    AbsoluteT, Coord, DeltaT, Event, EventCoordless, Mode, PixelMultiMode, TimeMode, D, D_SHIFT_F32,
 };
-use adder_codec_core::{UDshift, D_EMPTY, D_MAX, D_SHIFT, D_ZERO_INTEGRATION};
+use serde::{Deserialize, Serialize};
 use smallvec::{smallvec, SmallVec};
 use std::cmp::min;
 
@@ -13,7 +14,7 @@ pub type Intensity32 = f32;
 // pub type PixelAddress = u16;
 
 #[repr(packed)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct Event32 {
     pub coord: Coord,
     pub d: D,
@@ -31,7 +32,7 @@ impl From<Event32> for Event {
 }
 
 #[repr(packed)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct PixelState {
     d: D,
     integration: Intensity32,
@@ -39,7 +40,7 @@ pub(crate) struct PixelState {
 }
 
 #[repr(packed)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct PixelNode {
     /// Specifies if the next pixel in the arena vec exists
     alt: Option<()>,
@@ -50,6 +51,7 @@ pub struct PixelNode {
 
 // Each PixelNode is ~20 bytes. Each PixelArena is at least 20 + (6*20) 140 bytes, but takes at
 // least 144 bytes of space, I think?
+#[derive(Serialize, Deserialize)]
 pub struct PixelArena {
     pub coord: Coord,
     time_mode: TimeMode,
@@ -63,6 +65,29 @@ pub struct PixelArena {
     pub(crate) c_increase_counter: u8,
     dtm_reached: bool,
     popped_dtm: bool,
+
+    /// Number of events forced because the pixel reached `delta_t_max`
+    pub(crate) forced_events: u32,
+
+    /// Number of fractional bits kept in each emitted timestamp
+    pub(crate) t_fraction_bits: u8,
+
+    /// Number of frame values folded into `base_val` while seeding it from a mean
+    seed_count: u8,
+
+    /// Sum of the frame values folded into `base_val` while seeding it from a mean
+    seed_sum: u16,
+
+    /// Intensity per tick of the previous sample, for trapezoidal integration
+    last_sample_rate: Option<Intensity32>,
+
+    /// When a node fires partway through a sample, a remainder of the sample's intensity below
+    /// this is absorbed into the node's event rather than integrated into a new alt node
+    pub(crate) min_split_intensity: Intensity32,
+
+    /// The largest [`D`] value the pixel may fire with. The pixel must fire once its integration
+    /// reaches `2^d_max`.
+    pub(crate) d_max: D,
 }
 
 impl PixelArena {
@@ -83,7 +108,74 @@ impl PixelArena {
             c_increase_counter: 1,
             dtm_reached: false,
             popped_dtm: false,
+            forced_events: 0,
+            t_fraction_bits: 0,
+            seed_count: 0,
+            seed_sum: 0,
+            last_sample_rate: None,
+            min_split_intensity: 0.0,
+            d_max: D_MAX,
+        }
+    }
+
+    /// Restart the pixel's integration from a root node with `start_intensity`, as if the arena
+    /// were newly created, but keeping its allocation. The alt nodes are discarded. The pixel's
+    /// coordinate, parameters, and `forced_events` count are kept.
+    pub(crate) fn reset(&mut self, start_intensity: Intensity32) {
+        self.arena[0].reset(start_intensity, self.d_max);
+        self.length = 1;
+        self.last_fired_t = 0.0;
+        self.running_t = 0.0;
+        self.base_val = 0;
+        self.need_to_pop_top = false;
+        self.c_increase_counter = 1;
+        self.dtm_reached = false;
+        self.popped_dtm = false;
+        self.seed_count = 0;
+        self.seed_sum = 0;
+        self.last_sample_rate = None;
+    }
+
+    /// The intensity to integrate for a sample of `intensity` over `time` ticks, by the
+    /// trapezoidal rule: the mean of the previous and current samples' intensities per tick,
+    /// over `time`. The first sample is integrated as-is.
+    pub(crate) fn trapezoid_intensity(&mut self, intensity: Intensity32, time: f32) -> Intensity32 {
+        if time <= 0.0 {
+            return intensity;
         }
+        let rate = intensity / time;
+        let amount = match self.last_sample_rate {
+            Some(last_rate) => (last_rate + rate) / 2.0 * time,
+            None => intensity,
+        };
+        self.last_sample_rate = Some(rate);
+        amount
+    }
+
+    /// While the pixel has seen fewer than `seed_samples` frame values, fold `frame_val` into a
+    /// running mean, use the mean as the pixel's base value, and return `true`. The contrast
+    /// threshold shouldn't be checked while seeding, so that noise in the first few samples
+    /// doesn't fire events. With `seed_samples <= 1`, this always returns `false`, and the pixel
+    /// is seeded from its first sample as usual.
+    pub(crate) fn seed_base_val(&mut self, frame_val: u8, seed_samples: u8) -> bool {
+        if seed_samples <= 1 || self.seed_count >= seed_samples {
+            return false;
+        }
+        self.seed_sum += u16::from(frame_val);
+        self.seed_count += 1;
+        let count = u16::from(self.seed_count);
+        self.base_val = ((self.seed_sum + count / 2) / count) as u8;
+        true
+    }
+
+    /// Whether `frame_val` differs from the pixel's base value by more than its contrast
+    /// threshold. Rising intensities are compared against `c_thresh`, and falling intensities
+    /// against `c_thresh` shifted by `c_thresh_neg_offset`.
+    pub(crate) fn exceeds_c_thresh(&self, frame_val: u8, c_thresh_neg_offset: i16) -> bool {
+        let c_thresh_neg =
+            (i16::from(self.c_thresh) + c_thresh_neg_offset).clamp(0, i16::from(u8::MAX)) as u8;
+        frame_val < self.base_val.saturating_sub(c_thresh_neg)
+            || frame_val > self.base_val.saturating_add(self.c_thresh)
     }
 
     pub(crate) fn time_mode(&mut self, time_mode: Option<TimeMode>) {
@@ -103,7 +195,7 @@ impl PixelArena {
         node.state.delta_t = 0.0;
         match next_intensity {
             None => {}
-            Some(intensity) => node.state.d = get_d_from_intensity(intensity),
+            Some(intensity) => node.state.d = get_d_from_intensity(intensity, self.d_max),
         }
         debug_assert!(node.alt.is_none());
 
@@ -128,11 +220,12 @@ impl PixelArena {
                 };
             }
         }
-        debug_assert!(event.delta_t < u32::MAX as f32);
+        let t = event.delta_t * (1_u32 << self.t_fraction_bits) as f32;
+        debug_assert!(t < u32::MAX as f32);
         Event {
             coord: self.coord,
             d: event.d,
-            t: event.delta_t as DeltaT,
+            t: t as DeltaT,
         }
     }
 
@@ -142,8 +235,56 @@ impl PixelArena {
         mode: Mode,
         ref_time: DeltaT,
     ) -> Event {
+        if self.dtm_reached {
+            self.forced_events += 1;
+        }
         let mut event = self.pop_top_event_recursive(next_intensity);
         self.popped_dtm = true;
+        self.dtm_reached = false;
+        self.delta_t_to_absolute_t(&mut event, mode, ref_time)
+    }
+
+    /// If the pixel has already popped an event for reaching `delta_t_max` and has since gone
+    /// another `delta_t_max` without a change, fire an empty event (`d = D_EMPTY`) spanning all
+    /// the time since its last event, and restart integration. Decoders repeat the pixel's last
+    /// intensity for an empty event, so this keeps `delta_t` within `delta_t_max` for static
+    /// pixels without altering the reconstruction.
+    pub fn pop_forced_empty_event(&mut self, mode: Mode, ref_time: DeltaT) -> Option<Event> {
+        if !(self.dtm_reached && self.popped_dtm) {
+            return None;
+        }
+        self.forced_events += 1;
+        let d = self.arena[0].state.d;
+        Some(self.pop_empty_event(d, mode, ref_time))
+    }
+
+    /// Fire an empty event (`d = D_EMPTY`) spanning all the time since the pixel's last event,
+    /// and restart integration. Called after [`PixelArena::pop_best_events`] at an intra-frame
+    /// boundary, so that the pixel's next event is timed relative to the boundary.
+    pub fn pop_sync_event(
+        &mut self,
+        next_intensity: Intensity32,
+        mode: Mode,
+        ref_time: DeltaT,
+    ) -> Event {
+        self.pop_empty_event(
+            get_d_from_intensity(next_intensity, self.d_max),
+            mode,
+            ref_time,
+        )
+    }
+
+    /// Fire an empty event covering the root node's time, and restart integration at `next_d`
+    fn pop_empty_event(&mut self, next_d: D, mode: Mode, ref_time: DeltaT) -> Event {
+        let mut event = Event32 {
+            coord: self.coord,
+            d: D_EMPTY,
+            delta_t: self.arena[0].state.delta_t,
+        };
+        self.arena[0] = PixelNode::new(0.0);
+        self.arena[0].set_d(next_d);
+        self.length = 1;
+        self.dtm_reached = false;
         self.delta_t_to_absolute_t(&mut event, mode, ref_time)
     }
 
@@ -175,10 +316,11 @@ impl PixelArena {
                     });
 
                     if self.arena.len() > 1 {
-                        self.arena[1] = PixelNode::new(next_intensity);
+                        self.arena[1] = PixelNode::with_d_max(next_intensity, self.d_max);
                         self.length = 2;
                     } else {
-                        self.arena.push(PixelNode::new(next_intensity));
+                        self.arena
+                            .push(PixelNode::with_d_max(next_intensity, self.d_max));
                         self.length += 1;
                     }
 
@@ -245,7 +387,7 @@ impl PixelArena {
                 t: self.running_t as AbsoluteT,
             });
             // debug_assert!(buffer.len() == 2);
-            self.arena[0] = PixelNode::new(intensity);
+            self.arena[0] = PixelNode::with_d_max(intensity, self.d_max);
         } else {
             buffer.append(&mut local_buffer);
             // Move the last node to the front
@@ -276,7 +418,7 @@ impl PixelArena {
     ) -> Option<Event> {
         assert!(self.arena[0].best_event.is_none()); // Should only be called after popping events
                                                      // let head = &mut self.arena[0];
-        let next_d = get_d_from_intensity(next_intensity);
+        let next_d = get_d_from_intensity(next_intensity, self.d_max);
         let ret = if next_d < self.arena[0].state.d && self.arena[0].state.delta_t > 0.0 {
             let mut ret32 = Event32 {
                 coord: self.coord,
@@ -295,7 +437,7 @@ impl PixelArena {
     }
 
     /// Integrates the intensity. Returns bool indicating whether or not the topmost event MUST be popped
-    /// or else risk losing accuracy. Should only return true when `d=D_MAX`, which should be
+    /// or else risk losing accuracy. Should only return true when `d=d_max`, which should be
     /// extremely rare, or when `delta_t_max` is hit
     pub fn integrate(
         &mut self,
@@ -313,7 +455,7 @@ impl PixelArena {
         }
         let tail = &mut self.arena[self.length - 1];
         if tail.state.delta_t == 0.0 && tail.state.integration == 0.0 {
-            tail.state.d = get_d_from_intensity(intensity);
+            tail.state.d = get_d_from_intensity(intensity, self.d_max);
         }
         self.running_t += time;
 
@@ -322,11 +464,15 @@ impl PixelArena {
             let filled = match self.integrate_main(idx, intensity, time, mode) {
                 None => false,
                 Some((next_intensity, next_time)) => {
+                    // The node fired, so start a fresh alt node after it. The alt only receives
+                    // what's left of this sample (nothing, in frame-perfect mode), but every
+                    // later sample is integrated into both nodes, until one of them fires.
                     // self.arena.drain(idx + 1..);
                     if self.arena.len() > idx + 1 {
-                        self.arena[idx + 1] = PixelNode::new(intensity);
+                        self.arena[idx + 1] = PixelNode::with_d_max(intensity, self.d_max);
                     } else {
-                        self.arena.push(PixelNode::new(intensity));
+                        self.arena
+                            .push(PixelNode::with_d_max(intensity, self.d_max));
                     }
                     self.length = idx + 2;
                     self.arena[idx].alt = Some(());
@@ -350,7 +496,7 @@ impl PixelArena {
                     // node and the branching nodes
                     Continuous => {
                         if time > ref_time as f32 {
-                            self.arena[idx].state.d = get_d_from_intensity(intensity);
+                            self.arena[idx].state.d = get_d_from_intensity(intensity, self.d_max);
                         }
                     }
                 }
@@ -364,8 +510,10 @@ impl PixelArena {
         assert!(self.length > 0);
 
         self.dtm_reached = self.arena[0].state.delta_t >= dtm as f32;
-        self.need_to_pop_top =
-            self.arena[0].state.d == D_MAX || (self.dtm_reached && !self.popped_dtm);
+        // A root which has fired at `d_max` can't integrate any further
+        self.need_to_pop_top = (self.arena[0].state.d == self.d_max
+            && self.arena[0].best_event.is_some())
+            || (self.dtm_reached && !self.popped_dtm);
         // SAFETY:
         // By design, the integration will not exceed 2^[`D_MAX`], so we can
         // safely cast it to integer [`D`] type.
@@ -396,13 +544,19 @@ impl PixelArena {
         let mut d_usize = node.state.d as usize;
         if node.state.integration + intensity >= D_SHIFT_F32[d_usize] {
             // If the new intensity is much bigger, then we need to increase D accordingly, first
-            let new_d = get_d_from_intensity(node.state.integration + intensity);
+            let new_d = get_d_from_intensity(node.state.integration + intensity, self.d_max);
             node.state.d = new_d;
 
             d_usize = node.state.d as usize;
 
-            let prop = (D_SHIFT_F32[d_usize] - node.state.integration) / intensity;
+            let mut prop = (D_SHIFT_F32[d_usize] - node.state.integration) / intensity;
             debug_assert!(prop > 0.0);
+
+            // Rather than splitting off a tiny remainder, which could fire another alt node in
+            // turn, let the event span the whole sample
+            if mode == Continuous && intensity - (intensity * prop) < self.min_split_intensity {
+                prop = 1.0;
+            }
             node.best_event = Some(Event32 {
                 coord: self.coord,
                 d: node.state.d,
@@ -410,18 +564,24 @@ impl PixelArena {
             });
 
             // Increase d to prepare for the next integration of this pixel
-            if node.state.d < D_MAX {
+            if node.state.d < self.d_max {
                 node.state.integration += intensity;
                 node.state.delta_t += time;
 
                 // TODO: this is slow and dumb
                 loop {
                     d_usize += 1;
-                    if D_SHIFT[d_usize] > node.state.integration as UDshift {
-                        break;
+                    match d_shift(d_usize as D) {
+                        Some(shift) if shift <= node.state.integration as UDshift => {}
+                        Some(_) => break,
+                        // Don't run off the end of D_SHIFT
+                        None => {
+                            d_usize = D_MAX as usize;
+                            break;
+                        }
                     }
                 }
-                node.state.d = d_usize as D;
+                node.state.d = (d_usize as D).min(self.d_max);
             }
 
             if intensity - (intensity * prop) >= 0.0 {
@@ -443,7 +603,7 @@ impl PixelArena {
     }
 }
 
-fn get_d_from_intensity(intensity: Intensity32) -> D {
+fn get_d_from_intensity(intensity: Intensity32, d_max: D) -> D {
     min(
         {
             if intensity > 0.0 {
@@ -458,13 +618,18 @@ fn get_d_from_intensity(intensity: Intensity32) -> D {
                 0
             }
         },
-        D_MAX,
+        d_max,
     )
 }
 
 impl PixelNode {
     pub fn new(start_intensity: Intensity32) -> PixelNode {
-        let start_d = get_d_from_intensity(start_intensity);
+        Self::with_d_max(start_intensity, D_MAX)
+    }
+
+    /// Create a node whose starting [`D`] value is at most `d_max`
+    pub(crate) fn with_d_max(start_intensity: Intensity32, d_max: D) -> PixelNode {
+        let start_d = get_d_from_intensity(start_intensity, d_max);
         debug_assert!(start_d <= D_MAX);
         PixelNode {
             alt: None,
@@ -476,6 +641,49 @@ impl PixelNode {
             best_event: None,
         }
     }
+
+    /// Reinitialize the node in place as if it were newly created with `start_intensity` and
+    /// `d_max`. Clears the node's `alt` link and its best event. The alt nodes themselves live in
+    /// the owning [`PixelArena`], which is responsible for truncating them.
+    pub(crate) fn reset(&mut self, start_intensity: Intensity32, d_max: D) {
+        let start_d = get_d_from_intensity(start_intensity, d_max);
+        debug_assert!(start_d <= D_MAX);
+        self.alt = None;
+        self.state = PixelState {
+            d: start_d,
+            integration: 0.0,
+            delta_t: 0.0,
+        };
+        self.best_event = None;
+    }
+
+    /// Fire an event for the node's residual integration, which hasn't crossed its threshold,
+    /// e.g., at the end of a stream. Rather than using the node's target `d`, the event takes the
+    /// largest `d` covered by the integration, and the time it took to integrate that much at
+    /// the node's average rate, so the event's intensity matches the integration. The event's
+    /// Δt is capped at `dt_ref` ticks.
+    ///
+    /// Resets the node's integration. Returns `None` if nothing has been integrated.
+    pub fn flush(&mut self, dt_ref: f32) -> Option<EventCoordless> {
+        let integration = self.state.integration;
+        let delta_t = self.state.delta_t;
+        if integration <= 0.0 || delta_t <= 0.0 {
+            return None;
+        }
+        let d = if integration < 1.0 {
+            0
+        } else {
+            get_d_from_intensity(integration, D_MAX)
+        };
+        let t = (delta_t * D_SHIFT_F32[d as usize] / integration).min(dt_ref);
+        self.state.integration = 0.0;
+        self.state.delta_t = 0.0;
+        Some(EventCoordless {
+            d,
+            t: t as AbsoluteT,
+        })
+    }
+
     // pub fn new2(start_intensity: Intensity32) -> PixelNode {
     //     let start_d = min(get_d_from_intensity(start_intensity) + 1, D_MAX);
     //     assert!(start_d <= D_MAX);
@@ -493,6 +701,21 @@ impl PixelNode {
     pub fn set_d(&mut self, d: D) {
         self.state.d = d;
     }
+
+    /// The decimation of the node's current integration
+    pub fn d(&self) -> D {
+        self.state.d
+    }
+
+    /// The time accumulated in the node's current integration
+    pub fn delta_t(&self) -> f32 {
+        self.state.delta_t
+    }
+
+    /// The intensity accumulated in the node's current integration
+    pub fn integration(&self) -> Intensity32 {
+        self.state.integration
+    }
 }
 
 #[cfg(test)]
@@ -682,6 +905,209 @@ mod tests {
         make_tree2();
     }
 
+    #[test]
+    fn test_t_fraction_bits() {
+        // The root's best event fires at 25.6 ticks
+        let true_t = 25.6;
+
+        let mut tree = make_tree();
+        let whole = tree.pop_top_event(0.0, Continuous, 20);
+
+        let mut tree = make_tree();
+        tree.t_fraction_bits = 8;
+        let fractional = tree.pop_top_event(0.0, Continuous, 20);
+
+        assert_eq!(whole.d, fractional.d);
+        let whole_t = f64::from(whole.t);
+        let fractional_t = f64::from(fractional.t) / 256.0;
+        assert_eq!(whole_t, 25.0);
+        assert!((fractional_t - true_t).abs() < 1.0 / 256.0);
+        assert!((fractional_t - true_t).abs() < (whole_t - true_t).abs());
+    }
+
+    /// A huge sample spanning many ref intervals fires a chain of alt nodes, one per set bit of
+    /// its intensity. With `min_split_intensity`, the chain stops once the remainder falls below
+    /// the threshold, and the events leave less than the threshold of intensity unrepresented.
+    #[test]
+    fn test_min_split_intensity() {
+        let intensity = ((1_u32 << 20) - 1) as Intensity32;
+        let integrate = |min_split_intensity: Intensity32| -> PixelArena {
+            let mut tree = PixelArena::new(intensity, Coord::new(0, 0, None));
+            tree.time_mode(Some(DeltaT));
+            tree.min_split_intensity = min_split_intensity;
+            tree.integrate(
+                intensity,
+                (1 << 22) as f32,
+                Continuous,
+                1 << 30,
+                1,
+                0,
+                255,
+                PixelMultiMode::Normal,
+            );
+            tree
+        };
+        let represented = |tree: &PixelArena| -> Intensity32 {
+            tree.arena[..tree.length]
+                .iter()
+                .filter_map(|node| node.best_event)
+                .map(|event| D_SHIFT_F32[event.d as usize])
+                .sum()
+        };
+
+        let tree = integrate(0.0);
+        assert_eq!(tree.length, 21);
+        assert_eq!(represented(&tree), intensity);
+
+        let tolerance = 1024.0;
+        let tree = integrate(tolerance);
+        assert_eq!(tree.length, 11);
+        let error = intensity - represented(&tree);
+        assert!((0.0..tolerance).contains(&error));
+    }
+
+    /// A sample which fires the main node creates an empty alt node. In frame-perfect mode, none
+    /// of that sample is left over for the alt, but the next sample must reach both the main
+    /// node and the alt.
+    #[test]
+    fn test_alt_receives_sample_after_main_fires() {
+        let dtm = 10_000;
+        let mut tree = PixelArena::new(
+            100.0,
+            Coord {
+                x: 0,
+                y: 0,
+                c: None,
+            },
+        );
+        tree.time_mode(Some(DeltaT));
+
+        tree.integrate(
+            100.0,
+            20.0,
+            FramePerfect,
+            dtm,
+            20,
+            0,
+            255,
+            PixelMultiMode::Normal,
+        );
+        assert!(tree.arena[0].best_event.is_some());
+        assert!(tree.arena[0].alt.is_some());
+        assert_eq!(tree.length, 2);
+        let alt = &tree.arena[1];
+        assert!(alt.best_event.is_none());
+        assert!(f32_slack(alt.state.integration, 0.0));
+        assert!(f32_slack(alt.state.delta_t, 0.0));
+
+        // Not enough to fire the main node again, so it flows through to the alt. The empty alt
+        // takes its D from the sample, so the sample fires it in turn.
+        tree.integrate(
+            20.0,
+            20.0,
+            FramePerfect,
+            dtm,
+            20,
+            0,
+            255,
+            PixelMultiMode::Normal,
+        );
+        assert!(f32_slack(tree.arena[0].state.integration, 120.0));
+        assert!(f32_slack(tree.arena[0].state.delta_t, 40.0));
+        assert_eq!(tree.arena[0].state.d, 7);
+        let alt = &tree.arena[1];
+        assert!(f32_slack(alt.state.integration, 20.0));
+        assert!(f32_slack(alt.state.delta_t, 20.0));
+        assert_eq!(alt.state.d, 5);
+        let event = alt.best_event.unwrap();
+        assert_eq!(event.d, 4);
+        let tmp = event.delta_t;
+        assert!(approx_eq!(f32, tmp, 16.0, ulps = 2));
+        assert!(alt.alt.is_some());
+        assert_eq!(tree.length, 3);
+    }
+
+    #[test]
+    fn test_node_reset() {
+        let mut tree = PixelArena::new(
+            100.0,
+            Coord {
+                x: 0,
+                y: 0,
+                c: None,
+            },
+        );
+        tree.time_mode(Some(DeltaT));
+        tree.integrate(
+            100.0,
+            20.0,
+            FramePerfect,
+            10_000,
+            20,
+            0,
+            255,
+            PixelMultiMode::Normal,
+        );
+        assert!(tree.arena[0].alt.is_some());
+        assert!(tree.arena[0].best_event.is_some());
+        assert!(tree.length > 1);
+
+        tree.reset(50.0);
+        assert_eq!(tree.length, 1);
+        let node = &tree.arena[0];
+        assert!(node.alt.is_none());
+        assert!(node.best_event.is_none());
+        assert_eq!(node.d(), 50.0_f32.log2() as D);
+        assert!(f32_slack(node.integration(), 0.0));
+        assert!(f32_slack(node.delta_t(), 0.0));
+    }
+
+    #[test]
+    fn test_pop_best_states_without_best_event() {
+        let mut tree = PixelArena::new(
+            5.0,
+            Coord {
+                x: 0,
+                y: 0,
+                c: None,
+            },
+        );
+        tree.time_mode(Some(DeltaT));
+
+        // A partially-integrated pixel, as when flushing at the end of a stream
+        tree.arena[0].state.integration = 3.0;
+        tree.arena[0].state.delta_t = 10.0;
+        let mut events = Vec::new();
+        tree.pop_best_events(&mut events, Continuous, PixelMultiMode::Normal, 20, 5.0);
+        assert!(events.is_empty());
+        assert_eq!(tree.length, 1);
+        let node = &tree.arena[0];
+        assert!(node.best_event.is_none());
+        assert_eq!(node.d(), 2);
+        assert!(f32_slack(node.integration(), 3.0));
+        assert!(f32_slack(node.delta_t(), 10.0));
+    }
+
+    #[test]
+    fn test_node_flush() {
+        let mut node = PixelNode::new(100.0);
+        assert_eq!(node.flush(1000.0), None);
+
+        // Below the node's threshold of 64, so it doesn't fire on its own
+        node.state.integration = 48.0;
+        node.state.delta_t = 300.0;
+        assert_eq!(node.d(), 6);
+        assert_eq!(node.flush(1000.0), Some(EventCoordless { d: 5, t: 200 }));
+        assert!(f32_slack(node.integration(), 0.0));
+        assert!(f32_slack(node.delta_t(), 0.0));
+        assert_eq!(node.flush(1000.0), None);
+
+        // A dim residual is capped at the reference Δt
+        node.state.integration = 0.5;
+        node.state.delta_t = 800.0;
+        assert_eq!(node.flush(1000.0), Some(EventCoordless { d: 0, t: 1000 }));
+    }
+
     #[test]
     fn test_pop_best_states() {
         let mut tree = make_tree();
@@ -757,6 +1183,35 @@ mod tests {
         assert!(f32_slack(tree.arena[0].state.integration, 0.0));
     }
 
+    #[test]
+    fn test_clamped_d_max() {
+        let dtm = 100_000_000;
+        let mut tree = PixelArena::new(255.0, Coord::new(0, 0, None));
+        tree.d_max = 12;
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            if tree.need_to_pop_top {
+                events.push(tree.pop_top_event(255.0 * 8.0, Continuous, 255));
+            }
+            tree.integrate(
+                255.0 * 8.0,
+                255.0,
+                Continuous,
+                dtm,
+                255,
+                0,
+                255,
+                PixelMultiMode::Normal,
+            );
+        }
+        tree.pop_best_events(&mut events, Continuous, PixelMultiMode::Normal, 255, 0.0);
+
+        // The bright pixel climbs to d = 12, and no further
+        assert!(events.iter().all(|event| event.d <= 12));
+        assert!(events.iter().filter(|event| event.d == 12).count() > 10);
+        assert!(tree.arena[0].state.d <= 12);
+    }
+
     #[test]
     fn test_dtm() {
         let dtm = 240_000;
@@ -966,6 +1421,77 @@ mod tests {
         assert_eq!(head.best_event.unwrap().d, d - 1);
     }
 
+    /// Integrate a static black pixel for `frames` intervals of 1000 ticks, popping events the
+    /// same way the video transcoder does
+    fn integrate_static(tree: &mut PixelArena, frames: usize, clamp: bool) -> Vec<Event> {
+        let dtm = 2_000;
+        let mut events = Vec::new();
+        for _ in 0..frames {
+            tree.integrate(
+                0.0,
+                1_000.0,
+                Continuous,
+                dtm,
+                1_000,
+                0,
+                255,
+                PixelMultiMode::Normal,
+            );
+            if tree.need_to_pop_top {
+                events.push(tree.pop_top_event(0.0, Continuous, 1_000));
+            }
+            if clamp {
+                if let Some(event) = tree.pop_forced_empty_event(Continuous, 1_000) {
+                    events.push(event);
+                }
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_clamp_delta_t_max() {
+        let coord = Coord {
+            x: 0,
+            y: 0,
+            c: None,
+        };
+
+        // Without clamping, only the first delta_t_max forces an event
+        let mut tree = PixelArena::new(0.0, coord);
+        let events = integrate_static(&mut tree, 4, false);
+        assert_eq!(events.len(), 1);
+        assert_eq!(tree.forced_events, 1);
+
+        // With clamping, a pixel static for 2 * delta_t_max fires at delta_t_max and
+        // 2 * delta_t_max (in absolute time)
+        let mut tree = PixelArena::new(0.0, coord);
+        let events = integrate_static(&mut tree, 4, true);
+        assert_eq!(events.len(), 2);
+        assert_eq!(tree.forced_events, 2);
+        assert_eq!(
+            events[0],
+            Event {
+                coord,
+                d: D_ZERO_INTEGRATION,
+                t: 2_000
+            }
+        );
+        assert_eq!(
+            events[1],
+            Event {
+                coord,
+                d: D_EMPTY,
+                t: 4_000
+            }
+        );
+
+        // Nothing more is forced until another delta_t_max has elapsed
+        let events = integrate_static(&mut tree, 1, true);
+        assert!(events.is_empty());
+        assert_eq!(tree.forced_events, 2);
+    }
+
     fn f32_slack(num0: f32, num1: f32) -> bool {
         let slack = f32::EPSILON;
         if num1 - slack <= num0 && num1 + slack >= num0 {
@@ -1,10 +1,10 @@
 use crate::transcoder::source::video::FramedViewMode::SAE;
 use crate::transcoder::source::video::SourceError::BufferEmpty;
 use crate::transcoder::source::video::{
-    integrate_for_px, Source, SourceError, Video, VideoBuilder,
+    integrate_for_px, IntegrationRule, Source, SourceError, Video, VideoBuilder,
 };
 use adder_codec_core::Mode::{Continuous, FramePerfect};
-use adder_codec_core::{DeltaT, PixelMultiMode};
+use adder_codec_core::{DeltaT, PixelMultiMode, D};
 use davis_edi_rs::aedat::events_generated::Event as DvsEvent;
 use davis_edi_rs::util::reconstructor::{IterVal, ReconstructionError, Reconstructor};
 use rayon::iter::IndexedParallelIterator;
@@ -17,6 +17,7 @@ use bumpalo::Bump;
 use ndarray::{Array3, Axis};
 
 use itertools::Itertools;
+use log::info;
 use rayon::iter::IntoParallelIterator;
 use rayon::{current_num_threads, ThreadPool};
 use std::cmp::max;
@@ -28,9 +29,9 @@ use std::thread;
 use adder_codec_core::codec::{CodecError, EncoderOptions, EncoderType};
 use adder_codec_core::{Event, PlaneSize, SourceCamera, SourceType, TimeMode};
 
-use crate::framer::scale_intensity::{FrameValue, SaeTime};
+use crate::framer::scale_intensity::{practical_d_max, FrameValue, SaeTime};
 use crate::transcoder::event_pixel_tree::Intensity32;
-use crate::utils::cv::clamp_u8;
+use crate::utils::cv::{clamp_u8, BayerPattern, LumaWeights, ScaleFilter};
 use crate::utils::viz::ShowFeatureMode;
 use tokio::runtime::Runtime;
 use video_rs_adder_dep::Frame;
@@ -266,6 +267,10 @@ impl<W: Write + 'static> Integration<W> {
         }
 
         let chunk_rows = video.state.chunk_rows;
+        let c_thresh_neg_offset = video
+            .state
+            .params
+            .c_thresh_neg_offset(video.encoder.options.crf.get_parameters().c_thresh_baseline);
         // let px_per_chunk: usize =
         //     self.video.chunk_rows * self.video.width as usize * self.video.channels as usize;
         let big_buffer: Vec<Vec<Event>> = video
@@ -302,7 +307,6 @@ impl<W: Write + 'static> Integration<W> {
                         {
                             let px = &mut px_chunk
                                 [[(event.y() as usize) % chunk_rows, event.x() as usize, 0]];
-                            let base_val = px.base_val;
                             let last_val_ln = &mut dvs_last_ln_val_chunk
                                 [[(event.y() as usize) % chunk_rows, event.x() as usize, 0]];
                             let last_val = (last_val_ln.exp() - 1.0) * 255.0;
@@ -375,9 +379,7 @@ impl<W: Write + 'static> Integration<W> {
 
                             let frame_val_u8 = frame_val as u8; // TODO: don't let this be lossy here
 
-                            if frame_val_u8 < base_val.saturating_sub(px.c_thresh)
-                                || frame_val_u8 > base_val.saturating_add(px.c_thresh)
-                            {
+                            if px.exceeds_c_thresh(frame_val_u8, c_thresh_neg_offset) {
                                 px.pop_best_events(
                                     &mut buffer,
                                     Continuous,
@@ -422,9 +424,11 @@ impl<W: Write + 'static> Integration<W> {
         };
 
         // TODO: split off into separate function
-        // TODO: When there's full support for various bit-depth sources, modify this accordingly
-        let practical_d_max = fast_math::log2_raw(
-            255.0 * (video.state.params.delta_t_max / video.state.params.ref_time) as f32,
+        let practical_d_max = practical_d_max(
+            video.encoder.get_source_type(),
+            None,
+            video.state.params.delta_t_max,
+            video.state.params.ref_time,
         );
         db.iter_mut()
             .zip(video.state.running_intensities.iter_mut())
@@ -560,9 +564,11 @@ impl<W: Write + 'static> Integration<W> {
         };
 
         // TODO: split off into separate function
-        // TODO: When there's full support for various bit-depth sources, modify this accordingly
-        let practical_d_max = fast_math::log2_raw(
-            255.0 * (video.state.params.delta_t_max / video.state.params.ref_time) as f32,
+        let practical_d_max = practical_d_max(
+            video.encoder.get_source_type(),
+            None,
+            video.state.params.delta_t_max,
+            video.state.params.ref_time,
         );
         db.iter_mut()
             .zip(video.state.running_intensities.iter_mut())
@@ -609,8 +615,8 @@ impl<W: Write + 'static> Integration<W> {
     }
 }
 
-impl<W: Write + 'static + std::marker::Send> Source<W> for Davis<W> {
-    fn consume(
+impl<W: Write + 'static + std::marker::Send> Davis<W> {
+    fn consume_input(
         &mut self,
         view_interval: u32,
         thread_pool: &ThreadPool,
@@ -652,7 +658,7 @@ impl<W: Write + 'static + std::marker::Send> Source<W> for Davis<W> {
             match cached_mat_opt.unwrap() {
                 None => {
                     // We've reached the end of the input. Forcibly pop the last event from each pixel.
-                    println!("Popping remaining events");
+                    info!("Reached the end of the input. Popping remaining events");
                     let px_per_chunk: usize =
                         self.video.state.chunk_rows * self.video.state.plane.area_wc();
                     let big_buffer: Vec<Vec<Event>> = self
@@ -678,7 +684,8 @@ impl<W: Write + 'static + std::marker::Send> Source<W> for Davis<W> {
 
                     self.video.encoder.ingest_events_events(&big_buffer)?;
 
-                    return Err(SourceError::NoData);
+                    // The end of the input, rather than a live source with nothing new yet
+                    return Err(BufferEmpty);
                 }
                 Some((
                     mat,
@@ -910,6 +917,17 @@ impl<W: Write + 'static + std::marker::Send> Source<W> for Davis<W> {
 
         ret
     }
+}
+
+impl<W: Write + 'static + std::marker::Send> Source<W> for Davis<W> {
+    fn consume(
+        &mut self,
+        view_interval: u32,
+        thread_pool: &ThreadPool,
+    ) -> Result<Vec<Vec<Event>>, SourceError> {
+        let result = self.consume_input(view_interval, thread_pool);
+        self.video.record_consume(result)
+    }
 
     fn crf(&mut self, crf: u8) {
         self.video.update_crf(crf);
@@ -959,9 +977,9 @@ impl<W: Write + 'static + std::marker::Send> Source<W> for Davis<W> {
 }
 
 impl<W: Write + 'static> VideoBuilder<W> for Davis<W> {
-    fn contrast_thresholds(mut self, c_thresh_pos: u8, _c_thresh_neg: u8) -> Self {
+    fn contrast_thresholds(mut self, c_thresh_pos: u8, c_thresh_neg: u8) -> Self {
         self.video = self.video.c_thresh_pos(c_thresh_pos);
-        // self.video = self.video.c_thresh_neg(c_thresh_neg);
+        self.video = self.video.c_thresh_neg(c_thresh_neg);
         self
     }
 
@@ -993,8 +1011,8 @@ impl<W: Write + 'static> VideoBuilder<W> for Davis<W> {
         self
     }
 
-    fn c_thresh_neg(self, _c_thresh_neg: u8) -> Self {
-        // self.video = self.video.c_thresh_neg(c_thresh_neg);
+    fn c_thresh_neg(mut self, c_thresh_neg: u8) -> Self {
+        self.video = self.video.c_thresh_neg(c_thresh_neg);
         self
     }
 
@@ -1010,7 +1028,7 @@ impl<W: Write + 'static> VideoBuilder<W> for Davis<W> {
         delta_t_max: DeltaT,
         time_mode: Option<TimeMode>,
     ) -> Result<Self, SourceError> {
-        eprintln!("setting dtref to {}", ref_time);
+        info!("Setting reference time to {ref_time}");
         self.video = self
             .video
             .time_parameters(tps, ref_time, delta_t_max, time_mode)?;
@@ -1049,6 +1067,66 @@ impl<W: Write + 'static> VideoBuilder<W> for Davis<W> {
         self
     }
 
+    fn max_duration(mut self, seconds: f64) -> Self {
+        self.video = self.video.max_duration(seconds);
+        self
+    }
+
+    fn clamp_delta_t_max(mut self, clamp: bool) -> Self {
+        self.video = self.video.clamp_delta_t_max(clamp);
+        self
+    }
+
+    fn inline_quality(mut self, enable: bool) -> Self {
+        self.video = self.video.inline_quality(enable);
+        self
+    }
+
+    fn luma_weights(mut self, weights: LumaWeights) -> Self {
+        self.video = self.video.luma_weights(weights);
+        self
+    }
+
+    fn response_lut(mut self, lut: [f32; 256]) -> Self {
+        self.video = self.video.response_lut(lut);
+        self
+    }
+
+    fn t_fraction_bits(mut self, bits: u8) -> Self {
+        self.video = self.video.t_fraction_bits(bits);
+        self
+    }
+
+    fn seed_samples(mut self, samples: u8) -> Self {
+        self.video = self.video.seed_samples(samples);
+        self
+    }
+
+    fn integration_rule(mut self, rule: IntegrationRule) -> Self {
+        self.video = self.video.integration_rule(rule);
+        self
+    }
+
+    fn min_split_intensity(mut self, intensity: Intensity32) -> Self {
+        self.video = self.video.min_split_intensity(intensity);
+        self
+    }
+
+    fn d_max(mut self, d_max: D) -> Self {
+        self.video = self.video.d_max(d_max);
+        self
+    }
+
+    fn bayer_pattern(mut self, pattern: BayerPattern) -> Result<Self, SourceError> {
+        self.video = self.video.bayer_pattern(pattern)?;
+        Ok(self)
+    }
+
+    fn scale_filter(self, _filter: ScaleFilter) -> Result<Self, SourceError> {
+        // Event camera input is never scaled
+        Ok(self)
+    }
+
     #[cfg(feature = "feature-logging")]
     fn log_path(self, _name: String) -> Self {
         todo!()
@@ -1079,7 +1157,7 @@ pub fn get_next_image(
     let res = thread_pool.install(|| async {
         match reconstructor.next(with_events).await {
             None => {
-                println!("\nFinished!");
+                info!("Finished reconstructing");
                 Ok(None)
             }
             Some(res) => match res {
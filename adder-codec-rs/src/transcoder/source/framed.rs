@@ -1,18 +1,22 @@
+use crate::transcoder::event_pixel_tree::Intensity32;
 use crate::transcoder::source::video::SourceError;
 use crate::transcoder::source::video::Video;
-use crate::transcoder::source::video::{Source, VideoBuilder};
+use crate::transcoder::source::video::{IntegrationRule, Source, VideoBuilder};
 use adder_codec_core::Mode::FramePerfect;
-use adder_codec_core::{DeltaT, Event, PixelMultiMode, PlaneSize, SourceCamera, TimeMode};
+use adder_codec_core::{
+    DeltaT, Event, PixelMultiMode, PlaneSize, SourceCamera, TimeMode, TimeParams, D,
+};
 
 use crate::utils::viz::ShowFeatureMode;
 use adder_codec_core::codec::{EncoderOptions, EncoderType};
 
-use crate::utils::cv::handle_color;
 #[cfg(feature = "feature-logging")]
-use crate::utils::cv::{calculate_quality_metrics, QualityMetrics};
+use crate::utils::cv::{calculate_quality_metrics_in, QualityMetrics};
+use crate::utils::cv::{handle_color, scale_frame, BayerPattern, LumaWeights, ScaleFilter};
 
+use log::error;
 use rayon::ThreadPool;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::PathBuf;
 
 #[cfg(feature = "feature-logging")]
@@ -33,6 +37,11 @@ pub struct Framed<W: Write + 'static> {
     /// Scale of the input video. Input frames are resized to this scale before transcoding.
     pub scale: f64,
 
+    /// The interpolation for resizing input frames. If `None`, the decoder resizes them.
+    scale_filter: Option<ScaleFilter>,
+
+    source_path: PathBuf,
+
     /// Whether the input video is color
     color_input: bool,
 
@@ -47,7 +56,8 @@ impl<W: Write + 'static> Framed<W> {
         color_input: bool,
         scale: f64,
     ) -> Result<Framed<W>, SourceError> {
-        let source = Locator::Path(PathBuf::from(input_filename));
+        let source_path = PathBuf::from(input_filename);
+        let source = Locator::Path(source_path.clone());
         let mut cap = Decoder::new(&source)?;
         let (width, height) = cap.size();
         let width = ((width as f64) * scale) as u32;
@@ -73,6 +83,8 @@ impl<W: Write + 'static> Framed<W> {
             frame_idx_start: 0,
             source_fps,
             scale,
+            scale_filter: None,
+            source_path,
             color_input,
             video,
         })
@@ -91,6 +103,54 @@ impl<W: Write + 'static> Framed<W> {
         Ok(self)
     }
 
+    /// Change the scale of the input video mid-transcode, without rebuilding the source. The
+    /// pixel trees are reset at the new resolution, and the next consumed frame is scaled
+    /// accordingly.
+    ///
+    /// # Errors
+    /// * If `scale` is not positive
+    /// * If the video is writing an ADΔER stream, whose resolution is fixed
+    /// * If the source can't be reopened at the new scale
+    pub fn set_scale(&mut self, scale: f64) -> Result<(), SourceError> {
+        if scale <= 0.0 || !scale.is_finite() {
+            return Err(SourceError::BadParams(format!("invalid scale {scale}")));
+        }
+        let (width, height) = self.cap.size();
+        let mut width = ((width as f64) * scale) as u32;
+        let mut height = ((height as f64) * scale) as u32;
+
+        // If the decoder does the resizing, it must be reopened at the new size
+        let cap = match self.scale_filter {
+            Some(_) => None,
+            None => {
+                let cap = Decoder::new_with_options_and_resize(
+                    &Locator::Path(self.source_path.clone()),
+                    &Options::default(),
+                    Resize::Fit(width, height),
+                )?;
+                (width, height) = cap.size_out();
+                Some(cap)
+            }
+        };
+
+        let plane = PlaneSize::new(width as u16, height as u16, self.video.state.plane.c())?;
+        self.video.set_plane(plane)?;
+
+        if let Some(cap) = cap {
+            self.cap = cap;
+
+            // Resume where the previous decoder left off
+            let frame_idx = self.next_frame_idx();
+            if frame_idx > 0 {
+                let ts_millis = (frame_idx as f32 / self.source_fps * 1000.0) as i64;
+                self.cap.reader.seek(ts_millis)?;
+            }
+        }
+        self.scale = scale;
+        self.input_frame = Frame::default((height as usize, width as usize, 3));
+        Ok(())
+    }
+
     /// Automatically derive the ticks per second from the source FPS and `ref_time`
     pub fn auto_time_parameters(
         mut self,
@@ -98,11 +158,15 @@ impl<W: Write + 'static> Framed<W> {
         delta_t_max: DeltaT,
         time_mode: Option<TimeMode>,
     ) -> Result<Self, SourceError> {
-        if delta_t_max % ref_time == 0 {
-            let tps = (ref_time as f32 * self.source_fps) as DeltaT;
-            self.video = self
-                .video
-                .time_parameters(tps, ref_time, delta_t_max, time_mode)?;
+        if ref_time != 0 && delta_t_max % ref_time == 0 {
+            let params = TimeParams::from_fps(self.source_fps, ref_time, delta_t_max / ref_time)
+                .map_err(|e| SourceError::BadParams(e.to_string()))?;
+            self.video = self.video.time_parameters(
+                params.tps(),
+                params.ref_interval(),
+                params.delta_t_max(),
+                time_mode,
+            )?;
         } else {
             return Err(SourceError::BadParams(
                 "delta_t_max must be a multiple of ref_time".to_string(),
@@ -111,6 +175,53 @@ impl<W: Write + 'static> Framed<W> {
         Ok(self)
     }
 
+    /// Serialize the transcode state, along with the index of the next frame to read from the
+    /// input video, so that an interrupted transcode can be resumed with
+    /// [`Framed::load_state`]. See [`Video::save_state`].
+    ///
+    /// # Errors
+    /// Returns an error if the state cannot be written.
+    pub fn save_state<S: Write>(&self, writer: &mut S) -> Result<(), SourceError> {
+        self.video
+            .save_state_at(writer, Some(u64::from(self.next_frame_idx())))
+    }
+
+    /// Restore a transcode state written by [`Framed::save_state`], and advance the input video
+    /// to the first frame after the checkpoint. The source must be newly opened on the same
+    /// video, with the same scale and parameters as the one which was saved.
+    ///
+    /// Seeking would only land on a keyframe, so the frames before the checkpoint are decoded
+    /// and discarded instead.
+    ///
+    /// # Errors
+    /// Returns an error if the source has already read any frames, if the state cannot be read or
+    /// doesn't match the video, or if the input video ends before the checkpoint.
+    pub fn load_state<R: Read>(&mut self, reader: &mut R) -> Result<(), SourceError> {
+        let frame_idx = self.next_frame_idx();
+        if frame_idx != self.frame_idx_start {
+            return Err(SourceError::BadParams(
+                "a state can only be loaded into a source which hasn't read any frames".to_string(),
+            ));
+        }
+        let source_position = self.video.load_state_at(reader)?;
+        // A checkpoint saved by `Video::save_state` resumes from the number of frames integrated
+        let target = source_position.unwrap_or_else(|| u64::from(self.next_frame_idx()));
+        if target < u64::from(frame_idx) {
+            return Err(SourceError::BadParams(format!(
+                "the source starts at frame {frame_idx}, after the checkpoint at frame {target}"
+            )));
+        }
+        for _ in u64::from(frame_idx)..target {
+            self.cap.decode()?;
+        }
+        Ok(())
+    }
+
+    /// The index of the next frame to read from the input video
+    fn next_frame_idx(&self) -> u32 {
+        self.frame_idx_start + self.video.state.in_interval_count.saturating_sub(1)
+    }
+
     /// Get the number of ticks each frame is said to span
     pub fn get_ref_time(&self) -> u32 {
         self.video.state.params.ref_time
@@ -121,16 +232,20 @@ impl<W: Write + 'static> Framed<W> {
     }
 }
 
-impl<W: Write + 'static> Source<W> for Framed<W> {
+impl<W: Write + 'static> Framed<W> {
     /// Get pixel-wise intensities directly from source frame, and integrate them with
     /// `ref_time` (the number of ticks each frame is said to span)
-    fn consume(
+    fn consume_input(
         &mut self,
         view_interval: u32,
         thread_pool: &ThreadPool,
     ) -> Result<Vec<Vec<Event>>, SourceError> {
-        let (_, frame) = self.cap.decode()?;
-        self.input_frame = handle_color(frame, self.color_input)?;
+        let (_, mut frame) = self.cap.decode()?;
+        if let Some(filter) = self.scale_filter {
+            let plane = self.video.state.plane;
+            frame = scale_frame(&frame, plane.w_usize(), plane.h_usize(), filter);
+        }
+        self.input_frame = handle_color(frame, self.color_input, self.video.state.luma_weights)?;
 
         let res = thread_pool.install(|| {
             self.video.integrate_matrix(
@@ -146,9 +261,11 @@ impl<W: Write + 'static> Source<W> for Framed<W> {
                 let mut image_mat = self.video.state.running_intensities.clone();
 
                 #[rustfmt::skip]
-                    let metrics = calculate_quality_metrics(
+                    let metrics = calculate_quality_metrics_in(
                     &self.input_frame,
+                    self.get_input_color_space(),
                     &image_mat,
+                    self.video.get_color_space(),
                     QualityMetrics {
                         mse: Some(0.0),
                         psnr: Some(0.0),
@@ -162,6 +279,17 @@ impl<W: Write + 'static> Source<W> for Framed<W> {
         }
         res
     }
+}
+
+impl<W: Write + 'static> Source<W> for Framed<W> {
+    fn consume(
+        &mut self,
+        view_interval: u32,
+        thread_pool: &ThreadPool,
+    ) -> Result<Vec<Vec<Event>>, SourceError> {
+        let result = self.consume_input(view_interval, thread_pool);
+        self.video.record_consume(result)
+    }
 
     fn crf(&mut self, crf: u8) {
         self.video.update_crf(crf);
@@ -192,9 +320,9 @@ impl<W: Write + 'static> Source<W> for Framed<W> {
 }
 
 impl<W: Write + 'static> VideoBuilder<W> for Framed<W> {
-    fn contrast_thresholds(mut self, c_thresh_pos: u8, _c_thresh_neg: u8) -> Self {
+    fn contrast_thresholds(mut self, c_thresh_pos: u8, c_thresh_neg: u8) -> Self {
         self.video = self.video.c_thresh_pos(c_thresh_pos);
-        // self.video = self.video.c_thresh_neg(c_thresh_neg);
+        self.video = self.video.c_thresh_neg(c_thresh_neg);
         self
     }
 
@@ -248,7 +376,10 @@ impl<W: Write + 'static> VideoBuilder<W> for Framed<W> {
                 .video
                 .time_parameters(tps, ref_time, delta_t_max, time_mode)?;
         } else {
-            eprintln!("delta_t_max must be a multiple of ref_time");
+            error!(
+                "delta_t_max ({delta_t_max}) must be a multiple of ref_time ({ref_time}) for {}",
+                self.source_path.display()
+            );
         }
         Ok(self)
     }
@@ -285,6 +416,83 @@ impl<W: Write + 'static> VideoBuilder<W> for Framed<W> {
         self
     }
 
+    fn max_duration(mut self, seconds: f64) -> Self {
+        self.video = self.video.max_duration(seconds);
+        self
+    }
+
+    fn clamp_delta_t_max(mut self, clamp: bool) -> Self {
+        self.video = self.video.clamp_delta_t_max(clamp);
+        self
+    }
+
+    fn inline_quality(mut self, enable: bool) -> Self {
+        self.video = self.video.inline_quality(enable);
+        self
+    }
+
+    fn luma_weights(mut self, weights: LumaWeights) -> Self {
+        self.video = self.video.luma_weights(weights);
+        self
+    }
+
+    fn response_lut(mut self, lut: [f32; 256]) -> Self {
+        self.video = self.video.response_lut(lut);
+        self
+    }
+
+    fn t_fraction_bits(mut self, bits: u8) -> Self {
+        self.video = self.video.t_fraction_bits(bits);
+        self
+    }
+
+    fn seed_samples(mut self, samples: u8) -> Self {
+        self.video = self.video.seed_samples(samples);
+        self
+    }
+
+    fn integration_rule(mut self, rule: IntegrationRule) -> Self {
+        self.video = self.video.integration_rule(rule);
+        self
+    }
+
+    fn min_split_intensity(mut self, intensity: Intensity32) -> Self {
+        self.video = self.video.min_split_intensity(intensity);
+        self
+    }
+
+    fn d_max(mut self, d_max: D) -> Self {
+        self.video = self.video.d_max(d_max);
+        self
+    }
+
+    /// Decode the input as a single-channel Bayer mosaic, and demosaic it to a color transcode.
+    ///
+    /// This resets the underlying [`Video`], so it must be called before the other builder
+    /// methods.
+    fn bayer_pattern(mut self, pattern: BayerPattern) -> Result<Self, SourceError> {
+        let plane = self.video.state.plane;
+        if plane.c() != 3 {
+            self.video = Video::new(PlaneSize::new(plane.w(), plane.h(), 3)?, FramePerfect, None)?;
+        }
+        self.color_input = false;
+        self.video = self.video.bayer_pattern(pattern)?;
+        Ok(self)
+    }
+
+    /// Scale the input frames with the given filter, rather than letting the decoder do it.
+    ///
+    /// This reopens the source at its full resolution.
+    fn scale_filter(mut self, filter: ScaleFilter) -> Result<Self, SourceError> {
+        self.cap = Decoder::new(&Locator::Path(self.source_path.clone()))?;
+        self.scale_filter = Some(filter);
+        if self.frame_idx_start > 0 {
+            let ts_millis = (self.frame_idx_start as f32 / self.source_fps * 1000.0) as i64;
+            self.cap.reader.seek(ts_millis)?;
+        }
+        Ok(self)
+    }
+
     #[cfg(feature = "feature-logging")]
     fn log_path(mut self, name: String) -> Self {
         let date_time = Local::now();
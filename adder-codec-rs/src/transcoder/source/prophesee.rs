@@ -1,15 +1,17 @@
 use crate::framer::scale_intensity::{FrameValue, SaeTime};
+use crate::transcoder::event_pixel_tree::Intensity32;
 use crate::transcoder::source::video::FramedViewMode::SAE;
 use crate::transcoder::source::video::{
-    integrate_for_px, Source, SourceError, Video, VideoBuilder,
+    integrate_for_px, IntegrationRule, Source, SourceError, Video, VideoBuilder,
 };
-use crate::utils::cv::clamp_u8;
+use crate::utils::cv::{clamp_u8, BayerPattern, LumaWeights, ScaleFilter};
 use crate::utils::viz::ShowFeatureMode;
 use adder_codec_core::codec::{EncoderOptions, EncoderType};
 use adder_codec_core::Mode::Continuous;
 use adder_codec_core::{
-    DeltaT, Event, PixelMultiMode, PlaneSize, SourceCamera, SourceType, TimeMode,
+    BigT, DeltaT, Event, PixelMultiMode, PlaneSize, SourceCamera, SourceType, TimeMode, D,
 };
+use log::info;
 use ndarray::Array3;
 use rayon::ThreadPool;
 use serde::Deserialize;
@@ -119,8 +121,8 @@ impl<W: Write + 'static> Prophesee<W> {
     }
 }
 
-impl<W: Write + 'static + std::marker::Send> Source<W> for Prophesee<W> {
-    fn consume(
+impl<W: Write + 'static + std::marker::Send> Prophesee<W> {
+    fn consume_input(
         &mut self,
         view_interval: u32,
         thread_pool: &ThreadPool,
@@ -145,6 +147,11 @@ impl<W: Write + 'static + std::marker::Send> Source<W> for Prophesee<W> {
             self.running_t = 2;
         }
 
+        if self.video.max_duration_reached() {
+            end_events(self);
+            return Err(SourceError::BufferEmpty);
+        }
+
         // TODO hardcoded: scale the view interval to be 60 FPS GUI display
         let view_interval = (PROPHESEE_SOURCE_TPS / 60);
 
@@ -174,6 +181,9 @@ impl<W: Write + 'static + std::marker::Send> Source<W> for Prophesee<W> {
             }
         }
 
+        self.video.state.running_ticks =
+            BigT::from(self.running_t) * BigT::from(self.video.state.params.ref_time);
+
         let mut events: Vec<Event> = Vec::new();
         let crf_parameters = *self.video.encoder.options.crf.get_parameters();
 
@@ -297,6 +307,17 @@ impl<W: Write + 'static + std::marker::Send> Source<W> for Prophesee<W> {
 
         Ok(events_nested)
     }
+}
+
+impl<W: Write + 'static + std::marker::Send> Source<W> for Prophesee<W> {
+    fn consume(
+        &mut self,
+        view_interval: u32,
+        thread_pool: &ThreadPool,
+    ) -> Result<Vec<Vec<Event>>, SourceError> {
+        let result = self.consume_input(view_interval, thread_pool);
+        self.video.record_consume(result)
+    }
 
     fn crf(&mut self, crf: u8) {
         self.video.update_crf(crf);
@@ -448,9 +469,9 @@ fn decode_event(reader: &mut BufReader<File>) -> io::Result<(DvsEvent)> {
 }
 
 impl<W: Write + 'static> VideoBuilder<W> for Prophesee<W> {
-    fn contrast_thresholds(mut self, c_thresh_pos: u8, _c_thresh_neg: u8) -> Self {
+    fn contrast_thresholds(mut self, c_thresh_pos: u8, c_thresh_neg: u8) -> Self {
         self.video = self.video.c_thresh_pos(c_thresh_pos);
-        // self.video = self.video.c_thresh_neg(c_thresh_neg);
+        self.video = self.video.c_thresh_neg(c_thresh_neg);
         self
     }
 
@@ -482,8 +503,8 @@ impl<W: Write + 'static> VideoBuilder<W> for Prophesee<W> {
         self
     }
 
-    fn c_thresh_neg(self, _c_thresh_neg: u8) -> Self {
-        // self.video = self.video.c_thresh_neg(c_thresh_neg);
+    fn c_thresh_neg(mut self, c_thresh_neg: u8) -> Self {
+        self.video = self.video.c_thresh_neg(c_thresh_neg);
         self
     }
 
@@ -499,7 +520,7 @@ impl<W: Write + 'static> VideoBuilder<W> for Prophesee<W> {
         delta_t_max: DeltaT,
         time_mode: Option<TimeMode>,
     ) -> Result<Self, SourceError> {
-        eprintln!("setting dtref to {}", ref_time);
+        info!("Setting reference time to {ref_time}");
         self.video = self
             .video
             .time_parameters(tps, ref_time, delta_t_max, time_mode)?;
@@ -538,6 +559,66 @@ impl<W: Write + 'static> VideoBuilder<W> for Prophesee<W> {
         self
     }
 
+    fn max_duration(mut self, seconds: f64) -> Self {
+        self.video = self.video.max_duration(seconds);
+        self
+    }
+
+    fn clamp_delta_t_max(mut self, clamp: bool) -> Self {
+        self.video = self.video.clamp_delta_t_max(clamp);
+        self
+    }
+
+    fn inline_quality(mut self, enable: bool) -> Self {
+        self.video = self.video.inline_quality(enable);
+        self
+    }
+
+    fn luma_weights(mut self, weights: LumaWeights) -> Self {
+        self.video = self.video.luma_weights(weights);
+        self
+    }
+
+    fn response_lut(mut self, lut: [f32; 256]) -> Self {
+        self.video = self.video.response_lut(lut);
+        self
+    }
+
+    fn t_fraction_bits(mut self, bits: u8) -> Self {
+        self.video = self.video.t_fraction_bits(bits);
+        self
+    }
+
+    fn seed_samples(mut self, samples: u8) -> Self {
+        self.video = self.video.seed_samples(samples);
+        self
+    }
+
+    fn integration_rule(mut self, rule: IntegrationRule) -> Self {
+        self.video = self.video.integration_rule(rule);
+        self
+    }
+
+    fn min_split_intensity(mut self, intensity: Intensity32) -> Self {
+        self.video = self.video.min_split_intensity(intensity);
+        self
+    }
+
+    fn d_max(mut self, d_max: D) -> Self {
+        self.video = self.video.d_max(d_max);
+        self
+    }
+
+    fn bayer_pattern(mut self, pattern: BayerPattern) -> Result<Self, SourceError> {
+        self.video = self.video.bayer_pattern(pattern)?;
+        Ok(self)
+    }
+
+    fn scale_filter(self, _filter: ScaleFilter) -> Result<Self, SourceError> {
+        // Event camera input is never scaled
+        Ok(self)
+    }
+
     #[cfg(feature = "feature-logging")]
     fn log_path(self, _name: String) -> Self {
         todo!()
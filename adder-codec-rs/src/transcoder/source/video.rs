@@ -6,7 +6,7 @@ use std::cmp::min;
 use std::collections::HashSet;
 #[cfg(feature = "feature-logging")]
 use std::ffi::c_void;
-use std::io::{sink, Write};
+use std::io::{sink, Read, Write};
 use std::mem::swap;
 
 use adder_codec_core::codec::empty::stream::EmptyOutput;
@@ -16,15 +16,15 @@ use adder_codec_core::codec::{
     CodecError, CodecMetadata, EncoderOptions, EncoderType, LATEST_CODEC_VERSION,
 };
 use adder_codec_core::{
-    Coord, DeltaT, Event, Mode, PixelMultiMode, PlaneError, PlaneSize, SourceCamera, SourceType,
-    TimeMode, D_EMPTY,
+    BigT, Coord, DConfig, DeltaT, Event, Mode, PixelMultiMode, PlaneError, PlaneSize, SourceCamera,
+    SourceType, TimeMode, D_EMPTY,
 };
 use bumpalo::Bump;
 
 use std::sync::mpsc::{channel, Sender};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use crate::framer::scale_intensity::{FrameValue, SaeTime};
+use crate::framer::scale_intensity::{practical_d_max, FrameValue, SaeTime};
 use crate::transcoder::event_pixel_tree::{Intensity32, PixelArena};
 use adder_codec_core::D;
 #[cfg(feature = "opencv")]
@@ -36,14 +36,16 @@ use opencv::{highgui, imgproc::resize};
 use adder_codec_core::codec::compressed::stream::CompressedOutput;
 use adder_codec_core::Mode::Continuous;
 use itertools::Itertools;
-use ndarray::{Array, Array3, Axis, ShapeError};
+use log::warn;
+use ndarray::{Array, Array2, Array3, Axis, ShapeError};
 use rayon::iter::IndexedParallelIterator;
 use rayon::iter::IntoParallelIterator;
 use rayon::iter::ParallelIterator;
 use rayon::ThreadPool;
+use serde::{Deserialize, Serialize};
 
 use crate::transcoder::source::video::FramedViewMode::SAE;
-use crate::utils::cv::is_feature;
+use crate::utils::cv::{demosaic, is_feature, BayerPattern, ColorSpace, LumaWeights, ScaleFilter};
 
 use crate::utils::viz::{draw_feature_coord, ShowFeatureMode};
 use adder_codec_core::codec::rate_controller::{Crf, CrfParameters};
@@ -119,6 +121,48 @@ pub enum SourceError {
     /// I/O error
     #[error("I/O error")]
     IoError(#[from] std::io::Error),
+
+    /// Transcode state could not be serialized or deserialized
+    #[error("Transcode state error")]
+    StateError(#[from] bincode::Error),
+
+    /// An error recorded for [`Source::last_error`] whose underlying error couldn't be copied
+    #[error("{0}")]
+    Recorded(String),
+}
+
+impl SourceError {
+    /// Copy this error so that it can be kept for [`Source::last_error`] while the original is
+    /// returned to the caller. The variant is preserved where the underlying error can be copied;
+    /// otherwise, the error is recorded as its message.
+    fn duplicate(&self) -> SourceError {
+        match self {
+            SourceError::Open => SourceError::Open,
+            SourceError::BadParams(s) => SourceError::BadParams(s.clone()),
+            SourceError::StartOutOfBounds(frame) => SourceError::StartOutOfBounds(*frame),
+            SourceError::BufferEmpty => SourceError::BufferEmpty,
+            SourceError::BufferChannelClosed => SourceError::BufferChannelClosed,
+            SourceError::NoData => SourceError::NoData,
+            SourceError::UninitializedData => SourceError::UninitializedData,
+            #[cfg(feature = "open-cv")]
+            SourceError::OpencvError(e) => SourceError::OpencvError(e.clone()),
+            SourceError::VideoError(e) => SourceError::VideoError(e.clone()),
+            SourceError::ShapeError(e) => SourceError::ShapeError(e.clone()),
+            SourceError::PlaneError(e) => SourceError::PlaneError(e.clone()),
+            SourceError::VisionError(s) => SourceError::VisionError(s.clone()),
+            SourceError::IoError(e) => {
+                SourceError::IoError(std::io::Error::new(e.kind(), e.to_string()))
+            }
+            SourceError::StateError(e) => {
+                SourceError::StateError(Box::new(bincode::ErrorKind::Custom(e.to_string())))
+            }
+            SourceError::CodecError(e) => SourceError::Recorded(format!("{self}: {e:?}")),
+            #[cfg(feature = "open-cv")]
+            SourceError::EdiError(e) => SourceError::Recorded(format!("{self}: {e:?}")),
+            SourceError::JoinError(e) => SourceError::Recorded(format!("{self}: {e:?}")),
+            SourceError::Recorded(s) => SourceError::Recorded(s.clone()),
+        }
+    }
 }
 
 #[cfg(feature = "open-cv")]
@@ -139,6 +183,19 @@ impl From<video_rs_adder_dep::Error> for SourceError {
     }
 }
 
+/// How each pixel integrates the intensity of a sample over the time it spans
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum IntegrationRule {
+    /// Hold each sample's intensity over its whole interval
+    #[default]
+    Rectangular,
+
+    /// Interpolate linearly between the previous and current samples, integrating the mean of
+    /// their intensities over the interval. Smoother for framed sources, where the intensity is
+    /// only known at the sample points.
+    Trapezoidal,
+}
+
 /// The display mode
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
 pub enum FramedViewMode {
@@ -168,6 +225,32 @@ pub struct VideoStateParams {
 
     /// The reference time in ticks
     pub ref_time: u32,
+
+    /// Whether to keep forcing events for a pixel each time its accumulated time reaches
+    /// `delta_t_max`, rather than only the first time
+    pub clamp_delta_t_max: bool,
+
+    /// The baseline contrast threshold for falling intensities, for sensors which are more or
+    /// less sensitive to darkening than brightening. Each pixel's negative threshold keeps this
+    /// offset from its (adaptive) positive threshold. If `None`, the thresholds are equal.
+    pub c_thresh_neg: Option<u8>,
+
+    /// The number of initial frame values averaged to seed each pixel's base intensity. The
+    /// contrast threshold isn't checked until a pixel has seen this many values. Values of 0 and
+    /// 1 seed each pixel from its first value alone.
+    pub seed_samples: u8,
+
+    /// How each pixel integrates the intensity of a sample over the time it spans
+    pub integration_rule: IntegrationRule,
+}
+
+impl VideoStateParams {
+    /// The offset of each pixel's negative contrast threshold from its positive threshold, given
+    /// the baseline positive threshold
+    pub(crate) fn c_thresh_neg_offset(&self, c_thresh_baseline: u8) -> i16 {
+        self.c_thresh_neg
+            .map_or(0, |neg| i16::from(neg) - i16::from(c_thresh_baseline))
+    }
 }
 
 impl Default for VideoStateParams {
@@ -177,6 +260,10 @@ impl Default for VideoStateParams {
             pixel_multi_mode: Default::default(),
             delta_t_max: 7650,
             ref_time: 255,
+            clamp_delta_t_max: false,
+            c_thresh_neg: None,
+            seed_samples: 1,
+            integration_rule: IntegrationRule::default(),
         }
     }
 }
@@ -208,12 +295,51 @@ pub struct VideoState {
     /// The current instantaneous frame, for determining features
     pub running_intensities: Array3<u8>,
 
+    /// The channel order of `running_intensities`
+    pub(crate) color_space: ColorSpace,
+
     /// Whether or not to draw the features on the display mat, and the mode to do it in
     show_features: ShowFeatureMode,
 
     features: Vec<HashSet<Coord>>,
 
     pub feature_log_handle: Option<std::fs::File>,
+
+    /// The maximum duration of source time to transcode, in seconds. If `None`, the transcode
+    /// runs until the source is exhausted.
+    pub(crate) max_duration: Option<f64>,
+
+    /// The number of source ticks integrated so far
+    pub running_ticks: BigT,
+
+    /// The color filter pattern of a raw Bayer source. If set, single-channel input frames are
+    /// demosaiced before integration.
+    pub(crate) bayer_pattern: Option<BayerPattern>,
+
+    /// The number of intra-frame periods elapsed as of the last intra-frame
+    intra_frame_count: BigT,
+
+    /// Whether to measure the quality of the running reconstruction against each input frame.
+    /// See [`Video::rolling_psnr`].
+    pub(crate) inline_quality: bool,
+
+    /// The sum of squared errors between the input frames and the running reconstruction
+    quality_error_sum: f64,
+
+    /// The number of pixel values summed in `quality_error_sum`
+    quality_sample_count: u64,
+
+    /// The channel weights for converting color frames to grayscale
+    pub(crate) luma_weights: LumaWeights,
+
+    /// Maps each source pixel value to a linear intensity, undoing the camera's response curve
+    pub(crate) response_lut: Option<[f32; 256]>,
+
+    /// Number of fractional bits to keep in each event timestamp
+    pub(crate) t_fraction_bits: u8,
+
+    /// The range of [`D`] values the pixels may fire with
+    pub(crate) d_config: DConfig,
 }
 
 impl Default for VideoState {
@@ -229,13 +355,40 @@ impl Default for VideoState {
             show_live: false,
             feature_detection: false,
             running_intensities: Default::default(),
+            color_space: ColorSpace::default(),
             show_features: ShowFeatureMode::Off,
             features: Default::default(),
             feature_log_handle: None,
+            max_duration: None,
+            running_ticks: 0,
+            bayer_pattern: None,
+            intra_frame_count: 0,
+            inline_quality: false,
+            quality_error_sum: 0.0,
+            quality_sample_count: 0,
+            luma_weights: LumaWeights::default(),
+            response_lut: None,
+            t_fraction_bits: 0,
+            d_config: DConfig::default(),
         }
     }
 }
 
+/// The parts of a transcode's running state which are needed to resume it. See
+/// [`Video::save_state`]. The pixel trees are generic so that they can be saved by reference.
+#[derive(Serialize, Deserialize)]
+struct TranscodeCheckpoint<P> {
+    in_interval_count: u32,
+    running_ticks: BigT,
+    intra_frame_count: BigT,
+    running_intensities: Array3<u8>,
+
+    /// The index of the source's next input frame, for sources which can resume from one
+    source_position: Option<u64>,
+
+    event_pixel_trees: P,
+}
+
 // impl VideoState {
 //     fn update_crf(&mut self, crf: u8) {
 //         self.crf_quality = crf;
@@ -320,6 +473,51 @@ pub trait VideoBuilder<W> {
     /// Set whether or not to detect features, and whether or not to display the features
     fn detect_features(self, detect_features: bool, show_features: ShowFeatureMode) -> Self;
 
+    /// Set the maximum duration of source time to transcode, in seconds
+    fn max_duration(self, seconds: f64) -> Self;
+
+    /// Set whether to force an empty event every time a pixel's accumulated time reaches
+    /// `delta_t_max`
+    fn clamp_delta_t_max(self, clamp: bool) -> Self;
+
+    /// Treat the source as a raw Bayer mosaic with the given color filter pattern
+    fn bayer_pattern(self, pattern: BayerPattern) -> Result<Self, SourceError>
+    where
+        Self: std::marker::Sized;
+
+    /// Set whether to measure the transcode's quality as it runs. See [`Video::rolling_psnr`].
+    fn inline_quality(self, enable: bool) -> Self;
+
+    /// Set the interpolation used to scale source frames to the transcode resolution. Has no
+    /// effect for sources which aren't scaled.
+    fn scale_filter(self, filter: ScaleFilter) -> Result<Self, SourceError>
+    where
+        Self: std::marker::Sized;
+
+    /// Set the channel weights for converting color frames to grayscale. Defaults to
+    /// [`LumaWeights::REC_601`].
+    fn luma_weights(self, weights: LumaWeights) -> Self;
+
+    /// Linearize source frames through a calibrated camera response. See
+    /// [`Video::response_lut`].
+    fn response_lut(self, lut: [f32; 256]) -> Self;
+
+    /// Keep sub-tick precision in event timestamps. See [`Video::t_fraction_bits`].
+    fn t_fraction_bits(self, bits: u8) -> Self;
+
+    /// Seed each pixel from the mean of its first few values. See [`Video::seed_samples`].
+    fn seed_samples(self, samples: u8) -> Self;
+
+    /// Set how pixels integrate intensity over time. See [`Video::integration_rule`].
+    fn integration_rule(self, rule: IntegrationRule) -> Self;
+
+    /// Bound how finely a sample's intensity is split across alt nodes. See
+    /// [`Video::min_split_intensity`].
+    fn min_split_intensity(self, intensity: Intensity32) -> Self;
+
+    /// Cap the [`D`] values the pixels fire with. See [`Video::d_max`].
+    fn d_max(self, d_max: D) -> Self;
+
     #[cfg(feature = "feature-logging")]
     fn log_path(self, name: String) -> Self;
 }
@@ -343,6 +541,12 @@ pub struct Video<W: Write> {
     pub encoder: Encoder<W>,
 
     pub encoder_type: EncoderType,
+
+    /// The most recent error returned by [`Source::consume`]
+    pub(crate) last_error: Option<SourceError>,
+
+    /// The number of events dropped in each block over the last input interval
+    degradation_map: Option<Array2<u32>>,
     // TODO: Hold multiple encoder options and an enum, so that boxing isn't required.
     // Also hold a state for whether or not to write out events at all, so that a null writer isn't required.
     // Eric: this is somewhat addressed above
@@ -404,6 +608,9 @@ impl<W: Write + 'static> Video<W> {
             event_size: 0,
             source_camera: SourceCamera::default(), // TODO: Allow for setting this
             adu_interval: Default::default(),
+            delta_t_encoding: Default::default(),
+            pixel_multi_mode: PixelMultiMode::Normal,
+            t_fraction_bits: 0,
         };
 
         match writer {
@@ -420,6 +627,8 @@ impl<W: Write + 'static> Video<W> {
                     event_sender,
                     encoder,
                     encoder_type: EncoderType::Empty,
+                    last_error: None,
+                    degradation_map: None,
                 })
             }
             Some(w) => {
@@ -436,6 +645,8 @@ impl<W: Write + 'static> Video<W> {
                     event_sender,
                     encoder,
                     encoder_type: EncoderType::Empty,
+                    last_error: None,
+                    degradation_map: None,
                 })
             }
         }
@@ -450,7 +661,6 @@ impl<W: Write + 'static> Video<W> {
         for px in self.event_pixel_trees.iter_mut() {
             px.c_thresh = c_thresh_pos;
         }
-        dbg!("t");
         self.encoder
             .options
             .crf
@@ -458,17 +668,11 @@ impl<W: Write + 'static> Video<W> {
         self
     }
 
-    /// Set the negative contrast threshold
-    #[deprecated(
-        since = "0.3.4",
-        note = "please use `update_crf` or `update_quality_manual` instead"
-    )]
-    pub fn c_thresh_neg(self, _c_thresh_neg: u8) -> Self {
-        unimplemented!();
-        // for px in self.event_pixel_trees.iter_mut() {
-        //     px.c_thresh = c_thresh_neg;
-        // }
-        // self
+    /// Set the baseline contrast threshold for falling intensities, independently of the
+    /// positive threshold. See [`VideoStateParams::c_thresh_neg`].
+    pub fn c_thresh_neg(mut self, c_thresh_neg: u8) -> Self {
+        self.state.params.c_thresh_neg = Some(c_thresh_neg);
+        self
     }
 
     /// Set the number of rows to process at a time (in each thread)
@@ -506,28 +710,28 @@ impl<W: Write + 'static> Video<W> {
         });
 
         if ref_time > f32::MAX as u32 {
-            eprintln!(
+            warn!(
                 "Reference time {} is too large. Keeping current value of {}.",
                 ref_time, self.state.params.ref_time
             );
             return Ok(self);
         }
         if tps > f32::MAX as u32 {
-            eprintln!(
+            warn!(
                 "Time per sample {} is too large. Keeping current value of {}.",
                 tps, self.state.tps
             );
             return Ok(self);
         }
         if delta_t_max > f32::MAX as u32 {
-            eprintln!(
+            warn!(
                 "Delta t max {} is too large. Keeping current value of {}.",
                 delta_t_max, self.state.params.delta_t_max
             );
             return Ok(self);
         }
         if delta_t_max < ref_time {
-            eprintln!(
+            warn!(
                 "Delta t max {} is smaller than reference time {}. Keeping current value of {}.",
                 delta_t_max, ref_time, self.state.params.delta_t_max
             );
@@ -557,6 +761,19 @@ impl<W: Write + 'static> Video<W> {
         encoder_options: EncoderOptions,
         write: W,
     ) -> Result<Self, SourceError> {
+        // With fractional timestamps, all the stream's time fields are in fractional ticks
+        let scale = 1_u32.checked_shl(u32::from(self.state.t_fraction_bits));
+        let (Some(tps), Some(ref_interval), Some(delta_t_max)) = (
+            scale.and_then(|scale| self.state.tps.checked_mul(scale)),
+            scale.and_then(|scale| self.state.params.ref_time.checked_mul(scale)),
+            scale.and_then(|scale| self.state.params.delta_t_max.checked_mul(scale)),
+        ) else {
+            return Err(SourceError::BadParams(format!(
+                "Time parameters overflow with {} fractional timestamp bits",
+                self.state.t_fraction_bits
+            )));
+        };
+
         let encoder: Encoder<_> = match encoder_type {
             EncoderType::Compressed => {
                 #[cfg(feature = "compression")]
@@ -569,12 +786,15 @@ impl<W: Write + 'static> Video<W> {
                             header_size: 0,
                             time_mode: time_mode.unwrap_or_default(),
                             plane: self.state.plane,
-                            tps: self.state.tps,
-                            ref_interval: self.state.params.ref_time,
-                            delta_t_max: self.state.params.delta_t_max,
+                            tps,
+                            ref_interval,
+                            delta_t_max,
                             event_size: 0,
                             source_camera: source_camera.unwrap_or_default(),
                             adu_interval: adu_interval.unwrap_or_default(),
+                            delta_t_encoding: Default::default(),
+                            pixel_multi_mode: self.state.params.pixel_multi_mode,
+                            t_fraction_bits: self.state.t_fraction_bits,
                         },
                         write,
                     );
@@ -597,12 +817,15 @@ impl<W: Write + 'static> Video<W> {
                         header_size: 0,
                         time_mode: time_mode.unwrap_or_default(),
                         plane: self.state.plane,
-                        tps: self.state.tps,
-                        ref_interval: self.state.params.ref_time,
-                        delta_t_max: self.state.params.delta_t_max,
+                        tps,
+                        ref_interval,
+                        delta_t_max,
                         event_size: 0,
                         source_camera: source_camera.unwrap_or_default(),
                         adu_interval: Default::default(),
+                        delta_t_encoding: Default::default(),
+                        pixel_multi_mode: self.state.params.pixel_multi_mode,
+                        t_fraction_bits: self.state.t_fraction_bits,
                     },
                     write,
                 );
@@ -617,12 +840,15 @@ impl<W: Write + 'static> Video<W> {
                         header_size: 0,
                         time_mode: time_mode.unwrap_or_default(),
                         plane: self.state.plane,
-                        tps: self.state.tps,
-                        ref_interval: self.state.params.ref_time,
-                        delta_t_max: self.state.params.delta_t_max,
+                        tps,
+                        ref_interval,
+                        delta_t_max,
                         event_size: 0,
                         source_camera: source_camera.unwrap_or_default(),
                         adu_interval: Default::default(),
+                        delta_t_encoding: Default::default(),
+                        pixel_multi_mode: self.state.params.pixel_multi_mode,
+                        t_fraction_bits: self.state.t_fraction_bits,
                     },
                     sink(),
                 );
@@ -633,8 +859,10 @@ impl<W: Write + 'static> Video<W> {
         self.encoder = encoder;
         self.encoder_type = encoder_type;
 
+        let t_fraction_bits = self.state.t_fraction_bits;
         self.event_pixel_trees.par_map_inplace(|px| {
             px.time_mode(time_mode);
+            px.t_fraction_bits = t_fraction_bits;
         });
         Ok(self)
     }
@@ -645,6 +873,339 @@ impl<W: Write + 'static> Video<W> {
         self
     }
 
+    /// Limit the transcode to the first `seconds` of source time. Once the limit is reached,
+    /// further calls to integrate source data return [`SourceError::BufferEmpty`], so the
+    /// stream can be closed cleanly with [`Video::end_write_stream`].
+    pub fn max_duration(mut self, seconds: f64) -> Self {
+        self.state.max_duration = Some(seconds);
+        self
+    }
+
+    /// Force an event every time a pixel's accumulated time reaches `delta_t_max`.
+    ///
+    /// By default, a static pixel is only forced to fire once when it first reaches `delta_t_max`.
+    /// With clamping enabled, each further `delta_t_max` of unchanged time produces an empty
+    /// event (`d = D_EMPTY`), so no event ever spans more than `delta_t_max` ticks. These are
+    /// counted in [`Video::forced_event_count`].
+    pub fn clamp_delta_t_max(mut self, clamp: bool) -> Self {
+        self.state.params.clamp_delta_t_max = clamp;
+        self
+    }
+
+    /// Measure the quality of the transcode as it runs, without a separate reconstruction pass.
+    /// See [`Video::rolling_psnr`].
+    pub fn inline_quality(mut self, enable: bool) -> Self {
+        self.state.inline_quality = enable;
+        self
+    }
+
+    /// Set the channel order of the intensities written to `running_intensities`. This should
+    /// match the order of the source's frames, which is BGR for anything read through OpenCV.
+    pub fn color_space(mut self, color_space: ColorSpace) -> Self {
+        self.state.color_space = color_space;
+        self
+    }
+
+    /// Set the channel weights for converting color frames to grayscale
+    pub fn luma_weights(mut self, weights: LumaWeights) -> Self {
+        self.state.luma_weights = weights;
+        self
+    }
+
+    /// Map each source pixel value through `lut` before it enters the pixel tree, so that the
+    /// ADΔER model integrates linear light rather than the camera's nonlinear response. Entries
+    /// should lie in `[0, 255]`.
+    pub fn response_lut(mut self, lut: [f32; 256]) -> Self {
+        self.state.response_lut = Some(lut);
+        self
+    }
+
+    /// Keep `bits` fractional bits of each event's timestamp, rather than truncating it to a
+    /// whole tick. The pixel model measures time as a float, so this preserves the sub-tick
+    /// timing of events which fire partway through an input interval.
+    ///
+    /// The written stream's timestamps and time parameters are all in units of `2^-bits` ticks,
+    /// so the stream reads like one with a finer tick, and its header records `bits` (see
+    /// [`CodecMetadata::t_fraction_bits`]). Must be set before [`Video::write_out`].
+    pub fn t_fraction_bits(mut self, bits: u8) -> Self {
+        self.state.t_fraction_bits = bits;
+        self
+    }
+
+    /// Seed each pixel's base intensity from the mean of its first `samples` values, rather
+    /// than from its first value alone. For noisy sources, this avoids firing spurious events
+    /// while the pixels settle. See [`VideoStateParams::seed_samples`].
+    pub fn seed_samples(mut self, samples: u8) -> Self {
+        self.state.params.seed_samples = samples;
+        self
+    }
+
+    /// Set how each pixel integrates the intensity of a sample over the time it spans. The
+    /// contrast threshold is still checked against the current sample alone. See
+    /// [`IntegrationRule`].
+    pub fn integration_rule(mut self, rule: IntegrationRule) -> Self {
+        self.state.params.integration_rule = rule;
+        self
+    }
+
+    /// In continuous mode, when a pixel's event fires partway through a sample, the rest of
+    /// the sample is integrated into a new alt node, which may fire in turn. For a very large
+    /// sample, this can spawn a long chain of alt nodes in a single integration. A remainder
+    /// below `intensity` is instead absorbed into the firing node's event, which then spans the
+    /// whole sample. This bounds the chain, at the cost of up to `intensity` of each sample
+    /// going unrepresented by its events. Defaults to 0, which always splits.
+    pub fn min_split_intensity(mut self, intensity: Intensity32) -> Self {
+        for px in self.event_pixel_trees.iter_mut() {
+            px.min_split_intensity = intensity;
+        }
+        self
+    }
+
+    /// Cap the [`D`] values the pixels fire with at `d_max`, rather than [`D_MAX`]. A pixel must
+    /// fire once it has integrated `2^d_max` intensity, which bounds the dynamic range of each
+    /// event. Values above [`D_MAX`] are clamped. See [`DConfig`].
+    ///
+    /// [`D_MAX`]: adder_codec_core::D_MAX
+    pub fn d_max(mut self, d_max: D) -> Self {
+        self.state.d_config = DConfig::new(d_max);
+        let d_max = self.state.d_config.d_max;
+        for px in self.event_pixel_trees.iter_mut() {
+            px.d_max = d_max;
+        }
+        self
+    }
+
+    /// The range of [`D`] values the pixels may fire with. See [`Video::d_max`].
+    pub fn get_d_config(&self) -> &DConfig {
+        &self.state.d_config
+    }
+
+    /// Treat single-channel input frames as raw Bayer mosaics with the given color filter
+    /// pattern. Each frame is demosaiced into 3 channels before integration, so the video must
+    /// have a 3-channel plane.
+    pub fn bayer_pattern(mut self, pattern: BayerPattern) -> Result<Self, SourceError> {
+        if self.state.plane.c() != 3 {
+            return Err(SourceError::BadParams(format!(
+                "Demosaicing needs a 3-channel plane, but the video has {} channels",
+                self.state.plane.c()
+            )));
+        }
+        self.state.bayer_pattern = Some(pattern);
+        Ok(self)
+    }
+
+    /// Get the number of events forced so far because a pixel reached `delta_t_max`
+    pub fn forced_event_count(&self) -> u64 {
+        self.event_pixel_trees
+            .iter()
+            .map(|px| u64::from(px.forced_events))
+            .sum()
+    }
+
+    /// Get a snapshot of the transcoder's internal state, for debugging regions which emit too
+    /// many or too few events. Returns the current `d` and accumulated Δt of each pixel's head
+    /// node, as (height, width) maps. For color sources, only channel 0 is reported.
+    pub fn debug_state(&self) -> (Array2<D>, Array2<DeltaT>) {
+        let heads = self.event_pixel_trees.index_axis(Axis(2), 0);
+        (
+            heads.map(|px| px.arena[0].d()),
+            heads.map(|px| px.arena[0].delta_t() as DeltaT),
+        )
+    }
+
+    /// Change the size of the imaging plane mid-transcode, discarding each pixel's integration
+    /// state. The transcode's parameters and position are kept, so the new pixels start
+    /// integrating from the current time.
+    ///
+    /// # Errors
+    /// * If the video is writing a stream, since a stream's plane is fixed by its header
+    /// * If the new plane has a different number of channels
+    pub(crate) fn set_plane(&mut self, plane: PlaneSize) -> Result<(), SourceError> {
+        if self.encoder_type != EncoderType::Empty {
+            return Err(SourceError::BadParams(
+                "the plane size can't change while writing a stream".to_string(),
+            ));
+        }
+        if plane.c() != self.state.plane.c() {
+            return Err(SourceError::BadParams(
+                "the number of channels can't change mid-transcode".to_string(),
+            ));
+        }
+
+        let shape = (plane.h_usize(), plane.w_usize(), plane.c_usize());
+        let c_thresh = self.encoder.options.crf.get_parameters().c_thresh_baseline;
+        let time_mode = self.get_time_mode();
+        let running_t = self.state.running_ticks as f32;
+        let d_max = self.state.d_config.d_max;
+        if self.event_pixel_trees.dim() == shape {
+            // Reuse the pixel trees' allocations
+            for px in &mut self.event_pixel_trees {
+                px.reset(1.0);
+                px.c_thresh = c_thresh;
+                px.running_t = running_t;
+                px.last_fired_t = running_t;
+            }
+        } else {
+            self.event_pixel_trees = Array3::from_shape_fn(shape, |(y, x, c)| {
+                let mut px = PixelArena::new(
+                    1.0,
+                    Coord {
+                        x: x as u16,
+                        y: y as u16,
+                        c: match plane.c() {
+                            1 => None,
+                            _ => Some(c as u8),
+                        },
+                    },
+                );
+                px.c_thresh = c_thresh;
+                px.time_mode(Some(time_mode));
+                px.running_t = running_t;
+                px.last_fired_t = running_t;
+                px.d_max = d_max;
+                px
+            });
+        }
+
+        self.state.plane = plane;
+        self.state.running_intensities = Array3::zeros(shape);
+        self.display_frame_features = Array3::zeros(shape);
+        let mut num_chunks = plane.h_usize() / self.state.chunk_rows;
+        if plane.h_usize() % self.state.chunk_rows != 0 {
+            num_chunks += 1;
+        }
+        self.state.features = vec![HashSet::new(); num_chunks];
+
+        // Keep the (empty) encoder's metadata consistent with the new plane
+        let mut meta = *self.encoder.meta();
+        meta.plane = plane;
+        self.encoder = Encoder::new_empty(EmptyOutput::new(meta, sink()), self.encoder.options);
+        Ok(())
+    }
+
+    /// Serialize the transcode's pixel trees and position, so that an interrupted transcode can
+    /// be resumed with [`load_state`](Self::load_state).
+    ///
+    /// The encoder and its output are not part of the state. Events emitted after the checkpoint
+    /// go to whichever writer the resumed `Video` is given. The source's read position isn't
+    /// known here, so a source which reads its own input should be saved with its own
+    /// `save_state`, such as [`Framed::save_state`](super::framed::Framed::save_state).
+    ///
+    /// # Errors
+    /// Returns an error if the state cannot be written.
+    pub fn save_state<S: Write>(&self, writer: &mut S) -> Result<(), SourceError> {
+        self.save_state_at(writer, None)
+    }
+
+    /// Serialize the transcode state as in [`save_state`](Self::save_state), recording that
+    /// the source will next read input frame `source_position`
+    pub(crate) fn save_state_at<S: Write>(
+        &self,
+        writer: &mut S,
+        source_position: Option<u64>,
+    ) -> Result<(), SourceError> {
+        let checkpoint = TranscodeCheckpoint {
+            in_interval_count: self.state.in_interval_count,
+            running_ticks: self.state.running_ticks,
+            intra_frame_count: self.state.intra_frame_count,
+            running_intensities: self.state.running_intensities.clone(),
+            source_position,
+            event_pixel_trees: &self.event_pixel_trees,
+        };
+        bincode::serialize_into(writer, &checkpoint)?;
+        Ok(())
+    }
+
+    /// Restore a transcode state written by [`save_state`](Self::save_state). The `Video` must
+    /// already be configured with the same plane and parameters as the one which was saved.
+    /// The source should then be resumed from the input interval after the checkpoint.
+    ///
+    /// # Errors
+    /// Returns an error if the state cannot be read, or if it was saved for a different plane.
+    pub fn load_state<R: Read>(&mut self, reader: &mut R) -> Result<(), SourceError> {
+        self.load_state_at(reader).map(|_| ())
+    }
+
+    /// Restore a transcode state as in [`load_state`](Self::load_state). Returns the source
+    /// position recorded by [`save_state_at`](Self::save_state_at), if any.
+    pub(crate) fn load_state_at<R: Read>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<Option<u64>, SourceError> {
+        let checkpoint: TranscodeCheckpoint<Array3<PixelArena>> =
+            bincode::deserialize_from(reader)?;
+        if checkpoint.event_pixel_trees.dim() != self.event_pixel_trees.dim() {
+            return Err(SourceError::BadParams(format!(
+                "saved state has shape {:?}, but the video has shape {:?}",
+                checkpoint.event_pixel_trees.dim(),
+                self.event_pixel_trees.dim()
+            )));
+        }
+
+        self.state.in_interval_count = checkpoint.in_interval_count;
+        self.state.running_ticks = checkpoint.running_ticks;
+        self.state.intra_frame_count = checkpoint.intra_frame_count;
+        self.state.running_intensities = checkpoint.running_intensities;
+        self.event_pixel_trees = checkpoint.event_pixel_trees;
+        Ok(checkpoint.source_position)
+    }
+
+    /// The number of events the encoder's
+    /// [`EventDrop`](adder_codec_core::codec::EventDrop) policy dropped in each block of pixels
+    /// over the last input interval, so that a consumer can flag the degraded regions. Requires
+    /// [`EncoderOptions::degradation_map`]; returns `None` if it isn't enabled.
+    ///
+    /// Under [`EventDrop::Budget`](adder_codec_core::codec::EventDrop::Budget), an interval's
+    /// drops are only counted once the events of the next interval arrive, so the map trails the
+    /// input by an interval.
+    pub fn degradation_map(&self) -> Option<&Array2<u32>> {
+        self.degradation_map.as_ref()
+    }
+
+    /// The PSNR of the running reconstruction against every input frame so far, in dB.
+    ///
+    /// The running reconstruction is each pixel's intensity as implied by its pixel tree, i.e.,
+    /// the intensity its next event will carry. The squared error is accumulated over every
+    /// pixel of every input frame, so this is the PSNR of the transcode's overall MSE. Requires
+    /// [`inline_quality`](Self::inline_quality); returns NaN if no frames have been measured.
+    pub fn rolling_psnr(&self) -> f64 {
+        if self.state.quality_sample_count == 0 {
+            return f64::NAN;
+        }
+        let mut mse = self.state.quality_error_sum / self.state.quality_sample_count as f64;
+        if mse == 0.0 {
+            // Make sure that PSNR isn't undefined
+            mse = 0.0000001;
+        }
+        20.0 * 255.0_f64.log10() - 10.0 * mse.log10()
+    }
+
+    /// Get the maximum duration of the transcode, in ticks, derived from the current `tps`
+    pub fn get_max_duration_ticks(&self) -> Option<BigT> {
+        self.state
+            .max_duration
+            .map(|seconds| (seconds * f64::from(self.state.tps)) as BigT)
+    }
+
+    /// Keep a copy of the error from a failed [`Source::consume`], for [`Source::last_error`]
+    pub(crate) fn record_consume<T>(
+        &mut self,
+        result: Result<T, SourceError>,
+    ) -> Result<T, SourceError> {
+        if let Err(e) = &result {
+            self.last_error = Some(e.duplicate());
+        }
+        result
+    }
+
+    /// Whether the transcode has reached its maximum duration (if any)
+    pub fn max_duration_reached(&self) -> bool {
+        match self.get_max_duration_ticks() {
+            Some(limit) => self.state.running_ticks >= limit,
+            None => false,
+        }
+    }
+
     /// Close and flush the stream writer.
     /// # Errors
     /// Returns an error if the stream writer cannot be closed cleanly.
@@ -664,22 +1225,38 @@ impl<W: Write + 'static> Video<W> {
         time_spanned: f32,
         view_interval: u32,
     ) -> Result<Vec<Vec<Event>>, SourceError> {
+        if self.max_duration_reached() {
+            return Err(SourceError::BufferEmpty);
+        }
+
+        let matrix = match self.state.bayer_pattern {
+            Some(pattern) if matrix.dim().2 == 1 => demosaic(&matrix, pattern)?,
+            _ => matrix,
+        };
+
+        // let matrix_f32 = convert_u8_to_f32_simd(&matrix.into_raw_vec());
+        let matrix = match &self.state.response_lut {
+            Some(lut) => matrix.mapv(|val| lut[usize::from(val)]),
+            None => matrix.mapv(f32::from),
+        };
+
         if self.state.in_interval_count == 0 {
             self.set_initial_d(&matrix);
         }
 
         let parameters = *self.encoder.options.crf.get_parameters();
+        let intra_frame = self.intra_frame_due();
 
         self.state.in_interval_count += 1;
+        self.state.running_ticks += time_spanned as BigT;
 
         self.state.show_live = self.state.in_interval_count % view_interval == 0;
 
-        // let matrix_f32 = convert_u8_to_f32_simd(&matrix.into_raw_vec());
-        let matrix = matrix.mapv(f32::from);
-
-        // TODO: When there's full support for various bit-depth sources, modify this accordingly
-        let practical_d_max = fast_math::log2_raw(
-            255.0 * (self.state.params.delta_t_max / self.state.params.ref_time) as f32,
+        let practical_d_max = practical_d_max(
+            self.encoder.get_source_type(),
+            None,
+            self.state.params.delta_t_max,
+            self.state.params.ref_time,
         );
 
         let tpf = self.state.params.ref_time as f64;
@@ -712,6 +1289,21 @@ impl<W: Write + 'static> Video<W> {
                     .zip(matrix_chunk.iter())
                     .zip(running_chunk.iter_mut())
                 {
+                    if intra_frame {
+                        px.pop_best_events(
+                            &mut buffer,
+                            params.pixel_tree_mode,
+                            params.pixel_multi_mode,
+                            params.ref_time,
+                            *input,
+                        );
+                        buffer.push(px.pop_sync_event(
+                            *input,
+                            params.pixel_tree_mode,
+                            params.ref_time,
+                        ));
+                    }
+
                     integrate_for_px(
                         px,
                         base_val,
@@ -751,9 +1343,19 @@ impl<W: Write + 'static> Video<W> {
                 self.encoder.ingest_event(*e1)?;
             }
         }
+        self.degradation_map = self.encoder.take_degradation_map();
 
         self.display_frame_features = self.state.running_intensities.clone();
 
+        if self.state.inline_quality {
+            self.state.quality_error_sum += matrix
+                .iter()
+                .zip(self.state.running_intensities.iter())
+                .map(|(&input, &running)| (f64::from(input) - f64::from(running)).powi(2))
+                .sum::<f64>();
+            self.state.quality_sample_count += matrix.len() as u64;
+        }
+
         self.handle_features(&big_buffer)?;
 
         #[cfg(feature = "feature-logging")]
@@ -792,7 +1394,24 @@ impl<W: Write + 'static> Video<W> {
         Ok(big_buffer)
     }
 
-    fn set_initial_d(&mut self, frame: &Frame) {
+    /// Whether an intra-frame must be emitted before integrating the next input interval. See
+    /// [`EncoderOptions::intra_period`].
+    fn intra_frame_due(&mut self) -> bool {
+        match self.encoder.options.intra_period {
+            Some(period) => {
+                let periods = self.state.running_ticks / BigT::from(period);
+                if periods > self.state.intra_frame_count {
+                    self.state.intra_frame_count = periods;
+                    true
+                } else {
+                    false
+                }
+            }
+            None => false,
+        }
+    }
+
+    fn set_initial_d(&mut self, frame: &Array3<f32>) {
         self.event_pixel_trees
             .axis_chunks_iter_mut(Axis(0), self.state.chunk_rows)
             .into_par_iter()
@@ -803,13 +1422,18 @@ impl<W: Write + 'static> Video<W> {
             )
             .for_each(|(mut px, frame_chunk)| {
                 for (px, frame_val) in px.iter_mut().zip(frame_chunk.iter()) {
-                    let d_start = f32::from(*frame_val).log2().floor() as D;
+                    let d_start = frame_val.log2().floor() as D;
                     px.arena[0].set_d(d_start);
-                    px.base_val = *frame_val;
+                    px.base_val = *frame_val as u8;
                 }
             });
     }
 
+    /// Get the channel order of `running_intensities`
+    pub fn get_color_space(&self) -> ColorSpace {
+        self.state.color_space
+    }
+
     /// Get `ref_time`
     pub fn get_ref_time(&self) -> u32 {
         self.state.params.ref_time
@@ -851,21 +1475,12 @@ impl<W: Write + 'static> Video<W> {
         for px in self.event_pixel_trees.iter_mut() {
             px.c_thresh = c;
         }
-        dbg!("t1");
         self.encoder.options.crf.override_c_thresh_baseline(c)
     }
 
-    /// Set a new value for `c_thresh_neg`
-    #[deprecated(
-        since = "0.3.4",
-        note = "please use `update_crf` or `update_quality_manual` instead"
-    )]
-    pub fn update_adder_thresh_neg(&mut self, _c: u8) {
-        unimplemented!();
-        // for px in self.event_pixel_trees.iter_mut() {
-        //     px.c_thresh = c;
-        // }
-        // self.state.c_thresh_neg = c;
+    /// Set a new baseline value for `c_thresh_neg`. See [`VideoStateParams::c_thresh_neg`].
+    pub fn update_adder_thresh_neg(&mut self, c: u8) {
+        self.state.params.c_thresh_neg = Some(c);
     }
 
     pub(crate) fn handle_features(&mut self, big_buffer: &[Vec<Event>]) -> Result<(), SourceError> {
@@ -887,7 +1502,7 @@ impl<W: Write + 'static> Video<W> {
             .zip(new_features.iter_mut())
             .for_each(|((events, feature_set), new_features)| {
                 for (e1, e2) in events.iter().circular_tuple_windows() {
-                    if (e1.coord.c.is_none() || e1.coord.c == Some(0))
+                    if e1.coord.c_usize() == 0
                         && e1.coord != e2.coord
                         && (!cfg!(feature = "feature-logging-nonmaxsuppression") || e2.t != e1.t)
                         && e1.d != D_EMPTY
@@ -936,24 +1551,22 @@ impl<W: Write + 'static> Video<W> {
                     .unwrap();
             }
 
-            // Convert the running intensities to a Mat
-            let cv_type = match self.state.running_intensities.shape()[2] {
-                1 => opencv::core::CV_8UC1,
-                _ => opencv::core::CV_8UC3,
-            };
-
-            let mut cv_mat = unsafe {
+            // Convert the running intensities to a grayscale Mat
+            let gray_frame_features = crate::utils::cv::handle_color(
+                self.display_frame_features.clone(),
+                false,
+                self.state.luma_weights,
+            )?;
+            let cv_mat = unsafe {
                 let raw_parts::RawParts {
                     ptr,
                     length: _,
                     capacity: _,
-                } = raw_parts::RawParts::from_vec(
-                    self.display_frame_features.clone().into_raw_vec(),
-                ); // pixels will be move into_raw_parts，and return a manually drop pointer.
+                } = raw_parts::RawParts::from_vec(gray_frame_features.into_raw_vec()); // pixels will be move into_raw_parts，and return a manually drop pointer.
                 let mut cv_mat = opencv::core::Mat::new_rows_cols_with_data(
                     self.state.plane.h() as i32,
                     self.state.plane.w() as i32,
-                    cv_type,
+                    opencv::core::CV_8UC1,
                     ptr as *mut c_void,
                     opencv::core::Mat_AUTO_STEP,
                 )
@@ -963,11 +1576,6 @@ impl<W: Write + 'static> Video<W> {
                 cv_mat
             };
 
-            let tmp = cv_mat.clone();
-            if cv_type == opencv::core::CV_8UC3 {
-                opencv::imgproc::cvt_color(&tmp, &mut cv_mat, opencv::imgproc::COLOR_BGR2GRAY, 0)?;
-            }
-
             let start = Instant::now();
             let mut keypoints = opencv::core::Vector::<opencv::core::KeyPoint>::new();
 
@@ -1097,7 +1705,9 @@ impl<W: Write + 'static> Video<W> {
         self
     }
 
-    /// Update the CRF value and set the baseline c for all pixels
+    /// Update the CRF value and set the baseline c for all pixels.
+    ///
+    /// A CRF of 0 disables contrast thresholding, so every intensity change produces an event.
     pub(crate) fn update_crf(&mut self, crf: u8) {
         self.encoder.options.crf = Crf::new(Some(crf), self.state.plane);
         self.encoder.sync_crf();
@@ -1182,9 +1792,12 @@ pub fn integrate_for_px(
 
     *base_val = px.base_val;
 
-    if frame_val < base_val.saturating_sub(px.c_thresh)
-        || frame_val > base_val.saturating_add(px.c_thresh)
-    {
+    if px.seed_base_val(frame_val, params.seed_samples) {
+        // Still averaging the pixel's initial values
+    } else if px.exceeds_c_thresh(
+        frame_val,
+        params.c_thresh_neg_offset(parameters.c_thresh_baseline),
+    ) {
         let _tmp = buffer.len();
         px.pop_best_events(
             buffer,
@@ -1205,8 +1818,12 @@ pub fn integrate_for_px(
         }
     }
 
+    let integrated = match params.integration_rule {
+        IntegrationRule::Rectangular => intensity,
+        IntegrationRule::Trapezoidal => px.trapezoid_intensity(intensity, time_spanned),
+    };
     px.integrate(
-        intensity,
+        integrated,
         time_spanned,
         params.pixel_tree_mode,
         params.delta_t_max,
@@ -1221,6 +1838,13 @@ pub fn integrate_for_px(
         grew_buffer = true;
     }
 
+    if params.clamp_delta_t_max {
+        if let Some(event) = px.pop_forced_empty_event(params.pixel_tree_mode, params.ref_time) {
+            buffer.push(event);
+            grew_buffer = true;
+        }
+    }
+
     // if buffer.len() - start_len > 5 {
     //     dbg!("hm", buffer.len() - start_len);
     // }
@@ -1289,6 +1913,38 @@ pub trait Source<W: Write> {
         thread_pool: &ThreadPool,
     ) -> Result<Vec<Vec<Event>>, SourceError>;
 
+    /// Like [`Source::consume`], but waits up to `timeout` for new input from a live source,
+    /// rather than immediately returning [`SourceError::Open`] or [`SourceError::NoData`] when
+    /// none is available. Between attempts, the thread sleeps for a backoff which grows up to
+    /// 10 ms, so callers don't need to busy-loop.
+    ///
+    /// # Errors
+    /// * [`SourceError::Open`] or [`SourceError::NoData`] if no input arrived within `timeout`
+    /// * Any other error from [`Source::consume`], immediately
+    fn consume_blocking(
+        &mut self,
+        view_interval: u32,
+        timeout: Duration,
+        thread_pool: &ThreadPool,
+    ) -> Result<Vec<Vec<Event>>, SourceError> {
+        const MAX_BACKOFF: Duration = Duration::from_millis(10);
+        let deadline = Instant::now() + timeout;
+        let mut backoff = Duration::from_micros(100);
+        loop {
+            match self.consume(view_interval, thread_pool) {
+                Err(SourceError::Open | SourceError::NoData) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(SourceError::NoData);
+                    }
+                    std::thread::sleep(backoff.min(deadline - now));
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// Set the Constant Rate Factor (CRF) quality setting for the encoder. 0 is lossless, 9 is worst quality.
     fn crf(&mut self, crf: u8);
 
@@ -1304,8 +1960,62 @@ pub trait Source<W: Write> {
 
     fn get_input(&self) -> Option<&Frame>;
 
+    /// Get the channel order of the frame returned by [`Source::get_input`]
+    fn get_input_color_space(&self) -> ColorSpace {
+        ColorSpace::Bgr
+    }
+
     /// Get the last-calculated bitrate of the input (in bits per second)
     fn get_running_input_bitrate(&self) -> f64;
+
+    /// Get the error from the most recent [`Source::consume`] call that failed, if any. It is
+    /// kept after later successful calls, so a host can inspect why the transcode stopped even
+    /// after the returned error has been handled.
+    fn last_error(&self) -> Option<&SourceError> {
+        self.get_video_ref().last_error.as_ref()
+    }
+}
+
+/// Transcode `source` to the end of its input, handing each interval's events to `sink` as soon
+/// as they are produced, rather than collecting the whole stream first. Any encoder set up with
+/// [`Video::write_out`] still receives the same events.
+///
+/// The end of the input is signaled by [`SourceError::BufferEmpty`], or by a
+/// [`SourceError::VideoError`] for a decoder which has run out of frames. These are not treated
+/// as failures.
+///
+/// Returns the number of intervals consumed.
+///
+/// # Errors
+/// Any other error from [`Source::consume`], including other decoder errors
+pub fn transcode_streaming<W, S, F>(
+    source: &mut S,
+    view_interval: u32,
+    thread_pool: &ThreadPool,
+    mut sink: F,
+) -> Result<u64, SourceError>
+where
+    W: Write,
+    S: Source<W> + ?Sized,
+    F: FnMut(&[Event]),
+{
+    let mut intervals = 0;
+    loop {
+        match source.consume(view_interval, thread_pool) {
+            Ok(events) => {
+                sink(&events.concat());
+                intervals += 1;
+            }
+            Err(
+                SourceError::BufferEmpty
+                | SourceError::VideoError(
+                    video_rs_adder_dep::Error::ReadExhausted
+                    | video_rs_adder_dep::Error::DecodeExhausted,
+                ),
+            ) => return Ok(intervals),
+            Err(e) => return Err(e),
+        }
+    }
 }
 
 // fn convert_u8_to_f32_simd(input: &[u8]) -> Vec<f32> {
@@ -1325,3 +2035,1044 @@ pub trait Source<W: Write> {
 //
 //     result
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adder_codec_core::codec::decoder::Decoder;
+    use adder_codec_core::codec::raw::stream::RawInput;
+    use adder_codec_core::Mode::FramePerfect;
+    use bitstream_io::{BigEndian, BitReader};
+    use std::io::Cursor;
+    use std::net::{TcpListener, TcpStream};
+
+    #[test]
+    fn test_max_duration() {
+        let plane = PlaneSize::new(4, 4, 1).unwrap();
+        let source_fps = 30;
+        let ref_time = 255;
+        let tps = ref_time * source_fps;
+        let delta_t_max = ref_time * 4;
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(tps, ref_time, delta_t_max, Some(TimeMode::AbsoluteT))
+            .unwrap()
+            .write_out(
+                Some(SourceCamera::FramedU8),
+                Some(TimeMode::AbsoluteT),
+                None,
+                None,
+                EncoderType::Raw,
+                EncoderOptions::default(plane),
+                Vec::new(),
+            )
+            .unwrap()
+            .max_duration(2.0);
+
+        // A synthetic 10-second source
+        let mut frames_integrated = 0;
+        for i in 0..10 * source_fps {
+            let frame = Frame::from_elem((4, 4, 1), (100 + (i % 2) * 50) as u8);
+            match video.integrate_matrix(frame, ref_time as f32, 1) {
+                Ok(_) => frames_integrated += 1,
+                Err(SourceError::BufferEmpty) => break,
+                Err(e) => panic!("unexpected error: {e}"),
+            }
+        }
+        assert_eq!(frames_integrated, 2 * source_fps);
+        assert!(video.max_duration_reached());
+
+        let limit = video.get_max_duration_ticks().unwrap();
+        let bytes = video.end_write_stream().unwrap().unwrap();
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut max_t = 0;
+        while let Ok(event) = reader.digest_event(&mut bitreader) {
+            max_t = max_t.max(event.t);
+        }
+        assert!(BigT::from(max_t) <= limit);
+        assert!(BigT::from(max_t) > limit - BigT::from(delta_t_max));
+    }
+
+    #[test]
+    fn test_bayer_rggb_channels() {
+        use crate::utils::cv::{demosaic, BayerPattern};
+
+        let pattern = BayerPattern::Rggb;
+        assert_eq!(pattern.channel_at(0, 0), 0);
+        assert_eq!(pattern.channel_at(1, 0), 1);
+        assert_eq!(pattern.channel_at(0, 1), 1);
+        assert_eq!(pattern.channel_at(1, 1), 2);
+
+        // Every sample lands in the channel its filter passes
+        let mosaic = Frame::from_shape_fn((4, 4, 1), |(y, x, _)| (y * 4 + x) as u8 * 10);
+        let rgb = demosaic(&mosaic, pattern).unwrap();
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(rgb[[y, x, pattern.channel_at(x, y)]], mosaic[[y, x, 0]]);
+            }
+        }
+
+        // Transcode a flat red/green/blue scene captured through the RGGB filter
+        let colors = [200_u8, 100, 50];
+        let mosaic = Frame::from_shape_fn((4, 4, 1), |(y, x, _)| colors[pattern.channel_at(x, y)]);
+        let plane = PlaneSize::new(4, 4, 3).unwrap();
+        let ref_time = 255;
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(
+                ref_time * 30,
+                ref_time,
+                ref_time * 4,
+                Some(TimeMode::DeltaT),
+            )
+            .unwrap()
+            .bayer_pattern(pattern)
+            .unwrap();
+        for _ in 0..3 {
+            video
+                .integrate_matrix(mosaic.clone(), ref_time as f32, 1)
+                .unwrap();
+        }
+
+        for ((_, _, c), &val) in video.state.running_intensities.indexed_iter() {
+            assert!(
+                (i16::from(val) - i16::from(colors[c])).abs() <= 1,
+                "channel {c}: {val}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_bayer_pattern_needs_color_plane() {
+        use crate::utils::cv::BayerPattern;
+
+        let video: Video<Vec<u8>> =
+            Video::new(PlaneSize::new(4, 4, 1).unwrap(), FramePerfect, None).unwrap();
+        assert!(matches!(
+            video.bayer_pattern(BayerPattern::Rggb),
+            Err(SourceError::BadParams(_))
+        ));
+    }
+
+    #[test]
+    fn test_scale_filter_checkerboard() {
+        use crate::utils::cv::{scale_frame, ScaleFilter};
+
+        // A one-pixel checkerboard whose phase flips every frame, as with slight camera shake
+        let checkerboard = |phase: usize| {
+            Frame::from_shape_fn((8, 8, 1), |(y, x, _)| ((x + y + phase) % 2) as u8 * 255)
+        };
+
+        let count_events = |filter: ScaleFilter| -> usize {
+            let plane = PlaneSize::new(4, 4, 1).unwrap();
+            let ref_time = 255;
+            let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+                .unwrap()
+                .time_parameters(
+                    ref_time * 30,
+                    ref_time,
+                    ref_time * 4,
+                    Some(TimeMode::DeltaT),
+                )
+                .unwrap();
+            (0..8)
+                .map(|phase| {
+                    let frame = scale_frame(&checkerboard(phase), 4, 4, filter);
+                    video
+                        .integrate_matrix(frame, ref_time as f32, 1)
+                        .unwrap()
+                        .iter()
+                        .map(Vec::len)
+                        .sum::<usize>()
+                })
+                .sum()
+        };
+
+        // Area averaging sees a steady mid-gray, but nearest-neighbor aliases the pattern into
+        // whole-frame flicker
+        assert_eq!(
+            scale_frame(&checkerboard(0), 4, 4, ScaleFilter::Area),
+            Frame::from_elem((4, 4, 1), 128)
+        );
+        assert!(count_events(ScaleFilter::Area) < count_events(ScaleFilter::Nearest));
+    }
+
+    #[test]
+    fn test_asymmetric_c_thresh() {
+        let crf_parameters = CrfParameters {
+            c_thresh_baseline: 10,
+            c_thresh_max: 10,
+            c_increase_velocity: 1,
+            feature_c_radius: 0,
+        };
+
+        // Returns whether stepping the input from `from` to `to` exceeds the contrast threshold
+        let edge_fires = |c_thresh_neg: Option<u8>, from: u8, to: u8| -> bool {
+            let params = VideoStateParams {
+                pixel_tree_mode: FramePerfect,
+                c_thresh_neg,
+                ..Default::default()
+            };
+            let mut px = PixelArena::new(
+                f32::from(from),
+                Coord {
+                    x: 0,
+                    y: 0,
+                    c: None,
+                },
+            );
+            px.c_thresh = crf_parameters.c_thresh_baseline;
+            let mut buffer = Vec::new();
+            let mut base_val = 0;
+            for val in [from, from, to] {
+                integrate_for_px(
+                    &mut px,
+                    &mut base_val,
+                    val,
+                    f32::from(val),
+                    255.0,
+                    &mut buffer,
+                    &params,
+                    &crf_parameters,
+                );
+            }
+            px.base_val == to
+        };
+
+        // Symmetric thresholds
+        assert!(edge_fires(None, 100, 115));
+        assert!(edge_fires(None, 100, 85));
+
+        // A less sensitive negative threshold needs a bigger falling edge
+        assert!(edge_fires(Some(30), 100, 115));
+        assert!(!edge_fires(Some(30), 100, 85));
+        assert!(edge_fires(Some(30), 100, 65));
+
+        // A more sensitive negative threshold fires on a smaller falling edge
+        assert!(edge_fires(Some(2), 100, 95));
+        assert!(!edge_fires(Some(2), 100, 105));
+    }
+
+    #[test]
+    fn test_seed_samples() {
+        let crf_parameters = CrfParameters {
+            c_thresh_baseline: 10,
+            c_thresh_max: 10,
+            c_increase_velocity: 1,
+            feature_c_radius: 0,
+        };
+
+        // Returns the number of events fired for a pixel which is noisy for its first few
+        // values, then settles
+        let startup_events = |seed_samples: u8| -> usize {
+            let params = VideoStateParams {
+                pixel_tree_mode: FramePerfect,
+                seed_samples,
+                ..Default::default()
+            };
+            let mut px = PixelArena::new(
+                100.0,
+                Coord {
+                    x: 0,
+                    y: 0,
+                    c: None,
+                },
+            );
+            px.c_thresh = crf_parameters.c_thresh_baseline;
+            let mut buffer = Vec::new();
+            let mut base_val = 0;
+            for val in [100, 118, 84, 112, 102, 102, 102, 102] {
+                integrate_for_px(
+                    &mut px,
+                    &mut base_val,
+                    val,
+                    f32::from(val),
+                    255.0,
+                    &mut buffer,
+                    &params,
+                    &crf_parameters,
+                );
+            }
+            buffer.len()
+        };
+
+        let single = startup_events(1);
+        let seeded = startup_events(4);
+        assert!(single > 0);
+        assert!(seeded < single);
+    }
+
+    #[test]
+    fn test_integration_rule() {
+        // Never pop, so that the main node accumulates everything
+        let crf_parameters = CrfParameters {
+            c_thresh_baseline: 255,
+            c_thresh_max: 255,
+            c_increase_velocity: 1,
+            feature_c_radius: 0,
+        };
+
+        // A linear intensity ramp, sampled at the end of each interval
+        let samples: Vec<u8> = (0..=20).map(|k| 10 + 5 * k).collect();
+        let accumulated = |integration_rule: IntegrationRule| -> f32 {
+            let params = VideoStateParams {
+                pixel_tree_mode: FramePerfect,
+                delta_t_max: 255 * 1000,
+                integration_rule,
+                ..Default::default()
+            };
+            let mut px = PixelArena::new(
+                f32::from(samples[0]),
+                Coord {
+                    x: 0,
+                    y: 0,
+                    c: None,
+                },
+            );
+            px.c_thresh = crf_parameters.c_thresh_baseline;
+            let mut buffer = Vec::new();
+            let mut base_val = 0;
+            for &val in &samples {
+                integrate_for_px(
+                    &mut px,
+                    &mut base_val,
+                    val,
+                    f32::from(val),
+                    255.0,
+                    &mut buffer,
+                    &params,
+                    &crf_parameters,
+                );
+            }
+            px.arena[0].integration()
+        };
+
+        // The exact integral of the ramp over each interval is the mean of its end points
+        let exact: f32 = samples.iter().map(|&val| f32::from(val) - 2.5).sum();
+        let rectangular_error = (accumulated(IntegrationRule::Rectangular) - exact).abs();
+        let trapezoidal_error = (accumulated(IntegrationRule::Trapezoidal) - exact).abs();
+        assert!(trapezoidal_error < rectangular_error);
+        // Only the first interval, with no previous sample, is off
+        assert!((trapezoidal_error - 2.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_debug_state_gradient() {
+        let plane = PlaneSize::new(8, 1, 1).unwrap();
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None).unwrap();
+
+        // Each column is about twice as bright as the one before it
+        let frame = Frame::from_shape_fn((1, 8, 1), |(_, x, _)| ((2_u16 << x) - 1) as u8);
+        let ref_time = video.get_ref_time();
+        video.integrate_matrix(frame, ref_time as f32, 1).unwrap();
+
+        let (d, delta_t) = video.debug_state();
+        assert_eq!(d.dim(), (1, 8));
+        assert_eq!(delta_t.dim(), (1, 8));
+        for x in 1..8 {
+            assert!(d[[0, x]] > d[[0, x - 1]], "{d:?}");
+        }
+        assert!(delta_t.iter().all(|&t| t == delta_t[[0, 0]]), "{delta_t:?}");
+    }
+
+    #[test]
+    fn test_save_load_state_resumes() {
+        let plane = PlaneSize::new(4, 3, 1).unwrap();
+        let new_video = || -> Video<Vec<u8>> {
+            Video::new(plane, FramePerfect, None)
+                .unwrap()
+                .time_parameters(255 * 30, 255, 255 * 120, Some(TimeMode::AbsoluteT))
+                .unwrap()
+        };
+        let frame = |i: usize| {
+            Frame::from_shape_fn((3, 4, 1), |(y, x, _)| {
+                (40 + 17 * ((i / 3 + y * 4 + x) % 9)) as u8
+            })
+        };
+        let transcode = |video: &mut Video<Vec<u8>>, frames: std::ops::Range<usize>| {
+            let mut events = Vec::new();
+            for i in frames {
+                let ref_time = video.get_ref_time() as f32;
+                for chunk in video.integrate_matrix(frame(i), ref_time, 1).unwrap() {
+                    events.extend(chunk);
+                }
+            }
+            events
+        };
+
+        let mut uninterrupted = new_video();
+        let expected = transcode(&mut uninterrupted, 0..20);
+
+        let mut first = new_video();
+        let mut resumed_events = transcode(&mut first, 0..10);
+        let mut checkpoint = Vec::new();
+        first.save_state(&mut checkpoint).unwrap();
+        drop(first);
+
+        let mut second = new_video();
+        second.load_state(&mut Cursor::new(checkpoint)).unwrap();
+        resumed_events.extend(transcode(&mut second, 10..20));
+
+        assert!(!expected.is_empty());
+        assert_eq!(resumed_events, expected);
+        assert_eq!(
+            second.state.running_ticks,
+            uninterrupted.state.running_ticks
+        );
+
+        // A checkpoint can't be loaded into a video of a different size
+        let mut checkpoint = Vec::new();
+        second.save_state(&mut checkpoint).unwrap();
+        let mut other: Video<Vec<u8>> =
+            Video::new(PlaneSize::new(3, 3, 1).unwrap(), FramePerfect, None).unwrap();
+        assert!(other.load_state(&mut Cursor::new(checkpoint)).is_err());
+    }
+
+    #[test]
+    fn test_rolling_psnr_matches_offline() {
+        use crate::framer::reconstruct::reconstruct_frames;
+
+        let plane = PlaneSize::new(4, 3, 1).unwrap();
+        let ref_time = 255;
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(
+                ref_time * 30,
+                ref_time,
+                ref_time * 4,
+                Some(TimeMode::AbsoluteT),
+            )
+            .unwrap()
+            .inline_quality(true)
+            .write_out(
+                Some(SourceCamera::FramedU8),
+                Some(TimeMode::AbsoluteT),
+                None,
+                None,
+                EncoderType::Raw,
+                EncoderOptions::default(plane),
+                Vec::new(),
+            )
+            .unwrap();
+        assert!(video.rolling_psnr().is_nan());
+
+        // Each pixel holds a distinct intensity, which steps up halfway through
+        let source = |i: usize| {
+            Frame::from_shape_fn((3, 4, 1), |(y, x, _)| {
+                (30 + 15 * (y * 4 + x) + if i < 20 { 0 } else { 30 }) as u8
+            })
+        };
+        for i in 0..40 {
+            video
+                .integrate_matrix(source(i), ref_time as f32, 1)
+                .unwrap();
+        }
+        let inline_psnr = video.rolling_psnr();
+        let bytes = video.end_write_stream().unwrap().unwrap();
+
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let frames = reconstruct_frames(&mut reader, &mut bitreader, 30.0).unwrap();
+        assert!(!frames.is_empty());
+        let mut error_sum = 0.0;
+        for (i, frame) in frames.iter().enumerate() {
+            error_sum += frame
+                .iter()
+                .zip(source(i).iter())
+                .map(|(&a, &b)| (f64::from(a) - f64::from(b)).powi(2))
+                .sum::<f64>();
+        }
+        let offline_mse = (error_sum / (frames.len() * plane.volume()) as f64).max(0.0000001);
+
+        // Both reconstructions are near-lossless, where small absolute differences in error
+        // make for large differences in PSNR, so compare their MSEs instead
+        let inline_mse = 10.0_f64.powf((20.0 * 255.0_f64.log10() - inline_psnr) / 10.0);
+        assert!(
+            (inline_mse - offline_mse).abs() <= 1.0 + 0.5 * offline_mse,
+            "inline MSE {inline_mse}, offline MSE {offline_mse}"
+        );
+    }
+
+    #[test]
+    fn test_degradation_map() {
+        use adder_codec_core::codec::EventDrop;
+        use std::num::NonZeroU16;
+
+        let plane = PlaneSize::new(8, 8, 1).unwrap();
+        let ref_time = 255;
+        let mut options =
+            EncoderOptions::default(plane).degradation_map(NonZeroU16::new(4).unwrap());
+        options.event_drop = EventDrop::Budget {
+            events_per_interval: 2,
+        };
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(
+                ref_time * 30,
+                ref_time,
+                ref_time * 4,
+                Some(TimeMode::AbsoluteT),
+            )
+            .unwrap()
+            .write_out(
+                Some(SourceCamera::FramedU8),
+                Some(TimeMode::AbsoluteT),
+                None,
+                None,
+                EncoderType::Raw,
+                options,
+                Vec::new(),
+            )
+            .unwrap();
+
+        // The left half flickers, so it fires far more events than the steady right half, and
+        // so has far more of them dropped
+        let mut dropped = Array2::<u32>::zeros((2, 2));
+        for i in 0..20 {
+            let frame = Frame::from_shape_fn((8, 8, 1), |(_, x, _)| match (x < 4, i % 2) {
+                (true, 0) => 50,
+                (true, _) => 200,
+                (false, _) => 100,
+            });
+            video.integrate_matrix(frame, ref_time as f32, 1).unwrap();
+            dropped += video.degradation_map().unwrap();
+        }
+        let left = dropped.column(0).sum();
+        let right = dropped.column(1).sum();
+        assert!(left > 0);
+        assert!(left > right, "left {left}, right {right}");
+    }
+
+    #[test]
+    fn test_d_max() {
+        let plane = PlaneSize::new(2, 2, 1).unwrap();
+        let ref_time = 255;
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(
+                ref_time * 30,
+                ref_time,
+                ref_time * 120,
+                Some(TimeMode::AbsoluteT),
+            )
+            .unwrap()
+            .d_max(12);
+        assert_eq!(video.get_d_config().d_max, 12);
+
+        // A static, bright scene integrates up to d = 14 before reaching delta_t_max, so without
+        // the cap its events would exceed d = 12
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            let frame = Frame::from_elem((2, 2, 1), 255);
+            for chunk in video.integrate_matrix(frame, ref_time as f32, 1).unwrap() {
+                events.extend(chunk);
+            }
+        }
+        assert!(!events.is_empty());
+        assert!(events
+            .iter()
+            .all(|event| event.d <= 12 || event.d == D_EMPTY));
+        assert!(events.iter().any(|event| event.d == 12));
+    }
+
+    #[test]
+    fn test_intra_period_join_mid_stream() {
+        use adder_codec_core::{D_EMPTY, D_SHIFT};
+
+        let plane = PlaneSize::new(4, 4, 1).unwrap();
+        let ref_time = 255;
+        let intra_period = ref_time * 10;
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(
+                ref_time * 30,
+                ref_time,
+                ref_time * 120,
+                Some(TimeMode::AbsoluteT),
+            )
+            .unwrap()
+            .write_out(
+                Some(SourceCamera::FramedU8),
+                Some(TimeMode::AbsoluteT),
+                None,
+                None,
+                EncoderType::Raw,
+                EncoderOptions::default(plane)
+                    .intra_period(intra_period)
+                    .unwrap(),
+                Vec::new(),
+            )
+            .unwrap();
+
+        // Each pixel holds a distinct intensity, until they all change at frame 25
+        let original = |y: usize, x: usize| -> u8 { (50 + 10 * (y * 4 + x)) as u8 };
+        let mut events_before_second_intra = 0;
+        for i in 0..30 {
+            let frame = Frame::from_shape_fn(
+                (4, 4, 1),
+                |(y, x, _)| {
+                    if i < 25 {
+                        original(y, x)
+                    } else {
+                        10
+                    }
+                },
+            );
+            let events = video.integrate_matrix(frame, ref_time as f32, 1).unwrap();
+            if i < 20 {
+                events_before_second_intra += events.iter().map(Vec::len).sum::<usize>();
+            }
+        }
+        let bytes = video.end_write_stream().unwrap().unwrap();
+
+        // Join the stream at the second intra-frame
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let meta = *reader.meta();
+        let join_position = meta.header_size as u64
+            + events_before_second_intra as u64 * u64::from(meta.event_size);
+        reader
+            .set_input_stream_position(&mut bitreader, join_position)
+            .unwrap();
+
+        let mut baselines: Array3<Option<DeltaT>> = Array3::default((4, 4, 1));
+        let mut reconstructed: Array3<Option<f64>> = Array3::default((4, 4, 1));
+        while let Ok(event) = reader.digest_event(&mut bitreader) {
+            let idx = [event.coord.y_usize(), event.coord.x_usize(), 0];
+            if event.d == D_EMPTY {
+                assert_eq!(event.t, 20 * ref_time);
+                baselines[idx] = Some(event.t);
+            } else if let (Some(baseline), None) = (baselines[idx], reconstructed[idx]) {
+                reconstructed[idx] = Some(
+                    D_SHIFT[event.d as usize] as f64 / f64::from(event.t - baseline)
+                        * f64::from(ref_time),
+                );
+            }
+        }
+
+        for ((y, x, _), intensity) in reconstructed.indexed_iter() {
+            let intensity = intensity.expect("every pixel syncs within one period");
+            assert!(
+                (intensity - f64::from(original(y, x))).abs() <= 1.0,
+                "({x}, {y}): {intensity}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_delta_t_static_pixels() {
+        let plane = PlaneSize::new(4, 4, 1).unwrap();
+        let ref_time = 255;
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(
+                ref_time * 30,
+                ref_time,
+                ref_time * 4,
+                Some(TimeMode::DeltaT),
+            )
+            .unwrap()
+            .write_out(
+                Some(SourceCamera::FramedU8),
+                Some(TimeMode::DeltaT),
+                None,
+                None,
+                EncoderType::Raw,
+                EncoderOptions::default(plane)
+                    .intra_period(ref_time * 10)
+                    .unwrap(),
+                Vec::new(),
+            )
+            .unwrap();
+
+        // A static scene, so every pixel reaches delta_t_max and then keeps integrating, with
+        // intra-frame empty events spanning each pixel's running time
+        let mut event_count = 0;
+        for _ in 0..40 {
+            let frame = Frame::from_elem((4, 4, 1), 100);
+            let events = video.integrate_matrix(frame, ref_time as f32, 1).unwrap();
+            event_count += events.iter().map(Vec::len).sum::<usize>();
+        }
+        let bytes = video.end_write_stream().unwrap().unwrap();
+
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut decoded = 0;
+        loop {
+            match reader.digest_event(&mut bitreader) {
+                Ok(_) => decoded += 1,
+                Err(CodecError::Eof) => break,
+                Err(e) => panic!("{e:?}"),
+            }
+        }
+        assert!(event_count > 0);
+        assert_eq!(decoded, event_count);
+    }
+
+    /// Transcode a synthetic source at the given CRF, returning the number of events emitted and
+    /// the mean PSNR of the running reconstruction against the input frames
+    fn transcode_at_crf(crf: u8) -> (usize, f64) {
+        use crate::utils::cv::{calculate_quality_metrics, QualityMetrics};
+
+        let plane = PlaneSize::new(16, 16, 1).unwrap();
+        let ref_time = 255;
+        let tps = ref_time * 30;
+        let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(tps, ref_time, ref_time * 60, Some(TimeMode::AbsoluteT))
+            .unwrap();
+        video.update_crf(crf);
+
+        let mut event_count = 0;
+        let mut psnr_sum = 0.0;
+        let num_frames = 120;
+        for i in 0..num_frames {
+            // Small intensity steps which fall under the thresholds of the mid CRF levels
+            let frame = Frame::from_shape_fn((16, 16, 1), |(y, x, _)| {
+                (100 + (x + y) as u32 + ((i / 10) % 2) * 4) as u8
+            });
+            let events = video
+                .integrate_matrix(frame.clone(), ref_time as f32, 1)
+                .unwrap();
+            event_count += events.iter().map(Vec::len).sum::<usize>();
+
+            let metrics = calculate_quality_metrics(
+                &frame,
+                &video.state.running_intensities,
+                QualityMetrics {
+                    psnr: Some(0.0),
+                    mse: None,
+                    ssim: None,
+                },
+            )
+            .unwrap();
+            psnr_sum += metrics.psnr.unwrap();
+        }
+        (event_count, psnr_sum / f64::from(num_frames))
+    }
+
+    #[test]
+    fn test_crf_lossless_quality() {
+        let (lossless_events, lossless_psnr) = transcode_at_crf(0);
+        let (mid_events, mid_psnr) = transcode_at_crf(5);
+        assert!(lossless_events > mid_events);
+        assert!(lossless_psnr > mid_psnr);
+    }
+
+    #[test]
+    fn test_luma_weights_green() {
+        use crate::utils::cv::{handle_color, LumaWeights};
+
+        let green = Frame::from_shape_vec((1, 1, 3), vec![0, 255, 0]).unwrap();
+        let rec_601 = handle_color(green.clone(), false, LumaWeights::default()).unwrap();
+        let rec_709 = handle_color(green, false, LumaWeights::REC_709).unwrap();
+        assert_eq!(rec_601.dim(), (1, 1, 1));
+        assert_eq!(rec_601[[0, 0, 0]], 149);
+        assert_eq!(rec_709[[0, 0, 0]], 182);
+    }
+
+    #[test]
+    fn test_response_lut_linearizes() {
+        let gamma = 2.2_f32;
+        let linear = [16.0_f32, 64.0, 128.0, 224.0];
+        // The camera encodes linear light with a gamma curve, and the LUT inverts it
+        let frame = Frame::from_shape_fn((1, 4, 1), |(_, x, _)| {
+            (255.0 * (linear[x] / 255.0).powf(1.0 / gamma)).round() as u8
+        });
+        let mut lut = [0.0; 256];
+        for (val, entry) in lut.iter_mut().enumerate() {
+            *entry = 255.0 * (val as f32 / 255.0).powf(gamma);
+        }
+
+        let transcode = |lut: Option<[f32; 256]>| -> Array3<u8> {
+            let plane = PlaneSize::new(4, 1, 1).unwrap();
+            let ref_time = 255;
+            let mut video: Video<Vec<u8>> = Video::new(plane, FramePerfect, None)
+                .unwrap()
+                .time_parameters(
+                    ref_time * 30,
+                    ref_time,
+                    ref_time * 4,
+                    Some(TimeMode::DeltaT),
+                )
+                .unwrap();
+            if let Some(lut) = lut {
+                video = video.response_lut(lut);
+            }
+            for _ in 0..4 {
+                video
+                    .integrate_matrix(frame.clone(), ref_time as f32, 1)
+                    .unwrap();
+            }
+            video.state.running_intensities.clone()
+        };
+
+        let linearized = transcode(Some(lut));
+        for (x, &val) in linearized.iter().enumerate() {
+            assert!(
+                (f32::from(val) - linear[x]).abs() <= 2.0,
+                "pixel {x}: expected {}, got {val}",
+                linear[x]
+            );
+        }
+
+        // Without the LUT, the dark pixel reconstructs at its gamma-encoded value
+        let encoded = transcode(None);
+        assert!(f32::from(encoded[[0, 0, 0]]) > 2.0 * linear[0]);
+    }
+
+    #[test]
+    fn test_t_fraction_bits_header() {
+        let plane = PlaneSize::new(4, 4, 1).unwrap();
+        let ref_time = 255;
+        let new_video = |bits: u8| {
+            Video::<Vec<u8>>::new(plane, FramePerfect, None)
+                .unwrap()
+                .time_parameters(
+                    ref_time * 30,
+                    ref_time,
+                    ref_time * 4,
+                    Some(TimeMode::DeltaT),
+                )
+                .unwrap()
+                .t_fraction_bits(bits)
+                .write_out(
+                    Some(SourceCamera::FramedU8),
+                    Some(TimeMode::DeltaT),
+                    None,
+                    None,
+                    EncoderType::Raw,
+                    EncoderOptions::default(plane),
+                    Vec::new(),
+                )
+        };
+
+        let mut video = new_video(8).unwrap();
+        for i in 0..8 {
+            let frame = Frame::from_elem((4, 4, 1), (100 + (i % 2) * 50) as u8);
+            video.integrate_matrix(frame, ref_time as f32, 1).unwrap();
+        }
+        let bytes = video.end_write_stream().unwrap().unwrap();
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let meta = reader.meta();
+        assert_eq!(meta.t_fraction_bits, 8);
+        assert_eq!(meta.tps, ref_time * 30 * 256);
+        assert_eq!(meta.ref_interval, ref_time * 256);
+        assert_eq!(meta.delta_t_max, ref_time * 4 * 256);
+
+        // The scaled time parameters no longer fit in a timestamp
+        assert!(new_video(24).is_err());
+    }
+
+    /// A live source reading from a socket. Each byte received becomes an event with that `d`.
+    struct SocketSource {
+        video: Video<Vec<u8>>,
+        stream: TcpStream,
+    }
+
+    impl Source<Vec<u8>> for SocketSource {
+        fn consume(
+            &mut self,
+            _view_interval: u32,
+            _thread_pool: &ThreadPool,
+        ) -> Result<Vec<Vec<Event>>, SourceError> {
+            let mut buf = [0; 64];
+            match self.stream.read(&mut buf) {
+                Ok(0) => Err(SourceError::BufferEmpty),
+                Ok(len) => Ok(vec![buf[..len]
+                    .iter()
+                    .map(|&d| Event {
+                        coord: Coord {
+                            x: 0,
+                            y: 0,
+                            c: None,
+                        },
+                        d,
+                        t: 255,
+                    })
+                    .collect()]),
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Err(SourceError::NoData),
+                Err(_) => Err(SourceError::BufferChannelClosed),
+            }
+        }
+
+        fn crf(&mut self, crf: u8) {
+            self.video.update_crf(crf);
+        }
+
+        fn get_video_mut(&mut self) -> &mut Video<Vec<u8>> {
+            &mut self.video
+        }
+
+        fn get_video_ref(&self) -> &Video<Vec<u8>> {
+            &self.video
+        }
+
+        fn get_video(self) -> Video<Vec<u8>> {
+            self.video
+        }
+
+        fn get_input(&self) -> Option<&Frame> {
+            None
+        }
+
+        fn get_running_input_bitrate(&self) -> f64 {
+            0.0
+        }
+    }
+
+    #[test]
+    fn test_consume_blocking() {
+        let plane = PlaneSize::new(4, 4, 1).unwrap();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let connect = || {
+            let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            stream.set_nonblocking(true).unwrap();
+            let (server, _) = listener.accept().unwrap();
+            let source = SocketSource {
+                video: Video::new(plane, FramePerfect, None).unwrap(),
+                stream,
+            };
+            (source, server)
+        };
+        let delay = Duration::from_millis(50);
+
+        // The events are sent only after a delay
+        let (mut source, mut server) = connect();
+        let start = Instant::now();
+        let sender = std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            server.write_all(&[7]).unwrap();
+            server
+        });
+        let events = source
+            .consume_blocking(1, Duration::from_secs(1), &pool)
+            .unwrap();
+        assert!(start.elapsed() >= delay);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0][0].d, 7);
+
+        // The end of the input is reported straight away, rather than waited out
+        drop(sender.join().unwrap());
+        let start = Instant::now();
+        assert!(matches!(
+            source.consume_blocking(1, Duration::from_secs(60), &pool),
+            Err(SourceError::BufferEmpty)
+        ));
+        assert!(start.elapsed() < Duration::from_secs(60));
+
+        // Times out before the source has anything to give
+        let (mut source, _server) = connect();
+        let start = Instant::now();
+        assert!(matches!(
+            source.consume_blocking(1, Duration::from_millis(20), &pool),
+            Err(SourceError::NoData)
+        ));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert!(start.elapsed() < Duration::from_secs(60));
+    }
+
+    /// A source which integrates a fixed list of frames, then runs out
+    struct FrameListSource {
+        video: Video<Vec<u8>>,
+        frames: Vec<Frame>,
+    }
+
+    impl Source<Vec<u8>> for FrameListSource {
+        fn consume(
+            &mut self,
+            view_interval: u32,
+            _thread_pool: &ThreadPool,
+        ) -> Result<Vec<Vec<Event>>, SourceError> {
+            if self.frames.is_empty() {
+                return Err(SourceError::BufferEmpty);
+            }
+            let frame = self.frames.remove(0);
+            let ref_time = self.video.state.params.ref_time as f32;
+            self.video.integrate_matrix(frame, ref_time, view_interval)
+        }
+
+        fn crf(&mut self, crf: u8) {
+            self.video.update_crf(crf);
+        }
+
+        fn get_video_mut(&mut self) -> &mut Video<Vec<u8>> {
+            &mut self.video
+        }
+
+        fn get_video_ref(&self) -> &Video<Vec<u8>> {
+            &self.video
+        }
+
+        fn get_video(self) -> Video<Vec<u8>> {
+            self.video
+        }
+
+        fn get_input(&self) -> Option<&Frame> {
+            None
+        }
+
+        fn get_running_input_bitrate(&self) -> f64 {
+            0.0
+        }
+    }
+
+    #[test]
+    fn test_transcode_streaming() {
+        let plane = PlaneSize::new(8, 8, 1).unwrap();
+        let ref_time = 255;
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let video = Video::new(plane, FramePerfect, None)
+            .unwrap()
+            .time_parameters(
+                ref_time * 30,
+                ref_time,
+                ref_time * 4,
+                Some(TimeMode::AbsoluteT),
+            )
+            .unwrap()
+            .write_out(
+                Some(SourceCamera::FramedU8),
+                Some(TimeMode::AbsoluteT),
+                None,
+                None,
+                EncoderType::Raw,
+                EncoderOptions::default(plane),
+                Vec::new(),
+            )
+            .unwrap();
+        let frames = (0..20)
+            .map(|i| Frame::from_shape_fn((8, 8, 1), |(y, x, _)| (40 + x * 10 + y + i * 3) as u8))
+            .collect();
+        let mut source = FrameListSource { video, frames };
+
+        let mut batches = Vec::new();
+        let intervals = transcode_streaming(&mut source, 1, &pool, |events| {
+            batches.push(events.to_vec())
+        })
+        .unwrap();
+        assert_eq!(intervals, 20);
+        assert_eq!(batches.len(), 20);
+
+        let streamed = batches.concat();
+        assert!(!streamed.is_empty());
+
+        let bytes = source.video.end_write_stream().unwrap().unwrap();
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let mut decoded = Vec::new();
+        while let Ok(event) = reader.digest_event(&mut bitreader) {
+            decoded.push(event);
+        }
+        assert_eq!(streamed, decoded);
+    }
+}
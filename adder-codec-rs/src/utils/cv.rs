@@ -207,17 +207,66 @@ pub fn is_feature(
     Ok(false)
 }
 
-pub fn handle_color(mut input: Frame, color: bool) -> Result<Frame, SourceError> {
+/// The weights of the red, green, and blue channels when converting a color frame to grayscale
+/// (luma)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LumaWeights {
+    /// Weight of the red channel
+    pub r: f64,
+
+    /// Weight of the green channel
+    pub g: f64,
+
+    /// Weight of the blue channel
+    pub b: f64,
+}
+
+impl LumaWeights {
+    /// ITU-R BT.601 (standard definition) weights
+    pub const REC_601: LumaWeights = LumaWeights {
+        r: 0.299,
+        g: 0.587,
+        b: 0.114,
+    };
+
+    /// ITU-R BT.709 (high definition) weights
+    pub const REC_709: LumaWeights = LumaWeights {
+        r: 0.2126,
+        g: 0.7152,
+        b: 0.0722,
+    };
+
+    /// The luma of a pixel with the given channel values
+    #[must_use]
+    pub fn luma(&self, r: f64, g: f64, b: f64) -> f64 {
+        r * self.r + g * self.g + b * self.b
+    }
+}
+
+impl Default for LumaWeights {
+    fn default() -> Self {
+        LumaWeights::REC_601
+    }
+}
+
+/// If `color` is false, convert a 3-channel frame to a single grayscale channel with the given
+/// `weights`. Channels are in BGR order.
+pub fn handle_color(
+    mut input: Frame,
+    color: bool,
+    weights: LumaWeights,
+) -> Result<Frame, SourceError> {
     if !color {
         // Map the three color channels to a single grayscale channel
         input
             .exact_chunks_mut((1, 1, 3))
             .into_iter()
             .for_each(|mut v| unsafe {
-                *v.uget_mut((0, 0, 0)) = (*v.uget((0, 0, 0)) as f64 * 0.114
-                    + *v.uget((0, 0, 1)) as f64 * 0.587
-                    + *v.uget((0, 0, 2)) as f64 * 0.299)
-                    as u8;
+                *v.uget_mut((0, 0, 0)) = weights.luma(
+                    *v.uget((0, 0, 2)) as f64,
+                    *v.uget((0, 0, 1)) as f64,
+                    *v.uget((0, 0, 0)) as f64,
+                ) as u8;
             });
 
         // Remove the color channels
@@ -226,6 +275,119 @@ pub fn handle_color(mut input: Frame, color: bool) -> Result<Frame, SourceError>
     Ok(input)
 }
 
+/// The arrangement of color filters over a raw (mosaic) image sensor, named by the colors of
+/// each 2x2 cell in row-major order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BayerPattern {
+    /// Red at even rows and columns, blue at odd rows and columns
+    Rggb,
+
+    /// Blue at even rows and columns, red at odd rows and columns
+    Bggr,
+}
+
+impl BayerPattern {
+    /// The color channel (0 = red, 1 = green, 2 = blue) that the sensor samples at the given
+    /// pixel
+    #[must_use]
+    pub fn channel_at(self, x: usize, y: usize) -> usize {
+        let (even_even, odd_odd) = match self {
+            BayerPattern::Rggb => (0, 2),
+            BayerPattern::Bggr => (2, 0),
+        };
+        match (y % 2, x % 2) {
+            (0, 0) => even_even,
+            (1, 1) => odd_odd,
+            _ => 1,
+        }
+    }
+}
+
+/// Demosaic a single-channel Bayer frame into a 3-channel (RGB) frame.
+///
+/// Each pixel keeps its own sample for the channel its filter passes. The other two channels
+/// are filled from the samples of that color within the pixel's 2x2 cell (averaging the two
+/// green samples).
+///
+/// # Errors
+/// Returns an error if the frame does not have exactly one channel, or if its width or height
+/// is not a multiple of 2.
+pub fn demosaic(mosaic: &Frame, pattern: BayerPattern) -> Result<Frame, SourceError> {
+    let (height, width, channels) = mosaic.dim();
+    if channels != 1 {
+        return Err(SourceError::BadParams(
+            "Bayer input must have a single channel".to_string(),
+        ));
+    }
+    if width % 2 != 0 || height % 2 != 0 {
+        return Err(SourceError::BadParams(
+            "Bayer input must have an even width and height".to_string(),
+        ));
+    }
+
+    Ok(Frame::from_shape_fn((height, width, 3), |(y, x, c)| {
+        if pattern.channel_at(x, y) == c {
+            return mosaic[[y, x, 0]];
+        }
+        let (cell_y, cell_x) = (y - y % 2, x - x % 2);
+        let (mut sum, mut count) = (0_u16, 0_u16);
+        for (sample_y, sample_x) in [
+            (cell_y, cell_x),
+            (cell_y, cell_x + 1),
+            (cell_y + 1, cell_x),
+            (cell_y + 1, cell_x + 1),
+        ] {
+            if pattern.channel_at(sample_x, sample_y) == c {
+                sum += u16::from(mosaic[[sample_y, sample_x, 0]]);
+                count += 1;
+            }
+        }
+        (sum / count) as u8
+    }))
+}
+
+/// The interpolation used when scaling a source frame to the transcode resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScaleFilter {
+    /// Take the source pixel nearest the center of each output pixel. Fast, but aliases fine
+    /// detail, which can cause spurious events.
+    Nearest,
+
+    /// Average every source pixel covered by each output pixel
+    Area,
+}
+
+/// Scale a frame to the given width and height, using the given filter. The channel count is
+/// unchanged.
+#[must_use]
+pub fn scale_frame(frame: &Frame, width: usize, height: usize, filter: ScaleFilter) -> Frame {
+    let (src_height, src_width, channels) = frame.dim();
+    // The range of source indices covered by output index `i`, with at least one source index
+    let span = |i: usize, src: usize, dst: usize| {
+        let start = i * src / dst;
+        let end = ((i + 1) * src / dst).max(start + 1).min(src);
+        start..end
+    };
+
+    Frame::from_shape_fn((height, width, channels), |(y, x, c)| match filter {
+        ScaleFilter::Nearest => {
+            let src_y = ((2 * y + 1) * src_height / (2 * height)).min(src_height - 1);
+            let src_x = ((2 * x + 1) * src_width / (2 * width)).min(src_width - 1);
+            frame[[src_y, src_x, c]]
+        }
+        ScaleFilter::Area => {
+            let (mut sum, mut count) = (0_u32, 0_u32);
+            for src_y in span(y, src_height, height) {
+                for src_x in span(x, src_width, width) {
+                    sum += u32::from(frame[[src_y, src_x, c]]);
+                    count += 1;
+                }
+            }
+            ((sum + count / 2) / count) as u8
+        }
+    })
+}
+
 #[cfg(feature = "open-cv")]
 pub fn feature_precision_recall_accuracy(
     gt: &opencv::core::Vector<opencv::core::KeyPoint>,
@@ -296,8 +458,40 @@ impl Default for QualityMetrics {
     }
 }
 
+/// The order of the color channels in a 3-channel frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorSpace {
+    /// Blue, green, red. The order of frames decoded by OpenCV, and so of transcoder input and
+    /// `running_intensities`.
+    #[default]
+    Bgr,
+
+    /// Red, green, blue
+    Rgb,
+}
+
+impl ColorSpace {
+    /// Reorder the channels of `frame`, which is in this color space, to `target`. Frames
+    /// which don't have exactly 3 channels are returned unchanged.
+    #[must_use]
+    pub fn convert(self, frame: &Array3<u8>, target: ColorSpace) -> Array3<u8> {
+        let mut converted = frame.clone();
+        if self != target && frame.shape()[2] == 3 {
+            // Converting between BGR and RGB swaps the first and last channels either way
+            converted
+                .index_axis_mut(Axis(2), 0)
+                .assign(&frame.index_axis(Axis(2), 2));
+            converted
+                .index_axis_mut(Axis(2), 2)
+                .assign(&frame.index_axis(Axis(2), 0));
+        }
+        converted
+    }
+}
+
 /// Pass in the options for which metrics you want to evaluate by making them Some() in the `results`
-/// that you pass in
+/// that you pass in. Both frames must be in the same [`ColorSpace`]; otherwise, use
+/// [`calculate_quality_metrics_in`].
 pub fn calculate_quality_metrics(
     original: &Array3<u8>,
     reconstructed: &Array3<u8>,
@@ -324,6 +518,23 @@ pub fn calculate_quality_metrics(
     Ok(results)
 }
 
+/// Like [`calculate_quality_metrics`], but for frames in possibly different color spaces. The
+/// reconstruction is converted to the original's color space first, so that the metrics compare
+/// like channels.
+pub fn calculate_quality_metrics_in(
+    original: &Array3<u8>,
+    original_space: ColorSpace,
+    reconstructed: &Array3<u8>,
+    reconstructed_space: ColorSpace,
+    results: QualityMetrics,
+) -> Result<QualityMetrics, Box<dyn Error>> {
+    if original_space == reconstructed_space {
+        return calculate_quality_metrics(original, reconstructed, results);
+    }
+    let reconstructed = reconstructed_space.convert(reconstructed, original_space);
+    calculate_quality_metrics(original, &reconstructed, results)
+}
+
 fn calculate_mse(original: &Array3<u8>, reconstructed: &Array3<u8>) -> Result<f64, Box<dyn Error>> {
     if original.shape() != reconstructed.shape() {
         return Err("Shapes of original and reconstructed images must match".into());
@@ -427,3 +638,34 @@ pub fn clamp_u8(frame_val: &mut f64, last_val_ln: &mut f64) {
         *last_val_ln = 1.0_f64.ln_1p();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quality_metrics_color_space() {
+        let bgr = Array3::from_shape_fn((4, 4, 3), |(y, x, c)| (40 * c + 10 * x + y) as u8);
+        let rgb = ColorSpace::Bgr.convert(&bgr, ColorSpace::Rgb);
+        assert_eq!(rgb[[1, 2, 0]], bgr[[1, 2, 2]]);
+        assert_eq!(rgb[[1, 2, 1]], bgr[[1, 2, 1]]);
+        assert_eq!(ColorSpace::Rgb.convert(&rgb, ColorSpace::Bgr), bgr);
+
+        let metrics = QualityMetrics {
+            psnr: None,
+            mse: Some(0.0),
+            ssim: None,
+        };
+
+        // Mislabeling the reconstruction leaves its channels swapped
+        let swapped =
+            calculate_quality_metrics_in(&bgr, ColorSpace::Bgr, &rgb, ColorSpace::Bgr, metrics)
+                .unwrap();
+        assert!(swapped.mse.unwrap() > 1000.0);
+
+        let aligned =
+            calculate_quality_metrics_in(&bgr, ColorSpace::Bgr, &rgb, ColorSpace::Rgb, metrics)
+                .unwrap();
+        assert!(aligned.mse.unwrap() < 1e-3);
+    }
+}
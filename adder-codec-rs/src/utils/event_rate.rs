@@ -0,0 +1,127 @@
+use adder_codec_core::{BigT, DeltaT};
+use std::collections::VecDeque;
+
+/// Measures the event rate of a stream over a sliding window of time.
+///
+/// Each call to [`record`](EventRateMeter::record) adds the number of events produced over some
+/// span of ticks (e.g., one input frame). The oldest samples are dropped once the window is
+/// covered, so [`rate`](EventRateMeter::rate) reports a smoothed rate rather than the noisy
+/// count of a single interval.
+#[derive(Debug, Clone)]
+pub struct EventRateMeter {
+    tps: DeltaT,
+    window_ticks: BigT,
+
+    /// Ring buffer of (ticks spanned, event count) samples
+    samples: VecDeque<(BigT, u64)>,
+    window_span: BigT,
+    window_count: u64,
+}
+
+impl EventRateMeter {
+    /// Create a new meter with a window of `window_ticks` ticks.
+    ///
+    /// # Arguments
+    ///
+    /// * `tps`: the ticks per second of the stream
+    /// * `window_ticks`: the length of the sliding window, in ticks
+    #[must_use]
+    pub fn new(tps: DeltaT, window_ticks: BigT) -> Self {
+        Self {
+            tps,
+            window_ticks: window_ticks.max(1),
+            samples: VecDeque::new(),
+            window_span: 0,
+            window_count: 0,
+        }
+    }
+
+    /// Create a new meter with a window of `seconds` seconds.
+    #[must_use]
+    pub fn from_seconds(tps: DeltaT, seconds: f64) -> Self {
+        Self::new(tps, (seconds * f64::from(tps)) as BigT)
+    }
+
+    /// Record `count` events produced over a span of `ticks` ticks.
+    pub fn record(&mut self, count: u64, ticks: BigT) {
+        self.samples.push_back((ticks, count));
+        self.window_span += ticks;
+        self.window_count += count;
+
+        // Drop the oldest samples, as long as the rest still cover the window
+        while let Some(&(front_ticks, front_count)) = self.samples.front() {
+            if self.samples.len() == 1 || self.window_span - front_ticks < self.window_ticks {
+                break;
+            }
+            self.samples.pop_front();
+            self.window_span -= front_ticks;
+            self.window_count -= front_count;
+        }
+    }
+
+    /// The smoothed event rate, in events per second, over the current window
+    #[must_use]
+    pub fn rate(&self) -> f64 {
+        if self.window_span == 0 {
+            return 0.0;
+        }
+        self.window_count as f64 * f64::from(self.tps) / self.window_span as f64
+    }
+
+    /// Whether enough samples have been recorded to cover the whole window
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.window_span >= self.window_ticks
+    }
+
+    /// The ticks per second this meter was configured with
+    #[must_use]
+    pub fn tps(&self) -> DeltaT {
+        self.tps
+    }
+
+    /// Clear all recorded samples
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.window_span = 0;
+        self.window_count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bursty_rate_smoothed() {
+        let tps = 3000;
+        let ref_interval = 100;
+        let fps = f64::from(tps / ref_interval);
+        let mut meter = EventRateMeter::new(tps, 10 * BigT::from(ref_interval));
+
+        let counts = [0_u64, 1000, 0, 1000, 50, 950];
+        let min_rate = 0.0;
+        let max_rate = 1000.0 * fps;
+        for i in 0..60 {
+            meter.record(counts[i % counts.len()], BigT::from(ref_interval));
+            let rate = meter.rate();
+            assert!(rate >= min_rate && rate <= max_rate);
+            assert_eq!(meter.is_full(), i >= 9);
+        }
+
+        // With a full window of 10 intervals, the rate is the window's mean count times the fps
+        let window: u64 = (50..60).map(|i| counts[i % counts.len()]).sum();
+        let expected = window as f64 / 10.0 * fps;
+        assert!((meter.rate() - expected).abs() < 1e-6);
+
+        // A steady stream converges to its true rate once the window fills
+        for _ in 0..10 {
+            meter.record(500, BigT::from(ref_interval));
+        }
+        assert!((meter.rate() - 500.0 * fps).abs() < 1e-6);
+
+        meter.reset();
+        assert_eq!(meter.rate(), 0.0);
+        assert!(!meter.is_full());
+    }
+}
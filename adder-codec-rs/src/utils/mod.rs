@@ -8,6 +8,9 @@ pub mod stream_migration;
 /// Computer vision utilities
 pub mod cv;
 
+/// A module for measuring smoothed event rates
+pub mod event_rate;
+
 #[cfg(feature = "feature-logging")]
 pub mod logging;
 /// A module for visualizing streams
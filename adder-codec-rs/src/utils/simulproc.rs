@@ -6,6 +6,7 @@ use crate::transcoder::source::framed::Framed;
 use crate::transcoder::source::video::Source;
 use adder_codec_core::DeltaT;
 use clap::Parser;
+use log::{error, info};
 use rayon::ThreadPool;
 use serde::Serialize;
 use std::cmp::max;
@@ -158,8 +159,9 @@ impl<W: Write + 'static> SimulProcessor<W> {
                 )
                 .mode(INSTANTANEOUS)
                 .source(U8, FramedU8)
+                .pixel_multi_mode(source.video.state.params.pixel_multi_mode)
                 .finish::<T>()
-        });
+        })?;
 
         let mut output_stream = BufWriter::new(File::create(output_path)?);
 
@@ -179,7 +181,7 @@ impl<W: Write + 'static> SimulProcessor<W> {
                     if framer.ingest_events_events(events) {
                         match framer.write_multi_frame_bytes(&mut output_stream) {
                             Ok(0) => {
-                                eprintln!("Should have frame, but didn't");
+                                error!("Should have frame {frame_count}, but didn't");
                                 break;
                             }
                             Ok(frames_returned) => {
@@ -191,27 +193,27 @@ impl<W: Write + 'static> SimulProcessor<W> {
                                     now.elapsed().as_millis() / frames_returned as u128
                                 );
                                 if io::stdout().flush().is_err() {
-                                    eprintln!("Error flushing stdout");
+                                    error!("Error flushing stdout");
                                     break;
                                 };
                                 now = Instant::now();
                             }
                             Err(e) => {
-                                eprintln!("Error writing frame: {e}");
+                                error!("Error writing frame {frame_count}: {e}");
                                 break;
                             }
                         }
                     }
                     if output_stream.flush().is_err() {
-                        eprintln!("Error flushing output stream");
+                        error!("Error flushing output stream");
                         break;
                     }
                     if frame_count >= frame_max && frame_max > 0 {
-                        eprintln!("Wrote max frames. Exiting channel.");
+                        info!("Wrote max frames ({frame_count}). Exiting channel.");
                         break;
                     }
                 } else {
-                    eprintln!("Event receiver is closed. Exiting channel.");
+                    info!("Event receiver is closed after {frame_count} frames. Exiting channel.");
                     break;
                 };
             }
@@ -240,7 +242,7 @@ impl<W: Write + 'static> SimulProcessor<W> {
                     // };
                 }
                 Err(e) => {
-                    println!("Err: {e:?}");
+                    error!("Transcode stopped: {e:?}");
                     break;
                 }
             };
@@ -254,13 +256,16 @@ impl<W: Write + 'static> SimulProcessor<W> {
                     now.elapsed().as_millis()
                 );
                 if io::stdout().flush().is_err() {
-                    eprintln!("Error flushing stdout");
+                    error!("Error flushing stdout");
                     break;
                 };
                 now = Instant::now();
             }
             if video.state.in_interval_count >= frame_max && frame_max > 0 {
-                eprintln!("Wrote max frames. Exiting channel.");
+                info!(
+                    "Transcoded max frames ({}). Exiting channel.",
+                    video.state.in_interval_count
+                );
                 break;
             }
             // // TODO: temp
@@ -269,9 +274,9 @@ impl<W: Write + 'static> SimulProcessor<W> {
             // }
         }
 
-        println!("Closing stream...");
+        info!("Closing stream...");
         self.source.get_video_mut().end_write_stream()?;
-        println!("FINISHED");
+        info!("Finished");
 
         Ok(())
     }
@@ -1,7 +1,8 @@
 use adder_codec_core::codec::decoder::Decoder;
 use adder_codec_core::codec::encoder::Encoder;
-use adder_codec_core::{DeltaT, Event, SourceCamera, TimeMode};
-use bitstream_io::BigEndian;
+use adder_codec_core::codec::CodecError;
+use adder_codec_core::{BigT, DeltaT, Event, SourceCamera, TimeMode};
+use bitstream_io::{BigEndian, BitReader};
 use ndarray::Array3;
 use std::error::Error;
 use std::io::{Read, Seek, Write};
@@ -50,6 +51,14 @@ pub fn migrate_v2<W: Write + 'static, R: Read + Seek>(
                 break;
             }
         };
+        if event.coord.is_reserved() {
+            if event.coord.is_rebase() {
+                let offset = event.t;
+                t_tree.mapv_inplace(|t| t.saturating_sub(offset));
+                output_stream.rebase(offset)?;
+            }
+            continue;
+        }
         let t = &mut t_tree[[
             event.coord.y_usize(),
             event.coord.x_usize(),
@@ -87,6 +96,212 @@ pub fn migrate_v2<W: Write + 'static, R: Read + Seek>(
     Ok(output_stream)
 }
 
+/// Demultiplexes a 3-channel stream into three grayscale streams, one per channel. Each output
+/// event keeps its pixel coordinates and timestamp, but has its channel set to `None`. Rebase
+/// events apply to every channel, so they are written to all three outputs.
+///
+/// The output encoders must have been created with a single-channel plane of the same width and
+/// height as the input.
+///
+/// # Arguments
+///
+/// * `input_stream`: 3-channel input stream to be split
+/// * `bitreader`: bitreader to be used for reading the input stream
+/// * `out_r`, `out_g`, `out_b`: output streams for channels 0, 1, and 2, respectively
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub fn split_channels<W: Write + 'static, R: Read + Seek>(
+    input_stream: &mut Decoder<R>,
+    bitreader: &mut BitReader<R, BigEndian>,
+    out_r: &mut Encoder<W>,
+    out_g: &mut Encoder<W>,
+    out_b: &mut Encoder<W>,
+) -> Result<(), Box<dyn Error>> {
+    let plane = input_stream.meta().plane;
+    if plane.c() != 3 {
+        return Err("Input stream must have 3 channels".into());
+    }
+    for output in [&*out_r, &*out_g, &*out_b] {
+        let out_plane = output.meta().plane;
+        if out_plane.c() != 1 || out_plane.w() != plane.w() || out_plane.h() != plane.h() {
+            return Err("Output streams must be single-channel with the input's dimensions".into());
+        }
+    }
+
+    loop {
+        let mut event = match input_stream.digest_event(bitreader) {
+            Ok(event) => event,
+            Err(CodecError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if event.coord.is_reserved() {
+            if event.coord.is_rebase() {
+                for output in [&mut *out_r, &mut *out_g, &mut *out_b] {
+                    output.rebase(event.t)?;
+                }
+            }
+            continue;
+        }
+        let output = match event.coord.c_usize() {
+            0 => &mut *out_r,
+            1 => &mut *out_g,
+            2 => &mut *out_b,
+            _ => return Err("Event channel out of range".into()),
+        };
+        event.coord.c = None;
+        output.ingest_event(event)?;
+    }
+    Ok(())
+}
+
+/// Read the next event of a stream, or `None` at the end of it. Any other error is returned.
+fn next_event<R: Read + Seek>(
+    input: &mut Decoder<R>,
+    bitreader: &mut BitReader<R, BigEndian>,
+) -> Result<Option<Event>, CodecError> {
+    match input.digest_event(bitreader) {
+        Ok(event) => Ok(Some(event)),
+        Err(CodecError::Eof) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Multiplexes three grayscale streams into a single 3-channel stream. This is the inverse of
+/// [`split_channels`].
+///
+/// Events are interleaved by their `t` values, which gives a temporally ordered output for
+/// [`AbsoluteT`](TimeMode::AbsoluteT) streams. Each input's own event order is always preserved.
+/// The output encoder must have been created with a 3-channel plane of the same width and height
+/// as the inputs.
+///
+/// # Arguments
+///
+/// * `inputs`: the (decoder, bitreader) pairs for channels 0, 1, and 2, respectively
+/// * `output_stream`: 3-channel output stream to be written to
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub fn merge_channels<W: Write + 'static, R: Read + Seek>(
+    inputs: [(&mut Decoder<R>, &mut BitReader<R, BigEndian>); 3],
+    output_stream: &mut Encoder<W>,
+) -> Result<(), Box<dyn Error>> {
+    let plane = output_stream.meta().plane;
+    if plane.c() != 3 {
+        return Err("Output stream must have 3 channels".into());
+    }
+    for (input, _) in &inputs {
+        let in_plane = input.meta().plane;
+        if in_plane.c() != 1 || in_plane.w() != plane.w() || in_plane.h() != plane.h() {
+            return Err("Input streams must be single-channel with the output's dimensions".into());
+        }
+    }
+
+    let mut inputs = inputs;
+    let mut heads: [Option<Event>; 3] = [None; 3];
+    for (head, (input, bitreader)) in heads.iter_mut().zip(inputs.iter_mut()) {
+        *head = next_event(input, bitreader)?;
+    }
+
+    // Repeatedly take the earliest pending event across the three inputs
+    while let Some(channel) = (0..3)
+        .filter(|c| heads[*c].is_some())
+        .min_by_key(|c| heads[*c].map(|event| event.t))
+    {
+        if let Some(mut event) = heads[channel] {
+            event.coord.c = Some(channel as u8);
+            output_stream.ingest_event(event)?;
+        }
+        let (input, bitreader) = &mut inputs[channel];
+        heads[channel] = next_event(input, bitreader)?;
+    }
+    Ok(())
+}
+
+/// Quantizes the timing of an input stream to multiples of `q` ticks, writing the result to an
+/// output stream. Trading timing precision for fewer distinct `Δt` values makes the stream easier
+/// for an arithmetic coder to model, and so compress.
+///
+/// Each pixel's accumulated timestamp is rounded to the nearest multiple of `q`, rather than each
+/// `Δt` on its own, so the rounding error doesn't build up over the stream: every event lands
+/// within `q / 2` ticks of its original time, and every output `Δt` is a multiple of `q`. An
+/// event's `Δt` may therefore grow past `delta_t_max` by up to `q / 2` ticks, or shrink to 0.
+///
+/// [`AbsoluteT`](TimeMode::AbsoluteT) input streams are only read as such for codec version 2 or
+/// later. The output timestamps follow the output stream's time mode.
+///
+/// # Arguments
+///
+/// * `input_stream`: input stream to be quantized
+/// * `bitreader`: bitreader to be used for reading the input stream
+/// * `output_stream`: output stream to be written to
+/// * `q`: the quantization step, in ticks
+///
+/// returns: `Result<(), Box<dyn Error>>`
+pub fn quantize_delta_t<W: Write + 'static, R: Read + Seek>(
+    input_stream: &mut Decoder<R>,
+    bitreader: &mut BitReader<R, BigEndian>,
+    output_stream: &mut Encoder<W>,
+    q: DeltaT,
+) -> Result<(), Box<dyn Error>> {
+    if q == 0 {
+        return Err("Quantization step must be greater than 0".into());
+    }
+    let meta = *input_stream.meta();
+    let absolute_input = meta.codec_version >= 2 && meta.time_mode == TimeMode::AbsoluteT;
+    let absolute_output = output_stream.meta().time_mode == TimeMode::AbsoluteT;
+    let shape = (
+        meta.plane.h_usize(),
+        meta.plane.w_usize(),
+        meta.plane.c_usize(),
+    );
+
+    // Each pixel's original and quantized accumulated timestamps
+    let mut original_t: Array3<BigT> = Array3::zeros(shape);
+    let mut quantized_t: Array3<BigT> = Array3::zeros(shape);
+    let q = BigT::from(q);
+
+    loop {
+        let mut event = match input_stream.digest_event(bitreader) {
+            Ok(event) => event,
+            Err(CodecError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        if event.coord.is_reserved() {
+            if event.coord.is_rebase() {
+                let offset = BigT::from(event.t);
+                original_t.mapv_inplace(|t| t.saturating_sub(offset));
+                quantized_t.mapv_inplace(|t| t.saturating_sub(offset));
+                output_stream.rebase(event.t)?;
+            }
+            continue;
+        }
+        let idx = [
+            event.coord.y_usize(),
+            event.coord.x_usize(),
+            event.coord.c_usize(),
+        ];
+        if original_t.get(idx).is_none() {
+            return Err("Event coordinate outside the input plane".into());
+        }
+        if absolute_input {
+            original_t[idx] = BigT::from(event.t);
+        } else {
+            original_t[idx] += BigT::from(event.t);
+        }
+
+        let rounded = (original_t[idx] + q / 2) / q * q;
+        let t = if absolute_output {
+            rounded
+        } else {
+            rounded - quantized_t[idx]
+        };
+        event.t = DeltaT::try_from(t)?;
+        quantized_t[idx] = rounded;
+
+        output_stream.ingest_event(event)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::framer::driver::FramerMode::INSTANTANEOUS;
@@ -98,7 +313,7 @@ mod tests {
     use adder_codec_core::codec::{CodecMetadata, EncoderOptions};
     use adder_codec_core::SourceCamera::FramedU8;
     use adder_codec_core::TimeMode::AbsoluteT;
-    use adder_codec_core::{Coord, Event, PlaneSize, TimeMode};
+    use adder_codec_core::{Coord, Event, PixelMultiMode, PlaneSize, TimeMode};
     use bitstream_io::{BigEndian, BitReader};
     use ndarray::Array3;
     use std::fs::File;
@@ -126,6 +341,9 @@ mod tests {
                 event_size: 0,
                 source_camera: FramedU8,
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -177,6 +395,9 @@ mod tests {
                 event_size: 0,
                 source_camera: FramedU8,
                 adu_interval: 1,
+                delta_t_encoding: Default::default(),
+                pixel_multi_mode: PixelMultiMode::Normal,
+                t_fraction_bits: 0,
             },
             bufwriter,
         );
@@ -304,7 +525,7 @@ mod tests {
                     input_stream_t.get_source_type(),
                     input_stream_t.meta().source_camera,
                 )
-                .finish();
+                .finish()?;
 
         let bufreader = BufReader::new(File::open("./tests/samples/bunny_v2_dt.adder")?);
         let compression = RawInput::new();
@@ -325,7 +546,7 @@ mod tests {
                     input_stream_dt.get_source_type(),
                     input_stream_dt.meta().source_camera,
                 )
-                .finish();
+                .finish()?;
 
         let mut event_count = 0;
         let mut last_t = 0;
@@ -401,7 +622,7 @@ mod tests {
                     input_stream_t.get_source_type(),
                     input_stream_t.meta().source_camera,
                 )
-                .finish();
+                .finish()?;
 
         let bufreader = BufReader::new(File::open("./tests/samples/bunny_v2_dt_3.adder")?);
         let compression = RawInput::new();
@@ -422,7 +643,7 @@ mod tests {
                     input_stream_dt.get_source_type(),
                     input_stream_dt.meta().source_camera,
                 )
-                .finish();
+                .finish()?;
 
         let mut event_count = 0;
         let mut t_tree: Array3<u32> = Array3::from_shape_vec(
@@ -513,4 +734,443 @@ mod tests {
 
         Ok(())
     }
+
+    /// A rebase event in a color stream applies to every channel, so each grayscale stream gets a
+    /// copy of it, after the events that came before it
+    #[test]
+    fn test_split_channels_rebase() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::utils::stream_migration::split_channels;
+
+        let meta = |plane: PlaneSize| CodecMetadata {
+            codec_version: 3,
+            time_mode: AbsoluteT,
+            plane,
+            tps: 255 * 30,
+            ref_interval: 255,
+            delta_t_max: 2550,
+            source_camera: FramedU8,
+            ..Default::default()
+        };
+        let color_plane = PlaneSize::new(1, 1, 3)?;
+        let gray_plane = PlaneSize::new(1, 1, 1)?;
+        let new_encoder = |plane: PlaneSize| {
+            Encoder::new_raw(
+                RawOutput::new(meta(plane), BufWriter::new(Vec::new())),
+                EncoderOptions::default(plane),
+            )
+        };
+        let event = |c: u8, t: u32| Event {
+            coord: Coord {
+                x: 0,
+                y: 0,
+                c: Some(c),
+            },
+            d: 7,
+            t,
+        };
+
+        let mut stream = new_encoder(color_plane);
+        for c in 0..3 {
+            stream.ingest_event(event(c, 1000))?;
+        }
+        stream.rebase(500)?;
+        for c in 0..3 {
+            stream.ingest_event(event(c, 1000))?;
+        }
+        let color_bytes = stream.close_writer()?.unwrap().into_inner()?;
+
+        let mut bitreader =
+            BitReader::endian(BufReader::new(Cursor::new(&*color_bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+        let mut out_r = new_encoder(gray_plane);
+        let mut out_g = new_encoder(gray_plane);
+        let mut out_b = new_encoder(gray_plane);
+        split_channels(
+            &mut reader,
+            &mut bitreader,
+            &mut out_r,
+            &mut out_g,
+            &mut out_b,
+        )?;
+
+        for output in [out_r, out_g, out_b] {
+            let bytes = output.close_writer()?.unwrap().into_inner()?;
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+            let mut gray_event = event(0, 1000);
+            gray_event.coord.c = None;
+            assert_eq!(reader.digest_event(&mut bitreader)?, gray_event);
+            assert_eq!(reader.digest_event(&mut bitreader)?, Event::rebase(500));
+            assert_eq!(reader.digest_event(&mut bitreader)?, gray_event);
+            assert!(reader.digest_event(&mut bitreader).is_err());
+        }
+
+        Ok(())
+    }
+
+    /// A stream which is cut off partway through an event is an error, rather than the end of
+    /// the stream
+    #[test]
+    fn test_split_channels_truncated() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::utils::stream_migration::split_channels;
+
+        let meta = |plane: PlaneSize| CodecMetadata {
+            codec_version: 3,
+            time_mode: AbsoluteT,
+            plane,
+            tps: 255 * 30,
+            ref_interval: 255,
+            delta_t_max: 2550,
+            source_camera: FramedU8,
+            ..Default::default()
+        };
+        let color_plane = PlaneSize::new(1, 1, 3)?;
+        let gray_plane = PlaneSize::new(1, 1, 1)?;
+        let new_encoder = |plane: PlaneSize| {
+            Encoder::new_raw(
+                RawOutput::new(meta(plane), BufWriter::new(Vec::new())),
+                EncoderOptions::default(plane),
+            )
+        };
+
+        let mut stream = new_encoder(color_plane);
+        for c in 0..3 {
+            stream.ingest_event(Event {
+                coord: Coord {
+                    x: 0,
+                    y: 0,
+                    c: Some(c),
+                },
+                d: 7,
+                t: 1000,
+            })?;
+        }
+        let mut color_bytes = stream.close_writer()?.unwrap().into_inner()?;
+        // Drop the EOF event and part of the last event
+        color_bytes.truncate(color_bytes.len() - 13);
+
+        let mut bitreader =
+            BitReader::endian(BufReader::new(Cursor::new(&*color_bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+        let mut out_r = new_encoder(gray_plane);
+        let mut out_g = new_encoder(gray_plane);
+        let mut out_b = new_encoder(gray_plane);
+        assert!(split_channels(
+            &mut reader,
+            &mut bitreader,
+            &mut out_r,
+            &mut out_g,
+            &mut out_b,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    /// Split a color stream into three grayscale streams, check that each holds only its own
+    /// channel's events, then merge them back together
+    #[test]
+    fn test_split_merge_channels() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::utils::stream_migration::{merge_channels, split_channels};
+
+        let meta = |plane: PlaneSize| CodecMetadata {
+            codec_version: 3,
+            header_size: 0,
+            time_mode: AbsoluteT,
+            plane,
+            tps: 255 * 30,
+            ref_interval: 255,
+            delta_t_max: 2550,
+            event_size: 0,
+            source_camera: FramedU8,
+            adu_interval: 1,
+            delta_t_encoding: Default::default(),
+            pixel_multi_mode: PixelMultiMode::Normal,
+            t_fraction_bits: 0,
+        };
+        let color_plane = PlaneSize::new(2, 2, 3)?;
+        let gray_plane = PlaneSize::new(2, 2, 1)?;
+        let new_encoder = |plane: PlaneSize| {
+            Encoder::new_raw(
+                RawOutput::new(meta(plane), BufWriter::new(Vec::new())),
+                EncoderOptions::default(plane),
+            )
+        };
+
+        let events: Vec<Event> = (0..12_u16)
+            .map(|i| Event {
+                coord: Coord {
+                    x: i % 2,
+                    y: (i / 2) % 2,
+                    c: Some((i % 3) as u8),
+                },
+                d: i as u8,
+                t: 100 * (u32::from(i) + 1),
+            })
+            .collect();
+
+        let mut stream = new_encoder(color_plane);
+        for event in &events {
+            stream.ingest_event(*event)?;
+        }
+        let color_bytes = stream.close_writer()?.unwrap().into_inner()?;
+
+        let mut bitreader =
+            BitReader::endian(BufReader::new(Cursor::new(&*color_bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+        let mut out_r = new_encoder(gray_plane);
+        let mut out_g = new_encoder(gray_plane);
+        let mut out_b = new_encoder(gray_plane);
+        split_channels(
+            &mut reader,
+            &mut bitreader,
+            &mut out_r,
+            &mut out_g,
+            &mut out_b,
+        )?;
+
+        let mut gray_bytes = Vec::new();
+        for (channel, output) in [out_r, out_g, out_b].into_iter().enumerate() {
+            let bytes = output.close_writer()?.unwrap().into_inner()?;
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+            assert_eq!(reader.meta().plane.c(), 1);
+
+            let expected = events
+                .iter()
+                .filter(|event| event.coord.c_usize() == channel)
+                .map(|event| {
+                    let mut event = *event;
+                    event.coord.c = None;
+                    event
+                });
+            for expected_event in expected {
+                assert_eq!(reader.digest_event(&mut bitreader)?, expected_event);
+            }
+            assert!(reader.digest_event(&mut bitreader).is_err());
+            gray_bytes.push(bytes);
+        }
+
+        // Merge the channels back into a color stream
+        let mut bitreaders: Vec<_> = gray_bytes
+            .iter()
+            .map(|bytes| BitReader::endian(BufReader::new(Cursor::new(&**bytes)), BigEndian))
+            .collect();
+        let mut readers = Vec::new();
+        for bitreader in &mut bitreaders {
+            readers.push(Decoder::new_raw(RawInput::new(), bitreader)?);
+        }
+        let mut merged = new_encoder(color_plane);
+        {
+            let [br_r, br_g, br_b] = &mut bitreaders[..] else {
+                unreachable!()
+            };
+            let [r, g, b] = &mut readers[..] else {
+                unreachable!()
+            };
+            merge_channels([(r, br_r), (g, br_g), (b, br_b)], &mut merged)?;
+        }
+        let merged_bytes = merged.close_writer()?.unwrap().into_inner()?;
+        assert_eq!(merged_bytes, color_bytes);
+
+        Ok(())
+    }
+
+    /// Quantize a stream with jittery timing, and check that every event stays within `q / 2`
+    /// ticks of the original
+    #[test]
+    fn test_quantize_delta_t() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::utils::stream_migration::quantize_delta_t;
+
+        let plane = PlaneSize::new(4, 4, 1)?;
+        let new_encoder = || {
+            Encoder::new_raw(
+                RawOutput::new(
+                    CodecMetadata {
+                        codec_version: 3,
+                        time_mode: TimeMode::DeltaT,
+                        plane,
+                        tps: 255 * 30,
+                        ref_interval: 255,
+                        delta_t_max: 2550,
+                        ..Default::default()
+                    },
+                    BufWriter::new(Vec::new()),
+                ),
+                EncoderOptions::default(plane),
+            )
+        };
+
+        // Every pixel fires 20 times, with pseudo-random Δt around one source interval
+        let mut seed: u32 = 12345;
+        let mut events = Vec::new();
+        for _ in 0..20 {
+            for y in 0..4 {
+                for x in 0..4 {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    events.push(Event {
+                        coord: Coord { x, y, c: None },
+                        d: 7,
+                        t: 200 + (seed >> 16) % 100,
+                    });
+                }
+            }
+        }
+        let mut stream = new_encoder();
+        for event in &events {
+            stream.ingest_event(*event)?;
+        }
+        let bytes = stream.close_writer()?.unwrap().into_inner()?;
+
+        let q = 16;
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+        let mut output = new_encoder();
+        quantize_delta_t(&mut reader, &mut bitreader, &mut output, q)?;
+        let quantized_bytes = output.close_writer()?.unwrap().into_inner()?;
+
+        let mut bitreader =
+            BitReader::endian(BufReader::new(Cursor::new(&*quantized_bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+        let mut original_t: Array3<u32> = Array3::zeros((4, 4, 1));
+        let mut quantized_t: Array3<u32> = Array3::zeros((4, 4, 1));
+        for event in &events {
+            let out = reader.digest_event(&mut bitreader)?;
+            let (out_coord, coord) = (out.coord, event.coord);
+            assert_eq!(out_coord, coord);
+            assert_eq!(out.d, event.d);
+            assert_eq!(out.t % q, 0);
+
+            let idx = [event.coord.y_usize(), event.coord.x_usize(), 0];
+            original_t[idx] += event.t;
+            quantized_t[idx] += out.t;
+            assert!(original_t[idx].abs_diff(quantized_t[idx]) <= q / 2);
+        }
+        assert!(reader.digest_event(&mut bitreader).is_err());
+
+        Ok(())
+    }
+
+    /// An event outside the plane of the stream's header is an error, not a panic
+    #[test]
+    fn test_quantize_delta_t_out_of_plane() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::utils::stream_migration::quantize_delta_t;
+
+        let meta = |plane: PlaneSize| CodecMetadata {
+            codec_version: 3,
+            time_mode: TimeMode::DeltaT,
+            plane,
+            tps: 255 * 30,
+            ref_interval: 255,
+            delta_t_max: 2550,
+            ..Default::default()
+        };
+        let wide_plane = PlaneSize::new(4, 1, 1)?;
+        let mut stream = Encoder::new_raw(
+            RawOutput::new(meta(wide_plane), BufWriter::new(Vec::new())),
+            EncoderOptions::default(wide_plane),
+        );
+        stream.ingest_event(Event {
+            coord: Coord {
+                x: 3,
+                y: 0,
+                c: None,
+            },
+            d: 7,
+            t: 255,
+        })?;
+        let bytes = stream.close_writer()?.unwrap().into_inner()?;
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+        // Claim a plane which doesn't hold the event's pixel
+        let plane = PlaneSize::new(1, 1, 1)?;
+        reader.meta_mut().plane = plane;
+        let mut output = Encoder::new_raw(
+            RawOutput::new(meta(plane), BufWriter::new(Vec::new())),
+            EncoderOptions::default(plane),
+        );
+        assert!(quantize_delta_t(&mut reader, &mut bitreader, &mut output, 16).is_err());
+
+        Ok(())
+    }
+
+    /// Quantizing a stream with jittery timing makes it compress to fewer bytes
+    #[test]
+    #[cfg(feature = "compression")]
+    fn test_quantize_delta_t_compressed_size() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::utils::stream_migration::quantize_delta_t;
+        use adder_codec_core::codec::compressed::stream::CompressedOutput;
+
+        let plane = PlaneSize::new(16, 16, 1)?;
+        let meta = |time_mode: TimeMode| CodecMetadata {
+            codec_version: 3,
+            time_mode,
+            plane,
+            tps: 255 * 30,
+            ref_interval: 255,
+            delta_t_max: 2550,
+            adu_interval: 10,
+            ..Default::default()
+        };
+        let new_raw_encoder = || {
+            Encoder::new_raw(
+                RawOutput::new(meta(TimeMode::AbsoluteT), BufWriter::new(Vec::new())),
+                EncoderOptions::default(plane),
+            )
+        };
+
+        // Every pixel fires 40 times, with pseudo-random Δt around one source interval
+        let mut seed: u32 = 12345;
+        let mut pixel_t: Array3<u32> = Array3::zeros((16, 16, 1));
+        let mut events = Vec::new();
+        for _ in 0..40 {
+            for y in 0..16 {
+                for x in 0..16 {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    let t = &mut pixel_t[[y as usize, x as usize, 0]];
+                    *t += 200 + (seed >> 16) % 100;
+                    events.push(Event {
+                        coord: Coord { x, y, c: None },
+                        d: 7,
+                        t: *t,
+                    });
+                }
+            }
+        }
+        let mut stream = new_raw_encoder();
+        for event in &events {
+            stream.ingest_event(*event)?;
+        }
+        let bytes = stream.close_writer()?.unwrap().into_inner()?;
+
+        let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(&*bytes)), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+        let mut output = new_raw_encoder();
+        quantize_delta_t(&mut reader, &mut bitreader, &mut output, 16)?;
+        let quantized_bytes = output.close_writer()?.unwrap().into_inner()?;
+
+        // Compress a raw stream, feeding the events to the encoder in time order
+        let compressed_size = |bytes: &[u8]| -> Result<usize, Box<dyn std::error::Error>> {
+            let mut bitreader = BitReader::endian(BufReader::new(Cursor::new(bytes)), BigEndian);
+            let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader)?;
+            let mut events = Vec::new();
+            while let Ok(event) = reader.digest_event(&mut bitreader) {
+                events.push(event);
+            }
+            events.sort_by_key(|event| event.t);
+
+            let mut encoder = Encoder::new_compressed(
+                CompressedOutput::new(meta(TimeMode::AbsoluteT), Vec::new()),
+                EncoderOptions::default(plane),
+            );
+            for event in events {
+                encoder.ingest_event(event)?;
+            }
+            Ok(encoder.close_writer()?.unwrap().len())
+        };
+        assert!(compressed_size(&quantized_bytes)? < compressed_size(&bytes)?);
+
+        Ok(())
+    }
 }
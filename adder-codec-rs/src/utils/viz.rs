@@ -1,4 +1,5 @@
 use adder_codec_core::{Event, PixelAddress};
+use log::info;
 #[cfg(feature = "open-cv")]
 use opencv::core::{Mat, MatTraitConst, MatTraitConstManual};
 use std::error::Error;
@@ -45,7 +46,7 @@ pub fn write_frame_to_video_cv(
 /// * [`io::Error`] if there is an error writing to the file
 pub fn encode_video_ffmpeg(raw_path: &str, video_path: &str) -> io::Result<Output> {
     // ffmpeg -f rawvideo -pix_fmt gray -s:v 346x260 -r 60 -i ./tmp.gray8 -crf 0 -c:v libx264 ./output_file.mp4
-    println!("Writing reconstruction as .mp4 with ffmpeg");
+    info!("Writing reconstruction {raw_path} as .mp4 with ffmpeg");
     Command::new("ffmpeg")
         .args([
             "-f", "rawvideo", "-pix_fmt", "gray", "-s:v", "346x260", "-r", "30", "-i", raw_path,
@@ -7,14 +7,13 @@ use adder_codec_core::codec::EncoderOptions;
 use adder_codec_core::SourceCamera::FramedU8;
 use adder_codec_core::SourceType::*;
 use adder_codec_core::TimeMode::DeltaT;
-use adder_codec_core::{Coord, Event, EventCoordless, PlaneSize, TimeMode};
+use adder_codec_core::{Coord, Event, EventCoordless, PixelMultiMode, PlaneSize, TimeMode};
 use bitstream_io::{BigEndian, BitReader};
 use ndarray::{Array3, Axis};
 use std::fs;
 use std::fs::File;
 use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
-use std::process::Command;
 
 use adder_codec_rs::framer::driver::FramerMode::INSTANTANEOUS;
 use adder_codec_rs::framer::driver::{FrameSequence, Framer, FramerBuilder};
@@ -60,8 +59,7 @@ fn test_sample_perfect_dt() {
     let mut bitreader = BitReader::endian(bufreader, BigEndian);
     let mut reader = Decoder::new_raw(compression, &mut bitreader).unwrap();
 
-    let output_path = Path::new("./tests/samples/temp_sample_1");
-    let mut output_stream = BufWriter::new(File::create(output_path).unwrap());
+    let mut output_stream: Vec<u8> = Vec::new();
 
     let reconstructed_frame_rate = 24.0;
     // For instantaneous reconstruction, make sure the frame rate matches the source video rate
@@ -80,7 +78,8 @@ fn test_sample_perfect_dt() {
         )
         .mode(INSTANTANEOUS)
         .source(reader.get_source_type(), reader.meta().source_camera)
-        .finish();
+        .finish()
+        .unwrap();
 
     let mut frame_count = 0;
     loop {
@@ -109,19 +108,11 @@ fn test_sample_perfect_dt() {
 
     assert_eq!(frame_count, 221);
 
-    output_stream.flush().unwrap();
-    let output = if !cfg!(target_os = "windows") {
-        Command::new("sh")
-            .arg("-c")
-            .arg("cmp ./temp_sample_1 ./sample_1_instant_framed.gray")
-            .output()
-            .expect("failed to execute process")
-    } else {
-        fs::remove_file(output_path).unwrap();
-        return;
-    };
-    assert_eq!(output.stdout.len(), 0);
-    fs::remove_file(output_path).unwrap();
+    assert_golden(
+        &output_stream,
+        "sample_1_instant_framed.gray",
+        reader.meta().plane.volume(),
+    );
 }
 
 #[test]
@@ -134,8 +125,7 @@ fn test_sample_perfect_dt_color() {
     let mut bitreader = BitReader::endian(bufreader, BigEndian);
     let mut reader = Decoder::new_raw(compression, &mut bitreader).unwrap();
 
-    let output_path = Path::new("./tests/samples/temp_sample_2");
-    let mut output_stream = BufWriter::new(File::create(output_path).unwrap());
+    let mut output_stream: Vec<u8> = Vec::new();
 
     let reconstructed_frame_rate = 24.0;
     // For instantaneous reconstruction, make sure the frame rate matches the source video rate
@@ -154,7 +144,8 @@ fn test_sample_perfect_dt_color() {
         )
         .mode(INSTANTANEOUS)
         .source(reader.get_source_type(), reader.meta().source_camera)
-        .finish();
+        .finish()
+        .unwrap();
 
     let mut frame_count = 0;
     loop {
@@ -181,20 +172,12 @@ fn test_sample_perfect_dt_color() {
         }
     }
 
-    output_stream.flush().unwrap();
-    let output = if !cfg!(target_os = "windows") {
-        Command::new("sh")
-            .arg("-c")
-            .arg("cmp ./temp_sample_1 ./sample_2_instant_framed.bgr24")
-            .output()
-            .expect("failed to execute process")
-    } else {
-        fs::remove_file(output_path).unwrap();
-        return;
-    };
     assert_eq!(frame_count, 221);
-    assert_eq!(output.stdout.len(), 0);
-    fs::remove_file(output_path).unwrap();
+    assert_golden(
+        &output_stream,
+        "sample_2_instant_framed.bgr24",
+        reader.meta().plane.volume(),
+    );
 }
 
 #[test]
@@ -262,6 +245,9 @@ fn setup_raw_writer_v0(rand_num: u32) -> Encoder<BufWriter<File>> {
             event_size: 0,
             source_camera: Default::default(),
             adu_interval: 1,
+            delta_t_encoding: Default::default(),
+            pixel_multi_mode: PixelMultiMode::Normal,
+            t_fraction_bits: 0,
         },
         bufwriter,
     );
@@ -288,6 +274,9 @@ fn setup_raw_writer_v1(rand_num: u32) -> Encoder<BufWriter<File>> {
             event_size: 0,
             source_camera: FramedU8,
             adu_interval: 1,
+            delta_t_encoding: Default::default(),
+            pixel_multi_mode: PixelMultiMode::Normal,
+            t_fraction_bits: 0,
         },
         bufwriter,
     );
@@ -314,6 +303,9 @@ fn setup_raw_writer_v2(rand_num: u32) -> Encoder<BufWriter<File>> {
             event_size: 0,
             source_camera: FramedU8,
             adu_interval: 1,
+            delta_t_encoding: Default::default(),
+            pixel_multi_mode: PixelMultiMode::Normal,
+            t_fraction_bits: 0,
         },
         bufwriter,
     );
@@ -383,6 +375,50 @@ fn rand_u32() -> u32 {
     rand::thread_rng().gen()
 }
 
+/// Set this environment variable to rewrite the golden fixtures from the current reconstruction
+/// output instead of comparing against them.
+const REGENERATE_GOLDEN_VAR: &str = "ADDER_REGENERATE_GOLDEN";
+
+/// Compare reconstructed frame bytes against the golden fixture `tests/samples/<fixture>`,
+/// entirely in memory. The fixture is resolved against the crate root rather than the working
+/// directory, so the comparison holds wherever the tests are launched from.
+///
+/// Golden fixtures are raw `u8` frame dumps: frames of `frame_len` bytes back-to-back, each stored
+/// row-major with the color channels interleaved. Every sample is a single byte, so the fixtures
+/// are independent of the host's endianness. Any future fixture with wider samples must be stored
+/// big-endian, to match the byte order of the ADΔER streams they are reconstructed from.
+///
+/// Running the tests with `ADDER_REGENERATE_GOLDEN=1` rewrites the fixture from `actual`. The
+/// reconstruction is fully deterministic given the committed `.adder` input, so regenerating
+/// twice produces identical files.
+fn assert_golden(actual: &[u8], fixture: &str, frame_len: usize) {
+    let golden_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/samples")
+        .join(fixture);
+    assert_eq!(actual.len() % frame_len, 0, "partial reconstructed frame");
+    if std::env::var_os(REGENERATE_GOLDEN_VAR).is_some() {
+        fs::write(&golden_path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read(&golden_path).unwrap();
+    assert_eq!(
+        actual.len() / frame_len,
+        expected.len() / frame_len,
+        "reconstructed frame count differs from {fixture}"
+    );
+    assert_eq!(expected.len() % frame_len, 0, "partial frame in {fixture}");
+    if let Some(idx) = actual.iter().zip(expected.iter()).position(|(a, e)| a != e) {
+        panic!(
+            "reconstruction differs from {fixture} in frame {} at byte {}: got {}, expected {}",
+            idx / frame_len,
+            idx % frame_len,
+            actual[idx],
+            expected[idx]
+        );
+    }
+}
+
 #[test]
 fn read_header() {
     let n: u32 = rand::thread_rng().gen();
@@ -432,7 +468,8 @@ fn test_event_framer_ingest() {
         .time_parameters(50000, 1000, 1000, Some(50.0))
         .mode(INSTANTANEOUS)
         .source(U8, FramedU8)
-        .finish();
+        .finish()
+        .unwrap();
     let mut event: Event = Event {
         coord: Coord {
             x: 5,
@@ -466,7 +503,8 @@ fn test_event_framer_ingest_get_filled() {
         .time_parameters(50000, 1000, 1000, Some(50.0))
         .mode(INSTANTANEOUS)
         .source(U8, FramedU8)
-        .finish();
+        .finish()
+        .unwrap();
 
     for i in 0..5 {
         for j in 0..5 {
@@ -504,7 +542,8 @@ fn get_frame_bytes_eventcoordless() {
         .time_parameters(50000, 1000, 1000, Some(50.0))
         .mode(INSTANTANEOUS)
         .source(U8, FramedU8)
-        .finish();
+        .finish()
+        .unwrap();
     eprintln!("{}", std::mem::size_of::<Option<EventCoordless>>());
     for i in 0..5 {
         for j in 0..5 {
@@ -562,7 +601,8 @@ fn get_frame_bytes_u8() {
         .time_parameters(50000, 1000, 1000, Some(50.0))
         .mode(INSTANTANEOUS)
         .source(U8, FramedU8)
-        .finish();
+        .finish()
+        .unwrap();
 
     for i in 0..5 {
         for j in 0..5 {
@@ -619,7 +659,8 @@ fn get_frame_bytes_u16() {
         .time_parameters(50000, 1000, 1000, Some(50.0))
         .mode(INSTANTANEOUS)
         .source(U8, FramedU8)
-        .finish();
+        .finish()
+        .unwrap();
 
     for i in 0..5 {
         for j in 0..5 {
@@ -675,7 +716,8 @@ fn get_frame_bytes_u32() {
         .time_parameters(50000, 1000, 1000, Some(50.0))
         .mode(INSTANTANEOUS)
         .source(U8, FramedU8)
-        .finish();
+        .finish()
+        .unwrap();
 
     for i in 0..5 {
         for j in 0..5 {
@@ -787,7 +829,8 @@ fn test_get_empty_frame() {
         .time_parameters(50000, 1000, 1000, Some(50.0))
         .mode(INSTANTANEOUS)
         .source(U8, FramedU8)
-        .finish();
+        .finish()
+        .unwrap();
     let n: u32 = rand::thread_rng().gen();
     let path = "./TEST_".to_owned() + n.to_string().as_str() + ".addr";
     let file = File::create(&path).unwrap();
@@ -825,8 +868,7 @@ fn test_sample_unordered() {
     let mut bitreader = BitReader::endian(bufreader, BigEndian);
     let mut reader = Decoder::new_raw(compression, &mut bitreader).unwrap();
 
-    let output_path = Path::new("./tests/samples/temp_sample_3_unordered");
-    let mut output_stream = BufWriter::new(File::create(output_path).unwrap());
+    let mut output_stream: Vec<u8> = Vec::new();
 
     let reconstructed_frame_rate = 60.0;
     // For instantaneous reconstruction, make sure the frame rate matches the source video rate
@@ -845,7 +887,8 @@ fn test_sample_unordered() {
         )
         .mode(INSTANTANEOUS)
         .source(reader.get_source_type(), reader.meta().source_camera)
-        .finish();
+        .finish()
+        .unwrap();
     let mut frame_count = 0;
     loop {
         match reader.digest_event(&mut bitreader) {
@@ -873,19 +916,11 @@ fn test_sample_unordered() {
 
     assert_eq!(frame_count, 405);
 
-    output_stream.flush().unwrap();
-    let output = if !cfg!(target_os = "windows") {
-        Command::new("sh")
-            .arg("-c")
-            .arg("cmp ./temp_sample_3_unordered ./sample_3.gray")
-            .output()
-            .expect("failed to execute process")
-    } else {
-        fs::remove_file(output_path).unwrap();
-        return;
-    };
-    assert_eq!(output.stdout.len(), 0);
-    fs::remove_file(output_path).unwrap();
+    assert_golden(
+        &output_stream,
+        "sample_3.gray",
+        reader.meta().plane.volume(),
+    );
 }
 
 #[test]
@@ -898,8 +933,7 @@ fn test_sample_ordered() {
     let mut bitreader = BitReader::endian(bufreader, BigEndian);
     let mut reader = Decoder::new_raw(compression, &mut bitreader).unwrap();
 
-    let output_path = Path::new("./tests/samples/temp_sample_3_ordered");
-    let mut output_stream = BufWriter::new(File::create(output_path).unwrap());
+    let mut output_stream: Vec<u8> = Vec::new();
 
     let reconstructed_frame_rate = 60.0;
     // For instantaneous reconstruction, make sure the frame rate matches the source video rate
@@ -918,7 +952,8 @@ fn test_sample_ordered() {
         )
         .mode(INSTANTANEOUS)
         .source(reader.get_source_type(), reader.meta().source_camera)
-        .finish();
+        .finish()
+        .unwrap();
     let mut frame_count = 0;
     loop {
         match reader.digest_event(&mut bitreader) {
@@ -946,19 +981,11 @@ fn test_sample_ordered() {
 
     assert_eq!(frame_count, 405);
 
-    output_stream.flush().unwrap();
-    let output = if !cfg!(target_os = "windows") {
-        Command::new("sh")
-            .arg("-c")
-            .arg("cmp ./temp_sample_3_ordered ./sample_3.gray")
-            .output()
-            .expect("failed to execute process")
-    } else {
-        fs::remove_file(output_path).unwrap();
-        return;
-    };
-    assert_eq!(output.stdout.len(), 0);
-    fs::remove_file(output_path).unwrap();
+    assert_golden(
+        &output_stream,
+        "sample_3.gray",
+        reader.meta().plane.volume(),
+    );
 }
 
 // #[test]
@@ -1068,3 +1095,1802 @@ fn array3_test() {
         .collect();
     assert_eq!(ret, vec![1, 1]);
 }
+
+#[test]
+fn test_integration_proportional_energy_conserved() {
+    use adder_codec_core::SourceCamera::Dvs;
+    use adder_codec_rs::framer::driver::FramerMode::INTEGRATION;
+    use adder_codec_rs::framer::driver::IntegrationBoundary;
+
+    let delta_ts: [u32; 6] = [700, 1300, 250, 2000, 999, 1751];
+    let d = 16;
+    let expected_energy = (delta_ts.len() as u64) << d;
+    let total_ticks: u32 = delta_ts.iter().sum();
+
+    for output_fps in [30.0, 70.0, 130.0] {
+        let plane = PlaneSize::new(1, 1, 1).unwrap();
+        let mut frame_sequence: FrameSequence<u32> = FramerBuilder::new(plane, 64)
+            .codec_version(2, DeltaT)
+            .time_parameters(10000, 1000, 10000, Some(output_fps))
+            .mode(INTEGRATION)
+            .integration_boundary(IntegrationBoundary::Proportional)
+            .source(U32, Dvs)
+            .finish()
+            .unwrap();
+
+        for delta_t in delta_ts {
+            let mut event = Event {
+                coord: Coord::new(0, 0, None),
+                d,
+                t: delta_t,
+            };
+            frame_sequence.ingest_event(&mut event, None);
+        }
+
+        let tpf = frame_sequence.state.tpf;
+        let num_frames = (total_ticks + tpf - 1) / tpf;
+        let mut total_energy = 0_u64;
+        for _ in 0..num_frames {
+            let frame = frame_sequence.pop_next_frame().unwrap();
+            total_energy += u64::from(frame[0][[0, 0, 0]].unwrap_or(0));
+        }
+
+        // Each frame's contribution is truncated to an integer, so allow one unit of error per
+        // frame and per split
+        let tolerance = u64::from(num_frames) + delta_ts.len() as u64;
+        assert!(
+            expected_energy.abs_diff(total_energy) <= tolerance,
+            "fps {output_fps}: expected {expected_energy}, got {total_energy}"
+        );
+    }
+}
+
+#[test]
+fn test_last_chunk_color_channels() {
+    let plane = PlaneSize::new(5, 10, 3).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 4)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+    assert_eq!(frame_sequence.get_frame_chunks_num(), 3);
+
+    // Row 9 lands in the last chunk, which only holds 2 rows
+    let mut event = Event {
+        coord: Coord::new(3, 9, Some(2)),
+        d: 5,
+        t: 1000,
+    };
+    frame_sequence.ingest_event(&mut event, None);
+
+    assert_eq!(*frame_sequence.px_at_frame(9, 3, 2, 0).unwrap(), Some(32));
+    assert_eq!(*frame_sequence.px_at_frame(9, 3, 1, 0).unwrap(), None);
+    assert_eq!(*frame_sequence.px_at_frame(8, 4, 2, 0).unwrap(), None);
+    assert!(frame_sequence.px_at_frame(9, 3, 2, 1).is_err());
+}
+
+#[test]
+fn test_px_at_frame_out_of_bounds() {
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+
+    let plane = PlaneSize::new(5, 10, 3).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 4)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+    let mut event = Event {
+        coord: Coord::new(4, 9, Some(2)),
+        d: 5,
+        t: 1000,
+    };
+    frame_sequence.ingest_event(&mut event, None);
+    assert_eq!(*frame_sequence.px_at_frame(9, 4, 2, 0).unwrap(), Some(32));
+
+    // Out-of-range x, c, and y are reported rather than panicking
+    assert!(matches!(
+        frame_sequence.px_at_frame(9, 5, 2, 0),
+        Err(FrameSequenceError::InvalidIndex)
+    ));
+    assert!(matches!(
+        frame_sequence.px_at_frame(9, 4, 3, 0),
+        Err(FrameSequenceError::InvalidIndex)
+    ));
+    assert!(matches!(
+        frame_sequence.px_at_frame(10, 4, 2, 0),
+        Err(FrameSequenceError::InvalidIndex)
+    ));
+}
+
+#[test]
+fn test_channel_layout() {
+    use adder_codec_rs::framer::driver::ChannelLayout;
+
+    let plane = PlaneSize::new(2, 2, 3).unwrap();
+    for layout in [ChannelLayout::Interleaved, ChannelLayout::Planar] {
+        let mut frame_sequence: FrameSequence<u16> = FramerBuilder::new(plane, 1)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 1000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U16, FramedU8)
+            .channel_layout(layout)
+            .finish()
+            .unwrap();
+
+        // Give every pixel channel a distinct value
+        for y in 0..2 {
+            for x in 0..2 {
+                for c in 0..3 {
+                    let mut event = Event {
+                        coord: Coord::new(x, y, Some(c)),
+                        d: c * 4 + y as u8 * 2 + x as u8,
+                        t: 1000,
+                    };
+                    frame_sequence.ingest_event(&mut event, None);
+                }
+            }
+        }
+
+        let px = |y: usize, x: usize, c: usize| -> u16 {
+            frame_sequence.px_at_frame(y, x, c, 0).unwrap().unwrap()
+        };
+        let mut expected = Vec::new();
+        match layout {
+            ChannelLayout::Interleaved => {
+                for y in 0..2 {
+                    for x in 0..2 {
+                        for c in 0..3 {
+                            expected.push(px(y, x, c));
+                        }
+                    }
+                }
+            }
+            ChannelLayout::Planar => {
+                for c in 0..3 {
+                    for y in 0..2 {
+                        for x in 0..2 {
+                            expected.push(px(y, x, c));
+                        }
+                    }
+                }
+            }
+        }
+
+        let n: u32 = rand::thread_rng().gen();
+        let path = "./TEST_".to_owned() + n.to_string().as_str() + ".addr";
+        let mut output_writer = BufWriter::new(File::create(&path).unwrap());
+        frame_sequence
+            .write_frame_bytes(&mut output_writer)
+            .unwrap();
+        output_writer.flush().unwrap();
+        drop(output_writer);
+
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let written: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .collect();
+        assert_eq!(written, expected);
+    }
+}
+
+#[test]
+fn test_reconstruct_frames_in_memory() {
+    use adder_codec_rs::framer::reconstruct::reconstruct_frames;
+
+    let input_path = "./tests/samples/sample_1_raw_events.adder";
+    let bufreader = BufReader::new(File::open(input_path).unwrap());
+    let mut bitreader = BitReader::endian(bufreader, BigEndian);
+    let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+    let plane = reader.meta().plane;
+
+    let frames = reconstruct_frames(&mut reader, &mut bitreader, 24.0).unwrap();
+    assert_eq!(frames.len(), 221);
+    assert_eq!(
+        frames[0].shape(),
+        &[plane.h_usize(), plane.w_usize(), plane.c_usize()]
+    );
+
+    let golden = fs::read("./tests/samples/sample_1_instant_framed.gray").unwrap();
+    let first_frame: Vec<u8> = frames[0].iter().copied().collect();
+    assert_eq!(first_frame, golden[..plane.volume()]);
+}
+
+#[test]
+fn test_reconstruct_pixel() {
+    use adder_codec_core::codec::raw::stream::RawOutput;
+    use adder_codec_core::codec::CodecMetadata;
+    use adder_codec_rs::framer::reconstruct::reconstruct_pixel;
+    use std::io::Cursor;
+
+    let plane = PlaneSize::new(2, 2, 1).unwrap();
+    let mut encoder = Encoder::new_raw(
+        RawOutput::new(
+            CodecMetadata {
+                plane,
+                tps: 1000,
+                ref_interval: 100,
+                delta_t_max: 1000,
+                time_mode: DeltaT,
+                ..Default::default()
+            },
+            Vec::new(),
+        ),
+        EncoderOptions::default(plane),
+    );
+    let event = |x: u16, y: u16, d: u8, t: u32| Event {
+        coord: Coord::new(x, y, None),
+        d,
+        t,
+    };
+    encoder
+        .ingest_events(&[
+            event(1, 0, 7, 100),
+            event(0, 0, 3, 400),
+            event(1, 0, 6, 200),
+            event(1, 1, 8, 300),
+            event(1, 0, 8, 100),
+        ])
+        .unwrap();
+    let output = encoder.close_writer().unwrap().unwrap();
+
+    let decode = |x: u16, y: u16, c: u8| {
+        let mut bitreader = BitReader::endian(Cursor::new(output.clone()), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        reconstruct_pixel(&mut reader, &mut bitreader, x, y, c, 10.0)
+    };
+
+    // With 100 ticks per reference interval, each value is 2^d / Δt * 100, saturating at 255
+    assert_eq!(decode(1, 0, 0).unwrap(), vec![128, 32, 32, 255]);
+    assert_eq!(decode(0, 0, 0).unwrap(), vec![2; 4]);
+    assert!(decode(0, 1, 0).unwrap().is_empty());
+    assert!(decode(2, 0, 0).is_err());
+    assert!(decode(0, 0, 1).is_err());
+
+    // The same events with absolute timestamps, rebased partway through, give the same timeline
+    let mut encoder = Encoder::new_raw(
+        RawOutput::new(
+            CodecMetadata {
+                plane,
+                tps: 1000,
+                ref_interval: 100,
+                delta_t_max: 1000,
+                time_mode: TimeMode::AbsoluteT,
+                ..Default::default()
+            },
+            Vec::new(),
+        ),
+        EncoderOptions::default(plane),
+    );
+    encoder
+        .ingest_events(&[
+            event(1, 0, 7, 100),
+            event(0, 0, 3, 400),
+            event(1, 0, 6, 300),
+        ])
+        .unwrap();
+    encoder.rebase(200).unwrap();
+    encoder
+        .ingest_events(&[event(1, 1, 8, 100), event(1, 0, 8, 200)])
+        .unwrap();
+    let output = encoder.close_writer().unwrap().unwrap();
+    let mut bitreader = BitReader::endian(Cursor::new(output), BigEndian);
+    let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+    assert_eq!(
+        reconstruct_pixel(&mut reader, &mut bitreader, 1, 0, 0, 10.0).unwrap(),
+        vec![128, 32, 32, 255]
+    );
+}
+
+#[cfg(feature = "mp4-export")]
+#[test]
+fn test_write_mp4() {
+    use adder_codec_rs::framer::reconstruct::write_mp4;
+    use ndarray::Array3;
+    use video_rs_adder_dep::{Decoder as VideoDecoder, Locator};
+
+    for channels in [1, 3] {
+        let frames: Vec<Array3<u8>> = (0..10)
+            .map(|i| {
+                Array3::from_shape_fn((32, 48, channels), |(y, x, c)| {
+                    (x * 4 + y * 2 + c * 30 + i * 10) as u8
+                })
+            })
+            .collect();
+        let path = std::env::temp_dir().join(format!("adder_write_mp4_{channels}.mp4"));
+        write_mp4(&frames, &path, 30.0).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() > 0);
+
+        // The container can be demuxed and decoded back to the same number of frames
+        let mut decoder = VideoDecoder::new(&Locator::Path(path.clone())).unwrap();
+        assert_eq!(decoder.size(), (48, 32));
+        let decoded = decoder.decode_iter().take_while(Result::is_ok).count();
+        assert_eq!(decoded, frames.len());
+        fs::remove_file(&path).unwrap();
+    }
+
+    // Odd dimensions can't be encoded as YUV 4:2:0
+    let odd = vec![Array3::<u8>::zeros((31, 48, 1))];
+    let path = std::env::temp_dir().join("adder_write_mp4_odd.mp4");
+    assert!(write_mp4(&odd, &path, 30.0).is_err());
+    assert!(write_mp4(&[], &path, 30.0).is_err());
+}
+
+#[test]
+fn test_reconstruct_segment_slow_motion() {
+    use adder_codec_rs::framer::reconstruct::reconstruct_segment;
+
+    let input_path = "./tests/samples/sample_1_raw_events.adder";
+    let bufreader = BufReader::new(File::open(input_path).unwrap());
+    let mut bitreader = BitReader::endian(bufreader, BigEndian);
+    let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+    let plane = reader.meta().plane;
+    let tps = u64::from(reader.meta().tps);
+
+    // A 0.1-second segment, starting 1 second in, at 1000 fps
+    let t_start = tps;
+    let t_end = tps + tps / 10;
+    let frames = reconstruct_segment(&mut reader, &mut bitreader, t_start, t_end, 1000.0).unwrap();
+    assert_eq!(frames.len(), 100);
+    assert_eq!(
+        frames[0].shape(),
+        &[plane.h_usize(), plane.w_usize(), plane.c_usize()]
+    );
+}
+
+#[test]
+fn test_integration_overflow_modes() {
+    use adder_codec_core::SourceCamera::Dvs;
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+    use adder_codec_rs::framer::driver::FramerMode::INTEGRATION;
+    use adder_codec_rs::framer::driver::OverflowMode;
+
+    let plane = PlaneSize::new(1, 1, 1).unwrap();
+    let new_sequence = |overflow_mode: OverflowMode| -> FrameSequence<u8> {
+        FramerBuilder::new(plane, 64)
+            .codec_version(2, DeltaT)
+            .time_parameters(10000, 1000, 10000, Some(10.0))
+            .mode(INTEGRATION)
+            .overflow_mode(overflow_mode)
+            .source(U8, Dvs)
+            .finish()
+            .unwrap()
+    };
+
+    // Each event integrates to exactly 128 within the first frame, so two of them overflow a u8
+    let event = || Event {
+        coord: Coord::new(0, 0, None),
+        d: 7,
+        t: 128,
+    };
+
+    // Saturate caps the accumulated intensity at u8::MAX
+    let mut frame_sequence = new_sequence(OverflowMode::Saturate);
+    assert!(frame_sequence.try_ingest_event(&mut event(), None).is_ok());
+    assert!(frame_sequence.try_ingest_event(&mut event(), None).is_ok());
+    assert_eq!(*frame_sequence.px_at_frame(0, 0, 0, 0).unwrap(), Some(255));
+
+    // Error reports the overflow and leaves the pixel at its last in-range value
+    let mut frame_sequence = new_sequence(OverflowMode::Error);
+    assert!(frame_sequence.try_ingest_event(&mut event(), None).is_ok());
+    assert!(matches!(
+        frame_sequence.try_ingest_event(&mut event(), None),
+        Err(FrameSequenceError::IntensityOverflow)
+    ));
+    assert_eq!(*frame_sequence.px_at_frame(0, 0, 0, 0).unwrap(), Some(128));
+    // The overflow is only reported once
+    assert!(frame_sequence.check_overflow().is_ok());
+
+    // Wrap is modular arithmetic: 128 + 128 = 256 = 0 (mod 256)
+    let mut frame_sequence = new_sequence(OverflowMode::Wrap);
+    assert!(frame_sequence.try_ingest_event(&mut event(), None).is_ok());
+    assert!(frame_sequence.try_ingest_event(&mut event(), None).is_ok());
+    assert_eq!(*frame_sequence.px_at_frame(0, 0, 0, 0).unwrap(), Some(0));
+}
+
+#[test]
+fn test_integration_event_coordless() {
+    use adder_codec_core::SourceCamera::Dvs;
+    use adder_codec_rs::framer::driver::FramerMode::INTEGRATION;
+
+    let plane = PlaneSize::new(1, 1, 1).unwrap();
+    let mut frame_sequence: FrameSequence<EventCoordless> = FramerBuilder::new(plane, 64)
+        .codec_version(2, DeltaT)
+        .time_parameters(10000, 1000, 10000, Some(10.0))
+        .mode(INTEGRATION)
+        .source(U8, Dvs)
+        .finish()
+        .unwrap();
+
+    // Both events land in the first frame, so they're combined into one spanning both
+    for _ in 0..2 {
+        let mut event = Event {
+            coord: Coord::new(0, 0, None),
+            d: 7,
+            t: 128,
+        };
+        assert!(frame_sequence.try_ingest_event(&mut event, None).is_ok());
+    }
+
+    // 2^7 + 2^7 = 2^8
+    assert_eq!(
+        *frame_sequence.px_at_frame(0, 0, 0, 0).unwrap(),
+        Some(EventCoordless { d: 8, t: 256 })
+    );
+}
+
+#[test]
+fn test_collapse_mode_reconstruction() {
+    use adder_codec_core::codec::raw::stream::RawOutput;
+    use adder_codec_core::codec::{CodecMetadata, LATEST_CODEC_VERSION};
+    use adder_codec_core::D_EMPTY;
+    use std::io::Cursor;
+
+    let plane = PlaneSize::new(1, 1, 1).unwrap();
+    let ref_interval = 100;
+
+    // In Collapse mode, each collapsed event is followed by an empty event carrying the pixel's
+    // absolute running time, at the end of the delta_t_max span
+    let source_frames: Vec<u8> = [100, 200, 100]
+        .iter()
+        .flat_map(|v| std::iter::repeat(*v).take(4))
+        .collect();
+    let mut events = Vec::new();
+    for (segment, d) in [8, 9, 8].into_iter().enumerate() {
+        events.push(Event {
+            coord: Coord::new(0, 0, None),
+            d,
+            t: 256,
+        });
+        events.push(Event {
+            coord: Coord::new(0, 0, None),
+            d: D_EMPTY,
+            t: (segment as u32 + 1) * 4 * ref_interval,
+        });
+    }
+
+    let meta = CodecMetadata {
+        codec_version: LATEST_CODEC_VERSION,
+        header_size: 0,
+        time_mode: TimeMode::DeltaT,
+        plane,
+        tps: ref_interval * 30,
+        ref_interval,
+        delta_t_max: ref_interval * 4,
+        event_size: 0,
+        source_camera: FramedU8,
+        adu_interval: 1,
+        delta_t_encoding: Default::default(),
+        pixel_multi_mode: PixelMultiMode::Collapse,
+        t_fraction_bits: 0,
+    };
+    let mut encoder = Encoder::new_raw(
+        RawOutput::new(meta, BufWriter::new(Vec::new())),
+        EncoderOptions::default(plane),
+    );
+    encoder.ingest_events(&events).unwrap();
+    let bytes = encoder
+        .close_writer()
+        .unwrap()
+        .unwrap()
+        .into_inner()
+        .unwrap();
+
+    let reconstruction_error = |pixel_multi_mode: Option<PixelMultiMode>| -> u64 {
+        let mut bitreader = BitReader::endian(Cursor::new(bytes.clone()), BigEndian);
+        let mut reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        assert_eq!(reader.meta().pixel_multi_mode, PixelMultiMode::Collapse);
+
+        let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 64)
+            .codec_version(reader.meta().codec_version, reader.meta().time_mode)
+            .time_parameters(
+                reader.meta().tps,
+                reader.meta().ref_interval,
+                reader.meta().delta_t_max,
+                Some(30.0),
+            )
+            .mode(INSTANTANEOUS)
+            .source(reader.get_source_type(), reader.meta().source_camera)
+            .pixel_multi_mode(pixel_multi_mode.unwrap_or(reader.meta().pixel_multi_mode))
+            .finish()
+            .unwrap();
+        while let Ok(mut event) = reader.digest_event(&mut bitreader) {
+            frame_sequence.ingest_event(&mut event, None);
+        }
+
+        let mut error = 0;
+        for source_val in &source_frames {
+            let frame = frame_sequence.pop_next_frame().unwrap();
+            let val = frame[0][[0, 0, 0]].unwrap_or(0);
+            error += u64::from(val.abs_diff(*source_val)).pow(2);
+        }
+        error
+    };
+
+    // Using the mode from the header reconstructs the source exactly
+    let aware_error = reconstruction_error(None);
+    let ignored_error = reconstruction_error(Some(PixelMultiMode::Normal));
+    assert_eq!(aware_error, 0);
+    assert!(aware_error < ignored_error);
+}
+
+#[test]
+fn test_12_bit_source_intensity() {
+    use adder_codec_core::SourceCamera::FramedU16;
+
+    // Events for a synthetic 12-bit frame, integrated over one 1000-tick input interval:
+    // a full-scale pixel (~4095) and a half-scale pixel (2048)
+    let frame_values = |bit_depth: Option<u8>| -> (u8, u8) {
+        let plane = PlaneSize::new(2, 1, 1).unwrap();
+        let mut builder = FramerBuilder::new(plane, 64)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 1000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U16, FramedU16);
+        if let Some(bit_depth) = bit_depth {
+            builder = builder.source_bit_depth(bit_depth);
+        }
+        let mut frame_sequence: FrameSequence<u8> = builder.finish().unwrap();
+
+        for (x, d) in [(0, 12), (1, 11)] {
+            let mut event = Event {
+                coord: Coord::new(x, 0, None),
+                d,
+                t: 1000,
+            };
+            frame_sequence.ingest_event(&mut event, None);
+        }
+        (
+            frame_sequence.px_at_frame(0, 0, 0, 0).unwrap().unwrap(),
+            frame_sequence.px_at_frame(0, 1, 0, 0).unwrap().unwrap(),
+        )
+    };
+
+    // Declaring the 12-bit depth maps the full 12-bit range onto the full output range
+    assert_eq!(frame_values(Some(12)), (255, 127));
+
+    // Treating the data as full 16-bit crushes it into the bottom of the output range
+    let (full, half) = frame_values(None);
+    assert!(full <= 16);
+    assert!(half <= 8);
+}
+
+#[test]
+fn test_frames_approx_equal() {
+    let plane = PlaneSize::new(2, 2, 1).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 1)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+
+    for (x, y) in [(0, 0), (1, 0), (0, 1)] {
+        let mut event = Event {
+            coord: Coord::new(x, y, None),
+            d: 5,
+            t: 1000,
+        };
+        frame_sequence.ingest_event(&mut event, None);
+    }
+
+    let mut other = frame_sequence.clone();
+    assert!(frame_sequence.frames_approx_equal(&other, 0.0));
+
+    let mut event = Event {
+        coord: Coord::new(1, 1, None),
+        d: 5,
+        t: 1000,
+    };
+    frame_sequence.ingest_event(&mut event, None);
+    let mut event = Event {
+        coord: Coord::new(1, 1, None),
+        d: 6,
+        t: 1000,
+    };
+    other.ingest_event(&mut event, None);
+
+    assert!(!frame_sequence.frames_approx_equal(&other, 0.0));
+    assert!(frame_sequence.frames_approx_equal(&other, 255.0));
+}
+
+#[test]
+fn test_frame_energy() {
+    let plane = PlaneSize::new(3, 4, 1).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 2)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+
+    let mut ingest = |x, y| {
+        let mut event = Event {
+            coord: Coord::new(x, y, None),
+            d: 5,
+            t: 1000,
+        };
+        frame_sequence.ingest_event(&mut event, None);
+    };
+    for y in 0..4 {
+        for x in 0..3 {
+            if (x, y) != (2, 3) {
+                ingest(x, y);
+            }
+        }
+    }
+
+    // The unset pixel counts as 0
+    assert_eq!(frame_sequence.frame_energy(0).unwrap(), 11.0 * 32.0);
+
+    // A uniformly-lit frame
+    let mut event = Event {
+        coord: Coord::new(2, 3, None),
+        d: 5,
+        t: 1000,
+    };
+    frame_sequence.ingest_event(&mut event, None);
+    assert_eq!(
+        frame_sequence.frame_energy(0).unwrap(),
+        plane.volume() as f64 * 32.0
+    );
+
+    assert!(frame_sequence.frame_energy(100).is_err());
+}
+
+#[test]
+fn test_snapshot_restore() {
+    let plane = PlaneSize::new(3, 4, 1).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 2)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+
+    let mut event = Event {
+        coord: Coord::new(0, 0, None),
+        d: 5,
+        t: 1000,
+    };
+    frame_sequence.ingest_event(&mut event, None);
+    let before = frame_sequence.clone();
+    let snap = frame_sequence.snapshot();
+
+    let events = [(1, 0, 6, 1000), (0, 0, 4, 2500), (2, 3, 7, 1000)];
+    let replay = |frame_sequence: &mut FrameSequence<u8>| {
+        for (x, y, d, t) in events {
+            let mut event = Event {
+                coord: Coord::new(x, y, None),
+                d,
+                t,
+            };
+            frame_sequence.ingest_event(&mut event, None);
+        }
+    };
+    replay(&mut frame_sequence);
+    let first = frame_sequence.clone();
+    assert!(!frame_sequence.frames_approx_equal(&before, 0.0));
+
+    frame_sequence.restore(snap);
+    assert!(frame_sequence.frames_approx_equal(&before, 0.0));
+
+    replay(&mut frame_sequence);
+    assert!(frame_sequence.frames_approx_equal(&first, 0.0));
+    assert_eq!(frame_sequence.get_frames_len(), first.get_frames_len());
+    assert_eq!(
+        frame_sequence.pop_next_frame(),
+        first.clone().pop_next_frame()
+    );
+}
+
+#[test]
+fn test_force_pop_policy() {
+    use adder_codec_rs::framer::driver::{ForcePopPolicy, FrameSequenceError};
+
+    let plane = PlaneSize::new(2, 2, 1).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 1)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+
+    // Fill the first frame completely
+    for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        let mut event = Event {
+            coord: Coord::new(x, y, None),
+            d: 6,
+            t: 1000,
+        };
+        frame_sequence.ingest_event(&mut event, None);
+    }
+    assert!(frame_sequence.is_frame_filled(0).unwrap());
+    frame_sequence.pop_next_frame().unwrap();
+
+    // Only fill the top row of the second frame
+    for x in [0, 1] {
+        let mut event = Event {
+            coord: Coord::new(x, 0, None),
+            d: 5,
+            t: 1000,
+        };
+        frame_sequence.ingest_event(&mut event, None);
+    }
+    assert!(!frame_sequence.is_frame_filled(0).unwrap());
+
+    let force_pop = |policy| frame_sequence.clone().force_pop_next_frame(policy);
+
+    let frame = force_pop(ForcePopPolicy::FillWithLast).unwrap();
+    assert_eq!(frame[0][[0, 0, 0]], Some(32));
+    assert_eq!(frame[1][[0, 0, 0]], Some(64));
+    assert_eq!(frame[1][[0, 1, 0]], Some(64));
+
+    let frame = force_pop(ForcePopPolicy::FillWithZero).unwrap();
+    assert_eq!(frame[0][[0, 1, 0]], Some(32));
+    assert_eq!(frame[1][[0, 0, 0]], Some(0));
+    assert_eq!(frame[1][[0, 1, 0]], Some(0));
+
+    let mut errored = frame_sequence.clone();
+    assert!(matches!(
+        errored.force_pop_next_frame(ForcePopPolicy::Error),
+        Err(FrameSequenceError::UnfilledFrame)
+    ));
+    assert!(errored.frames_approx_equal(&frame_sequence, 0.0));
+}
+
+#[test]
+fn test_framer_invalid_chunk_rows() {
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+
+    let plane = PlaneSize::new(2, 1, 1).unwrap();
+    let frame_sequence = FramerBuilder::new(plane, 0)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish::<u8>();
+    assert!(matches!(
+        frame_sequence,
+        Err(FrameSequenceError::InvalidChunkRows)
+    ));
+}
+
+#[test]
+fn test_framer_time_params() {
+    use adder_codec_core::{TimeParams, TimeParamsError};
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+
+    let plane = PlaneSize::new(2, 1, 1).unwrap();
+    let new_builder = || {
+        FramerBuilder::new(plane, 1)
+            .codec_version(1, TimeMode::DeltaT)
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8)
+    };
+
+    let params = TimeParams::from_fps(50.0, 1000, 1).unwrap();
+    let from_params: FrameSequence<u8> = new_builder()
+        .time_params(params, Some(50.0))
+        .finish()
+        .unwrap();
+    let from_raw: FrameSequence<u8> = new_builder()
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .finish()
+        .unwrap();
+    assert_eq!(from_params.state.tpf, from_raw.state.tpf);
+    assert_eq!(from_params.state.tpf, params.tpf(Some(50.0)));
+
+    // Δt_max can't be shorter than the reference interval
+    let frame_sequence = new_builder()
+        .time_parameters(50000, 1000, 999, Some(50.0))
+        .finish::<u8>();
+    assert!(matches!(
+        frame_sequence,
+        Err(FrameSequenceError::InvalidTimeParams(
+            TimeParamsError::DeltaTMaxTooSmall { .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_max_pending_frames() {
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+
+    let plane = PlaneSize::new(2, 1, 1).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 1)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .max_pending_frames(4)
+        .finish()
+        .unwrap();
+
+    let mut event = Event {
+        coord: Coord::new(0, 0, None),
+        d: 5,
+        t: 1000,
+    };
+    assert!(frame_sequence.try_ingest_event(&mut event, None).is_ok());
+
+    // An event spanning 10 more frames would grow the buffer past the cap
+    let mut event = Event {
+        coord: Coord::new(0, 0, None),
+        d: 5,
+        t: 10_000,
+    };
+    assert!(matches!(
+        frame_sequence.try_ingest_event(&mut event, None),
+        Err(FrameSequenceError::TooManyPendingFrames)
+    ));
+    assert_eq!(*frame_sequence.px_at_frame(0, 0, 0, 0).unwrap(), Some(32));
+    assert_eq!(*frame_sequence.px_at_frame(0, 0, 0, 3).unwrap(), Some(3));
+    assert!(frame_sequence.px_at_frame(0, 0, 0, 4).is_err());
+
+    // The status resets once reported
+    assert!(frame_sequence.check_pending_frames().is_ok());
+
+    // The frames past the cap are filled by the pixel's later events, so frames keep completing
+    // as the other pixel catches up
+    let mut popped = 0;
+    for _ in 0..20 {
+        for x in 0..2 {
+            let mut event = Event {
+                coord: Coord::new(x, 0, None),
+                d: 5,
+                t: 1000,
+            };
+            frame_sequence.ingest_event(&mut event, None);
+        }
+        while frame_sequence.is_frame_0_filled() {
+            frame_sequence.pop_next_frame().unwrap();
+            popped += 1;
+        }
+    }
+    assert!(popped > 10);
+}
+
+#[test]
+fn test_max_pending_frames_zero() {
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+
+    let plane = PlaneSize::new(2, 1, 1).unwrap();
+    let frame_sequence = FramerBuilder::new(plane, 1)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .max_pending_frames(0)
+        .finish::<u8>();
+    assert!(matches!(
+        frame_sequence,
+        Err(FrameSequenceError::InvalidMaxPendingFrames)
+    ));
+}
+
+#[test]
+fn test_pop_next_frame_nhwc() {
+    let plane = PlaneSize::new(3, 4, 1).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 2)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+
+    // Write one pixel in each chunk; the rest are left empty
+    for (x, y) in [(0, 0), (2, 3)] {
+        let mut event = Event {
+            coord: Coord::new(x, y, None),
+            d: 5,
+            t: 1000,
+        };
+        frame_sequence.ingest_event(&mut event, None);
+    }
+
+    let tensor = frame_sequence.pop_next_frame_nhwc().unwrap();
+    assert_eq!(tensor.shape(), &[1, 4, 3, 1]);
+    assert!(tensor.iter().all(|v| (0.0..=1.0).contains(v)));
+    assert!((tensor[[0, 0, 0, 0]] - 32.0 / 255.0).abs() < f32::EPSILON);
+    assert!((tensor[[0, 3, 2, 0]] - 32.0 / 255.0).abs() < f32::EPSILON);
+    assert_eq!(tensor[[0, 1, 1, 0]], 0.0);
+}
+
+#[test]
+fn test_peek_frame() {
+    let plane = PlaneSize::new(3, 4, 1).unwrap();
+    let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 2)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+
+    for (x, y, d) in [(0, 0, 5), (2, 3, 6)] {
+        let mut event = Event {
+            coord: Coord::new(x, y, None),
+            d,
+            t: 1000,
+        };
+        frame_sequence.ingest_event(&mut event, None);
+    }
+
+    let peeked: Vec<Array3<Option<u8>>> = frame_sequence
+        .peek_frame(0)
+        .unwrap()
+        .into_iter()
+        .cloned()
+        .collect();
+    assert_eq!(peeked.len(), 2);
+    assert_eq!(peeked[0][[0, 0, 0]], Some(32));
+    assert_eq!(peeked[1][[1, 2, 0]], Some(64));
+
+    // Peeking doesn't consume the frame
+    assert_eq!(
+        frame_sequence.peek_frame(0).unwrap(),
+        peeked.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(frame_sequence.pop_next_frame().unwrap(), peeked);
+    assert!(frame_sequence.peek_frame(100).is_none());
+}
+
+#[test]
+fn test_diff_streams() {
+    use adder_codec_core::D_MAX;
+    use adder_codec_rs::framer::reconstruct::diff_streams;
+    use std::io::Cursor;
+
+    let input_path = "./tests/samples/sample_1_raw_events.adder";
+    let open = |bytes: Vec<u8>| {
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let reader = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        (reader, bitreader)
+    };
+    let original = fs::read(input_path).unwrap();
+
+    // A stream doesn't diverge from itself
+    let (mut a, mut a_bitreader) = open(original.clone());
+    let (mut b, mut b_bitreader) = open(original.clone());
+    let report = diff_streams(&mut a, &mut a_bitreader, &mut b, &mut b_bitreader, 24.0).unwrap();
+    assert!(report.frames_compared > 0);
+    assert_eq!(report.mean_abs_diff, 0.0);
+    assert_eq!(report.max_abs_diff, 0);
+    assert_eq!(report.max_divergence_frame, None);
+
+    // Double the intensity of every 10th event, by incrementing its d
+    let meta = *a.meta();
+    let event_size = meta.event_size as usize;
+    let d_offset = event_size - 5; // d is followed by a 4-byte timestamp
+    let mut perturbed = original.clone();
+    for event in perturbed[meta.header_size..]
+        .chunks_exact_mut(event_size)
+        .step_by(10)
+    {
+        if event[d_offset] < D_MAX {
+            event[d_offset] += 1;
+        }
+    }
+
+    let (mut a, mut a_bitreader) = open(original);
+    let (mut b, mut b_bitreader) = open(perturbed);
+    let report = diff_streams(&mut a, &mut a_bitreader, &mut b, &mut b_bitreader, 24.0).unwrap();
+    assert!(report.mean_abs_diff > 0.0);
+    assert!(report.max_abs_diff > 0);
+    assert!(report.max_divergence_frame.unwrap() < report.frames_compared);
+}
+
+#[test]
+fn test_boundary_mode() {
+    use adder_codec_rs::framer::driver::BoundaryMode;
+
+    let plane = PlaneSize::new(1, 1, 1).unwrap();
+    for boundary_mode in [BoundaryMode::Inclusive, BoundaryMode::Exclusive] {
+        let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(plane, 1)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 1000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .boundary_mode(boundary_mode)
+            .source(U8, FramedU8)
+            .finish()
+            .unwrap();
+        assert_eq!(frame_sequence.state.tpf, 1000);
+
+        // The event ends exactly on the boundary between frames 0 and 1
+        let mut event = Event {
+            coord: Coord::new(0, 0, None),
+            d: 5,
+            t: 1000,
+        };
+        frame_sequence.ingest_event(&mut event, None);
+
+        assert_eq!(*frame_sequence.px_at_frame(0, 0, 0, 0).unwrap(), Some(32));
+        match boundary_mode {
+            BoundaryMode::Inclusive => assert!(frame_sequence.px_at_frame(0, 0, 0, 1).is_err()),
+            BoundaryMode::Exclusive => {
+                assert_eq!(*frame_sequence.px_at_frame(0, 0, 0, 1).unwrap(), Some(32));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_min_fill_fraction() {
+    let plane = PlaneSize::new(20, 10, 1).unwrap();
+    for min_fill_fraction in [None, Some(0.99)] {
+        let mut builder = FramerBuilder::new(plane, 4)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 1000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8);
+        if let Some(fraction) = min_fill_fraction {
+            builder = builder.min_fill_fraction(fraction);
+        }
+        let mut frame_sequence: FrameSequence<u8> = builder.finish().unwrap();
+
+        // Every pixel except (0, 0) fires
+        let mut filled = false;
+        for y in 0..10 {
+            for x in 0..20 {
+                if (x, y) == (0, 0) {
+                    continue;
+                }
+                let mut event = Event {
+                    coord: Coord::new(x, y, None),
+                    d: 5,
+                    t: 1000,
+                };
+                filled = frame_sequence.ingest_event(&mut event, None);
+            }
+        }
+
+        match min_fill_fraction {
+            None => assert!(!filled),
+            Some(_) => {
+                assert!(filled);
+                let frame = frame_sequence.pop_next_frame().unwrap();
+                assert_eq!(frame[0][[0, 0, 0]], Some(0));
+                assert_eq!(frame[0][[0, 1, 0]], Some(32));
+                assert!(frame.iter().all(|chunk| chunk.iter().all(Option::is_some)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_min_fill_fraction_write_multi_frame_bytes() {
+    let plane = PlaneSize::new(20, 10, 1).unwrap();
+    for min_fill_fraction in [None, Some(0.99)] {
+        let mut builder = FramerBuilder::new(plane, 4)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 1000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8);
+        if let Some(fraction) = min_fill_fraction {
+            builder = builder.min_fill_fraction(fraction);
+        }
+        let mut frame_sequence: FrameSequence<u8> = builder.finish().unwrap();
+
+        // Every pixel except (0, 0) fires
+        for y in 0..10 {
+            for x in 0..20 {
+                if (x, y) == (0, 0) {
+                    continue;
+                }
+                let mut event = Event {
+                    coord: Coord::new(x, y, None),
+                    d: 5,
+                    t: 1000,
+                };
+                frame_sequence.ingest_event(&mut event, None);
+            }
+        }
+
+        let mut bytes = Vec::new();
+        let written = frame_sequence.write_multi_frame_bytes(&mut bytes).unwrap();
+        match min_fill_fraction {
+            None => {
+                assert_eq!(written, 0);
+                assert!(bytes.is_empty());
+            }
+            Some(_) => {
+                assert_eq!(written, 1);
+                assert_eq!(bytes.len(), 200);
+                assert_eq!(bytes[0], 0);
+                assert_eq!(bytes[1], 32);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_min_fill_fraction_invalid() {
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+
+    let plane = PlaneSize::new(2, 1, 1).unwrap();
+    for fraction in [0.0, -0.5, 1.5, f32::NAN] {
+        let frame_sequence = FramerBuilder::new(plane, 1)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 1000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8)
+            .min_fill_fraction(fraction)
+            .finish::<u8>();
+        assert!(matches!(
+            frame_sequence,
+            Err(FrameSequenceError::InvalidFillFraction)
+        ));
+    }
+}
+
+#[test]
+fn test_suggest_chunk_rows() {
+    use adder_codec_rs::framer::driver::suggest_chunk_rows;
+
+    let plane = PlaneSize::new(1920, 1080, 1).unwrap();
+    let rows = suggest_chunk_rows(plane, 256 * 1024, 1);
+    assert!((2..=16).contains(&rows), "{rows}");
+
+    // Wider elements and more channels need fewer rows
+    let color = PlaneSize::new(1920, 1080, 3).unwrap();
+    assert!(suggest_chunk_rows(color, 256 * 1024, 4) < rows);
+
+    // Always at least one row, and no more than the plane holds
+    assert_eq!(suggest_chunk_rows(color, 1024, 8), 1);
+    let small = PlaneSize::new(16, 8, 1).unwrap();
+    assert_eq!(suggest_chunk_rows(small, 256 * 1024, 1), 8);
+}
+
+#[test]
+fn test_frame_stream_header() {
+    use adder_codec_rs::framer::driver::{read_frame_header, ChannelLayout};
+    use adder_codec_rs::framer::scale_intensity::FrameElementType;
+    use std::io::Cursor;
+
+    let plane = PlaneSize::new(5, 3, 3).unwrap();
+    let mut frame_sequence: FrameSequence<u16> = FramerBuilder::new(plane, 2)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 1000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .channel_layout(ChannelLayout::Planar)
+        .source(U16, FramedU8)
+        .finish()
+        .unwrap();
+
+    let mut bytes = Vec::new();
+    frame_sequence.write_frame_header(&mut bytes).unwrap();
+    let header_len = bytes.len();
+    frame_sequence.write_frame_bytes(&mut bytes).unwrap();
+
+    let mut reader = Cursor::new(&bytes);
+    let header = read_frame_header(&mut reader).unwrap();
+    assert_eq!(header.width, 5);
+    assert_eq!(header.height, 3);
+    assert_eq!(header.channels, 3);
+    assert_eq!(header.element_type, FrameElementType::U16);
+    assert_eq!(header.channel_layout, ChannelLayout::Planar);
+    assert_eq!(header.fps, 50.0);
+
+    // The frame follows the header directly
+    assert_eq!(reader.position() as usize, header_len);
+    assert_eq!(bytes.len() - header_len, 5 * 3 * 3 * 2);
+
+    // A stream without a header is rejected
+    assert!(read_frame_header(&mut Cursor::new(&bytes[header_len..])).is_err());
+}
+
+#[test]
+fn test_framed_set_scale() {
+    use adder_codec_rs::transcoder::source::framed::Framed;
+    use adder_codec_rs::transcoder::source::video::Source;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let mut source: Framed<Vec<u8>> =
+        Framed::new("./tests/samples/bunny_crop4.mp4".to_string(), false, 1.0)
+            .unwrap()
+            .auto_time_parameters(255, 255 * 30, None)
+            .unwrap();
+    let full = source.get_video_ref().state.plane;
+
+    for _ in 0..2 {
+        source.consume(1, &pool).unwrap();
+    }
+    assert_eq!(source.get_last_input_frame().dim().0, full.h_usize());
+
+    source.set_scale(0.5).unwrap();
+    for _ in 0..2 {
+        let events = source.consume(1, &pool).unwrap();
+        for event in events.iter().flatten() {
+            assert!(event.coord.x < full.w() / 2 + 1);
+            assert!(event.coord.y < full.h() / 2 + 1);
+        }
+        let plane = source.get_video_ref().state.plane;
+        assert!(plane.w().abs_diff(full.w() / 2) <= 1);
+        assert!(plane.h().abs_diff(full.h() / 2) <= 1);
+        let (h, w, _) = source.get_last_input_frame().dim();
+        assert_eq!((w, h), (plane.w_usize(), plane.h_usize()));
+    }
+
+    assert!(source.set_scale(0.0).is_err());
+}
+
+#[test]
+fn test_framed_save_load_state() {
+    use adder_codec_rs::transcoder::source::framed::Framed;
+    use adder_codec_rs::transcoder::source::video::Source;
+    use std::io::Cursor;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let new_source = || -> Framed<Vec<u8>> {
+        Framed::new("./tests/samples/bunny_crop4.mp4".to_string(), false, 0.5)
+            .unwrap()
+            .auto_time_parameters(255, 255 * 30, None)
+            .unwrap()
+    };
+    let transcode = |source: &mut Framed<Vec<u8>>, frames: usize| {
+        let mut events = Vec::new();
+        for _ in 0..frames {
+            events.extend(source.consume(1, &pool).unwrap().into_iter().flatten());
+        }
+        events
+    };
+
+    let mut uninterrupted = new_source();
+    let expected = transcode(&mut uninterrupted, 10);
+
+    let mut first = new_source();
+    let mut resumed = transcode(&mut first, 5);
+    let mut checkpoint = Vec::new();
+    first.save_state(&mut checkpoint).unwrap();
+    drop(first);
+
+    // The resumed source picks up from the sixth frame, rather than re-reading the input
+    let mut second = new_source();
+    second.load_state(&mut Cursor::new(&checkpoint)).unwrap();
+    resumed.extend(transcode(&mut second, 5));
+    assert!(!expected.is_empty());
+    assert_eq!(resumed, expected);
+    assert_eq!(
+        second.get_last_input_frame(),
+        uninterrupted.get_last_input_frame()
+    );
+
+    // A source which has already read frames can't be rewound to the checkpoint
+    assert!(second.load_state(&mut Cursor::new(&checkpoint)).is_err());
+}
+
+#[test]
+fn test_source_last_error() {
+    use adder_codec_rs::transcoder::source::framed::Framed;
+    use adder_codec_rs::transcoder::source::video::{Source, SourceError};
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let mut source: Framed<Vec<u8>> =
+        Framed::new("./tests/samples/bunny_crop4.mp4".to_string(), false, 1.0)
+            .unwrap()
+            .auto_time_parameters(255, 255 * 30, None)
+            .unwrap();
+    source.consume(1, &pool).unwrap();
+    assert!(source.last_error().is_none());
+
+    // Consume until the video runs out of frames
+    let error = loop {
+        if let Err(e) = source.consume(1, &pool) {
+            break e;
+        }
+    };
+    assert!(matches!(error, SourceError::VideoError(_)));
+    assert!(matches!(
+        source.last_error(),
+        Some(SourceError::VideoError(_))
+    ));
+}
+
+#[test]
+fn test_rebase_event() {
+    use adder_codec_core::codec::CodecMetadata;
+    use adder_codec_rs::framer::driver::FramerMode;
+    use std::io::Cursor;
+
+    let plane = PlaneSize::new(4, 2, 1).unwrap();
+    let meta = CodecMetadata {
+        codec_version: 1,
+        time_mode: DeltaT,
+        plane,
+        tps: 50000,
+        ref_interval: 1000,
+        delta_t_max: 2000,
+        source_camera: FramedU8,
+        ..Default::default()
+    };
+
+    // Every pixel fires once per round, with staggered intervals
+    let encode = |rebase: Option<u32>| -> Vec<u8> {
+        let mut encoder: Encoder<Vec<u8>> = Encoder::new_raw(
+            adder_codec_core::codec::raw::stream::RawOutput::new(meta, Vec::new()),
+            EncoderOptions::default(plane),
+        );
+        for round in 0..20_u16 {
+            if round == 10 {
+                if let Some(offset) = rebase {
+                    encoder.rebase(offset).unwrap();
+                }
+            }
+            for y in 0..2 {
+                for x in 0..4 {
+                    encoder
+                        .ingest_event(Event {
+                            coord: Coord::new(x, y, None),
+                            d: 3 + ((x + y + round) % 4) as u8,
+                            t: 1000 * (1 + u32::from((x + y + round) % 2)),
+                        })
+                        .unwrap();
+                }
+            }
+        }
+        encoder.close_writer().unwrap().unwrap()
+    };
+
+    let reconstruct = |bytes: Vec<u8>, mode: FramerMode| -> Vec<Vec<Array3<Option<u8>>>> {
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let mut decoder = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let meta = *decoder.meta();
+        let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(meta.plane, 1)
+            .codec_version(meta.codec_version, meta.time_mode)
+            .time_parameters(meta.tps, meta.ref_interval, meta.delta_t_max, Some(50.0))
+            .mode(mode)
+            .source(U8, meta.source_camera)
+            .finish()
+            .unwrap();
+
+        let mut frames = Vec::new();
+        while let Ok(mut event) = decoder.digest_event(&mut bitreader) {
+            frame_sequence.ingest_event(&mut event, None);
+            while frame_sequence.is_frame_0_filled() {
+                frames.push(frame_sequence.pop_next_frame().unwrap());
+            }
+        }
+        frames
+    };
+
+    for mode in [INSTANTANEOUS, FramerMode::INTEGRATION] {
+        let expected = reconstruct(encode(None), mode);
+        assert!(expected.len() > 10);
+
+        // Neither a reference-aligned nor an unaligned base changes the reconstruction
+        for offset in [5000, 4500] {
+            assert_eq!(reconstruct(encode(Some(offset)), mode), expected);
+        }
+    }
+}
+
+#[test]
+fn test_periodic_rebase() {
+    use adder_codec_core::codec::CodecMetadata;
+    use adder_codec_rs::framer::driver::FramerMode;
+    use std::io::Cursor;
+
+    let plane = PlaneSize::new(4, 2, 1).unwrap();
+
+    // Every pixel fires once per round, with staggered intervals. Absolute timestamps are
+    // accumulated per pixel.
+    let encode = |time_mode: TimeMode, rebase_period: Option<u32>| -> Vec<u8> {
+        let meta = CodecMetadata {
+            time_mode,
+            plane,
+            tps: 50000,
+            ref_interval: 1000,
+            delta_t_max: 2000,
+            source_camera: FramedU8,
+            ..Default::default()
+        };
+        let mut options = EncoderOptions::default(plane);
+        if let Some(period) = rebase_period {
+            options = options.rebase_period(period).unwrap();
+        }
+        let mut encoder: Encoder<Vec<u8>> = Encoder::new_raw(
+            adder_codec_core::codec::raw::stream::RawOutput::new(meta, Vec::new()),
+            options,
+        );
+        let mut pixel_t = [0_u32; 8];
+        for round in 0..20_u16 {
+            for y in 0..2 {
+                for x in 0..4 {
+                    let interval = 1000 * (1 + u32::from((x + y + round) % 2));
+                    let t = &mut pixel_t[(y * 4 + x) as usize];
+                    *t += interval;
+                    encoder
+                        .ingest_event(Event {
+                            coord: Coord::new(x, y, None),
+                            d: 3 + ((x + y + round) % 4) as u8,
+                            t: if time_mode == TimeMode::AbsoluteT {
+                                *t
+                            } else {
+                                interval
+                            },
+                        })
+                        .unwrap();
+                }
+            }
+        }
+        encoder.close_writer().unwrap().unwrap()
+    };
+
+    // Returns the reconstructed frames, the number of rebase events, and the largest timestamp
+    // written to the stream
+    let reconstruct = |bytes: Vec<u8>, mode: FramerMode| {
+        let mut bitreader = BitReader::endian(Cursor::new(bytes), BigEndian);
+        let mut decoder = Decoder::new_raw(RawInput::new(), &mut bitreader).unwrap();
+        let meta = *decoder.meta();
+        let mut frame_sequence: FrameSequence<u8> = FramerBuilder::new(meta.plane, 1)
+            .codec_version(meta.codec_version, meta.time_mode)
+            .time_parameters(meta.tps, meta.ref_interval, meta.delta_t_max, Some(50.0))
+            .mode(mode)
+            .source(U8, meta.source_camera)
+            .finish()
+            .unwrap();
+
+        let mut frames: Vec<Vec<Array3<Option<u8>>>> = Vec::new();
+        let mut rebases = 0;
+        let mut max_t = 0;
+        while let Ok(mut event) = decoder.digest_event(&mut bitreader) {
+            if event.coord.is_rebase() {
+                rebases += 1;
+            } else {
+                max_t = max_t.max(event.t);
+            }
+            frame_sequence.ingest_event(&mut event, None);
+            while frame_sequence.is_frame_0_filled() {
+                frames.push(frame_sequence.pop_next_frame().unwrap());
+            }
+        }
+        (frames, rebases, max_t)
+    };
+
+    for time_mode in [TimeMode::DeltaT, TimeMode::AbsoluteT] {
+        for mode in [INSTANTANEOUS, FramerMode::INTEGRATION] {
+            let (expected, rebases, expected_max_t) = reconstruct(encode(time_mode, None), mode);
+            assert!(expected.len() > 10);
+            assert_eq!(rebases, 0);
+
+            let (frames, rebases, max_t) = reconstruct(encode(time_mode, Some(5000)), mode);
+            assert_eq!(frames, expected);
+            assert!(rebases > 0);
+            if time_mode == TimeMode::AbsoluteT {
+                assert!(max_t < expected_max_t);
+            } else {
+                assert_eq!(max_t, expected_max_t);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ingest_events_events_matches_ingest_event() {
+    let plane = PlaneSize::new(64, 48, 1).unwrap();
+    let chunk_rows = 8;
+    let builder = || {
+        FramerBuilder::new(plane, chunk_rows)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 3000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8)
+    };
+    let mut bucketed: FrameSequence<u8> = builder().finish().unwrap();
+    let mut sequential: FrameSequence<u8> = builder().finish().unwrap();
+
+    let mut bucketed_frames = Vec::new();
+    let mut sequential_frames = Vec::new();
+    for round in 0..6_u16 {
+        let mut buckets = vec![Vec::new(); 48 / chunk_rows];
+        for y in 0..48 {
+            for x in 0..64 {
+                buckets[y as usize / chunk_rows].push(Event {
+                    coord: Coord::new(x, y, None),
+                    d: 2 + ((x ^ y ^ round) % 5) as u8,
+                    t: 1000 * (1 + u32::from((x + y + round) % 3)),
+                });
+            }
+        }
+
+        for mut event in buckets.iter().flatten().copied() {
+            sequential.ingest_event(&mut event, None);
+        }
+        bucketed.ingest_events_events(buckets);
+
+        assert!(bucketed.frames_approx_equal(&sequential, 0.0));
+        while bucketed.is_frame_0_filled() {
+            bucketed_frames.push(bucketed.pop_next_frame().unwrap());
+        }
+        while sequential.is_frame_0_filled() {
+            sequential_frames.push(sequential.pop_next_frame().unwrap());
+        }
+    }
+    assert!(!bucketed_frames.is_empty());
+    assert_eq!(bucketed_frames, sequential_frames);
+}
+
+#[test]
+fn test_ingest_event_batch_matches_ingest_event() {
+    let plane = PlaneSize::new(64, 48, 1).unwrap();
+    let builder = || {
+        FramerBuilder::new(plane, 8)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 1000, 3000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8)
+    };
+    let mut batched: FrameSequence<u8> = builder().finish().unwrap();
+    let mut sequential: FrameSequence<u8> = builder().finish().unwrap();
+
+    let mut batched_frames = Vec::new();
+    let mut sequential_frames = Vec::new();
+    for round in 0..6_u16 {
+        // Outside the plane, so it's ignored
+        let mut events = vec![Event {
+            coord: Coord::new(0, 48, None),
+            d: 5,
+            t: 1000,
+        }];
+        // Row-interleaved, so each chunk's events are spread across the batch
+        for x in 0..64 {
+            for y in 0..48 {
+                events.push(Event {
+                    coord: Coord::new(x, y, None),
+                    d: 2 + ((x ^ y ^ round) % 5) as u8,
+                    t: 1000 * (1 + u32::from((x + y + round) % 3)),
+                });
+            }
+        }
+
+        for mut event in events.iter().copied() {
+            sequential.ingest_event(&mut event, None);
+        }
+
+        // Past the plane's last column, or on a channel it doesn't have, so they're ignored by
+        // the batch (but would panic in `ingest_event`)
+        let mut batch = events.clone();
+        batch.push(Event {
+            coord: Coord::new(64, 0, None),
+            d: 5,
+            t: 1000,
+        });
+        batch.push(Event {
+            coord: Coord::new(0, 0, Some(1)),
+            d: 5,
+            t: 1000,
+        });
+        assert_eq!(
+            batched.ingest_event_batch(&batch),
+            sequential.is_frame_0_filled()
+        );
+
+        assert!(batched.frames_approx_equal(&sequential, 0.0));
+        while batched.is_frame_0_filled() {
+            batched_frames.push(batched.pop_next_frame().unwrap());
+        }
+        while sequential.is_frame_0_filled() {
+            sequential_frames.push(sequential.pop_next_frame().unwrap());
+        }
+    }
+    assert!(!batched_frames.is_empty());
+    assert_eq!(batched_frames, sequential_frames);
+}
+
+#[test]
+fn test_ingest_event_batch_rebase() {
+    let plane = PlaneSize::new(16, 12, 1).unwrap();
+    let builder = || {
+        FramerBuilder::new(plane, 4)
+            .codec_version(2, TimeMode::AbsoluteT)
+            .time_parameters(50000, 1000, 3000, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8)
+    };
+    let mut batched: FrameSequence<u8> = builder().finish().unwrap();
+    let mut sequential: FrameSequence<u8> = builder().finish().unwrap();
+
+    // Absolute timestamps, with a rebase partway through. Every pixel has passed the new base by
+    // then, and the timestamps after it are relative to it.
+    let mut pixel_t = vec![0_u32; plane.volume()];
+    let mut events = Vec::new();
+    for round in 0..6_u16 {
+        if round == 3 {
+            events.push(Event::rebase(2000));
+            pixel_t.iter_mut().for_each(|t| *t -= 2000);
+        }
+        for y in 0..12 {
+            for x in 0..16 {
+                let t = &mut pixel_t[usize::from(y * 16 + x)];
+                *t += 1000 * (1 + u32::from((x + y + round) % 3));
+                events.push(Event {
+                    coord: Coord::new(x, y, None),
+                    d: 2 + ((x ^ y ^ round) % 5) as u8,
+                    t: *t,
+                });
+            }
+        }
+    }
+
+    for mut event in events.iter().copied() {
+        sequential.ingest_event(&mut event, None);
+    }
+    assert_eq!(
+        batched.ingest_event_batch(&events),
+        sequential.is_frame_0_filled()
+    );
+    assert!(batched.frames_approx_equal(&sequential, 0.0));
+
+    let mut batched_frames = Vec::new();
+    while batched.is_frame_0_filled() {
+        batched_frames.push(batched.pop_next_frame().unwrap());
+    }
+    let mut sequential_frames = Vec::new();
+    while sequential.is_frame_0_filled() {
+        sequential_frames.push(sequential.pop_next_frame().unwrap());
+    }
+    assert!(!batched_frames.is_empty());
+    assert_eq!(batched_frames, sequential_frames);
+}
+
+#[test]
+fn test_ingest_events_events_misaligned() {
+    use adder_codec_rs::framer::driver::FrameSequenceError;
+
+    let plane = PlaneSize::new(4, 6, 1).unwrap();
+    let mut framer: FrameSequence<u8> = FramerBuilder::new(plane, 2)
+        .codec_version(1, TimeMode::DeltaT)
+        .time_parameters(50000, 1000, 3000, Some(50.0))
+        .mode(INSTANTANEOUS)
+        .source(U8, FramedU8)
+        .finish()
+        .unwrap();
+    let event = |y: u16| Event {
+        coord: Coord::new(0, y, None),
+        d: 5,
+        t: 1000,
+    };
+
+    // Bucketed as if the chunks had 3 rows, so row 2 lands in the first bucket
+    let misaligned = vec![vec![event(0), event(1), event(2)], vec![], vec![]];
+    assert!(matches!(
+        framer.try_ingest_events_events(misaligned),
+        Err(FrameSequenceError::MisalignedChunks)
+    ));
+    assert!(matches!(
+        framer.try_ingest_events_events(vec![vec![event(0)], vec![]]),
+        Err(FrameSequenceError::MisalignedChunks)
+    ));
+
+    // Nothing was ingested
+    for y in 0..3 {
+        assert!(framer.px_at_current(y, 0, 0).unwrap().is_none());
+    }
+
+    let aligned = vec![vec![event(0), event(1)], vec![event(2)], vec![]];
+    assert!(!framer.try_ingest_events_events(aligned).unwrap());
+    for y in 0..3 {
+        assert!(framer.px_at_current(y, 0, 0).unwrap().is_some());
+    }
+}
+
+#[test]
+fn test_framer_start_offset() {
+    let first_frame = |start_offset: u64| -> u8 {
+        let mut framer: FrameSequence<u8> = FramerBuilder::new(PlaneSize::new(1, 1, 1).unwrap(), 1)
+            .codec_version(1, TimeMode::DeltaT)
+            .time_parameters(50000, 500, 500, Some(50.0))
+            .mode(INSTANTANEOUS)
+            .source(U8, FramedU8)
+            .start_offset_ticks(start_offset)
+            .finish()
+            .unwrap();
+
+        // The pixel's intensity doubles after t = 2500
+        for i in 1..=10_u32 {
+            let mut event = Event {
+                coord: Coord::new(0, 0, None),
+                d: if i * 500 <= 2500 { 5 } else { 6 },
+                t: 500,
+            };
+            if framer.ingest_event(&mut event, None) {
+                break;
+            }
+        }
+        framer.pop_next_frame().unwrap()[0][[0, 0, 0]].unwrap()
+    };
+
+    // Frame 0 covers (0, 1000]
+    assert_eq!(first_frame(0), 32);
+
+    // Frame 0 covers (2500, 3500]
+    assert_eq!(first_frame(2500), 64);
+}
+
+#[path = "../benches/common/mod.rs"]
+mod bench_common;
+
+/// Runs one iteration of each reconstruction benchmark on its generated data
+#[test]
+fn test_framer_benchmark_smoke() {
+    let events = bench_common::frame_of_events();
+
+    let mut framer = bench_common::new_framer();
+    framer.ingest_events_events(events.clone());
+
+    let mut framer = bench_common::filled_framer(&events);
+    let mut output = Vec::new();
+    let frame_count = framer.write_multi_frame_bytes(&mut output).unwrap();
+    assert!(frame_count > 0);
+    assert_eq!(
+        output.len(),
+        frame_count as usize * bench_common::WIDTH as usize * bench_common::HEIGHT as usize
+    );
+}
+
+/// Collects every log record, so tests can check what the library logged
+struct CaptureLogger {
+    records: std::sync::Mutex<Vec<(log::Level, String)>>,
+}
+
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((record.level(), record.args().to_string()));
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE_LOGGER: CaptureLogger = CaptureLogger {
+    records: std::sync::Mutex::new(Vec::new()),
+};
+
+#[test]
+fn test_transcode_error_is_logged() {
+    use adder_codec_rs::transcoder::source::framed::Framed;
+    use adder_codec_rs::transcoder::source::video::VideoBuilder;
+
+    // Other tests may have installed the logger already
+    let _ = log::set_logger(&CAPTURE_LOGGER);
+    log::set_max_level(log::LevelFilter::Warn);
+
+    let path = "./tests/samples/bunny_crop4.mp4";
+    let _source: Framed<Vec<u8>> = Framed::new(path.to_string(), false, 1.0)
+        .unwrap()
+        .time_parameters(255 * 30, 255, 1000, None)
+        .unwrap();
+
+    let records = CAPTURE_LOGGER.records.lock().unwrap();
+    assert!(records
+        .iter()
+        .any(|(level, message)| *level == log::Level::Error
+            && message.contains("must be a multiple of ref_time")
+            && message.contains(path)));
+}
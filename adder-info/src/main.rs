@@ -1,4 +1,6 @@
 use crate::TimeMode::AbsoluteT;
+use adder_codec_core::codec::decoder::{ValidationReport, ViolationKind};
+use adder_codec_core::codec::CodecMetadata;
 use adder_codec_core::*;
 use adder_codec_rs::framer::scale_intensity::event_to_intensity;
 use adder_codec_rs::utils::stream_migration::absolute_event_to_dt_event;
@@ -19,6 +21,135 @@ pub struct MyArgs {
     /// Calculate dynamic range of the event stream? (Takes more time)
     #[clap(short, long, action)]
     pub(crate) dynamic_range: bool,
+
+    /// Print the distribution of event D values? (Takes more time)
+    #[clap(long, visible_alias = "histogram", action)]
+    pub(crate) d_histogram: bool,
+
+    /// Report how events are distributed across the color channels? (Takes more time)
+    #[clap(long, action)]
+    pub(crate) channel_counts: bool,
+
+    /// Report the temporal compression ratio versus the equivalent framed video? (Takes more time)
+    #[clap(long, action)]
+    pub(crate) compression_ratio: bool,
+
+    /// Report the mean event rate and temporal density of the stream? (Takes more time)
+    #[clap(long, action)]
+    pub(crate) temporal_stats: bool,
+
+    /// Check every event against the header, and report the first bad or truncated event?
+    /// (Takes more time)
+    #[clap(long, action)]
+    pub(crate) validate: bool,
+}
+
+/// Counts of events for each decimation value in 0..=D_MAX
+pub(crate) struct DHistogram {
+    counts: [u64; D_MAX as usize + 1],
+
+    /// Empty events ([`D_EMPTY`])
+    empty: u64,
+
+    /// Zero-integration events ([`D_ZERO_INTEGRATION`])
+    zero_integration: u64,
+
+    /// Events with any other D value above [`D_MAX`]
+    special: u64,
+}
+
+impl Default for DHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; D_MAX as usize + 1],
+            empty: 0,
+            zero_integration: 0,
+            special: 0,
+        }
+    }
+}
+
+impl DHistogram {
+    pub(crate) fn record(&mut self, d: D) {
+        match self.counts.get_mut(d as usize) {
+            Some(count) => *count += 1,
+            None if d == D_EMPTY => self.empty += 1,
+            None if d == D_ZERO_INTEGRATION => self.zero_integration += 1,
+            None => self.special += 1,
+        }
+    }
+
+    pub(crate) fn count(&self, d: D) -> u64 {
+        self.counts.get(d as usize).copied().unwrap_or(0)
+    }
+
+    pub(crate) fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The mean D value, ignoring special D values
+    pub(crate) fn mean(&self) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let sum: u64 = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(d, count)| d as u64 * count)
+            .sum();
+        Some(sum as f64 / total as f64)
+    }
+
+    /// The most frequent D value, ignoring special D values. Ties go to the lowest D.
+    pub(crate) fn mode(&self) -> Option<D> {
+        if self.total() == 0 {
+            return None;
+        }
+        let mut mode = 0;
+        for (d, count) in self.counts.iter().enumerate() {
+            if *count > self.counts[mode] {
+                mode = d;
+            }
+        }
+        Some(mode as D)
+    }
+}
+
+/// Counts of events for each color channel
+pub(crate) struct ChannelCounts {
+    counts: Vec<u64>,
+
+    /// Events with no channel on a multi-channel stream, which are counted under channel 0
+    missing: u64,
+
+    /// Events with a channel beyond the stream's channel count, which are not counted
+    out_of_range: u64,
+}
+
+impl ChannelCounts {
+    pub(crate) fn new(channels: u8) -> Self {
+        Self {
+            counts: vec![0; channels.max(1) as usize],
+            missing: 0,
+            out_of_range: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, c: Option<u8>) {
+        if c.is_none() && self.counts.len() > 1 {
+            self.missing += 1;
+        }
+        match self.counts.get_mut(c.unwrap_or(0) as usize) {
+            Some(count) => *count += 1,
+            None => self.out_of_range += 1,
+        }
+    }
+
+    pub(crate) fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
 }
 
 fn main() -> Result<(), Box<dyn error::Error>> {
@@ -37,6 +168,7 @@ fn adder_info(args: MyArgs, out: impl Write) -> Result<(), Box<dyn error::Error>
     let file_size = Path::new(file_path).metadata()?.len();
 
     let meta = *stream.meta();
+    let format = stream.format_info();
 
     // TODO: Need a different mechanism for compressed files
     let num_events = (eof_position_bytes - 1 - meta.header_size as u64) / meta.event_size as u64;
@@ -50,8 +182,9 @@ fn adder_info(args: MyArgs, out: impl Write) -> Result<(), Box<dyn error::Error>
     writeln!(handle, "\tColor channels: {}", meta.plane.c())?;
     writeln!(handle, "Source camera: {:?}", meta.source_camera)?;
     writeln!(handle, "ADΔER transcoder parameters")?;
-    writeln!(handle, "\tCodec version: {}", meta.codec_version)?;
-    writeln!(handle, "\tTime mode: {:?}", meta.time_mode)?;
+    writeln!(handle, "\tCodec version: {}", format.codec_version)?;
+    writeln!(handle, "\tCompressed: {}", format.compressed)?;
+    writeln!(handle, "\tTime mode: {:?}", format.time_mode)?;
     writeln!(handle, "\tTicks per second: {}", meta.tps)?;
     writeln!(
         handle,
@@ -68,12 +201,20 @@ fn adder_info(args: MyArgs, out: impl Write) -> Result<(), Box<dyn error::Error>
 
     // Calculate the dynamic range of the events. That is, what is the highest intensity
     // event, and what is the lowest intensity event?
-    if args.dynamic_range {
-        let divisor = num_events / 100;
+    if args.dynamic_range
+        || args.d_histogram
+        || args.channel_counts
+        || args.compression_ratio
+        || args.temporal_stats
+    {
+        let divisor = (num_events / 100).max(1);
         stream.set_input_stream_position(&mut bitreader, first_event_position)?;
         let mut max_intensity: Intensity = 0.0;
         let mut min_intensity: Intensity = f64::MAX;
         let mut event_count: u64 = 0;
+        let mut d_histogram = DHistogram::default();
+        let mut channel_counts = ChannelCounts::new(meta.plane.c());
+        let mut duration_ticks: BigT = 0;
 
         // Setup time tracker for AbsoluteT mode
         let data = vec![0_u32; meta.plane.volume()];
@@ -87,7 +228,64 @@ fn adder_info(args: MyArgs, out: impl Write) -> Result<(), Box<dyn error::Error>
             data,
         )?;
 
-        while let Ok(mut event) = stream.digest_event(&mut bitreader) {
+        // Running timestamp of each pixel, for finding the stream duration
+        let mut running_t: Array3<BigT> = Array3::zeros((
+            meta.plane.h_usize(),
+            meta.plane.w_usize(),
+            meta.plane.c_usize(),
+        ));
+
+        // The sum of the offsets of the rebase events read so far
+        let mut time_base: BigT = 0;
+
+        for event in stream.events(&mut bitreader) {
+            let mut event = event?;
+            event_count += 1;
+            if event_count % divisor == 0 {
+                write!(
+                    handle,
+                    "\rReading events...{}%",
+                    (event_count * 100) / num_events.max(1)
+                )?;
+                handle.flush()?;
+            }
+
+            if event.coord.is_reserved() {
+                if event.coord.is_rebase() {
+                    let offset = event.t;
+                    running_t.mapv_inplace(|t| t.saturating_sub(BigT::from(offset)));
+                    t_tree.mapv_inplace(|t| t.saturating_sub(offset));
+                    time_base += BigT::from(offset);
+                }
+                continue;
+            }
+
+            if args.d_histogram {
+                d_histogram.record(event.d);
+            }
+
+            if args.channel_counts {
+                channel_counts.record(event.coord.c);
+            }
+
+            if args.compression_ratio || args.temporal_stats {
+                let px_t = &mut running_t[[
+                    event.coord.y_usize(),
+                    event.coord.x_usize(),
+                    event.coord.c_usize(),
+                ]];
+                if meta.codec_version >= 2 && meta.time_mode == AbsoluteT {
+                    *px_t = BigT::from(event.t);
+                } else {
+                    *px_t += BigT::from(event.t);
+                }
+                duration_ticks = duration_ticks.max(*px_t + time_base);
+            }
+
+            if !args.dynamic_range {
+                continue;
+            }
+
             if meta.codec_version >= 2 && meta.time_mode == AbsoluteT {
                 let last_t = &mut t_tree[[
                     event.coord.y_usize(),
@@ -119,32 +317,32 @@ fn adder_info(args: MyArgs, out: impl Write) -> Result<(), Box<dyn error::Error>
                 }
                 _ => {}
             }
+        }
 
-            event_count += 1;
-            if event_count % divisor == 0 {
-                write!(
-                    handle,
-                    "\rCalculating dynamic range...{}%",
-                    (event_count * 100) / num_events
-                )?;
-                handle.flush()?;
-            }
+        if args.dynamic_range {
+            write_dynamic_range(&mut handle, &meta, max_intensity, min_intensity)?;
         }
 
-        let theory_dr_ratio = D_SHIFT[D_SHIFT.len() - 1] as f64 / (1.0 / meta.delta_t_max as f64);
-        let theory_dr_db = 10.0 * theory_dr_ratio.log10();
-        let theory_dr_bits = theory_dr_ratio.log2();
-        writeln!(handle, "\rDynamic range                       ")?;
-        writeln!(handle, "\tTheoretical range:")?;
-        writeln!(handle, "\t\t{theory_dr_db:.4} dB (power)")?;
-        writeln!(handle, "\t\t{theory_dr_bits:.4} bits")?;
+        if args.d_histogram {
+            write_d_histogram(&mut handle, &d_histogram)?;
+        }
+
+        if args.channel_counts {
+            write_channel_counts(&mut handle, &channel_counts)?;
+        }
 
-        let real_dr_ratio = max_intensity / min_intensity;
-        let real_dr_db = 10.0 * real_dr_ratio.log10();
-        let real_dr_bits = real_dr_ratio.log2();
-        writeln!(handle, "\tRealized range:")?;
-        writeln!(handle, "\t\t{real_dr_db:.4} dB (power)")?;
-        writeln!(handle, "\t\t{real_dr_bits:.4} bits")?;
+        if args.compression_ratio {
+            write_compression_ratio(&mut handle, &meta, duration_ticks, event_count)?;
+        }
+
+        if args.temporal_stats {
+            write_temporal_stats(&mut handle, &meta, duration_ticks, event_count)?;
+        }
+    }
+
+    if args.validate {
+        let report = stream.validate(&mut bitreader)?;
+        write_validation(&mut handle, &report)?;
     }
 
     handle.flush()?;
@@ -152,16 +350,224 @@ fn adder_info(args: MyArgs, out: impl Write) -> Result<(), Box<dyn error::Error>
     Ok(())
 }
 
+fn write_dynamic_range(
+    handle: &mut impl Write,
+    meta: &CodecMetadata,
+    max_intensity: Intensity,
+    min_intensity: Intensity,
+) -> io::Result<()> {
+    let theory_dr_ratio = D_SHIFT[D_SHIFT.len() - 1] as f64 / (1.0 / meta.delta_t_max as f64);
+    let theory_dr_db = 10.0 * theory_dr_ratio.log10();
+    let theory_dr_bits = theory_dr_ratio.log2();
+    writeln!(handle, "\rDynamic range                       ")?;
+    writeln!(handle, "\tTheoretical range:")?;
+    writeln!(handle, "\t\t{theory_dr_db:.4} dB (power)")?;
+    writeln!(handle, "\t\t{theory_dr_bits:.4} bits")?;
+
+    let real_dr_ratio = max_intensity / min_intensity;
+    let real_dr_db = 10.0 * real_dr_ratio.log10();
+    let real_dr_bits = real_dr_ratio.log2();
+    writeln!(handle, "\tRealized range:")?;
+    writeln!(handle, "\t\t{real_dr_db:.4} dB (power)")?;
+    writeln!(handle, "\t\t{real_dr_bits:.4} bits")?;
+    Ok(())
+}
+
+/// The width, in characters, of the longest bar in the D histogram chart
+const HISTOGRAM_BAR_WIDTH: u64 = 40;
+
+fn write_d_histogram(handle: &mut impl Write, histogram: &DHistogram) -> io::Result<()> {
+    writeln!(handle, "\rD histogram                       ")?;
+    let total = histogram.total();
+    let max_count = histogram.counts.iter().copied().max().unwrap_or(0);
+    for d in 0..=D_MAX {
+        let count = histogram.count(d);
+        if count > 0 {
+            let percent = count as f64 * 100.0 / total as f64;
+            // Round up, so that every nonzero count gets at least one character
+            let bar_len = (count * HISTOGRAM_BAR_WIDTH).div_ceil(max_count) as usize;
+            writeln!(
+                handle,
+                "\td={d}: {count} ({percent:.2}%)\t|{}",
+                "#".repeat(bar_len)
+            )?;
+        }
+    }
+    writeln!(handle, "\tEmpty (d={D_EMPTY}): {}", histogram.empty)?;
+    writeln!(
+        handle,
+        "\tZero integration (d={D_ZERO_INTEGRATION}): {}",
+        histogram.zero_integration
+    )?;
+    if histogram.special > 0 {
+        writeln!(handle, "\tSpecial D values: {}", histogram.special)?;
+    }
+    match (histogram.mean(), histogram.mode()) {
+        (Some(mean), Some(mode)) => {
+            writeln!(handle, "\tMean d: {mean:.4}")?;
+            writeln!(handle, "\tMode d: {mode}")?;
+        }
+        _ => writeln!(handle, "\tNo events with D in 0..={D_MAX}")?,
+    }
+    Ok(())
+}
+
+fn write_channel_counts(handle: &mut impl Write, counts: &ChannelCounts) -> io::Result<()> {
+    writeln!(handle, "\rChannel counts                       ")?;
+    if counts.counts.len() == 1 {
+        writeln!(handle, "\tSingle-channel stream")?;
+        return Ok(());
+    }
+    let total = counts.total();
+    for (c, count) in counts.counts.iter().enumerate() {
+        let percent = if total == 0 {
+            0.0
+        } else {
+            *count as f64 * 100.0 / total as f64
+        };
+        writeln!(handle, "\tc={c}: {count} ({percent:.2}%)")?;
+    }
+    if counts.missing > 0 {
+        writeln!(
+            handle,
+            "\tWARNING: {} events have no channel, and were counted under c=0",
+            counts.missing
+        )?;
+    }
+    if counts.out_of_range > 0 {
+        writeln!(
+            handle,
+            "\tWARNING: {} events have a channel beyond the stream's {} channels",
+            counts.out_of_range,
+            counts.counts.len()
+        )?;
+    }
+    Ok(())
+}
+
+fn write_validation(handle: &mut impl Write, report: &ValidationReport) -> io::Result<()> {
+    writeln!(handle, "\rValidation                       ")?;
+    writeln!(handle, "\tEvents scanned: {}", report.events_scanned)?;
+    match report.first_violation {
+        None => writeln!(handle, "\tNo violations found")?,
+        Some(violation) => {
+            let description = match violation.kind {
+                ViolationKind::Inconsistent(inconsistency) => format!("{inconsistency:?}"),
+                ViolationKind::Truncated => "truncated by the end of the stream".to_string(),
+            };
+            writeln!(
+                handle,
+                "\tFirst violation: event {} at byte {}: {description}",
+                violation.index, violation.offset
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// The ratio of pixel samples in the equivalent framed video to the number of ADΔER events.
+///
+/// The framed video is assumed to run at the native frame rate of the source
+/// (`tps / ref_interval`) for `duration_ticks`. Returns `None` if there are no events.
+pub(crate) fn temporal_compression_ratio(
+    meta: &CodecMetadata,
+    duration_ticks: BigT,
+    num_events: u64,
+) -> Option<f64> {
+    if num_events == 0 || meta.tps == 0 || meta.ref_interval == 0 {
+        return None;
+    }
+    let native_fps = meta.tps as f64 / meta.ref_interval as f64;
+    let duration_secs = duration_ticks as f64 / meta.tps as f64;
+    let framed_samples = meta.plane.volume() as f64 * native_fps * duration_secs;
+    Some(framed_samples / num_events as f64)
+}
+
+/// The mean number of events per second and per tick, over a stream lasting `duration_ticks`.
+/// Returns `None` if the stream has no duration (e.g., it spans a single frame).
+pub(crate) fn temporal_stats(
+    meta: &CodecMetadata,
+    duration_ticks: BigT,
+    num_events: u64,
+) -> Option<(f64, f64)> {
+    if duration_ticks == 0 || meta.tps == 0 {
+        return None;
+    }
+    let duration_secs = duration_ticks as f64 / meta.tps as f64;
+    Some((
+        num_events as f64 / duration_secs,
+        num_events as f64 / duration_ticks as f64,
+    ))
+}
+
+fn write_temporal_stats(
+    handle: &mut impl Write,
+    meta: &CodecMetadata,
+    duration_ticks: BigT,
+    num_events: u64,
+) -> io::Result<()> {
+    writeln!(handle, "\rTemporal statistics                       ")?;
+    match temporal_stats(meta, duration_ticks, num_events) {
+        Some((events_per_sec, events_per_tick)) => {
+            writeln!(handle, "\tMean event rate: {events_per_sec:.4} events/s")?;
+            writeln!(
+                handle,
+                "\tTemporal density: {events_per_tick:.6} events/tick"
+            )?;
+        }
+        None => {
+            writeln!(handle, "\tMean event rate: N/A")?;
+            writeln!(handle, "\tTemporal density: N/A")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_compression_ratio(
+    handle: &mut impl Write,
+    meta: &CodecMetadata,
+    duration_ticks: BigT,
+    num_events: u64,
+) -> io::Result<()> {
+    writeln!(handle, "\rTemporal compression                       ")?;
+    writeln!(
+        handle,
+        "\tDuration: {:.4} seconds",
+        duration_ticks as f64 / meta.tps.max(1) as f64
+    )?;
+    match temporal_compression_ratio(meta, duration_ticks, num_events) {
+        Some(ratio) => writeln!(handle, "\tTemporal compression ratio: {ratio:.4}")?,
+        None => writeln!(
+            handle,
+            "\tTemporal compression ratio: undefined (no events)"
+        )?,
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{adder_info, MyArgs};
-    use std::io::Cursor;
+    use crate::{
+        adder_info, temporal_compression_ratio, temporal_stats, ChannelCounts, DHistogram, MyArgs,
+    };
+    use adder_codec_core::codec::encoder::Encoder;
+    use adder_codec_core::codec::raw::stream::RawOutput;
+    use adder_codec_core::codec::{CodecMetadata, EncoderOptions};
+    use adder_codec_core::{Coord, Event, PlaneSize, TimeMode, D_EMPTY, D_ZERO_INTEGRATION};
+    use clap::Parser;
+    use std::fs::File;
+    use std::io::{BufWriter, Cursor};
 
     #[test]
     fn test_adder_info() -> Result<(), Box<dyn std::error::Error>> {
         let args = MyArgs {
             input: "./tests/test_sample.adder".to_string(),
             dynamic_range: true,
+            d_histogram: false,
+            channel_counts: false,
+            temporal_stats: false,
+            compression_ratio: false,
+            validate: false,
         };
 
         let mut data = Vec::new();
@@ -188,4 +594,342 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_d_histogram_stats() {
+        let mut histogram = DHistogram::default();
+        for (d, n) in [(3, 10), (7, 25), (12, 5)] {
+            for _ in 0..n {
+                histogram.record(d);
+            }
+        }
+        histogram.record(D_EMPTY);
+        histogram.record(D_ZERO_INTEGRATION);
+        histogram.record(D_ZERO_INTEGRATION);
+        histogram.record(200);
+
+        assert_eq!(histogram.count(3), 10);
+        assert_eq!(histogram.count(7), 25);
+        assert_eq!(histogram.count(12), 5);
+        assert_eq!(histogram.count(4), 0);
+        assert_eq!(histogram.total(), 40);
+        assert_eq!(histogram.empty, 1);
+        assert_eq!(histogram.zero_integration, 2);
+        assert_eq!(histogram.special, 1);
+        assert_eq!(histogram.mode(), Some(7));
+        assert!((histogram.mean().unwrap() - 6.625).abs() < 1e-9);
+
+        assert_eq!(DHistogram::default().mode(), None);
+        assert_eq!(DHistogram::default().mean(), None);
+    }
+
+    #[test]
+    fn test_d_histogram_output() -> Result<(), Box<dyn std::error::Error>> {
+        let plane = PlaneSize::new(4, 4, 1)?;
+        let path = std::env::temp_dir().join("adder_info_d_histogram.adder");
+        {
+            let meta = CodecMetadata {
+                plane,
+                time_mode: TimeMode::DeltaT,
+                tps: 7650,
+                ref_interval: 255,
+                delta_t_max: 2550,
+                ..Default::default()
+            };
+            let writer = BufWriter::new(File::create(&path)?);
+            let mut encoder =
+                Encoder::new_raw(RawOutput::new(meta, writer), EncoderOptions::default(plane));
+
+            // Known D frequencies: d=3 x10, d=7 x25, d=12 x5
+            let mut i = 0_u16;
+            for (d, n) in [(3, 10), (7, 25), (12, 5)] {
+                for _ in 0..n {
+                    encoder.ingest_event(Event {
+                        coord: Coord {
+                            x: i % 4,
+                            y: (i / 4) % 4,
+                            c: None,
+                        },
+                        d,
+                        t: 255,
+                    })?;
+                    i += 1;
+                }
+            }
+            encoder.close_writer()?.unwrap().into_inner()?;
+        }
+
+        let args = MyArgs {
+            input: path.to_str().unwrap().to_string(),
+            dynamic_range: false,
+            d_histogram: true,
+            channel_counts: false,
+            temporal_stats: false,
+            compression_ratio: false,
+            validate: false,
+        };
+        let mut data = Vec::new();
+        adder_info(args, Cursor::new(&mut data))?;
+        std::fs::remove_file(&path)?;
+
+        let string = String::from_utf8(data)?;
+        assert!(string.contains("event count: 40"));
+        assert!(string.contains("d=3: 10 (25.00%)"));
+        assert!(string.contains("d=7: 25 (62.50%)"));
+        assert!(string.contains("d=12: 5 (12.50%)"));
+        assert!(!string.contains("d=4:"));
+        assert!(string.contains("Mean d: 6.6250"));
+        assert!(string.contains("Mode d: 7"));
+
+        // The most frequent D gets the full-width bar, and the others are scaled to it
+        assert!(string.contains("d=7: 25 (62.50%)\t|########################################\n"));
+        assert!(string.contains("d=3: 10 (25.00%)\t|################\n"));
+        assert!(string.contains("d=12: 5 (12.50%)\t|########\n"));
+        assert!(string.contains("Empty (d=255): 0"));
+        assert!(string.contains("Zero integration (d=254): 0"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_alias() {
+        let args = MyArgs::parse_from(["adderinfo", "-i", "in.adder", "--histogram"]);
+        assert!(args.d_histogram);
+        let args = MyArgs::parse_from(["adderinfo", "-i", "in.adder", "--d-histogram"]);
+        assert!(args.d_histogram);
+    }
+
+    #[test]
+    fn test_channel_counts() {
+        let mut counts = ChannelCounts::new(3);
+        for c in [0, 1, 1, 2, 2, 2] {
+            counts.record(Some(c));
+        }
+        counts.record(None);
+        counts.record(Some(3));
+        assert_eq!(counts.counts, vec![2, 2, 3]);
+        assert_eq!(counts.total(), 7);
+        assert_eq!(counts.missing, 1);
+        assert_eq!(counts.out_of_range, 1);
+
+        // Events with no channel are expected on a single-channel stream
+        let mut counts = ChannelCounts::new(1);
+        counts.record(None);
+        assert_eq!(counts.counts, vec![1]);
+        assert_eq!(counts.missing, 0);
+    }
+
+    #[test]
+    fn test_channel_counts_output() -> Result<(), Box<dyn std::error::Error>> {
+        let plane = PlaneSize::new(4, 4, 3)?;
+        let path = std::env::temp_dir().join("adder_info_channel_counts.adder");
+        {
+            let meta = CodecMetadata {
+                plane,
+                time_mode: TimeMode::DeltaT,
+                tps: 7650,
+                ref_interval: 255,
+                delta_t_max: 2550,
+                ..Default::default()
+            };
+            let writer = BufWriter::new(File::create(&path)?);
+            let mut encoder =
+                Encoder::new_raw(RawOutput::new(meta, writer), EncoderOptions::default(plane));
+
+            // Known channel frequencies: c=0 x5, c=1 x10, c=2 x5
+            for (c, n) in [(0, 5), (1, 10), (2, 5)] {
+                for i in 0..n {
+                    encoder.ingest_event(Event {
+                        coord: Coord {
+                            x: i % 4,
+                            y: i / 4,
+                            c: Some(c),
+                        },
+                        d: 7,
+                        t: 255,
+                    })?;
+                }
+            }
+            encoder.close_writer()?.unwrap().into_inner()?;
+        }
+
+        let args = MyArgs {
+            input: path.to_str().unwrap().to_string(),
+            channel_counts: true,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        adder_info(args, Cursor::new(&mut data))?;
+        std::fs::remove_file(&path)?;
+
+        let string = String::from_utf8(data)?;
+        assert!(string.contains("event count: 20"));
+        assert!(string.contains("c=0: 5 (25.00%)"));
+        assert!(string.contains("c=1: 10 (50.00%)"));
+        assert!(string.contains("c=2: 5 (25.00%)"));
+        assert!(!string.contains("WARNING"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_temporal_compression_ratio() -> Result<(), Box<dyn std::error::Error>> {
+        let plane = PlaneSize::new(4, 4, 1)?;
+        let meta = CodecMetadata {
+            plane,
+            time_mode: TimeMode::DeltaT,
+            tps: 7650,
+            ref_interval: 255,
+            delta_t_max: 2550,
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join("adder_info_compression_ratio.adder");
+        {
+            let writer = BufWriter::new(File::create(&path)?);
+            let mut encoder =
+                Encoder::new_raw(RawOutput::new(meta, writer), EncoderOptions::default(plane));
+
+            // Every pixel fires twice, 5 source frames apart: 1/3 second at 30 fps
+            for _ in 0..2 {
+                for y in 0..4 {
+                    for x in 0..4 {
+                        encoder.ingest_event(Event {
+                            coord: Coord { x, y, c: None },
+                            d: 7,
+                            t: 1275,
+                        })?;
+                    }
+                }
+            }
+            encoder.close_writer()?.unwrap().into_inner()?;
+        }
+
+        // 16 pixels * 30 fps * (1/3) s = 160 framed samples, over 32 events
+        let ratio = temporal_compression_ratio(&meta, 2550, 32).unwrap();
+        assert!((ratio - 5.0).abs() < 1e-9);
+        assert_eq!(temporal_compression_ratio(&meta, 2550, 0), None);
+
+        let args = MyArgs {
+            input: path.to_str().unwrap().to_string(),
+            dynamic_range: false,
+            d_histogram: false,
+            channel_counts: false,
+            temporal_stats: false,
+            compression_ratio: true,
+            validate: false,
+        };
+        let mut data = Vec::new();
+        adder_info(args, Cursor::new(&mut data))?;
+        std::fs::remove_file(&path)?;
+
+        let string = String::from_utf8(data)?;
+        assert!(string.contains("event count: 32"));
+        assert!(string.contains("Duration: 0.3333 seconds"));
+        assert!(string.contains("Temporal compression ratio: 5.0000"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_temporal_stats() -> Result<(), Box<dyn std::error::Error>> {
+        let plane = PlaneSize::new(4, 4, 1)?;
+        let meta = CodecMetadata {
+            plane,
+            time_mode: TimeMode::DeltaT,
+            tps: 7650,
+            ref_interval: 255,
+            delta_t_max: 2550,
+            ..Default::default()
+        };
+        let path = std::env::temp_dir().join("adder_info_temporal_stats.adder");
+        {
+            let writer = BufWriter::new(File::create(&path)?);
+            let mut encoder =
+                Encoder::new_raw(RawOutput::new(meta, writer), EncoderOptions::default(plane));
+
+            // Every pixel fires twice, 5 source frames apart: 32 events over 1/3 second
+            for _ in 0..2 {
+                for y in 0..4 {
+                    for x in 0..4 {
+                        encoder.ingest_event(Event {
+                            coord: Coord { x, y, c: None },
+                            d: 7,
+                            t: 1275,
+                        })?;
+                    }
+                }
+            }
+            encoder.close_writer()?.unwrap().into_inner()?;
+        }
+
+        let (events_per_sec, events_per_tick) = temporal_stats(&meta, 2550, 32).unwrap();
+        assert!((events_per_sec - 96.0).abs() < 1e-9);
+        assert!((events_per_tick - 32.0 / 2550.0).abs() < 1e-12);
+        assert_eq!(temporal_stats(&meta, 0, 32), None);
+
+        let args = MyArgs {
+            input: path.to_str().unwrap().to_string(),
+            temporal_stats: true,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        adder_info(args, Cursor::new(&mut data))?;
+        std::fs::remove_file(&path)?;
+
+        let string = String::from_utf8(data)?;
+        assert!(string.contains("Temporal statistics"));
+        assert!(string.contains("Mean event rate: 96.0000 events/s"));
+        assert!(string.contains("Temporal density: 0.012549 events/tick"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_output() -> Result<(), Box<dyn std::error::Error>> {
+        let plane = PlaneSize::new(4, 4, 1)?;
+        let path = std::env::temp_dir().join("adder_info_validate.adder");
+        let (header_size, event_size);
+        {
+            let meta = CodecMetadata {
+                plane,
+                time_mode: TimeMode::DeltaT,
+                tps: 7650,
+                ref_interval: 255,
+                delta_t_max: 2550,
+                ..Default::default()
+            };
+            let writer = BufWriter::new(File::create(&path)?);
+            let mut encoder =
+                Encoder::new_raw(RawOutput::new(meta, writer), EncoderOptions::default(plane));
+            for x in 0..4 {
+                encoder.ingest_event(Event {
+                    coord: Coord { x, y: 0, c: None },
+                    d: 7,
+                    // The third event spans more than delta_t_max
+                    t: if x == 2 { 2551 } else { 255 },
+                })?;
+            }
+            header_size = encoder.meta().header_size;
+            event_size = usize::from(encoder.meta().event_size);
+            encoder.close_writer()?.unwrap().into_inner()?;
+        }
+
+        let args = MyArgs {
+            input: path.to_str().unwrap().to_string(),
+            validate: true,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        adder_info(args, Cursor::new(&mut data))?;
+        std::fs::remove_file(&path)?;
+
+        let string = String::from_utf8(data)?;
+        assert!(string.contains("Events scanned: 4"));
+        assert!(string.contains(&format!(
+            "First violation: event 2 at byte {}: DeltaTExceedsMax",
+            header_size + 2 * event_size
+        )));
+
+        Ok(())
+    }
 }
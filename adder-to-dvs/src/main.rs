@@ -140,6 +140,9 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     let mut current_t = 0;
     let mut max_px_event_count = 0;
 
+    // The sum of the offsets of the rebase events read so far
+    let mut time_base: u128 = 0;
+
     loop {
         if event_count % divisor == 0 {
             write!(
@@ -175,6 +178,14 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         match stream.digest_event(&mut bitreader) {
             Ok(mut event) => {
                 event_count += 1;
+                if event.coord.is_reserved() {
+                    // Pixel times are kept absolute here, so a rebase event only offsets the
+                    // timestamps read after it
+                    if event.coord.is_rebase() {
+                        time_base += event.t as u128;
+                    }
+                    continue;
+                }
                 let y = event.coord.y as usize;
                 let x = event.coord.x as usize;
                 let c = event.coord.c.unwrap_or(0) as usize;
@@ -187,7 +198,7 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                             pixels[[y, x, c]] = Some(DvsPixel {
                                 d: event.d,
                                 frame_intensity_ln: event_to_frame_intensity(&event, frame_length),
-                                t: event.t as u128,
+                                t: event.t as u128 + time_base,
                             });
                         }
                         _ => {
@@ -200,8 +211,8 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                             px.t += event.t as u128;
                         } else {
                             let tmp = px.t;
-                            px.t = event.t as u128;
-                            event.t -= tmp as u32;
+                            px.t = event.t as u128 + time_base;
+                            event.t = (px.t - tmp) as u32;
                         }
 
                         if is_framed(meta.source_camera) {
@@ -361,13 +372,14 @@ fn set_instant_dvs_pixel(
 }
 
 fn event_to_frame_intensity(event: &Event, frame_length: u128) -> f64 {
-    if event.d == D_ZERO_INTEGRATION {
-        return 0.0;
-    }
+    // Covers D_ZERO_INTEGRATION, D_EMPTY, and corrupted values
+    let shift = match d_shift(event.d) {
+        Some(shift) => shift,
+        None => return 0.0,
+    };
     match event.t {
-        0 => ((D_SHIFT[event.d as usize] as f64 * frame_length as f64) / 255.0).ln_1p(),
-        _ => (((D_SHIFT[event.d as usize] as f64 / event.t as f64) * frame_length as f64) / 255.0)
-            .ln_1p(),
+        0 => ((shift as f64 * frame_length as f64) / 255.0).ln_1p(),
+        _ => (((shift as f64 / event.t as f64) * frame_length as f64) / 255.0).ln_1p(),
     }
 }
 
@@ -21,6 +21,7 @@ use bevy_egui::egui::{
 };
 
 use crate::transcoder::adder::replace_adder_transcoder;
+use log::info;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
@@ -110,7 +111,7 @@ fn update_ui_scale_factor(
             let scale_factor = if toggle_scale_factor.unwrap_or(true) {
                 1.0
             } else {
-                eprintln!(
+                info!(
                     "Primary window found, using scale factor: {}",
                     window.scale_factor()
                 );
@@ -13,6 +13,7 @@ use adder_codec_rs::transcoder::source::video::FramedViewMode;
 use adder_codec_rs::utils::cv::is_feature;
 use adder_codec_rs::utils::viz::draw_feature_coord;
 use bevy::prelude::Image;
+use log::{error, info};
 use ndarray::Array;
 use ndarray::Array3;
 
@@ -29,11 +30,13 @@ pub type PlayerStreamArtifact = (u64, StreamState, Option<Image>);
 
 #[derive(Default, Clone, Debug)]
 pub struct StreamState {
-    pub(crate) current_t_ticks: DeltaT,
+    pub(crate) current_t_ticks: BigT,
     pub(crate) tps: DeltaT,
     pub(crate) file_pos: u64,
     pub(crate) volume: usize,
     pub(crate) last_timestamps: Array3<DeltaT>,
+    // The sum of the offsets of the rebase events read so far
+    pub(crate) time_base: BigT,
     // The current instantaneous frame, for determining features
     pub running_intensities: Array3<i32>,
 }
@@ -108,9 +111,10 @@ impl AdderPlayer {
                         .buffer_limit(buffer_limit)
                         .view_mode(view_mode)
                         .detect_features(detect_features)
-                        .source(stream.get_source_type(), meta.source_camera);
+                        .source(stream.get_source_type(), meta.source_camera)
+                        .pixel_multi_mode(meta.pixel_multi_mode);
 
-                    let frame_sequence: FrameSequence<u8> = framer_builder.clone().finish();
+                    let frame_sequence: FrameSequence<u8> = framer_builder.clone().finish()?;
 
                     Ok(AdderPlayer {
                         stream_state: StreamState {
@@ -128,6 +132,7 @@ impl AdderPlayer {
                                 meta.plane.w_usize(),
                                 meta.plane.c_usize(),
                             )),
+                            time_base: 0,
                         },
                         framer_builder: Some(framer_builder),
                         frame_sequence: Some(frame_sequence),
@@ -194,7 +199,7 @@ impl AdderPlayer {
                 match &mut self.frame_sequence {
                     None => {
                         // TODO: error
-                        eprintln!("TODO Error");
+                        error!("Rewound to the start of the stream without a framer");
                     }
                     Some(frame_sequence) => {
                         frame_sequence.state.reset();
@@ -278,6 +283,16 @@ impl AdderPlayer {
             }
 
             match stream.decoder.digest_event(&mut stream.bitreader) {
+                Ok(event) if event.coord.is_reserved() => {
+                    // A rebase event offsets the timestamps of the events after it
+                    if event.coord.is_rebase() {
+                        let offset = event.t;
+                        self.stream_state
+                            .last_timestamps
+                            .mapv_inplace(|t| t.saturating_sub(offset));
+                        self.stream_state.time_base += BigT::from(offset);
+                    }
+                }
                 Ok(mut event) if event.d <= D_ZERO_INTEGRATION => {
                     event_count += 1;
                     let y = event.coord.y as i32;
@@ -288,8 +303,9 @@ impl AdderPlayer {
                     // }
 
                     if meta.time_mode == TimeMode::AbsoluteT {
-                        if event.t > self.stream_state.current_t_ticks {
-                            self.stream_state.current_t_ticks = event.t;
+                        let absolute_t = BigT::from(event.t) + self.stream_state.time_base;
+                        if absolute_t > self.stream_state.current_t_ticks {
+                            self.stream_state.current_t_ticks = absolute_t;
                         }
 
                         let dt = event.t
@@ -331,11 +347,14 @@ impl AdderPlayer {
                                 * meta.ref_interval;
                         }
 
-                        if self.stream_state.last_timestamps[[y as usize, x as usize, c as usize]]
-                            > self.stream_state.current_t_ticks
+                        if BigT::from(
+                            self.stream_state.last_timestamps[[y as usize, x as usize, c as usize]],
+                        ) > self.stream_state.current_t_ticks
                         {
-                            self.stream_state.current_t_ticks = self.stream_state.last_timestamps
-                                [[y as usize, x as usize, c as usize]];
+                            self.stream_state.current_t_ticks = BigT::from(
+                                self.stream_state.last_timestamps
+                                    [[y as usize, x as usize, c as usize]],
+                            );
                         }
                     }
 
@@ -453,14 +472,18 @@ impl AdderPlayer {
                             .decoder
                             .set_input_stream_position(&mut stream.bitreader, 1)?;
                     }
-                    self.frame_sequence =
-                        self.framer_builder.clone().map(|builder| builder.finish());
+                    self.frame_sequence = self
+                        .framer_builder
+                        .clone()
+                        .map(|builder| builder.finish())
+                        .transpose()?;
                     self.stream_state.last_timestamps = Array::zeros((
                         meta.plane.h_usize(),
                         meta.plane.w_usize(),
                         meta.plane.c_usize(),
                     ));
                     self.stream_state.current_t_ticks = 0;
+                    self.stream_state.time_base = 0;
                     self.current_frame = 0;
 
                     break None;
@@ -588,7 +611,7 @@ impl AdderPlayer {
             // )?;
             // show_display_force("keypoints", &keypoint_mat, 1)?;
 
-            self.stream_state.current_t_ticks += frame_sequence.state.tpf;
+            self.stream_state.current_t_ticks += BigT::from(frame_sequence.state.tpf);
 
             let image_mat = self.display_frame.clone();
             let color = image_mat.shape()[2] == 3;
@@ -611,7 +634,9 @@ impl AdderPlayer {
                     event_count += 1;
                     let filled = frame_sequence.ingest_event(&mut event, last_event);
 
-                    last_event = Some(event);
+                    if !event.coord.is_reserved() {
+                        last_event = Some(event);
+                    }
 
                     if filled {
                         return Ok((event_count, image_bevy));
@@ -619,8 +644,7 @@ impl AdderPlayer {
                 }
                 Err(e) => {
                     if !frame_sequence.flush_frame_buffer() {
-                        eprintln!("Player error: {}", e);
-                        eprintln!("Completely done");
+                        info!("Finished playing the stream after {event_count} events: {e}");
                         // TODO: Need to reset the UI event count events_ppc count when looping back here
                         // Loop/restart back to the beginning
                         if stream.decoder.get_compression_type() == EncoderType::Raw {
@@ -634,8 +658,11 @@ impl AdderPlayer {
                                 .set_input_stream_position(&mut stream.bitreader, 1)?;
                         }
 
-                        self.frame_sequence =
-                            self.framer_builder.clone().map(|builder| builder.finish());
+                        self.frame_sequence = self
+                            .framer_builder
+                            .clone()
+                            .map(|builder| builder.finish())
+                            .transpose()?;
                         self.stream_state.last_timestamps = Array::zeros((
                             meta.plane.h_usize(),
                             meta.plane.w_usize(),
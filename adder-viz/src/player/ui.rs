@@ -11,6 +11,7 @@ use bevy::prelude::{Commands, Image, Res, ResMut};
 
 use bevy::time::Time;
 use bevy_egui::egui::{Color32, RichText, Ui};
+use log::info;
 
 use crate::player::adder::{AdderPlayer, PlayerStreamArtifact, StreamState};
 use crate::{add_checkbox_row, add_radio_row, add_slider_row, Images};
@@ -151,7 +152,7 @@ impl PlayerState {
                 let handle = images.add(image);
                 handles.image_view = handle;
             } else if self.ui_info_state.stream_state.file_pos == 1 {
-                dbg!("Looping...");
+                info!("Looping the stream");
                 self.reset_update_adder_params(true);
 
                 return Ok(());
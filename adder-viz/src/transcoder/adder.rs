@@ -1,7 +1,5 @@
 use std::error::Error;
 
-use adder_codec_rs::adder_codec_core::DeltaT;
-
 #[cfg(feature = "open-cv")]
 use adder_codec_rs::transcoder::source::davis::Davis;
 use adder_codec_rs::transcoder::source::framed::Framed;
@@ -23,6 +21,7 @@ use adder_codec_rs::adder_codec_core::SourceCamera::{DavisU8, Dvs, FramedU8};
 use adder_codec_rs::transcoder::source::prophesee::Prophesee;
 use adder_codec_rs::transcoder::source::video::{Source, VideoBuilder};
 use bevy_egui::egui::{Color32, RichText};
+use log::{error, info, warn};
 #[cfg(feature = "open-cv")]
 use opencv::Result;
 
@@ -189,6 +188,7 @@ impl AdderTranscoder {
                         let output_string = output_path_opt
                             .map(|output_path| output_path.to_str().expect("Bad path").to_string());
 
+                        let time_params = ui_state.davis_time_params()?;
                         let mut davis_source: Davis<BufWriter<File>> =
                             Davis::new(reconstructor, rt, ui_state.davis_mode_radio_state)?
                                 .optimize_adder_controller(false) // TODO
@@ -201,24 +201,12 @@ impl AdderTranscoder {
                                         .unwrap_or(DEFAULT_CRF_QUALITY),
                                 )
                                 .time_parameters(
-                                    1000000_u32,
-                                    (1_000_000.0 / ui_state.davis_output_fps) as DeltaT,
-                                    ((1_000_000.0 / ui_state.davis_output_fps)
-                                        * ui_state.delta_t_max_mult as f64)
-                                        as u32,
+                                    time_params.tps(),
+                                    time_params.ref_interval(),
+                                    time_params.delta_t_max(),
                                     Some(ui_state.time_mode),
                                 )?;
 
-                        // Override time parameters if we're in framed mode
-                        if ui_state.davis_mode_radio_state == TranscoderMode::Framed {
-                            davis_source = davis_source.time_parameters(
-                                (255.0 * ui_state.davis_output_fps) as u32,
-                                255,
-                                255 * ui_state.delta_t_max_mult,
-                                Some(ui_state.time_mode),
-                            )?;
-                        }
-
                         if let Some(output_string) = output_string {
                             let writer = BufWriter::new(File::create(output_string)?);
                             davis_source = *davis_source.write_out(
@@ -300,10 +288,11 @@ pub(crate) fn replace_adder_transcoder(
     output_path_opt: Option<PathBuf>,
     current_frame: u32,
 ) {
-    dbg!("Looping video");
+    info!("Restarting transcoder for {input_path_buf_0:?} at frame {current_frame}");
 
     let ui_info_state = &mut transcoder_state.ui_info_state;
     ui_info_state.events_per_sec = 0.0;
+    ui_info_state.event_rate_meter.reset();
     ui_info_state.events_ppc_total = 0.0;
     ui_info_state.events_total = 0;
     ui_info_state.events_ppc_per_sec = 0.0;
@@ -334,11 +323,15 @@ pub(crate) fn replace_adder_transcoder(
                 }
             }
             Err(e) => {
+                error!(
+                    "Could not open transcoder for {}: {e}",
+                    input_path.display()
+                );
                 transcoder_state.transcoder = AdderTranscoder::default();
                 ui_info_state.source_name = RichText::new(e.to_string()).color(Color32::RED);
             }
         };
     } else {
-        eprintln!("No input path");
+        warn!("No input path");
     }
 }
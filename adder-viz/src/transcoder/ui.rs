@@ -8,6 +8,7 @@ use bevy::ecs::system::Resource;
 use bevy::prelude::{Assets, Commands, Image, Res, ResMut, Time};
 use bevy_egui::egui;
 use bevy_egui::egui::{RichText, Ui};
+use log::error;
 use rayon::current_num_threads;
 use std::collections::VecDeque;
 use std::error::Error;
@@ -16,10 +17,13 @@ use crate::utils::PlotY;
 use adder_codec_rs::adder_codec_core::codec::rate_controller::{Crf, CRF, DEFAULT_CRF_QUALITY};
 use adder_codec_rs::adder_codec_core::codec::{EncoderOptions, EncoderType, EventDrop, EventOrder};
 use adder_codec_rs::adder_codec_core::TimeMode;
+#[cfg(feature = "open-cv")]
+use adder_codec_rs::adder_codec_core::{DeltaT, TimeParams, TimeParamsError};
 use adder_codec_rs::adder_codec_core::{PixelMultiMode, PlaneSize};
 #[cfg(feature = "open-cv")]
 use adder_codec_rs::transcoder::source::davis::TranscoderMode::RawDvs;
-use adder_codec_rs::utils::cv::{calculate_quality_metrics, QualityMetrics};
+use adder_codec_rs::utils::cv::{calculate_quality_metrics_in, QualityMetrics};
+use adder_codec_rs::utils::event_rate::EventRateMeter;
 use adder_codec_rs::utils::viz::ShowFeatureMode;
 use bevy_egui::egui::plot::Corner::LeftTop;
 use bevy_egui::egui::plot::Legend;
@@ -119,8 +123,27 @@ impl Default for ParamsUiState {
     }
 }
 
+impl ParamsUiState {
+    /// The time parameters for a DAVIS source, derived from the output frame rate and the Δt_max
+    /// multiplier. In framed mode, each output frame spans 255 ticks. Otherwise, each tick is a
+    /// microsecond.
+    #[cfg(feature = "open-cv")]
+    pub(crate) fn davis_time_params(&self) -> Result<TimeParams, TimeParamsError> {
+        if self.davis_mode_radio_state == TranscoderMode::Framed {
+            TimeParams::from_fps(self.davis_output_fps as f32, 255, self.delta_t_max_mult)
+        } else {
+            TimeParams::from_tps(
+                1_000_000,
+                (1_000_000.0 / self.davis_output_fps) as DeltaT,
+                self.delta_t_max_mult,
+            )
+        }
+    }
+}
+
 pub struct InfoUiState {
     pub events_per_sec: f64,
+    pub event_rate_meter: EventRateMeter,
     pub events_ppc_per_sec: f64,
     pub events_ppc_total: f64,
     pub events_total: u64,
@@ -161,6 +184,7 @@ impl Default for InfoUiState {
 
         InfoUiState {
             events_per_sec: 0.,
+            event_rate_meter: EventRateMeter::from_seconds(1, 1.0),
             events_ppc_per_sec: 0.,
             events_ppc_total: 0.0,
             events_total: 0,
@@ -643,21 +667,33 @@ impl TranscoderState {
 
         match source.consume(1, &pool) {
             Ok(events_vec_vec) => {
+                let mut event_count = 0;
                 for events_vec in events_vec_vec {
-                    ui_info_state.events_total += events_vec.len() as u64;
-                    ui_info_state.events_per_sec += events_vec.len() as f64;
+                    event_count += events_vec.len() as u64;
                 }
+                ui_info_state.events_total += event_count;
                 ui_info_state.events_ppc_total = ui_info_state.events_total as f64
                     / (source.get_video_ref().state.plane.volume() as f64);
-                let source_fps = source.get_video_ref().get_tps() as f64
-                    / source.get_video_ref().get_ref_time() as f64;
-                ui_info_state.events_per_sec *= source_fps;
+
+                // Smooth the event rate over the last second of source time
+                let tps = source.get_video_ref().get_tps();
+                if ui_info_state.event_rate_meter.tps() != tps {
+                    ui_info_state.event_rate_meter = EventRateMeter::from_seconds(tps, 1.0);
+                }
+                ui_info_state.event_rate_meter.record(
+                    event_count,
+                    u64::from(source.get_video_ref().get_ref_time()),
+                );
+                ui_info_state.events_per_sec = ui_info_state.event_rate_meter.rate();
                 ui_info_state.events_ppc_per_sec = ui_info_state.events_per_sec
                     / (source.get_video_ref().state.plane.volume() as f64);
             }
             Err(SourceError::Open) => {}
             Err(e) => {
-                eprintln!("Error: {:?}", e);
+                error!(
+                    "Transcode of {:?} stopped after {} events: {e:?}",
+                    self.ui_info_state.input_path_0, self.ui_info_state.events_total
+                );
                 source.get_video_mut().end_write_stream()?;
                 self.ui_info_state.output_path = None;
                 self.ui_info_state.output_name = Default::default();
@@ -679,9 +715,11 @@ impl TranscoderState {
 
         if let Some(input) = source.get_input() {
             #[rustfmt::skip]
-            let metrics = calculate_quality_metrics(
+            let metrics = calculate_quality_metrics_in(
                 input,
+                source.get_input_color_space(),
                 image_mat,
+                source.get_video_ref().get_color_space(),
                 QualityMetrics {
                     mse: if self.ui_state.metric_mse {Some(0.0)} else {None},
                     psnr: if self.ui_state.metric_psnr {Some(0.0)} else {None},